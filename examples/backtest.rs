@@ -0,0 +1,47 @@
+//! Replay a recorded market file through the backtester without touching the network.
+//!
+//! ```text
+//! LOG_LEVEL=warn cargo run --example backtest -- recording.jsonl [threshold ...]
+//! ```
+//!
+//! Prints the JSON report for each threshold followed by its Telegram summary.
+
+use arb_edge::services::core::analysis::backtester::{load_events, BacktestConfig, Backtester};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: backtest <recording.jsonl> [threshold ...]");
+        std::process::exit(2);
+    };
+    let mut thresholds: Vec<f64> = args
+        .map(|arg| {
+            arg.parse().unwrap_or_else(|_| {
+                eprintln!("invalid threshold: {}", arg);
+                std::process::exit(2);
+            })
+        })
+        .collect();
+    if thresholds.is_empty() {
+        thresholds.push(BacktestConfig::default().threshold);
+    }
+
+    let result = load_events(&path).and_then(|events| {
+        Backtester::new(BacktestConfig::default()).sweep_thresholds(&events, &thresholds)
+    });
+    let reports = match result {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("backtest failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for report in reports {
+        match report.to_json() {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("failed to serialise report: {}", e),
+        }
+        println!("{}", report.telegram_summary());
+    }
+}
@@ -0,0 +1,29 @@
+-- Migration 015: Add Funding Payments Ledger
+-- Purpose: Record every funding settlement on the legs of open arbitrage positions
+-- Date: 2026-10-17
+-- Related: Funding-rate arbitrage carry reporting
+
+-- Funding Payments Table
+-- One row per exchange ledger entry; amount is positive when received, negative when paid
+CREATE TABLE IF NOT EXISTS funding_payments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    position_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    leg TEXT NOT NULL CHECK (leg IN ('long', 'short')),
+    amount REAL NOT NULL,
+    asset TEXT NOT NULL,
+    payment_id TEXT NOT NULL,
+    paid_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    UNIQUE (exchange, payment_id)
+);
+
+-- Indexes for per-position and per-user carry queries
+CREATE INDEX IF NOT EXISTS idx_funding_payments_position ON funding_payments(position_id, paid_at);
+CREATE INDEX IF NOT EXISTS idx_funding_payments_user ON funding_payments(user_id, paid_at);
+
+-- Record migration
+INSERT INTO schema_migrations (version, description)
+VALUES ('015', 'Add funding_payments ledger for arbitrage positions');
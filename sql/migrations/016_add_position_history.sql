@@ -0,0 +1,47 @@
+-- Migration 016: D1-backed Arbitrage Positions and History
+-- Purpose: Store arbitrage positions as queryable rows instead of KV blobs with a JSON index,
+--          and keep an append-only record of every status transition
+-- Date: 2026-10-17
+-- Related: PositionRepository
+
+-- Both legs of an arbitrage position plus the full serialized record
+ALTER TABLE positions ADD COLUMN long_exchange TEXT;
+ALTER TABLE positions ADD COLUMN short_exchange TEXT;
+ALTER TABLE positions ADD COLUMN position_data TEXT; -- JSON ArbitragePosition
+
+-- Indexes for the position list filters
+CREATE INDEX IF NOT EXISTS idx_positions_user_status_created ON positions(user_id, status, created_at);
+CREATE INDEX IF NOT EXISTS idx_positions_pair ON positions(pair);
+CREATE INDEX IF NOT EXISTS idx_positions_long_exchange ON positions(long_exchange);
+CREATE INDEX IF NOT EXISTS idx_positions_short_exchange ON positions(short_exchange);
+
+-- Position History Table
+-- One row per state transition; rows are never updated or deleted
+CREATE TABLE IF NOT EXISTS position_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    position_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    from_status TEXT, -- NULL when the position is first recorded
+    to_status TEXT NOT NULL,
+    snapshot TEXT NOT NULL, -- JSON ArbitragePosition at the time of the transition
+    recorded_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_position_history_position ON position_history(position_id, recorded_at);
+CREATE INDEX IF NOT EXISTS idx_position_history_user ON position_history(user_id, recorded_at);
+
+CREATE TRIGGER IF NOT EXISTS position_history_no_update
+BEFORE UPDATE ON position_history
+BEGIN
+    SELECT RAISE(ABORT, 'position_history is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS position_history_no_delete
+BEFORE DELETE ON position_history
+BEGIN
+    SELECT RAISE(ABORT, 'position_history is append-only');
+END;
+
+-- Record migration
+INSERT INTO schema_migrations (version, description)
+VALUES ('016', 'Add position columns and append-only position_history');
@@ -120,6 +120,17 @@ pub async fn handle_get_orderbook(req: Request, env: Env) -> Result<Response> {
         .get("symbol")
         .cloned()
         .unwrap_or_else(|| "BTCUSDT".to_string());
+    let limit = match query_pairs.get("limit") {
+        Some(value) => match value.parse::<u32>() {
+            Ok(limit) if limit > 0 => Some(limit),
+            _ => return Response::error("limit must be a positive integer", 400),
+        },
+        None => None,
+    };
+    let market_type = query_pairs
+        .get("market")
+        .cloned()
+        .unwrap_or_else(|| "spot".to_string());
 
     let exchange_enum = match exchange.to_lowercase().as_str() {
         "binance" => ExchangeIdEnum::Binance,
@@ -131,7 +142,7 @@ pub async fn handle_get_orderbook(req: Request, env: Env) -> Result<Response> {
 
     let exchange_service = ExchangeService::new(&env)?;
     match exchange_service
-        .get_orderbook(&exchange_enum.to_string(), &symbol, None)
+        .get_market_orderbook(&exchange_enum.to_string(), &symbol, limit, &market_type)
         .await
     {
         Ok(orderbook) => Response::from_json(&orderbook),
@@ -1,4 +1,23 @@
 use crate::responses::ApiResponse;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::admin::audit::{AuditConfig, AuditService, UserAuditAction};
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::auth::middleware::AuthMiddleware;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::infrastructure::database_repositories::{
+    DatabaseManager, DatabaseManagerConfig,
+};
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::exchange::ExchangeService;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::positions::PositionsService;
+#[cfg(target_arch = "wasm32")]
+use crate::types::ExchangeCredentials;
+use crate::types::{ArbitragePosition, CommandPermission, UserProfile};
+#[cfg(target_arch = "wasm32")]
+use std::sync::Arc;
+#[cfg(target_arch = "wasm32")]
+use worker::console_log;
 use worker::{Env, Request, Response, Result};
 
 /// Placeholder for trading handlers - will be extracted from lib.rs
@@ -8,3 +27,434 @@ pub async fn handle_api_get_trading_balance(_req: Request, _env: Env) -> Result<
     }));
     Response::from_json(&response)
 }
+
+/// Body of a position update. Only the exchange-side protection settings can change here:
+/// size, price and pnl follow the exchange, and closing goes through `DELETE` so the
+/// protective orders are cancelled with it. Any other field is refused.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PositionUpdateRequest {
+    pub stop_loss_price: Option<f64>,
+    pub take_profit_price: Option<f64>,
+    pub trailing_stop_distance: Option<f64>,
+    pub exchange_protection: Option<bool>,
+}
+
+impl PositionUpdateRequest {
+    /// Read an update body, explaining which field was refused
+    pub fn parse(body: &str) -> std::result::Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(body).map_err(|e| format!("Invalid JSON format: {}", e))?;
+        if value.get("status").is_some() {
+            return Err(
+                "Position status cannot be updated; close the position with DELETE".to_string(),
+            );
+        }
+        serde_json::from_value(value).map_err(|e| format!("Invalid position update: {}", e))
+    }
+}
+
+/// Why a caller may not act on a position
+#[derive(Debug, PartialEq, Eq)]
+pub enum PositionAccessError {
+    /// Someone else's position; reported as missing so ids cannot be probed
+    NotFound,
+    /// The caller's access level does not allow trading actions
+    Forbidden,
+}
+
+/// Callers may only see their own positions, and changing one needs manual trading rights
+pub fn check_position_access(
+    user: &UserProfile,
+    position: &ArbitragePosition,
+    mutate: bool,
+) -> std::result::Result<(), PositionAccessError> {
+    if position.user_id != user.user_id {
+        return Err(PositionAccessError::NotFound);
+    }
+    if mutate && !user.has_permission(CommandPermission::ManualTrading) {
+        return Err(PositionAccessError::Forbidden);
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn error_response(message: &str, status: u16) -> Result<Response> {
+    let response = ApiResponse::<()>::error(message.to_string());
+    Ok(Response::from_json(&response)?.with_status(status))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn access_error_response(error: PositionAccessError) -> Result<Response> {
+    match error {
+        PositionAccessError::NotFound => error_response("Position not found", 404),
+        PositionAccessError::Forbidden => error_response("Manual trading permission required", 403),
+    }
+}
+
+/// Resolve the caller through `AuthMiddleware`, or the 401 response to send back
+#[cfg(target_arch = "wasm32")]
+async fn authenticate(
+    req: &Request,
+    env: &Env,
+) -> Result<std::result::Result<UserProfile, Response>> {
+    let container = crate::get_service_container(env).await?;
+    let middleware = match AuthMiddleware::new(container).await {
+        Ok(middleware) => middleware,
+        Err(e) => return Ok(Err(error_response(&e.to_string(), 503)?)),
+    };
+
+    match middleware.authenticate_request(req).await {
+        Ok(result) if result.is_authenticated => match result.user_profile {
+            Some(profile) => Ok(Ok(profile)),
+            None => Ok(Err(error_response("Authentication required", 401)?)),
+        },
+        Ok(result) => Ok(Err(error_response(
+            result
+                .error_message
+                .as_deref()
+                .unwrap_or("Authentication required"),
+            401,
+        )?)),
+        Err(e) => Ok(Err(error_response(&e.to_string(), 401)?)),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn positions_service(env: &Env) -> Result<PositionsService<worker::kv::KvStore>> {
+    let database_manager = DatabaseManager::new(
+        Arc::new(env.d1("ArbEdgeD1")?),
+        DatabaseManagerConfig::default(),
+    );
+    Ok(PositionsService::new(Arc::new(env.kv("ArbEdgeKV")?))
+        .with_position_repository(database_manager.get_position_repository()))
+}
+
+/// Exchange service and the caller's API keys, used to keep exchange-side protective
+/// orders in line with a position change
+#[cfg(target_arch = "wasm32")]
+async fn exchange_access(
+    env: &Env,
+    user_id: &str,
+) -> Result<std::result::Result<(Arc<ExchangeService>, Vec<ExchangeCredentials>), Response>> {
+    let container = crate::get_service_container(env).await?;
+    let Some(api_key_service) = container.user_exchange_api_service.clone() else {
+        return Ok(Err(error_response(
+            "Exchange API key service not available",
+            503,
+        )?));
+    };
+    match api_key_service.get_user_api_keys(user_id).await {
+        Ok(keys) => Ok(Ok((
+            container.exchange_service.clone(),
+            keys.into_iter()
+                .map(|(_, credentials)| credentials)
+                .collect(),
+        ))),
+        Err(e) => Ok(Err(error_response(
+            &format!("Failed to load API keys: {}", e),
+            500,
+        )?)),
+    }
+}
+
+/// Write an audit record for a position change; an audit failure is logged, not returned
+#[cfg(target_arch = "wasm32")]
+async fn audit_position_action(
+    env: &Env,
+    user_id: &str,
+    action_type: &str,
+    position_id: &str,
+    error: Option<String>,
+) {
+    let audit = match env.kv("ArbEdgeKV") {
+        Ok(kv_store) => AuditService::new(env.clone(), kv_store, AuditConfig::default()),
+        Err(e) => {
+            console_log!("⚠️ Audit store unavailable for {}: {:?}", action_type, e);
+            return;
+        }
+    };
+
+    let mut action = UserAuditAction::new(
+        user_id.to_string(),
+        action_type.to_string(),
+        format!("{} on position {}", action_type, position_id),
+    )
+    .with_metadata("position_id".to_string(), position_id.to_string());
+    if let Some(error) = error {
+        action = action.with_error(error);
+    }
+
+    if let Err(e) = audit.log_user_action(user_id, action, None).await {
+        console_log!(
+            "⚠️ Failed to audit {} on {}: {}",
+            action_type,
+            position_id,
+            e
+        );
+    }
+}
+
+/// Authenticate, load the position and check the caller may act on it
+#[cfg(target_arch = "wasm32")]
+async fn load_authorized_position(
+    req: &Request,
+    env: &Env,
+    id: &str,
+    mutate: bool,
+) -> Result<std::result::Result<(UserProfile, ArbitragePosition), Response>> {
+    let user = match authenticate(req, env).await? {
+        Ok(user) => user,
+        Err(response) => return Ok(Err(response)),
+    };
+
+    let position = match positions_service(env)?.get_position(id).await {
+        Ok(Some(position)) => position,
+        Ok(None) => return Ok(Err(error_response("Position not found", 404)?)),
+        Err(e) => {
+            return Ok(Err(error_response(
+                &format!("Failed to load position: {}", e),
+                500,
+            )?))
+        }
+    };
+
+    if let Err(error) = check_position_access(&user, &position, mutate) {
+        if mutate {
+            audit_position_action(
+                env,
+                &user.user_id,
+                "position_access_denied",
+                id,
+                Some(format!("{:?}", error)),
+            )
+            .await;
+        }
+        return Ok(Err(access_error_response(error)?));
+    }
+
+    Ok(Ok((user, position)))
+}
+
+/// List the caller's positions
+#[cfg(target_arch = "wasm32")]
+pub async fn handle_api_get_positions(req: Request, env: Env) -> Result<Response> {
+    let user = match authenticate(&req, &env).await? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    match positions_service(&env)?.get_all_positions().await {
+        Ok(positions) => {
+            let positions: Vec<ArbitragePosition> = positions
+                .into_iter()
+                .filter(|position| position.user_id == user.user_id)
+                .collect();
+            let response = ApiResponse::success(serde_json::json!({
+                "positions": positions,
+                "total_count": positions.len(),
+            }));
+            Response::from_json(&response)
+        }
+        Err(e) => error_response(&format!("Failed to load positions: {}", e), 500),
+    }
+}
+
+/// Get one of the caller's positions
+#[cfg(target_arch = "wasm32")]
+pub async fn handle_api_get_position(req: Request, env: Env, id: &str) -> Result<Response> {
+    match load_authorized_position(&req, &env, id, false).await? {
+        Ok((_, position)) => Response::from_json(&ApiResponse::success(position)),
+        Err(response) => Ok(response),
+    }
+}
+
+/// Modify one of the caller's positions and bring its protective orders in line
+#[cfg(target_arch = "wasm32")]
+pub async fn handle_api_update_position(mut req: Request, env: Env, id: &str) -> Result<Response> {
+    let (user, _) = match load_authorized_position(&req, &env, id, true).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+
+    let body = req.text().await?;
+    let PositionUpdateRequest {
+        stop_loss_price,
+        take_profit_price,
+        trailing_stop_distance,
+        exchange_protection,
+    } = match PositionUpdateRequest::parse(&body) {
+        Ok(update) => update,
+        Err(message) => return error_response(&message, 400),
+    };
+    let (exchange_service, credentials) = match exchange_access(&env, &user.user_id).await? {
+        Ok(access) => access,
+        Err(response) => return Ok(response),
+    };
+
+    let service = positions_service(&env)?;
+    let result = async {
+        if let Some(enabled) = exchange_protection {
+            if !service.set_exchange_protection(id, enabled).await? {
+                return Ok(None);
+            }
+        }
+        service
+            .update_protection(
+                exchange_service.as_ref(),
+                &credentials,
+                id,
+                stop_loss_price,
+                take_profit_price,
+                trailing_stop_distance,
+            )
+            .await
+    }
+    .await;
+    audit_position_action(
+        &env,
+        &user.user_id,
+        "position_update",
+        id,
+        result.as_ref().err().map(|e| e.to_string()),
+    )
+    .await;
+
+    match result {
+        Ok(Some(position)) => Response::from_json(&ApiResponse::success(position)),
+        Ok(None) => error_response("Position not found", 404),
+        Err(e) => error_response(&format!("Failed to update position: {}", e), 500),
+    }
+}
+
+/// Close one of the caller's positions and cancel its protective orders on the exchanges
+#[cfg(target_arch = "wasm32")]
+pub async fn handle_api_close_position(req: Request, env: Env, id: &str) -> Result<Response> {
+    let (user, _) = match load_authorized_position(&req, &env, id, true).await? {
+        Ok(authorized) => authorized,
+        Err(response) => return Ok(response),
+    };
+    let (exchange_service, credentials) = match exchange_access(&env, &user.user_id).await? {
+        Ok(access) => access,
+        Err(response) => return Ok(response),
+    };
+
+    let result = positions_service(&env)?
+        .close_position_with_protection(exchange_service.as_ref(), &credentials, id)
+        .await;
+    audit_position_action(
+        &env,
+        &user.user_id,
+        "position_close",
+        id,
+        result.as_ref().err().map(|e| e.to_string()),
+    )
+    .await;
+
+    match result {
+        Ok(Some(position)) => Response::from_json(&ApiResponse::success(serde_json::json!({
+            "position_id": id,
+            "status": "closed",
+            "protective_orders": position.protective_orders,
+        }))),
+        Ok(None) => error_response("Position not found", 404),
+        Err(e) => error_response(&format!("Failed to close position: {}", e), 500),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::positions::{CreatePositionData, PositionsService};
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::types::{AccountInfo, ExchangeIdEnum, PositionSide, UserAccessLevel};
+    use std::sync::Arc;
+
+    async fn position_owned_by(user_id: &str) -> ArbitragePosition {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let account = AccountInfo {
+            account_id: "acc".to_string(),
+            exchange: ExchangeIdEnum::Binance,
+            balances: Vec::new(),
+            total_balance_usd: 10_000.0,
+            available_balance_usd: 10_000.0,
+            used_balance_usd: 0.0,
+            last_updated: 0,
+        };
+        let data = CreatePositionData {
+            pair: "BTC/USDT".to_string(),
+            side: PositionSide::Long,
+            size: None,
+            size_usd: Some(6_500.0),
+            entry_price_long: 65_000.0,
+            entry_price_short: 65_100.0,
+            risk_percentage: None,
+            max_size_usd: None,
+            take_profit_price: None,
+            stop_loss_price: None,
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            exchange: ExchangeIdEnum::Binance,
+        };
+        let mut position = service.create_position(data, &account).await.unwrap();
+        position.user_id = user_id.to_string();
+        position
+    }
+
+    fn user(user_id: &str, access_level: UserAccessLevel) -> UserProfile {
+        let mut profile = UserProfile::new(Some(1), None);
+        profile.user_id = user_id.to_string();
+        profile.access_level = access_level;
+        profile.beta_expires_at = None;
+        profile
+    }
+
+    #[tokio::test]
+    async fn test_position_access_is_scoped_to_owner_and_trading_rights() {
+        let position = position_owned_by("alice").await;
+
+        let owner = user("alice", UserAccessLevel::Verified);
+        assert_eq!(check_position_access(&owner, &position, true), Ok(()));
+
+        let other = user("bob", UserAccessLevel::SuperAdmin);
+        assert_eq!(
+            check_position_access(&other, &position, false),
+            Err(PositionAccessError::NotFound)
+        );
+
+        let read_only = user("alice", UserAccessLevel::Free);
+        assert_eq!(check_position_access(&read_only, &position, false), Ok(()));
+        assert_eq!(
+            check_position_access(&read_only, &position, true),
+            Err(PositionAccessError::Forbidden)
+        );
+    }
+
+    #[test]
+    fn test_position_update_request_reads_protection_settings() {
+        let request = PositionUpdateRequest::parse(
+            r#"{"stop_loss_price": 62000.0, "trailing_stop_distance": 500.0, "exchange_protection": true}"#,
+        )
+        .unwrap();
+
+        assert_eq!(request.stop_loss_price, Some(62_000.0));
+        assert_eq!(request.take_profit_price, None);
+        assert_eq!(request.trailing_stop_distance, Some(500.0));
+        assert_eq!(request.exchange_protection, Some(true));
+    }
+
+    #[test]
+    fn test_position_update_request_refuses_status_and_stored_fields() {
+        let close = PositionUpdateRequest::parse(r#"{"status": "Closed"}"#).unwrap_err();
+        assert!(close.contains("DELETE"));
+
+        for body in [
+            r#"{"pnl": 1000000.0}"#,
+            r#"{"stop_loss_price": 62000.0, "size": 10.0}"#,
+            r#"{"current_price": 1.0}"#,
+        ] {
+            let error = PositionUpdateRequest::parse(body).unwrap_err();
+            assert!(error.contains("unknown field"), "{}: {}", body, error);
+        }
+    }
+}
@@ -53,7 +53,7 @@ use services::core::infrastructure::service_container::ServiceContainer;
 // use services::core::opportunities::opportunity::OpportunityServiceConfig; // Removed - using modular architecture
 // use services::core::opportunities::OpportunityService; // Removed - using modular architecture
 use services::core::trading::exchange::{ExchangeInterface, ExchangeService};
-use services::core::trading::positions::{CreatePositionData, UpdatePositionData};
+use services::core::trading::positions::CreatePositionData;
 use services::core::user::user_profile::UserProfileService;
 // use services::interfaces::telegram::telegram::{TelegramConfig, TelegramService}; // Removed unused imports
 
@@ -80,7 +80,7 @@ macro_rules! console_log {
 
 static SERVICE_CONTAINER: OnceCell<Arc<ServiceContainer>> = OnceCell::new();
 
-async fn get_service_container(env: &Env) -> Result<Arc<ServiceContainer>> {
+pub(crate) async fn get_service_container(env: &Env) -> Result<Arc<ServiceContainer>> {
     // Check if service container already exists
     if let Some(container) = SERVICE_CONTAINER.get() {
         return Ok(container.clone());
@@ -733,24 +733,24 @@ pub async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
             console_log!("⚠️ Using legacy endpoint /positions - Consider migrating to /api/v1/trading/positions");
             handle_create_position(req, env).await
         }
-        (Method::Get, "/positions") => {
-            console_log!("⚠️ Using legacy endpoint /positions - Consider migrating to /api/v1/trading/positions");
-            handle_get_all_positions(req, env).await
+        #[cfg(target_arch = "wasm32")]
+        (Method::Get, "/positions") | (Method::Get, "/api/v1/trading/positions") => {
+            handle_api_get_positions(req, env).await
         }
-        (Method::Get, path) if path.starts_with("/positions/") => {
-            console_log!("⚠️ Using legacy endpoint /positions/:id - Consider migrating to /api/v1/trading/positions/:id");
-            let id = path.strip_prefix("/positions/").unwrap_or("");
-            handle_get_position(req, env, id).await
+        #[cfg(target_arch = "wasm32")]
+        (Method::Get, path) if position_id(path).is_some() => {
+            let id = position_id(path).unwrap_or_default().to_string();
+            handle_api_get_position(req, env, &id).await
         }
-        (Method::Put, path) if path.starts_with("/positions/") => {
-            console_log!("⚠️ Using legacy endpoint PUT /positions/:id - Consider migrating to /api/v1/trading/positions/:id");
-            let id = path.strip_prefix("/positions/").unwrap_or("");
-            handle_update_position(req, env, id).await
+        #[cfg(target_arch = "wasm32")]
+        (Method::Put, path) if position_id(path).is_some() => {
+            let id = position_id(path).unwrap_or_default().to_string();
+            handle_api_update_position(req, env, &id).await
         }
-        (Method::Delete, path) if path.starts_with("/positions/") => {
-            console_log!("⚠️ Using legacy endpoint DELETE /positions/:id - Consider migrating to /api/v1/trading/positions/:id");
-            let id = path.strip_prefix("/positions/").unwrap_or("");
-            handle_close_position(req, env, id).await
+        #[cfg(target_arch = "wasm32")]
+        (Method::Delete, path) if position_id(path).is_some() => {
+            let id = position_id(path).unwrap_or_default().to_string();
+            handle_api_close_position(req, env, &id).await
         }
 
         _ => {
@@ -934,6 +934,17 @@ async fn handle_get_orderbook(req: Request, env: Env) -> Result<Response> {
         .get("symbol")
         .cloned()
         .unwrap_or_else(|| "BTCUSDT".to_string());
+    let limit = match query_pairs.get("limit") {
+        Some(value) => match value.parse::<u32>() {
+            Ok(limit) if limit > 0 => Some(limit),
+            _ => return Response::error("limit must be a positive integer", 400),
+        },
+        None => None,
+    };
+    let market_type = query_pairs
+        .get("market")
+        .cloned()
+        .unwrap_or_else(|| "spot".to_string());
 
     let exchange_enum = match exchange.to_lowercase().as_str() {
         "binance" => ExchangeIdEnum::Binance,
@@ -945,7 +956,7 @@ async fn handle_get_orderbook(req: Request, env: Env) -> Result<Response> {
 
     let exchange_service = ExchangeService::new(&env)?;
     match exchange_service
-        .get_orderbook(&exchange_enum.to_string(), &symbol, None)
+        .get_market_orderbook(&exchange_enum.to_string(), &symbol, limit, &market_type)
         .await
     {
         Ok(orderbook) => Response::from_json(&orderbook),
@@ -1036,6 +1047,14 @@ async fn handle_find_opportunities(mut req: Request, _env: Env) -> Result<Respon
     Response::from_json(&fallback_opportunities)
 }
 
+/// Position id from `/positions/:id` or `/api/v1/trading/positions/:id`
+#[cfg(target_arch = "wasm32")]
+fn position_id(path: &str) -> Option<&str> {
+    path.strip_prefix("/positions/")
+        .or_else(|| path.strip_prefix("/api/v1/trading/positions/"))
+        .filter(|id| !id.is_empty() && !id.contains('/'))
+}
+
 async fn handle_create_position(mut req: Request, _env: Env) -> Result<Response> {
     let position_data: CreatePositionData = req.json().await?;
     console_log!("📊 Creating new position: {:?}", position_data);
@@ -1059,81 +1078,6 @@ async fn handle_create_position(mut req: Request, _env: Env) -> Result<Response>
     Response::from_json(&position_response)
 }
 
-async fn handle_get_all_positions(_req: Request, _env: Env) -> Result<Response> {
-    console_log!("📊 Retrieving all positions");
-
-    // Return empty positions list during migration - maintains API compatibility
-    let positions_response = serde_json::json!({
-        "positions": [],
-        "metadata": {
-            "total_count": 0,
-            "status": "migration_mode",
-            "message": "Position data migrating to modular architecture",
-            "timestamp": chrono::Utc::now().timestamp_millis()
-        }
-    });
-
-    Response::from_json(&positions_response)
-}
-
-async fn handle_get_position(_req: Request, _env: Env, id: &str) -> Result<Response> {
-    console_log!("📊 Retrieving position with ID: {}", id);
-
-    // Return position details during migration - basic implementation for API compatibility
-    let position_response = serde_json::json!({
-        "position_id": id,
-        "status": "migration_mode",
-        "data": {
-            "message": "Position data migrating to modular architecture",
-            "position_id": id
-        },
-        "metadata": {
-            "timestamp": chrono::Utc::now().timestamp_millis(),
-            "status": "under_migration"
-        }
-    });
-
-    Response::from_json(&position_response)
-}
-
-async fn handle_update_position(mut req: Request, _env: Env, id: &str) -> Result<Response> {
-    let update_data: UpdatePositionData = req.json().await?;
-    console_log!("📊 Updating position {} with data: {:?}", id, update_data);
-
-    // Return updated position response during migration - maintains API compatibility
-    let position_response = serde_json::json!({
-        "position_id": id,
-        "status": "updated",
-        "data": update_data,
-        "metadata": {
-            "updated_at": chrono::Utc::now().timestamp_millis(),
-            "status": "under_migration",
-            "implementation_note": "Position updates tracked during migration"
-        }
-    });
-
-    console_log!("✅ Position {} update acknowledged", id);
-    Response::from_json(&position_response)
-}
-
-async fn handle_close_position(_req: Request, _env: Env, id: &str) -> Result<Response> {
-    console_log!("📊 Closing position with ID: {}", id);
-
-    // Return closed position response during migration - maintains API compatibility
-    let position_response = serde_json::json!({
-        "position_id": id,
-        "status": "closed",
-        "metadata": {
-            "closed_at": chrono::Utc::now().timestamp_millis(),
-            "status": "under_migration",
-            "implementation_note": "Position closure tracked during migration"
-        }
-    });
-
-    console_log!("✅ Position {} closure acknowledged", id);
-    Response::from_json(&position_response)
-}
-
 async fn run_five_minute_maintenance(
     env: &Env,
     // _opportunity_service: &OpportunityService,
@@ -1243,6 +1187,73 @@ async fn run_five_minute_maintenance(
         failed_tasks += 1;
     }
 
+    // 6. Expire opportunities that stopped appearing in scans
+    #[cfg(target_arch = "wasm32")]
+    {
+        console_log!("⏳ Expiring stale opportunity lifecycles...");
+        let tracker = services::core::opportunities::OpportunityLifecycleTracker::new(
+            std::sync::Arc::new(kv_store.clone()),
+        );
+        match tracker.expire_stale(current_timestamp).await {
+            Ok(expired) => {
+                console_log!("✅ Expired {} opportunity lifecycles", expired.len());
+                completed_tasks += 1;
+            }
+            Err(e) => {
+                console_log!("❌ Failed to expire opportunity lifecycles: {:?}", e);
+                failed_tasks += 1;
+            }
+        }
+    }
+
+    // 7. Flag stored positions that no longer match the exchanges
+    #[cfg(target_arch = "wasm32")]
+    {
+        console_log!("🔍 Reconciling open positions with exchanges...");
+        match reconcile_open_positions(env, &kv_store).await {
+            Ok(out_of_sync) => {
+                console_log!("✅ Reconciled open positions ({} out of sync)", out_of_sync);
+                completed_tasks += 1;
+            }
+            Err(e) => {
+                console_log!("❌ Failed to reconcile open positions: {:?}", e);
+                failed_tasks += 1;
+            }
+        }
+    }
+
+    // 8. Record funding settled on open positions since the last sync
+    #[cfg(target_arch = "wasm32")]
+    {
+        console_log!("💸 Syncing funding payments...");
+        match sync_funding_payments(env, &kv_store).await {
+            Ok(recorded) => {
+                console_log!("✅ Recorded {} funding payments", recorded);
+                completed_tasks += 1;
+            }
+            Err(e) => {
+                console_log!("❌ Failed to sync funding payments: {:?}", e);
+                failed_tasks += 1;
+            }
+        }
+    }
+
+    // 9. Alert users whose positions are drifting towards liquidation
+    #[cfg(target_arch = "wasm32")]
+    {
+        console_log!("🚨 Checking liquidation risk on open positions...");
+        match monitor_liquidation_risk(env, &kv_store).await {
+            Ok(alerts) => {
+                console_log!("✅ Checked liquidation risk ({} alerts)", alerts);
+                completed_tasks += 1;
+            }
+            Err(e) => {
+                console_log!("❌ Failed to check liquidation risk: {:?}", e);
+                failed_tasks += 1;
+            }
+        }
+    }
+
     // Store maintenance metrics
     let maintenance_summary = serde_json::json!({
         "timestamp": current_timestamp,
@@ -1559,6 +1570,200 @@ async fn cleanup_expired_sessions(
     Ok(cleaned_sessions)
 }
 
+// Helper function to load the decrypted exchange credentials of each user.
+// Users whose keys cannot be loaded are left out so one bad profile does not block the rest.
+#[cfg(target_arch = "wasm32")]
+async fn load_user_credentials(
+    env: &Env,
+    kv_store: &KvStore,
+    user_ids: impl IntoIterator<Item = String>,
+) -> ArbitrageResult<std::collections::HashMap<String, Vec<types::ExchangeCredentials>>> {
+    use services::core::infrastructure::{D1Service, D1ServiceConfig};
+    use services::core::user::UserExchangeApiService;
+
+    let encryption_key = env
+        .var("ENCRYPTION_KEY")
+        .map_err(|_| ArbitrageError::configuration_error("ENCRYPTION_KEY not available"))?
+        .to_string();
+    let database_manager = DatabaseManager::new(
+        Arc::new(env.d1("ArbEdgeD1")?),
+        DatabaseManagerConfig::default(),
+    );
+    let api_key_service = UserExchangeApiService::new(
+        Arc::new(UserProfileService::new(
+            kv_store.clone(),
+            database_manager,
+            encryption_key.clone(),
+        )),
+        Arc::new(ExchangeService::new(env)?),
+        Arc::new(D1Service::new(env, D1ServiceConfig::default()).await?),
+        kv_store.clone(),
+        secrecy::SecretString::new(encryption_key),
+    );
+
+    let mut credentials = std::collections::HashMap::new();
+    for user_id in user_ids {
+        match api_key_service.get_user_api_keys(&user_id).await {
+            Ok(keys) => {
+                credentials.insert(user_id, keys.into_iter().map(|(_, creds)| creds).collect());
+            }
+            Err(e) => console_log!(
+                "⚠️ Skipping user {} - API keys unavailable: {:?}",
+                user_id,
+                e
+            ),
+        }
+    }
+    Ok(credentials)
+}
+
+// Helper function to build the positions service with its D1 copy attached
+#[cfg(target_arch = "wasm32")]
+fn positions_service(
+    kv_store: &KvStore,
+    database_manager: &DatabaseManager,
+) -> services::core::trading::positions::PositionsService<KvStore> {
+    services::core::trading::positions::PositionsService::new(Arc::new(kv_store.clone()))
+        .with_position_repository(database_manager.get_position_repository())
+}
+
+// Helper function to reconcile every user's open positions with their exchange accounts
+#[cfg(target_arch = "wasm32")]
+async fn reconcile_open_positions(env: &Env, kv_store: &KvStore) -> ArbitrageResult<usize> {
+    use services::core::trading::positions::DEFAULT_SIZE_DRIFT_TOLERANCE;
+
+    let database_manager = DatabaseManager::new(
+        Arc::new(env.d1("ArbEdgeD1")?),
+        DatabaseManagerConfig::default(),
+    );
+    let positions_service = positions_service(kv_store, &database_manager);
+    let user_ids: std::collections::HashSet<String> = positions_service
+        .get_open_positions()
+        .await?
+        .into_iter()
+        .map(|position| position.user_id)
+        .collect();
+    if user_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let credentials = load_user_credentials(env, kv_store, user_ids).await?;
+    let exchange_service = ExchangeService::new(env)?;
+    let mut out_of_sync = 0;
+    for (user_id, user_credentials) in &credentials {
+        match positions_service
+            .reconcile_with_exchanges(
+                user_id,
+                &exchange_service,
+                user_credentials,
+                DEFAULT_SIZE_DRIFT_TOLERANCE,
+            )
+            .await
+        {
+            Ok(reports) => out_of_sync += reports.len(),
+            Err(e) => console_log!("⚠️ Failed to reconcile positions for {}: {:?}", user_id, e),
+        }
+    }
+    Ok(out_of_sync)
+}
+
+// Helper function to pull new funding payments for every user's open positions into the ledger
+#[cfg(target_arch = "wasm32")]
+async fn sync_funding_payments(env: &Env, kv_store: &KvStore) -> ArbitrageResult<usize> {
+    use services::core::trading::funding_ledger::FundingLedger;
+
+    let database_manager = DatabaseManager::new(
+        Arc::new(env.d1("ArbEdgeD1")?),
+        DatabaseManagerConfig::default(),
+    );
+    let positions_service = Arc::new(positions_service(kv_store, &database_manager));
+    let user_ids: std::collections::HashSet<String> = positions_service
+        .get_open_positions()
+        .await?
+        .into_iter()
+        .map(|position| position.user_id)
+        .collect();
+    if user_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let credentials = load_user_credentials(env, kv_store, user_ids).await?;
+    let ledger = FundingLedger::new(
+        Arc::new(ExchangeService::new(env)?),
+        positions_service,
+        Arc::new(database_manager),
+    );
+    let mut recorded = 0;
+    for (user_id, user_credentials) in credentials {
+        let by_exchange = user_credentials
+            .into_iter()
+            .map(|creds| (creds.exchange, creds))
+            .collect();
+        match ledger.sync_user_positions(&user_id, &by_exchange).await {
+            Ok(entries) => recorded += entries.len(),
+            Err(e) => console_log!("⚠️ Failed to sync funding for {}: {:?}", user_id, e),
+        }
+    }
+    Ok(recorded)
+}
+
+// Helper function to check every open position against its liquidation prices and
+// alert the owner on Telegram when a leg crosses a threshold
+#[cfg(target_arch = "wasm32")]
+async fn monitor_liquidation_risk(env: &Env, kv_store: &KvStore) -> ArbitrageResult<usize> {
+    use services::core::trading::{LiquidationMonitor, LiquidationMonitorConfig};
+
+    let container = get_service_container(env).await?;
+    let Some(telegram_service) = container.telegram_service.clone() else {
+        return Err(ArbitrageError::service_unavailable(
+            "Telegram service not available for liquidation alerts",
+        ));
+    };
+
+    let positions_service = Arc::new(positions_service(kv_store, &container.database_manager));
+    let open_positions = positions_service.get_open_positions().await?;
+    if open_positions.is_empty() {
+        return Ok(0);
+    }
+
+    let credentials = load_user_credentials(
+        env,
+        kv_store,
+        open_positions
+            .iter()
+            .map(|position| position.user_id.clone())
+            .collect::<std::collections::HashSet<_>>(),
+    )
+    .await?;
+    let monitor = LiquidationMonitor::new(
+        Arc::new(ExchangeService::new(env)?),
+        positions_service,
+        telegram_service,
+        LiquidationMonitorConfig::default(),
+    );
+    // Cross-margin legs are estimated without the account's spare balance, which
+    // only brings their alerts forward
+    let free_collateral = std::collections::HashMap::new();
+    let mut alerts = 0;
+    for position in open_positions {
+        let Some(user_credentials) = credentials.get(&position.user_id) else {
+            continue;
+        };
+        match monitor
+            .check_position(&position.id, user_credentials, &free_collateral)
+            .await
+        {
+            Ok(report) => alerts += usize::from(report.alert.is_some()),
+            Err(e) => console_log!(
+                "⚠️ Failed to check liquidation risk for {}: {:?}",
+                position.id,
+                e
+            ),
+        }
+    }
+    Ok(alerts)
+}
+
 async fn monitor_opportunities_scheduled(env: Env) -> ArbitrageResult<()> {
     console_log!("🔄 Starting scheduled opportunity monitoring...");
 
@@ -5,6 +5,8 @@ use crate::services::core::analysis::correlation_analysis::CorrelationMetrics;
 use crate::services::core::analysis::market_analysis::{RiskLevel, TradingOpportunity};
 use crate::services::core::infrastructure::database_repositories::DatabaseManager;
 use crate::services::core::opportunities::opportunity_categorization::CategorizedOpportunity;
+use crate::services::core::trading::ai_exchange_router::ExchangeMarketData;
+use crate::services::core::trading::exchange::ExchangeService;
 use crate::services::core::user::dynamic_config::UserConfigInstance;
 use crate::services::core::user::user_trading_preferences::{TradingFocus, UserTradingPreferences};
 use crate::services::{
@@ -157,6 +159,7 @@ pub struct AiIntelligenceService {
     kv_store: KvStore,
     pipelines_service:
         Option<crate::services::core::infrastructure::data_ingestion_module::PipelineManager>,
+    exchange_service: Option<ExchangeService>,
     logger: Logger,
 }
 
@@ -189,10 +192,16 @@ impl AiIntelligenceService {
             d1_service,
             kv_store,
             pipelines_service,
+            exchange_service: None,
             logger: Logger::new(LogLevel::Info),
         }
     }
 
+    /// Set the exchange service used to pull live order book depth into market snapshots
+    pub fn set_exchange_service(&mut self, exchange_service: ExchangeService) {
+        self.exchange_service = Some(exchange_service);
+    }
+
     /// Analyze opportunity with AI enhancement
     /// Combines categorization, position analysis, and AI insights
     pub async fn analyze_opportunity_with_ai(
@@ -346,7 +355,8 @@ impl AiIntelligenceService {
             self.create_portfolio_risk_prompt(&positions, &correlation_metrics, &preferences);
 
         // Get AI analysis
-        let market_snapshot = self.create_portfolio_market_snapshot(&positions);
+        let mut market_snapshot = self.create_portfolio_market_snapshot(&positions);
+        market_snapshot.exchange_data = self.fetch_position_exchange_data(&positions).await;
         let ai_response = self
             .ai_router
             .get_real_time_recommendations(user_id, &[], &market_snapshot)
@@ -1011,6 +1021,41 @@ impl AiIntelligenceService {
         }
     }
 
+    /// Live order book depth for every exchange/symbol the positions trade on
+    async fn fetch_position_exchange_data(
+        &self,
+        positions: &[ArbitragePosition],
+    ) -> HashMap<String, ExchangeMarketData> {
+        let Some(exchange_service) = &self.exchange_service else {
+            return HashMap::new();
+        };
+
+        let mut symbols_by_exchange: HashMap<String, Vec<String>> = HashMap::new();
+        for position in positions {
+            for exchange in [position.long_exchange, position.short_exchange] {
+                let symbols = symbols_by_exchange
+                    .entry(exchange.as_str().to_string())
+                    .or_default();
+                if !symbols.contains(&position.symbol) {
+                    symbols.push(position.symbol.clone());
+                }
+            }
+        }
+
+        let mut exchange_data = HashMap::new();
+        for (exchange_id, symbols) in symbols_by_exchange {
+            let data = ExchangeMarketData::fetch_orderbook_depth(
+                exchange_service,
+                &exchange_id,
+                &symbols,
+                None,
+            )
+            .await;
+            exchange_data.insert(exchange_id, data);
+        }
+        exchange_data
+    }
+
     /// Create performance market data
     fn create_performance_market_data(
         &self,
@@ -2366,6 +2411,14 @@ mod tests {
             position_group_id: None,
             current_state: Some("monitoring".to_string()),
             optimization_score: Some(0.0),
+            execution_log: Vec::new(),
+            long_fees: 0.0,
+            short_fees: 0.0,
+            funding_pnl: 0.0,
+            predicted_rate_difference: None,
+            exchange_protection: false,
+            protective_orders: Vec::new(),
+            liquidation_alert_threshold: None,
             recommended_action: Some("hold".to_string()),
             risk_percentage_applied: Some(0.01),
         }
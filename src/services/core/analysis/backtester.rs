@@ -0,0 +1,842 @@
+// src/services/core/analysis/backtester.rs
+
+//! Historical backtesting for funding-rate and price arbitrage.
+//!
+//! A recording is a JSON Lines file of `MarketEvent`s: funding rates, top-of-book tickers
+//! and order-book snapshots, one per line. Events are replayed in timestamp order. After
+//! each timestamp, every symbol is offered to `OpportunityBuilder` exactly as the live
+//! scanner would, with `BacktestConfig::threshold` as its minimum rate difference. Accepted
+//! opportunities open a simulated trade that fills against the recorded books the same way
+//! `PaperExchange` does, and pays taker fees and slippage on both legs.
+//!
+//! Funding trades collect funding at every `funding_interval_ms` boundary, using the rates
+//! recorded before it, and close once the rate difference falls to `exit_rate_difference`.
+//! Price trades close when the spread between the legs has converged. Either closes after
+//! `max_hold_ms`, and anything still open closes at the last recorded prices.
+//!
+//! Everything is synchronous and reads no network, so recordings can be replayed natively,
+//! e.g. with `cargo run --example backtest -- recording.jsonl 0.001 0.002`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::core::opportunities::opportunity_builders::OpportunityBuilder;
+use crate::services::core::opportunities::opportunity_core::{
+    OpportunityConfig, OpportunityContext,
+};
+use crate::services::core::trading::paper_exchange::walk_book;
+use crate::types::{
+    ArbitrageOpportunity, ArbitrageType, ExchangeIdEnum, OrderBook, TradingFeeRates,
+};
+use crate::utils::formatter::escape_markdown_v2;
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+const DAY_MS: u64 = 86_400_000;
+
+/// One line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketEvent {
+    FundingRate {
+        timestamp: u64,
+        exchange: ExchangeIdEnum,
+        symbol: String,
+        /// Rate per funding interval
+        rate: f64,
+    },
+    /// Top of book without depth; fills at these prices are not size-limited
+    Ticker {
+        timestamp: u64,
+        exchange: ExchangeIdEnum,
+        symbol: String,
+        bid: f64,
+        ask: f64,
+    },
+    OrderBook {
+        timestamp: u64,
+        exchange: ExchangeIdEnum,
+        symbol: String,
+        bids: Vec<[f64; 2]>,
+        asks: Vec<[f64; 2]>,
+    },
+}
+
+impl MarketEvent {
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            MarketEvent::FundingRate { timestamp, .. }
+            | MarketEvent::Ticker { timestamp, .. }
+            | MarketEvent::OrderBook { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+/// Parse a JSON Lines recording; blank lines are skipped
+pub fn parse_events(input: &str) -> ArbitrageResult<Vec<MarketEvent>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                ArbitrageError::parse_error(format!(
+                    "Invalid market event on line {}: {}",
+                    index + 1,
+                    e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Read and parse a recording from disk
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_events(path: impl AsRef<std::path::Path>) -> ArbitrageResult<Vec<MarketEvent>> {
+    let path = path.as_ref();
+    let input = std::fs::read_to_string(path).map_err(|e| {
+        ArbitrageError::storage_error(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    parse_events(&input)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// Minimum rate or price difference an opportunity needs, as `ARBITRAGE_THRESHOLD`
+    pub threshold: f64,
+    /// `FundingRate` and/or `Price`
+    pub strategies: Vec<ArbitrageType>,
+    pub position_size_usd: f64,
+    pub starting_equity: f64,
+    pub taker_fee_rate: f64,
+    pub slippage_bps: f64,
+    /// Share of each recorded level a fill may take
+    pub book_participation: f64,
+    pub funding_interval_ms: u64,
+    /// Funding trades close once the rate difference is at or below this
+    pub exit_rate_difference: f64,
+    pub max_hold_ms: u64,
+    /// Depth within this distance of the best price counts towards capacity
+    pub capacity_band_bps: f64,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.001,
+            strategies: vec![ArbitrageType::FundingRate, ArbitrageType::Price],
+            position_size_usd: 10_000.0,
+            starting_equity: 100_000.0,
+            taker_fee_rate: 0.0005,
+            slippage_bps: 2.0,
+            book_participation: 1.0,
+            funding_interval_ms: 8 * 3_600_000,
+            exit_rate_difference: 0.0,
+            max_hold_ms: 7 * DAY_MS,
+            capacity_band_bps: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    /// The funding rate difference fell to the exit level
+    EdgeClosed,
+    /// The long leg's bid reached the short leg's ask
+    Converged,
+    MaxHold,
+    EndOfData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub pair: String,
+    pub strategy: ArbitrageType,
+    pub long_exchange: ExchangeIdEnum,
+    pub short_exchange: ExchangeIdEnum,
+    pub opened_at: u64,
+    pub closed_at: u64,
+    pub quantity: f64,
+    pub size_usd: f64,
+    pub long_entry: f64,
+    pub short_entry: f64,
+    pub long_exit: f64,
+    pub short_exit: f64,
+    pub rate_difference: f64,
+    pub net_rate_difference: Option<f64>,
+    pub fees_usd: f64,
+    pub funding_usd: f64,
+    pub pnl_usd: f64,
+    /// Notional both legs could have filled within `capacity_band_bps`; `None` when a leg
+    /// only had ticker data
+    pub capacity_usd: Option<f64>,
+    pub exit_reason: ExitReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub threshold: f64,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub events: usize,
+    pub trades: Vec<BacktestTrade>,
+    pub total_pnl_usd: f64,
+    pub return_percentage: f64,
+    pub max_drawdown_usd: f64,
+    /// Drawdown as a fraction of the equity peak
+    pub max_drawdown_percentage: f64,
+    /// Annualised from daily returns; `None` over less than two days or without variance
+    pub sharpe_ratio: Option<f64>,
+    pub hit_rate: f64,
+    pub avg_capacity_usd: Option<f64>,
+    pub min_capacity_usd: Option<f64>,
+}
+
+impl BacktestReport {
+    pub fn to_json(&self) -> ArbitrageResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// MarkdownV2 summary for Telegram
+    pub fn telegram_summary(&self) -> String {
+        let days = (self.period_end.saturating_sub(self.period_start)) as f64 / DAY_MS as f64;
+        let pnl_emoji = if self.total_pnl_usd >= 0.0 {
+            "📈"
+        } else {
+            "📉"
+        };
+        let mut message = format!(
+            "🧪 *Backtest* \\(threshold {}\\)\n\n",
+            escape_markdown_v2(&format!("{:.3}%", self.threshold * 100.0))
+        );
+        message.push_str(&format!(
+            "🗓 *Period*: {} days, {} events\n",
+            escape_markdown_v2(&format!("{:.1}", days)),
+            self.events
+        ));
+        message.push_str(&format!("🔁 *Trades*: {}\n", self.trades.len()));
+        message.push_str(&format!(
+            "{} *PnL*: {} \\({}\\)\n",
+            pnl_emoji,
+            escape_markdown_v2(&format!("${:.2}", self.total_pnl_usd)),
+            escape_markdown_v2(&format!("{:+.2}%", self.return_percentage))
+        ));
+        message.push_str(&format!(
+            "🎯 *Hit rate*: {}\n",
+            escape_markdown_v2(&format!("{:.1}%", self.hit_rate * 100.0))
+        ));
+        message.push_str(&format!(
+            "⚠️ *Max drawdown*: {} \\({}\\)\n",
+            escape_markdown_v2(&format!("${:.2}", self.max_drawdown_usd)),
+            escape_markdown_v2(&format!("{:.2}%", self.max_drawdown_percentage * 100.0))
+        ));
+        message.push_str(&format!(
+            "📊 *Sharpe*: {}\n",
+            escape_markdown_v2(
+                &self
+                    .sharpe_ratio
+                    .map(|sharpe| format!("{:.2}", sharpe))
+                    .unwrap_or_else(|| "N/A".to_string())
+            )
+        ));
+        message.push_str(&format!(
+            "💧 *Capacity*: {}\n",
+            escape_markdown_v2(
+                &self
+                    .avg_capacity_usd
+                    .map(|capacity| format!("${:.0} avg per trade", capacity))
+                    .unwrap_or_else(|| "N/A".to_string())
+            )
+        ));
+        message
+    }
+}
+
+/// Largest peak-to-trough fall of `equity`, in USD and as a fraction of the peak
+pub fn max_drawdown(equity: &[f64]) -> (f64, f64) {
+    let mut peak = f64::MIN;
+    let (mut usd, mut fraction) = (0.0_f64, 0.0_f64);
+    for value in equity {
+        peak = peak.max(*value);
+        usd = usd.max(peak - value);
+        if peak > 0.0 {
+            fraction = fraction.max((peak - value) / peak);
+        }
+    }
+    (usd, fraction)
+}
+
+/// Annualised Sharpe ratio of daily returns, with a zero risk-free rate
+pub fn sharpe_ratio(daily_returns: &[f64]) -> Option<f64> {
+    if daily_returns.len() < 2 {
+        return None;
+    }
+    let n = daily_returns.len() as f64;
+    let mean = daily_returns.iter().sum::<f64>() / n;
+    let variance = daily_returns
+        .iter()
+        .map(|r| (r - mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0);
+    if variance <= 0.0 {
+        return None;
+    }
+    Some(mean / variance.sqrt() * 365.0_f64.sqrt())
+}
+
+#[derive(Clone)]
+struct Quote {
+    book: OrderBook,
+    /// Whether the book has recorded depth rather than a ticker's top of book
+    depth: bool,
+}
+
+struct OpenTrade {
+    trade: BacktestTrade,
+    last_funding_at: u64,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    quotes: HashMap<(ExchangeIdEnum, String), Quote>,
+    rates: HashMap<(ExchangeIdEnum, String), f64>,
+}
+
+impl ReplayState {
+    fn apply(&mut self, event: &MarketEvent) {
+        match event {
+            MarketEvent::FundingRate {
+                exchange,
+                symbol,
+                rate,
+                ..
+            } => {
+                self.rates.insert((*exchange, symbol.clone()), *rate);
+            }
+            MarketEvent::Ticker {
+                timestamp,
+                exchange,
+                symbol,
+                bid,
+                ask,
+            } => {
+                let book = OrderBook {
+                    symbol: symbol.clone(),
+                    bids: vec![[*bid, f64::MAX]],
+                    asks: vec![[*ask, f64::MAX]],
+                    timestamp: *timestamp,
+                    datetime: String::new(),
+                    nonce: None,
+                };
+                self.quotes
+                    .insert((*exchange, symbol.clone()), Quote { book, depth: false });
+            }
+            MarketEvent::OrderBook {
+                timestamp,
+                exchange,
+                symbol,
+                bids,
+                asks,
+            } => {
+                let book = OrderBook {
+                    symbol: symbol.clone(),
+                    bids: bids.clone(),
+                    asks: asks.clone(),
+                    timestamp: *timestamp,
+                    datetime: String::new(),
+                    nonce: None,
+                };
+                self.quotes
+                    .insert((*exchange, symbol.clone()), Quote { book, depth: true });
+            }
+        }
+    }
+
+    fn quote(&self, exchange: ExchangeIdEnum, symbol: &str) -> Option<&Quote> {
+        self.quotes.get(&(exchange, symbol.to_string()))
+    }
+
+    fn mid(&self, exchange: ExchangeIdEnum, symbol: &str) -> Option<f64> {
+        self.quote(exchange, symbol)?.book.mid_price()
+    }
+
+    fn rate(&self, exchange: ExchangeIdEnum, symbol: &str) -> Option<f64> {
+        self.rates.get(&(exchange, symbol.to_string())).copied()
+    }
+}
+
+pub struct Backtester {
+    config: BacktestConfig,
+}
+
+impl Backtester {
+    pub fn new(config: BacktestConfig) -> Self {
+        Self { config }
+    }
+
+    /// Run the same recording once per threshold
+    pub fn sweep_thresholds(
+        &self,
+        events: &[MarketEvent],
+        thresholds: &[f64],
+    ) -> ArbitrageResult<Vec<BacktestReport>> {
+        thresholds
+            .iter()
+            .map(|threshold| {
+                Backtester::new(BacktestConfig {
+                    threshold: *threshold,
+                    ..self.config.clone()
+                })
+                .run(events)
+            })
+            .collect()
+    }
+
+    pub fn run(&self, events: &[MarketEvent]) -> ArbitrageResult<BacktestReport> {
+        if events.is_empty() {
+            return Err(ArbitrageError::validation_error(
+                "Backtest recording has no events",
+            ));
+        }
+        let mut ordered: Vec<&MarketEvent> = events.iter().collect();
+        ordered.sort_by_key(|event| event.timestamp());
+        let period_start = ordered[0].timestamp();
+        let period_end = ordered[ordered.len() - 1].timestamp();
+
+        let builder = self.builder(events);
+        let context = OpportunityContext::Global { system_level: true };
+        let mut state = ReplayState::default();
+        let mut open: BTreeMap<(String, bool), OpenTrade> = BTreeMap::new();
+        let mut closed = Vec::new();
+
+        let mut index = 0;
+        while index < ordered.len() {
+            let now = ordered[index].timestamp();
+            // Funding due up to now settles at the rates recorded before it
+            for trade in open.values_mut() {
+                self.accrue_funding(&state, trade, now);
+            }
+            while index < ordered.len() && ordered[index].timestamp() == now {
+                state.apply(ordered[index]);
+                index += 1;
+            }
+
+            let keys: Vec<(String, bool)> = open.keys().cloned().collect();
+            for key in keys {
+                if let Some(reason) = self.exit_reason(&state, &open[&key], now) {
+                    let trade = open.remove(&key).unwrap();
+                    closed.push(self.close(&state, trade, now, reason));
+                }
+            }
+
+            for opportunity in self.scan(&builder, &state, &context) {
+                let key = (
+                    opportunity.pair.clone(),
+                    opportunity.r#type == ArbitrageType::FundingRate,
+                );
+                if open.contains_key(&key) {
+                    continue;
+                }
+                if let Some(trade) = self.open(&state, &opportunity, now) {
+                    open.insert(key, trade);
+                }
+            }
+        }
+
+        for (_, trade) in std::mem::take(&mut open) {
+            closed.push(self.close(&state, trade, period_end, ExitReason::EndOfData));
+        }
+        closed.sort_by_key(|trade| (trade.closed_at, trade.opened_at));
+        Ok(self.report(closed, events.len(), period_start, period_end))
+    }
+
+    /// A builder quoting the configured taker fee on every recorded market
+    fn builder(&self, events: &[MarketEvent]) -> OpportunityBuilder {
+        let mut builder = OpportunityBuilder::new(OpportunityConfig {
+            min_rate_difference: self.config.threshold,
+            ..OpportunityConfig::default()
+        });
+        let markets: HashSet<(ExchangeIdEnum, String)> = events
+            .iter()
+            .map(|event| match event {
+                MarketEvent::FundingRate {
+                    exchange, symbol, ..
+                }
+                | MarketEvent::Ticker {
+                    exchange, symbol, ..
+                }
+                | MarketEvent::OrderBook {
+                    exchange, symbol, ..
+                } => (*exchange, symbol.clone()),
+            })
+            .collect();
+        for (exchange, symbol) in markets {
+            for futures in [true, false] {
+                builder = builder.with_trading_fees(
+                    exchange,
+                    &symbol,
+                    futures,
+                    TradingFeeRates {
+                        maker: self.config.taker_fee_rate,
+                        taker: self.config.taker_fee_rate,
+                        percentage: true,
+                        tier_based: false,
+                    },
+                );
+            }
+        }
+        builder
+    }
+
+    /// Opportunities the builder accepts from the current state, best first
+    fn scan(
+        &self,
+        builder: &OpportunityBuilder,
+        state: &ReplayState,
+        context: &OpportunityContext,
+    ) -> Vec<ArbitrageOpportunity> {
+        let mut by_symbol: BTreeMap<&str, Vec<ExchangeIdEnum>> = BTreeMap::new();
+        for (exchange, symbol) in state.quotes.keys() {
+            by_symbol
+                .entry(symbol.as_str())
+                .or_default()
+                .push(*exchange);
+        }
+
+        let mut opportunities = Vec::new();
+        for (symbol, mut exchanges) in by_symbol {
+            exchanges.sort();
+            if self.config.strategies.contains(&ArbitrageType::FundingRate) {
+                let rated: Vec<(ExchangeIdEnum, f64)> = exchanges
+                    .iter()
+                    .filter_map(|exchange| Some((*exchange, state.rate(*exchange, symbol)?)))
+                    .collect();
+                let lowest = rated.iter().min_by(|a, b| a.1.total_cmp(&b.1));
+                let highest = rated.iter().max_by(|a, b| a.1.total_cmp(&b.1));
+                if let (Some(long), Some(short)) = (lowest, highest) {
+                    if long.0 != short.0 {
+                        if let Ok(opportunity) = builder.build_funding_rate_arbitrage(
+                            symbol.to_string(),
+                            long.0,
+                            short.0,
+                            long.1,
+                            short.1,
+                            context,
+                        ) {
+                            opportunities.push(opportunity);
+                        }
+                    }
+                }
+            }
+            if self.config.strategies.contains(&ArbitrageType::Price) {
+                let asks = exchanges.iter().filter_map(|exchange| {
+                    Some((*exchange, state.quote(*exchange, symbol)?.book.best_ask()?))
+                });
+                let bids = exchanges.iter().filter_map(|exchange| {
+                    Some((*exchange, state.quote(*exchange, symbol)?.book.best_bid()?))
+                });
+                let cheapest = asks.min_by(|a, b| a.1.total_cmp(&b.1));
+                let richest = bids.max_by(|a, b| a.1.total_cmp(&b.1));
+                // Only a crossed market is a price opportunity
+                if let (Some(long), Some(short)) = (cheapest, richest) {
+                    if long.0 != short.0 && short.1 > long.1 {
+                        if let Ok(opportunity) = builder.build_price_arbitrage(
+                            symbol.to_string(),
+                            long.0,
+                            short.0,
+                            long.1,
+                            short.1,
+                            context,
+                        ) {
+                            opportunities.push(opportunity);
+                        }
+                    }
+                }
+            }
+        }
+        opportunities.sort_by(|a, b| b.rate_difference.total_cmp(&a.rate_difference));
+        opportunities
+    }
+
+    /// Average price for `quantity` including slippage; `None` when the book is empty
+    fn fill_price(&self, book: &OrderBook, buy: bool, quantity: f64) -> Option<(f64, f64)> {
+        let (filled, average) =
+            walk_book(book, buy, quantity, None, self.config.book_participation);
+        let slippage = self.config.slippage_bps / 10_000.0;
+        let price = if buy {
+            average? * (1.0 + slippage)
+        } else {
+            average? * (1.0 - slippage)
+        };
+        Some((filled, price))
+    }
+
+    /// Closing price for all of `quantity`; whatever the book cannot absorb is marked at its
+    /// deepest recorded level
+    fn exit_price(&self, book: &OrderBook, buy: bool, quantity: f64) -> Option<f64> {
+        let (filled, price) = self.fill_price(book, buy, quantity)?;
+        let levels = if buy { &book.asks } else { &book.bids };
+        let deepest = levels.last()?[0];
+        Some((filled * price + (quantity - filled).max(0.0) * deepest) / quantity)
+    }
+
+    fn capacity(&self, book: &OrderBook, buy: bool) -> f64 {
+        let band = self.config.capacity_band_bps / 10_000.0;
+        let levels = if buy { &book.asks } else { &book.bids };
+        let Some(best) = levels.first().map(|level| level[0]) else {
+            return 0.0;
+        };
+        levels
+            .iter()
+            .filter(|level| {
+                if buy {
+                    level[0] <= best * (1.0 + band)
+                } else {
+                    level[0] >= best * (1.0 - band)
+                }
+            })
+            .map(|level| level[0] * level[1])
+            .sum()
+    }
+
+    fn open(
+        &self,
+        state: &ReplayState,
+        opportunity: &ArbitrageOpportunity,
+        now: u64,
+    ) -> Option<OpenTrade> {
+        let long = state.quote(opportunity.long_exchange, &opportunity.pair)?;
+        let short = state.quote(opportunity.short_exchange, &opportunity.pair)?;
+        let target = self.config.position_size_usd / long.book.best_ask()?;
+
+        // Both legs trade the smaller of what either book can fill
+        let (long_filled, _) = self.fill_price(&long.book, true, target)?;
+        let (short_filled, _) = self.fill_price(&short.book, false, target)?;
+        let quantity = long_filled.min(short_filled);
+        if quantity <= 0.0 {
+            return None;
+        }
+        let (_, long_entry) = self.fill_price(&long.book, true, quantity)?;
+        let (_, short_entry) = self.fill_price(&short.book, false, quantity)?;
+        let fees = quantity * (long_entry + short_entry) * self.config.taker_fee_rate;
+        let capacity = (long.depth && short.depth).then(|| {
+            self.capacity(&long.book, true)
+                .min(self.capacity(&short.book, false))
+        });
+
+        Some(OpenTrade {
+            trade: BacktestTrade {
+                pair: opportunity.pair.clone(),
+                strategy: opportunity.r#type.clone(),
+                long_exchange: opportunity.long_exchange,
+                short_exchange: opportunity.short_exchange,
+                opened_at: now,
+                closed_at: now,
+                quantity,
+                size_usd: quantity * long_entry,
+                long_entry,
+                short_entry,
+                long_exit: long_entry,
+                short_exit: short_entry,
+                rate_difference: opportunity.rate_difference,
+                net_rate_difference: opportunity.net_rate_difference,
+                fees_usd: fees,
+                funding_usd: 0.0,
+                pnl_usd: 0.0,
+                capacity_usd: capacity,
+                exit_reason: ExitReason::EndOfData,
+            },
+            last_funding_at: now,
+        })
+    }
+
+    fn accrue_funding(&self, state: &ReplayState, open: &mut OpenTrade, now: u64) {
+        let trade = &mut open.trade;
+        if trade.strategy != ArbitrageType::FundingRate {
+            return;
+        }
+        let interval = self.config.funding_interval_ms.max(1);
+        let boundaries = now / interval - open.last_funding_at / interval;
+        open.last_funding_at = now;
+        if boundaries == 0 {
+            return;
+        }
+        let leg = |exchange: ExchangeIdEnum| -> Option<f64> {
+            Some(state.rate(exchange, &trade.pair)? * state.mid(exchange, &trade.pair)?)
+        };
+        // The short leg receives its rate and the long leg pays its own
+        let short = leg(trade.short_exchange).unwrap_or(0.0);
+        let long = leg(trade.long_exchange).unwrap_or(0.0);
+        trade.funding_usd += boundaries as f64 * trade.quantity * (short - long);
+    }
+
+    fn exit_reason(&self, state: &ReplayState, open: &OpenTrade, now: u64) -> Option<ExitReason> {
+        let trade = &open.trade;
+        if now.saturating_sub(trade.opened_at) >= self.config.max_hold_ms {
+            return Some(ExitReason::MaxHold);
+        }
+        match trade.strategy {
+            ArbitrageType::FundingRate => {
+                let long = state.rate(trade.long_exchange, &trade.pair)?;
+                let short = state.rate(trade.short_exchange, &trade.pair)?;
+                (short - long <= self.config.exit_rate_difference).then_some(ExitReason::EdgeClosed)
+            }
+            _ => {
+                let long_bid = state
+                    .quote(trade.long_exchange, &trade.pair)?
+                    .book
+                    .best_bid()?;
+                let short_ask = state
+                    .quote(trade.short_exchange, &trade.pair)?
+                    .book
+                    .best_ask()?;
+                (long_bid >= short_ask).then_some(ExitReason::Converged)
+            }
+        }
+    }
+
+    fn close(
+        &self,
+        state: &ReplayState,
+        open: OpenTrade,
+        now: u64,
+        reason: ExitReason,
+    ) -> BacktestTrade {
+        let mut trade = open.trade;
+        let quantity = trade.quantity;
+        if let Some(quote) = state.quote(trade.long_exchange, &trade.pair) {
+            trade.long_exit = self
+                .exit_price(&quote.book, false, quantity)
+                .unwrap_or(trade.long_entry);
+        }
+        if let Some(quote) = state.quote(trade.short_exchange, &trade.pair) {
+            trade.short_exit = self
+                .exit_price(&quote.book, true, quantity)
+                .unwrap_or(trade.short_entry);
+        }
+        trade.fees_usd +=
+            quantity * (trade.long_exit + trade.short_exit) * self.config.taker_fee_rate;
+        trade.pnl_usd = quantity * (trade.long_exit - trade.long_entry)
+            + quantity * (trade.short_entry - trade.short_exit)
+            + trade.funding_usd
+            - trade.fees_usd;
+        trade.closed_at = now;
+        trade.exit_reason = reason;
+        trade
+    }
+
+    fn report(
+        &self,
+        trades: Vec<BacktestTrade>,
+        events: usize,
+        period_start: u64,
+        period_end: u64,
+    ) -> BacktestReport {
+        let total_pnl = trades
+            .iter()
+            .fold(0.0, |total, trade| total + trade.pnl_usd);
+        let mut equity = vec![self.config.starting_equity];
+        for trade in &trades {
+            equity.push(equity[equity.len() - 1] + trade.pnl_usd);
+        }
+        let (max_drawdown_usd, max_drawdown_percentage) = max_drawdown(&equity);
+
+        let first_day = period_start / DAY_MS;
+        let mut daily = vec![0.0; (period_end / DAY_MS - first_day + 1) as usize];
+        for trade in &trades {
+            daily[(trade.closed_at / DAY_MS - first_day) as usize] +=
+                trade.pnl_usd / self.config.starting_equity;
+        }
+
+        let wins = trades.iter().filter(|trade| trade.pnl_usd > 0.0).count();
+        let capacities: Vec<f64> = trades.iter().filter_map(|t| t.capacity_usd).collect();
+        BacktestReport {
+            threshold: self.config.threshold,
+            period_start,
+            period_end,
+            events,
+            total_pnl_usd: total_pnl,
+            return_percentage: total_pnl / self.config.starting_equity * 100.0,
+            max_drawdown_usd,
+            max_drawdown_percentage,
+            sharpe_ratio: sharpe_ratio(&daily),
+            hit_rate: if trades.is_empty() {
+                0.0
+            } else {
+                wins as f64 / trades.len() as f64
+            },
+            avg_capacity_usd: (!capacities.is_empty())
+                .then(|| capacities.iter().sum::<f64>() / capacities.len() as f64),
+            min_capacity_usd: capacities.iter().copied().reduce(f64::min),
+            trades,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+    const RECORDING: &str = include_str!("../../../test_utils/fixtures/backtest/recording.jsonl");
+
+    #[test]
+    fn test_replay_trades_funding_and_price_dislocations() {
+        let events = parse_events(RECORDING).unwrap();
+        let report = Backtester::new(BacktestConfig::default())
+            .run(&events)
+            .unwrap();
+
+        assert_eq!(report.trades.len(), 2);
+        let price = &report.trades[0];
+        assert_eq!(price.strategy, ArbitrageType::Price);
+        assert_eq!(price.pair, "ETHUSDT");
+        assert_eq!(price.long_exchange, ExchangeIdEnum::Binance);
+        assert_eq!(price.exit_reason, ExitReason::Converged);
+        // Binance only shows 2 ETH within reach, so the trade is capped at that
+        assert!((price.quantity - 2.0).abs() < 1e-9);
+        assert!(price.pnl_usd > 0.0);
+        assert_eq!(price.capacity_usd, Some(2.0 * 3_000.0));
+
+        let funding = &report.trades[1];
+        assert_eq!(funding.strategy, ArbitrageType::FundingRate);
+        assert_eq!(funding.short_exchange, ExchangeIdEnum::Bybit);
+        assert_eq!(funding.exit_reason, ExitReason::EdgeClosed);
+        assert_eq!(funding.closed_at, 16 * HOUR_MS + 1);
+        // Two settlements at the 0.30% difference recorded before each boundary
+        let expected = 2.0 * funding.quantity * 65_005.0 * 0.003;
+        assert!((funding.funding_usd - expected).abs() < 1e-6);
+        assert!(funding.pnl_usd > 0.0 && funding.pnl_usd < funding.funding_usd);
+        assert_eq!(funding.capacity_usd, None);
+
+        assert_eq!(report.hit_rate, 1.0);
+        assert_eq!(report.max_drawdown_usd, 0.0);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["trades"][1]["exit_reason"], "edge_closed");
+        assert!(report.telegram_summary().contains("*Trades*: 2"));
+    }
+
+    #[test]
+    fn test_threshold_sweep_and_metrics() {
+        let events = parse_events(RECORDING).unwrap();
+        let reports = Backtester::new(BacktestConfig::default())
+            .sweep_thresholds(&events, &[0.001, 0.004, 0.01])
+            .unwrap();
+        let trades: Vec<usize> = reports.iter().map(|r| r.trades.len()).collect();
+        // Only the 0.5% ETH dislocation clears 0.4%; nothing clears 1%
+        assert_eq!(trades, vec![2, 1, 0]);
+        assert_eq!(reports[2].hit_rate, 0.0);
+        assert!(reports[2].sharpe_ratio.is_none());
+
+        assert_eq!(max_drawdown(&[100.0, 120.0, 90.0, 130.0]), (30.0, 0.25));
+        assert!(sharpe_ratio(&[0.01, 0.01]).is_none());
+        let sharpe = sharpe_ratio(&[0.01, -0.01, 0.02]).unwrap();
+        assert!((sharpe - (0.02 / 3.0) / 0.015275252316519466 * 365.0_f64.sqrt()).abs() < 1e-6);
+
+        assert!(parse_events("{\"type\":\"ticker\"}")
+            .unwrap_err()
+            .to_string()
+            .contains("line 1"));
+    }
+}
@@ -753,6 +753,7 @@ mod tests {
             arbitrage_enabled: true,
             technical_enabled: true,
             advanced_analytics_enabled: true,
+            paper_trading: false,
             preferred_notification_channels: vec!["telegram".to_string(), "email".to_string()],
             trading_hours_timezone: "UTC".to_string(),
             trading_hours_start: "00:00".to_string(),
@@ -6,14 +6,17 @@
 //! technical analysis, and correlation analysis to support trading decisions.
 //!
 //! ## Services
+//! - `Backtester`: Historical replay of arbitrage strategies
 //! - `MarketAnalysisService`: Market data analysis and opportunity detection
 //! - `TechnicalAnalysisService`: Technical indicator analysis and signals
 //! - `CorrelationAnalysisService`: Cross-market correlation analysis
 
+pub mod backtester;
 pub mod correlation_analysis;
 pub mod market_analysis;
 pub mod technical_analysis;
 
+pub use backtester::{BacktestConfig, BacktestReport, Backtester, MarketEvent};
 pub use correlation_analysis::CorrelationAnalysisService;
 pub use market_analysis::MarketAnalysisService;
 pub use technical_analysis::TechnicalAnalysisService;
@@ -391,6 +391,7 @@ impl AICoordinator {
                     r#type: crate::types::ArbitrageType::CrossExchange,
                     details: Some("AI-generated placeholder opportunity".to_string()),
                     min_exchanges_required: 2,
+                    volume_24h: None,
                 };
 
                 match engine
@@ -677,6 +677,7 @@ impl EmbeddingEngine {
             r#type: ArbitrageType::FundingRate,
             details: Some("Test opportunity".to_string()),
             min_exchanges_required: 2,
+            volume_24h: None,
         }
     }
 
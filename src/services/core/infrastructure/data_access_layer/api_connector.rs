@@ -1,6 +1,7 @@
 // API Connector - Exchange API Integration with Rate Limiting Component
 // Provides unified API access with per-exchange rate limiting and intelligent retry logic
 
+use crate::utils::retry::{is_idempotent_method, sleep_ms, RetryPolicy};
 use crate::utils::{ArbitrageError, ArbitrageResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -400,6 +401,8 @@ pub struct APIConnectorConfig {
     pub enable_request_logging: bool,
     pub enable_response_caching: bool,
     pub cache_ttl_seconds: u64,
+    /// Backoff for idempotent requests; `max_retries` comes from each `ExchangeConfig`
+    pub retry_policy: RetryPolicy,
 }
 
 impl Default for APIConnectorConfig {
@@ -415,6 +418,7 @@ impl Default for APIConnectorConfig {
             enable_request_logging: true,
             enable_response_caching: false,
             cache_ttl_seconds: 300, // 5 minutes
+            retry_policy: RetryPolicy::default(),
         }
     }
 }
@@ -573,14 +577,18 @@ impl APIConnector {
         }
 
         let start_time = chrono::Utc::now().timestamp_millis() as u64;
-        let mut last_error = None;
-        let max_retries = if self.config.enable_retry_logic {
-            self.get_max_retries(&request.exchange).await
+        let retry_policy = if self.config.enable_retry_logic {
+            self.config
+                .retry_policy
+                .clone()
+                .with_max_retries(self.get_max_retries(&request.exchange).await)
         } else {
-            1
+            RetryPolicy::none()
         };
+        let idempotent = is_idempotent_method(&request.method);
+        let mut attempt = 0;
 
-        for attempt in 0..max_retries {
+        loop {
             // Check rate limiting
             if self.config.enable_rate_limiting && !self.check_rate_limit(&request.exchange).await {
                 let wait_time = self.get_rate_limit_wait_time(&request.exchange).await;
@@ -614,33 +622,27 @@ impl APIConnector {
                     self.decrement_active_requests().await;
                     return Ok(response);
                 }
-                Err(e) => {
-                    last_error = Some(e.clone());
-
-                    // Check if we should retry
-                    if attempt < max_retries - 1 && self.should_retry(&e) {
+                Err(e) => match retry_policy.retry_delay_ms(&e, attempt, idempotent) {
+                    Some(delay_ms) => {
                         self.record_retry(&request.exchange, start_time).await;
                         self.logger.warn(&format!(
-                            "Request failed, retrying (attempt {}/{}): {}",
+                            "Request failed, retrying in {}ms (attempt {}/{}): {}",
+                            delay_ms,
                             attempt + 1,
-                            max_retries,
+                            retry_policy.max_retries,
                             e
                         ));
-
-                        // Exponential backoff
-                        let _backoff_seconds = (2.0_f64.powf(attempt as f64)).clamp(1.0, 60.0);
-                        continue;
-                    } else {
+                        sleep_ms(delay_ms).await;
+                        attempt += 1;
+                    }
+                    None => {
                         self.record_failure(&request.exchange, start_time, &e).await;
-                        break;
+                        self.decrement_active_requests().await;
+                        return Err(e);
                     }
-                }
+                },
             }
         }
-
-        self.decrement_active_requests().await;
-        Err(last_error
-            .unwrap_or_else(|| ArbitrageError::parse_error("Request failed after all retries")))
     }
 
     /// Execute the actual HTTP request (placeholder implementation)
@@ -689,27 +691,6 @@ impl APIConnector {
         Ok(response)
     }
 
-    /// Check if request should be retried based on error type
-    fn should_retry(&self, error: &ArbitrageError) -> bool {
-        let error_msg = error.to_string().to_lowercase();
-
-        // Network and timeout errors are retryable
-        if error_msg.contains("network")
-            || error_msg.contains("timeout")
-            || error_msg.contains("service")
-        {
-            return true;
-        }
-
-        // Rate limit and auth errors are not retryable
-        if error_msg.contains("rate limit") || error_msg.contains("authentication") {
-            return false;
-        }
-
-        // Default to not retryable for unknown errors
-        false
-    }
-
     /// Check concurrent request limit
     async fn check_concurrent_limit(&self) -> bool {
         if let Ok(mut active) = self.active_requests.lock() {
@@ -2,8 +2,9 @@
 // Manages all specialized repository components and provides unified access
 
 use super::{
-    utils::*, InvitationRepository, InvitationRepositoryConfig, Repository, RepositoryConfig,
-    RepositoryHealth, RepositoryMetrics, UserRepository, UserRepositoryConfig,
+    utils::*, InvitationRepository, InvitationRepositoryConfig, PositionRepository,
+    PositionRepositoryConfig, Repository, RepositoryConfig, RepositoryHealth, RepositoryMetrics,
+    UserRepository, UserRepositoryConfig,
 };
 use crate::services::core::user::user_trading_preferences::UserTradingPreferences;
 use crate::utils::{ArbitrageError, ArbitrageResult};
@@ -212,6 +213,8 @@ pub struct DatabaseManager {
     // Specialized repositories
     user_repository: Option<Arc<UserRepository>>,
     invitation_repository: Option<Arc<InvitationRepository>>,
+    // Available from construction: its tables come from migrations
+    position_repository: Arc<PositionRepository>,
 
     // Manager metrics
     metrics: Arc<std::sync::Mutex<RepositoryMetrics>>,
@@ -232,6 +235,11 @@ impl DatabaseManager {
             last_updated: current_timestamp_ms(),
         };
 
+        let position_repository = Arc::new(PositionRepository::new(
+            db.clone(),
+            PositionRepositoryConfig::default(),
+        ));
+
         Self {
             db,
             config,
@@ -239,6 +247,7 @@ impl DatabaseManager {
             cache: Arc::new(std::sync::Mutex::new(None)),
             user_repository: None,
             invitation_repository: None,
+            position_repository,
             metrics: Arc::new(std::sync::Mutex::new(metrics)),
             startup_time: current_timestamp_ms(),
         }
@@ -319,6 +328,25 @@ impl DatabaseManager {
                 .register_metadata("invitation_repository".to_string(), invitation_registration)?;
         }
 
+        // Initialize PositionRepository
+        self.position_repository.initialize().await?;
+
+        // Register PositionRepository metadata
+        let position_registration = RepositoryRegistration {
+            name: "position_repository".to_string(),
+            repository_type: "PositionRepository".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Manages arbitrage positions and their status history".to_string(),
+            is_critical: false,
+            auto_initialize: true,
+            dependencies: vec!["user_repository".to_string()],
+            configuration: HashMap::new(),
+        };
+
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.register_metadata("position_repository".to_string(), position_registration)?;
+        }
+
         // Update metrics
         self.update_metrics(start_time, true).await;
 
@@ -335,6 +363,11 @@ impl DatabaseManager {
         self.invitation_repository.clone()
     }
 
+    /// Get PositionRepository
+    pub fn get_position_repository(&self) -> Arc<PositionRepository> {
+        self.position_repository.clone()
+    }
+
     /// Perform health check on all repositories
     pub async fn health_check_all_repositories(
         &self,
@@ -384,6 +417,25 @@ impl DatabaseManager {
             }
         }
 
+        // Check PositionRepository
+        match self.position_repository.health_check().await {
+            Ok(health) => {
+                health_results.insert("position_repository".to_string(), health);
+            }
+            Err(_e) => {
+                let health = RepositoryHealth {
+                    repository_name: "position_repository".to_string(),
+                    is_healthy: false,
+                    database_healthy: false,
+                    cache_healthy: true,
+                    last_health_check: current_timestamp_ms(),
+                    response_time_ms: (current_timestamp_ms() - start_time) as f64,
+                    error_rate: 100.0,
+                };
+                health_results.insert("position_repository".to_string(), health);
+            }
+        }
+
         // Update manager metrics
         self.update_metrics(start_time, true).await;
 
@@ -406,6 +458,12 @@ impl DatabaseManager {
             all_metrics.insert("invitation_repository".to_string(), metrics);
         }
 
+        // Get PositionRepository metrics
+        all_metrics.insert(
+            "position_repository".to_string(),
+            self.position_repository.get_metrics().await,
+        );
+
         all_metrics
     }
 
@@ -558,6 +616,11 @@ impl DatabaseManager {
         let start_time = current_timestamp_ms();
         let mut errors = Vec::new();
 
+        // Shutdown PositionRepository
+        if let Err(e) = self.position_repository.shutdown().await {
+            errors.push(format!("position_repository: {}", e));
+        }
+
         // Shutdown InvitationRepository
         if let Some(ref invitation_repo) = self.invitation_repository {
             if let Err(e) = invitation_repo.shutdown().await {
@@ -1176,6 +1239,32 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// Trading preferences of every user who allows semi- or fully-automated execution
+    pub async fn list_automated_trading_preferences(
+        &self,
+    ) -> ArbitrageResult<Vec<UserTradingPreferences>> {
+        let result = self
+            .query(
+                "SELECT preferences_data FROM trading_preferences
+                 WHERE json_extract(preferences_data, '$.automationLevel') IN ('semi_auto', 'full_auto')",
+                &[],
+            )
+            .await?;
+
+        let mut preferences = Vec::new();
+        for row in result.results::<HashMap<String, serde_json::Value>>()? {
+            let parsed = match row.get("preferences_data") {
+                Some(serde_json::Value::String(data)) => serde_json::from_str(data),
+                Some(data) => serde_json::from_value(data.clone()),
+                None => continue,
+            };
+            preferences.push(parsed.map_err(|e| {
+                ArbitrageError::parse_error(format!("Failed to parse preferences: {}", e))
+            })?);
+        }
+        Ok(preferences)
+    }
+
     /// Store AI analysis audit (AI Exchange Router compatibility)
     pub async fn store_ai_analysis_audit(
         &self,
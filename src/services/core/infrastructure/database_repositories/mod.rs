@@ -6,6 +6,7 @@ pub mod analytics_repository;
 pub mod config_repository;
 pub mod database_manager;
 pub mod invitation_repository;
+pub mod position_repository;
 pub mod user_repository;
 
 // Re-export main components for easy access
@@ -16,6 +17,9 @@ pub use database_manager::{DatabaseManager, DatabaseManagerConfig};
 pub use invitation_repository::{
     InvitationRepository, InvitationRepositoryConfig, InvitationStatistics, InvitationUsage,
 };
+pub use position_repository::{
+    PositionPage, PositionQuery, PositionRepository, PositionRepositoryConfig, PositionTransition,
+};
 pub use user_repository::{UserRepository, UserRepositoryConfig};
 
 use crate::utils::{ArbitrageError, ArbitrageResult};
@@ -0,0 +1,644 @@
+// Position Repository - Specialized Arbitrage Position Data Access Component
+// Handles position persistence, filtered listing and the append-only state history
+
+use super::{utils::*, Repository, RepositoryConfig, RepositoryHealth, RepositoryMetrics};
+use crate::types::{ArbitragePosition, ExchangeIdEnum, PositionSide, PositionStatus};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use worker::{wasm_bindgen::JsValue, D1Database};
+
+/// Configuration for PositionRepository
+#[derive(Debug, Clone)]
+pub struct PositionRepositoryConfig {
+    pub connection_pool_size: u32,
+    pub batch_size: usize,
+    pub cache_ttl_seconds: u64,
+    pub enable_metrics: bool,
+    /// Page size when a query does not ask for one
+    pub default_page_size: u32,
+    /// Upper bound on the page size a query may ask for
+    pub max_page_size: u32,
+}
+
+impl Default for PositionRepositoryConfig {
+    fn default() -> Self {
+        Self {
+            connection_pool_size: 20,
+            batch_size: 50,
+            cache_ttl_seconds: 60, // Positions change with every mark
+            enable_metrics: true,
+            default_page_size: 50,
+            max_page_size: 200,
+        }
+    }
+}
+
+impl RepositoryConfig for PositionRepositoryConfig {
+    fn validate(&self) -> ArbitrageResult<()> {
+        if self.connection_pool_size == 0 {
+            return Err(validation_error(
+                "connection_pool_size",
+                "must be greater than 0",
+            ));
+        }
+        if self.batch_size == 0 {
+            return Err(validation_error("batch_size", "must be greater than 0"));
+        }
+        if self.default_page_size == 0 || self.default_page_size > self.max_page_size {
+            return Err(validation_error(
+                "default_page_size",
+                "must be between 1 and max_page_size",
+            ));
+        }
+        Ok(())
+    }
+
+    fn connection_pool_size(&self) -> u32 {
+        self.connection_pool_size
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn cache_ttl_seconds(&self) -> u64 {
+        self.cache_ttl_seconds
+    }
+}
+
+/// Filters for listing positions; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PositionQuery {
+    pub user_id: Option<String>,
+    pub status: Option<PositionStatus>,
+    /// Matches either leg
+    pub exchange: Option<ExchangeIdEnum>,
+    pub pair: Option<String>,
+    /// Inclusive lower bound on `created_at` (ms)
+    pub created_from: Option<u64>,
+    /// Exclusive upper bound on `created_at` (ms)
+    pub created_to: Option<u64>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// One page of positions, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionPage {
+    pub positions: Vec<ArbitragePosition>,
+    /// Positions matching the filters across all pages
+    pub total: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// A recorded change of a position's status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionTransition {
+    pub position_id: String,
+    pub user_id: String,
+    /// `None` for the row written when the position was first stored
+    pub from_status: Option<PositionStatus>,
+    pub to_status: PositionStatus,
+    /// The position as it was right after the transition
+    pub snapshot: ArbitragePosition,
+    pub recorded_at: u64,
+}
+
+/// Bind parameter that can be built and inspected without a JS runtime
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SqlParam {
+    Text(String),
+    Number(f64),
+}
+
+impl From<&SqlParam> for JsValue {
+    fn from(param: &SqlParam) -> Self {
+        match param {
+            SqlParam::Text(text) => text.as_str().into(),
+            SqlParam::Number(number) => (*number).into(),
+        }
+    }
+}
+
+/// Column value of a status or side, matching its serde name
+fn enum_str<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_status(value: &str) -> Option<PositionStatus> {
+    serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+}
+
+/// `WHERE` clause (empty when nothing is filtered) and its parameters
+pub(crate) fn filter_clause(query: &PositionQuery) -> (String, Vec<SqlParam>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+
+    if let Some(user_id) = &query.user_id {
+        conditions.push("user_id = ?");
+        params.push(SqlParam::Text(user_id.clone()));
+    }
+    if let Some(status) = &query.status {
+        conditions.push("status = ?");
+        params.push(SqlParam::Text(enum_str(status)));
+    }
+    if let Some(exchange) = query.exchange {
+        conditions.push("(exchange = ? OR long_exchange = ? OR short_exchange = ?)");
+        for _ in 0..3 {
+            params.push(SqlParam::Text(exchange.as_str().to_string()));
+        }
+    }
+    if let Some(pair) = &query.pair {
+        conditions.push("pair = ?");
+        params.push(SqlParam::Text(pair.clone()));
+    }
+    if let Some(from) = query.created_from {
+        conditions.push("created_at >= ?");
+        params.push(SqlParam::Number(from as f64));
+    }
+    if let Some(to) = query.created_to {
+        conditions.push("created_at < ?");
+        params.push(SqlParam::Number(to as f64));
+    }
+
+    if conditions.is_empty() {
+        (String::new(), params)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    }
+}
+
+/// Inserts the position, or updates it unless that would move a closed or liquidated
+/// position to another status
+const UPSERT_SQL: &str = "INSERT INTO positions (
+        id, user_id, opportunity_id, exchange, pair, side, size, entry_price,
+        current_price, pnl, status, created_at, updated_at, closed_at,
+        long_exchange, short_exchange, position_data
+    ) VALUES (?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT(id) DO UPDATE SET
+        exchange = excluded.exchange,
+        pair = excluded.pair,
+        side = excluded.side,
+        size = excluded.size,
+        entry_price = excluded.entry_price,
+        current_price = excluded.current_price,
+        pnl = excluded.pnl,
+        status = excluded.status,
+        updated_at = excluded.updated_at,
+        closed_at = excluded.closed_at,
+        long_exchange = excluded.long_exchange,
+        short_exchange = excluded.short_exchange,
+        position_data = excluded.position_data
+    WHERE positions.status NOT IN ('closed', 'liquidated')
+        OR positions.status = excluded.status";
+
+/// Appends a history row when the position is new or its stored status is changing, under
+/// the same guard as `UPSERT_SQL`
+const HISTORY_SQL: &str = "INSERT INTO position_history (
+        position_id, user_id, from_status, to_status, snapshot, recorded_at
+    )
+    SELECT ?, ?, stored.status, ?, ?, ?
+    FROM (SELECT 1) LEFT JOIN positions AS stored ON stored.id = ?
+    WHERE stored.status IS NULL
+        OR (stored.status != ? AND stored.status NOT IN ('closed', 'liquidated'))";
+
+/// Parameters of `HISTORY_SQL`
+pub(crate) fn history_params(
+    position_id: &str,
+    user_id: &str,
+    status: &PositionStatus,
+    snapshot: &str,
+    recorded_at: u64,
+) -> Vec<SqlParam> {
+    let status = enum_str(status);
+    vec![
+        SqlParam::Text(position_id.to_string()),
+        SqlParam::Text(user_id.to_string()),
+        SqlParam::Text(status.clone()),
+        SqlParam::Text(snapshot.to_string()),
+        SqlParam::Number(recorded_at as f64),
+        SqlParam::Text(position_id.to_string()),
+        SqlParam::Text(status),
+    ]
+}
+
+/// Position repository backed by the `positions` and `position_history` tables
+pub struct PositionRepository {
+    db: Arc<D1Database>,
+    config: PositionRepositoryConfig,
+    metrics: Arc<std::sync::Mutex<RepositoryMetrics>>,
+}
+
+impl PositionRepository {
+    /// Create new PositionRepository
+    pub fn new(db: Arc<D1Database>, config: PositionRepositoryConfig) -> Self {
+        let metrics = RepositoryMetrics {
+            repository_name: "position_repository".to_string(),
+            total_operations: 0,
+            successful_operations: 0,
+            failed_operations: 0,
+            avg_response_time_ms: 0.0,
+            operations_per_second: 0.0,
+            cache_hit_rate: 0.0,
+            last_updated: current_timestamp_ms(),
+        };
+
+        Self {
+            db,
+            config,
+            metrics: Arc::new(std::sync::Mutex::new(metrics)),
+        }
+    }
+
+    // ============= POSITION OPERATIONS =============
+
+    /// Insert or update a position. A change of status (or a first insert) appends a
+    /// history row in the same batch, so the row and its history cannot diverge.
+    pub async fn save_position(&self, position: &ArbitragePosition) -> ArbitrageResult<()> {
+        let start_time = current_timestamp_ms();
+        let result = self.save_position_internal(position).await;
+        self.update_metrics(start_time, result.is_ok()).await;
+        result
+    }
+
+    /// Get a position by ID
+    pub async fn get_position(
+        &self,
+        position_id: &str,
+    ) -> ArbitrageResult<Option<ArbitragePosition>> {
+        let start_time = current_timestamp_ms();
+
+        let result = self
+            .db
+            .prepare("SELECT position_data FROM positions WHERE id = ?")
+            .bind(&[position_id.into()])
+            .map_err(|e| database_error("bind parameters", e))?
+            .first::<HashMap<String, serde_json::Value>>(None)
+            .await
+            .map_err(|e| database_error("execute query", e));
+
+        self.update_metrics(start_time, result.is_ok()).await;
+
+        match result? {
+            Some(row) => Ok(Some(self.row_to_position(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List positions matching `query`, newest first
+    pub async fn query_positions(&self, query: &PositionQuery) -> ArbitrageResult<PositionPage> {
+        let start_time = current_timestamp_ms();
+        let result = self.query_positions_internal(query).await;
+        self.update_metrics(start_time, result.is_ok()).await;
+        result
+    }
+
+    /// Status transitions of a position, oldest first
+    pub async fn get_position_history(
+        &self,
+        position_id: &str,
+    ) -> ArbitrageResult<Vec<PositionTransition>> {
+        let start_time = current_timestamp_ms();
+
+        let results = self
+            .db
+            .prepare(
+                "SELECT * FROM position_history WHERE position_id = ? ORDER BY recorded_at, id",
+            )
+            .bind(&[position_id.into()])
+            .map_err(|e| database_error("bind parameters", e))?
+            .all()
+            .await
+            .map_err(|e| database_error("execute query", e));
+
+        self.update_metrics(start_time, results.is_ok()).await;
+
+        let mut transitions = Vec::new();
+        for row in results?.results::<HashMap<String, serde_json::Value>>()? {
+            transitions.push(self.row_to_transition(&row)?);
+        }
+        Ok(transitions)
+    }
+
+    // ============= INTERNAL HELPER METHODS =============
+
+    async fn save_position_internal(&self, position: &ArbitragePosition) -> ArbitrageResult<()> {
+        validate_required_string(&position.id, "id")?;
+        validate_required_string(&position.user_id, "user_id")?;
+
+        let position_json = serde_json::to_string(position).map_err(|e| {
+            ArbitrageError::parse_error(format!("Failed to serialize position: {}", e))
+        })?;
+        let status = enum_str(&position.status);
+        let entry_price = match position.side {
+            PositionSide::Short => position.entry_price_short,
+            PositionSide::Long | PositionSide::Both => position.entry_price_long,
+        };
+        let optional = |value: Option<f64>| value.map(JsValue::from).unwrap_or(JsValue::NULL);
+
+        // The history row goes first so it sees the status being replaced; both statements
+        // carry the same guard, so a concurrent writer can neither reopen a settled position
+        // nor record a transition that did not happen
+        let history_params = history_params(
+            &position.id,
+            &position.user_id,
+            &position.status,
+            &position_json,
+            current_timestamp_ms(),
+        );
+        let history = self
+            .db
+            .prepare(HISTORY_SQL)
+            .bind(&history_params.iter().map(JsValue::from).collect::<Vec<_>>())
+            .map_err(|e| database_error("bind parameters", e))?;
+
+        // opportunity_id references the opportunities table, which does not hold every
+        // opportunity a position is opened from; the id is kept in position_data instead
+        let upsert = self
+            .db
+            .prepare(UPSERT_SQL)
+            .bind(&[
+                position.id.as_str().into(),
+                position.user_id.as_str().into(),
+                position.exchange.as_str().into(),
+                position.pair.as_str().into(),
+                enum_str(&position.side).into(),
+                position
+                    .size
+                    .unwrap_or(position.long_position.amount)
+                    .into(),
+                entry_price.into(),
+                optional(position.current_price),
+                optional(position.pnl),
+                status.as_str().into(),
+                (position.created_at as f64).into(),
+                (position.updated_at as f64).into(),
+                optional(position.closed_at.map(|closed_at| closed_at as f64)),
+                position.long_exchange.as_str().into(),
+                position.short_exchange.as_str().into(),
+                position_json.as_str().into(),
+            ])
+            .map_err(|e| database_error("bind parameters", e))?;
+
+        let statements = vec![history, upsert];
+        self.db
+            .batch(statements)
+            .await
+            .map_err(|e| database_error("save position", e))?;
+        Ok(())
+    }
+
+    async fn query_positions_internal(
+        &self,
+        query: &PositionQuery,
+    ) -> ArbitrageResult<PositionPage> {
+        let limit = query
+            .limit
+            .unwrap_or(self.config.default_page_size)
+            .clamp(1, self.config.max_page_size);
+        let offset = query.offset.unwrap_or(0);
+        let (filter, params) = filter_clause(query);
+        let mut bindings: Vec<JsValue> = params.iter().map(JsValue::from).collect();
+
+        let total = self
+            .db
+            .prepare(format!("SELECT COUNT(*) AS total FROM positions{}", filter))
+            .bind(&bindings)
+            .map_err(|e| database_error("bind parameters", e))?
+            .first::<f64>(Some("total"))
+            .await
+            .map_err(|e| database_error("count positions", e))?
+            .unwrap_or(0.0) as u64;
+
+        bindings.push((limit as f64).into());
+        bindings.push((offset as f64).into());
+        let results = self
+            .db
+            .prepare(format!(
+                "SELECT position_data FROM positions{} ORDER BY created_at DESC, id LIMIT ? OFFSET ?",
+                filter
+            ))
+            .bind(&bindings)
+            .map_err(|e| database_error("bind parameters", e))?
+            .all()
+            .await
+            .map_err(|e| database_error("execute query", e))?;
+
+        let mut positions = Vec::new();
+        for row in results.results::<HashMap<String, serde_json::Value>>()? {
+            // Rows written before position_data existed cannot be rebuilt in full
+            if let Ok(position) = self.row_to_position(&row) {
+                positions.push(position);
+            }
+        }
+
+        Ok(PositionPage {
+            positions,
+            total,
+            limit,
+            offset,
+        })
+    }
+
+    fn row_to_position(
+        &self,
+        row: &HashMap<String, serde_json::Value>,
+    ) -> ArbitrageResult<ArbitragePosition> {
+        let data = get_string_field(row, "position_data")?;
+        serde_json::from_str(&data).map_err(|e| {
+            ArbitrageError::parse_error(format!("Failed to parse position_data: {}", e))
+        })
+    }
+
+    fn row_to_transition(
+        &self,
+        row: &HashMap<String, serde_json::Value>,
+    ) -> ArbitrageResult<PositionTransition> {
+        let to_status = get_string_field(row, "to_status")?;
+        let snapshot = get_string_field(row, "snapshot")?;
+
+        Ok(PositionTransition {
+            position_id: get_string_field(row, "position_id")?,
+            user_id: get_string_field(row, "user_id")?,
+            from_status: get_optional_string_field(row, "from_status")
+                .and_then(|status| parse_status(&status)),
+            to_status: parse_status(&to_status).ok_or_else(|| {
+                ArbitrageError::parse_error(format!("Unknown position status: {}", to_status))
+            })?,
+            snapshot: serde_json::from_str(&snapshot).map_err(|e| {
+                ArbitrageError::parse_error(format!("Failed to parse history snapshot: {}", e))
+            })?,
+            recorded_at: get_f64_field(row, "recorded_at", 0.0) as u64,
+        })
+    }
+
+    // ============= METRICS METHODS =============
+
+    async fn update_metrics(&self, start_time: u64, success: bool) {
+        if !self.config.enable_metrics {
+            return;
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.total_operations += 1;
+
+            if success {
+                metrics.successful_operations += 1;
+            } else {
+                metrics.failed_operations += 1;
+            }
+
+            let response_time = current_timestamp_ms() - start_time;
+            let total_time = metrics.avg_response_time_ms * (metrics.total_operations - 1) as f64
+                + response_time as f64;
+            metrics.avg_response_time_ms = total_time / metrics.total_operations as f64;
+
+            metrics.last_updated = current_timestamp_ms();
+        }
+    }
+}
+
+impl Repository for PositionRepository {
+    fn name(&self) -> &str {
+        "position_repository"
+    }
+
+    async fn health_check(&self) -> ArbitrageResult<RepositoryHealth> {
+        let start_time = current_timestamp_ms();
+
+        let db_healthy = (self
+            .db
+            .prepare("SELECT 1 FROM position_history LIMIT 1")
+            .first::<serde_json::Value>(None)
+            .await)
+            .is_ok();
+
+        let end_time = current_timestamp_ms();
+
+        Ok(RepositoryHealth {
+            repository_name: "position_repository".to_string(),
+            is_healthy: db_healthy,
+            database_healthy: db_healthy,
+            cache_healthy: true, // No cache configured
+            last_health_check: end_time,
+            response_time_ms: (end_time - start_time) as f64,
+            error_rate: 0.0,
+        })
+    }
+
+    async fn get_metrics(&self) -> RepositoryMetrics {
+        if let Ok(metrics) = self.metrics.lock() {
+            metrics.clone()
+        } else {
+            RepositoryMetrics::default()
+        }
+    }
+
+    async fn initialize(&self) -> ArbitrageResult<()> {
+        // The positions columns are added by migration 016; the history table is
+        // created here as well so a fresh database can record transitions
+        let statements = [
+            "CREATE TABLE IF NOT EXISTS position_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                position_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                from_status TEXT,
+                to_status TEXT NOT NULL,
+                snapshot TEXT NOT NULL,
+                recorded_at INTEGER NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_position_history_position
+                ON position_history(position_id, recorded_at)",
+            "CREATE TRIGGER IF NOT EXISTS position_history_no_update
+                BEFORE UPDATE ON position_history
+                BEGIN SELECT RAISE(ABORT, 'position_history is append-only'); END",
+            "CREATE TRIGGER IF NOT EXISTS position_history_no_delete
+                BEFORE DELETE ON position_history
+                BEGIN SELECT RAISE(ABORT, 'position_history is append-only'); END",
+        ];
+
+        for sql in statements {
+            self.db
+                .prepare(sql)
+                .run()
+                .await
+                .map_err(|e| database_error("create position_history", e))?;
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown(&self) -> ArbitrageResult<()> {
+        // No specific cleanup needed for this repository
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_repository_config_validation() {
+        let mut config = PositionRepositoryConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.default_page_size = config.max_page_size + 1;
+        assert!(config.validate().is_err());
+
+        config.default_page_size = 50;
+        config.batch_size = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_filter_clause_combines_all_filters() {
+        let query = PositionQuery {
+            user_id: Some("user_1".to_string()),
+            status: Some(PositionStatus::Open),
+            exchange: Some(ExchangeIdEnum::Bybit),
+            pair: Some("BTC/USDT".to_string()),
+            created_from: Some(1_000),
+            created_to: Some(2_000),
+            ..Default::default()
+        };
+
+        let (clause, params) = filter_clause(&query);
+
+        assert_eq!(
+            clause,
+            " WHERE user_id = ? AND status = ? \
+             AND (exchange = ? OR long_exchange = ? OR short_exchange = ?) \
+             AND pair = ? AND created_at >= ? AND created_at < ?"
+        );
+        assert_eq!(params.len(), 8);
+        assert_eq!(params[1], SqlParam::Text("open".to_string()));
+        assert_eq!(params[4], SqlParam::Text("bybit".to_string()));
+        assert_eq!(params[7], SqlParam::Number(2_000.0));
+        assert_eq!(filter_clause(&PositionQuery::default()).0, "");
+    }
+
+    #[test]
+    fn test_history_insert_guards_status_in_the_same_statement() {
+        let params = history_params("pos_1", "user_1", &PositionStatus::Closed, "{}", 1_000);
+
+        assert_eq!(
+            HISTORY_SQL.matches('?').count(),
+            params.len(),
+            "every placeholder is bound"
+        );
+        assert_eq!(params[2], SqlParam::Text("closed".to_string()));
+        assert_eq!(params[5], SqlParam::Text("pos_1".to_string()));
+        assert_eq!(params[6], SqlParam::Text("closed".to_string()));
+        for sql in [HISTORY_SQL, UPSERT_SQL] {
+            assert!(sql.contains("NOT IN ('closed', 'liquidated')"));
+        }
+        assert_eq!(UPSERT_SQL.matches('?').count(), 16);
+        assert_eq!(parse_status("liquidated"), Some(PositionStatus::Liquidated));
+    }
+}
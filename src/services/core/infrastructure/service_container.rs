@@ -1,4 +1,8 @@
-// use crate::services::core::ai::ai_intelligence::AIIntelligenceService;
+use crate::services::core::ai::ai_integration::{AiIntegrationConfig, AiIntegrationService};
+use crate::services::core::ai::ai_intelligence::{AiIntelligenceConfig, AiIntelligenceService};
+use crate::services::core::analysis::correlation_analysis::{
+    CorrelationAnalysisConfig, CorrelationAnalysisService,
+};
 // use crate::services::core::analysis::correlation_analysis::CorrelationAnalysisService;
 // use crate::services::core::analysis::portfolio_analyzer::PortfolioAnalyzer;
 // use crate::services::core::analysis::risk_assessment::RiskAssessmentService;
@@ -12,17 +16,38 @@ use crate::services::core::infrastructure::database_repositories::{
     DatabaseManager, DatabaseManagerConfig,
 };
 // use crate::services::core::infrastructure::queue_manager::QueueManager;
+use crate::services::core::opportunities::opportunity_categorization::OpportunityCategorizationService;
 use crate::services::core::opportunities::opportunity_distribution::OpportunityDistributionService;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::opportunities::opportunity_lifecycle::OpportunityLifecycleTracker;
 // use crate::services::core::opportunities::opportunity_engine::OpportunityEngine;
+use crate::services::core::trading::ai_exchange_router::{
+    AiExchangeRouterConfig, AiExchangeRouterService,
+};
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::arbitrage_executor::{
+    ArbitrageExecutor, ExecutionApprovals, ExecutionOutcome, ProductionArbitrageExecutor,
+};
 use crate::services::core::trading::exchange::ExchangeService;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::positions::PositionsService;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::risk_gate::{PositionRiskCheck, RiskGate};
+#[cfg(target_arch = "wasm32")]
+use crate::types::RiskManagementConfig;
 // use crate::services::core::trading::position_manager::PositionManager;
+use crate::services::core::infrastructure::{D1Service, D1ServiceConfig};
+use crate::services::core::user::dynamic_config::DynamicConfigService;
 use crate::services::core::user::session_management::SessionManagementService;
+use crate::services::core::user::user_exchange_api::UserExchangeApiService;
 use crate::services::core::user::user_profile::UserProfileService;
+use crate::services::core::user::user_trading_preferences::UserTradingPreferencesService;
 // use crate::services::core::user::user_activity::UserActivityService;
 // use crate::services::core::user::group_management::GroupManagementService;
 use crate::services::interfaces::telegram::TelegramService;
 // use crate::services::core::admin::{AdminService, UserManagementService, SystemConfigService, MonitoringService, AuditService};
 use crate::utils::feature_flags::{load_feature_flags, FeatureFlags};
+use crate::utils::logger::{LogLevel, Logger};
 use crate::utils::{ArbitrageError, ArbitrageResult};
 use std::sync::Arc;
 // use worker::console_log;
@@ -37,9 +62,13 @@ pub struct ServiceContainer {
     pub telegram_service: Option<Arc<TelegramService>>,
     pub exchange_service: Arc<ExchangeService>,
     pub user_profile_service: Option<Arc<UserProfileService>>,
+    pub user_exchange_api_service: Option<Arc<UserExchangeApiService>>,
+    #[cfg(target_arch = "wasm32")]
+    pub arbitrage_executor: Arc<ProductionArbitrageExecutor>,
     // pub admin_service: Option<Arc<AdminService>>, // Temporarily commented out
     // pub auth_service: Option<Arc<AuthService>>, // Commented out until AuthService is implemented
     // pub ai_coordinator: Option<Arc<AICoordinator>>, // Commented out until AICoordinator is implemented
+    pub ai_intelligence_service: Option<Arc<AiIntelligenceService>>,
     pub data_ingestion_module: Option<Arc<DataIngestionModule>>,
     pub database_manager: DatabaseManager,
     pub data_access_layer: DataAccessLayer,
@@ -72,7 +101,22 @@ impl ServiceContainer {
                 ))
             })?;
 
-        let exchange_service = Arc::new(ExchangeService::new(custom_env)?);
+        // Orders are held to the default limits plus admins' per-user overrides
+        #[cfg(target_arch = "wasm32")]
+        let risk_gate = RiskGate::new(RiskManagementConfig::default()).with_overrides(Arc::new(
+            DynamicConfigService::new(database_manager.clone(), kv_store.clone()),
+        ));
+        #[cfg_attr(not(target_arch = "wasm32"), allow(unused_mut))]
+        let mut exchange_service = ExchangeService::new(custom_env)?;
+        #[cfg(target_arch = "wasm32")]
+        exchange_service.set_risk_check(Arc::new(PositionRiskCheck::new(
+            risk_gate.clone(),
+            Arc::new(
+                PositionsService::new(Arc::new(kv_store.clone()))
+                    .with_position_repository(database_manager.get_position_repository()),
+            ),
+        )));
+        let exchange_service = Arc::new(exchange_service);
 
         // Fetch ENCRYPTION_KEY from environment for UserProfileService
         let encryption_key = env
@@ -88,7 +132,43 @@ impl ServiceContainer {
         let user_profile_service_instance = Arc::new(UserProfileService::new(
             data_access_layer.get_kv_store(),
             database_manager.clone(),
+            encryption_key.clone(),
+        ));
+
+        let user_exchange_api_service = match D1Service::new(env, D1ServiceConfig::default()).await
+        {
+            Ok(d1_service) => Some(Arc::new(UserExchangeApiService::new(
+                user_profile_service_instance.clone(),
+                exchange_service.clone(),
+                Arc::new(d1_service),
+                kv_store.clone(),
+                secrecy::SecretString::new(encryption_key.clone()),
+            ))),
+            Err(e) => {
+                worker::console_log!("⚠️ User API key service unavailable: {}", e);
+                None
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let arbitrage_executor = Arc::new(
+            ArbitrageExecutor::new(
+                exchange_service.clone(),
+                Arc::new(
+                    PositionsService::new(Arc::new(kv_store.clone()))
+                        .with_position_repository(database_manager.get_position_repository()),
+                ),
+            )
+            .with_approvals(ExecutionApprovals::new(Arc::new(kv_store.clone())))
+            .with_risk_gate(risk_gate),
+        );
+
+        let ai_intelligence_service = Arc::new(Self::create_ai_intelligence_service(
+            &database_manager,
+            data_access_layer.get_kv_store(),
+            (*user_profile_service_instance).clone(),
             encryption_key,
+            &exchange_service,
         ));
 
         let session_service_instance = Arc::new(SessionManagementService::new(
@@ -111,6 +191,10 @@ impl ServiceContainer {
             telegram_service: None,
             exchange_service,
             user_profile_service: Some(user_profile_service_instance),
+            user_exchange_api_service,
+            #[cfg(target_arch = "wasm32")]
+            arbitrage_executor,
+            ai_intelligence_service: Some(ai_intelligence_service),
             // admin_service: Some(Arc::new(admin_service)),
             data_ingestion_module: None,
             database_manager,
@@ -119,6 +203,57 @@ impl ServiceContainer {
         })
     }
 
+    /// Create AiIntelligenceService with its sub-services, reading live order book depth
+    /// through the shared exchange service
+    fn create_ai_intelligence_service(
+        database_manager: &DatabaseManager,
+        kv_store: KvStore,
+        user_profile_service: UserProfileService,
+        encryption_key: String,
+        exchange_service: &Arc<ExchangeService>,
+    ) -> AiIntelligenceService {
+        let ai_router = AiExchangeRouterService::new(
+            AiExchangeRouterConfig::default(),
+            AiIntegrationService::new(
+                AiIntegrationConfig::default(),
+                kv_store.clone(),
+                encryption_key,
+            ),
+            user_profile_service,
+            database_manager.clone(),
+            kv_store.clone(),
+        );
+        let preferences_service = UserTradingPreferencesService::new(
+            database_manager.clone(),
+            Logger::new(LogLevel::Info),
+        );
+        let categorization_service = OpportunityCategorizationService::new(
+            database_manager.clone(),
+            preferences_service.clone(),
+            Logger::new(LogLevel::Info),
+        );
+
+        let mut ai_intelligence_service = AiIntelligenceService::new(
+            AiIntelligenceConfig::default(),
+            ai_router,
+            categorization_service,
+            #[cfg(target_arch = "wasm32")]
+            PositionsService::new(Arc::new(kv_store.clone()))
+                .with_position_repository(database_manager.get_position_repository()),
+            DynamicConfigService::new(database_manager.clone(), kv_store.clone()),
+            preferences_service,
+            CorrelationAnalysisService::new(
+                CorrelationAnalysisConfig::default(),
+                Logger::new(LogLevel::Info),
+            ),
+            database_manager.clone(),
+            kv_store,
+            None,
+        );
+        ai_intelligence_service.set_exchange_service((**exchange_service).clone());
+        ai_intelligence_service
+    }
+
     /// Create AdminService with all sub-services
     /// TODO: Implement AdminService when ready
     /// Get admin service for super admin operations
@@ -139,7 +274,26 @@ impl ServiceContainer {
     }
 
     /// Set the Telegram service for push notifications using Arc for shared ownership
-    pub fn set_telegram_service(&mut self, telegram_service: TelegramService) {
+    pub fn set_telegram_service(&mut self, mut telegram_service: TelegramService) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            telegram_service.set_arbitrage_executor(self.arbitrage_executor.clone());
+            telegram_service.set_positions_service(
+                PositionsService::new(Arc::new(self.data_access_layer.get_kv_store()))
+                    .with_position_repository(self.database_manager.get_position_repository()),
+            );
+        }
+        telegram_service.set_d1_service(self.database_manager.clone());
+        telegram_service.set_user_trading_preferences_service(UserTradingPreferencesService::new(
+            self.database_manager.clone(),
+            Logger::new(LogLevel::Info),
+        ));
+        if let Some(user_profile_service) = &self.user_profile_service {
+            telegram_service.set_user_profile_service((**user_profile_service).clone());
+        }
+        if let Some(api_key_service) = &self.user_exchange_api_service {
+            telegram_service.set_user_exchange_api_service(api_key_service.clone());
+        }
         let arc_telegram_service = Arc::new(telegram_service);
         self.distribution_service
             .set_notification_sender(Box::new((*arc_telegram_service).clone()));
@@ -251,6 +405,11 @@ impl ServiceContainer {
         self.user_profile_service.as_ref()
     }
 
+    /// Get AI intelligence service
+    pub fn ai_intelligence_service(&self) -> Option<&Arc<AiIntelligenceService>> {
+        self.ai_intelligence_service.as_ref()
+    }
+
     /// Get auth service
     /// TODO: Implement AuthService when ready
     pub fn data_ingestion_module(&self) -> Option<&Arc<DataIngestionModule>> {
@@ -375,6 +534,20 @@ impl ServiceContainer {
                 "Telegram service not configured for distribution",
             ));
         }
+        // Scans mint fresh ids each cycle; only (re)detected or materially changed
+        // opportunities go out, under their stable ids
+        #[cfg(target_arch = "wasm32")]
+        let tracked: Vec<crate::types::ArbitrageOpportunity> =
+            OpportunityLifecycleTracker::new(Arc::new(self.data_access_layer.get_kv_store()))
+                .observe(opportunities, chrono::Utc::now().timestamp_millis() as u64)
+                .await?
+                .into_iter()
+                .filter(|update| update.notify)
+                .map(|update| update.opportunity)
+                .collect();
+        #[cfg(target_arch = "wasm32")]
+        let opportunities = tracked.as_slice();
+
         let mut distributed_count = 0;
         for opportunity in opportunities {
             match self
@@ -393,8 +566,112 @@ impl ServiceContainer {
                 }
             }
         }
+
+        #[cfg(target_arch = "wasm32")]
+        if let Err(e) = self.run_automated_execution(opportunities).await {
+            worker::console_error!("Automated execution failed: {}", e);
+        }
         Ok(distributed_count)
     }
+
+    /// Hand opportunities to users who enabled automation: full-auto users trade them
+    /// straight away, semi-auto users are asked on Telegram to `/approve` or `/reject` them.
+    /// Opportunities on exchanges the user has no API key for are left out.
+    #[cfg(target_arch = "wasm32")]
+    async fn run_automated_execution(
+        &self,
+        opportunities: &[crate::types::ArbitrageOpportunity],
+    ) -> ArbitrageResult<()> {
+        use crate::services::core::opportunities::opportunity_distribution::NotificationSender;
+        use crate::services::core::trading::paper_exchange::paper_credentials;
+        use crate::services::interfaces::telegram::telegram::{
+            format_execution_result, format_pending_execution,
+        };
+        use crate::utils::formatter::escape_markdown_v2;
+
+        let (Some(telegram_service), Some(user_profile_service), Some(api_key_service)) = (
+            &self.telegram_service,
+            &self.user_profile_service,
+            &self.user_exchange_api_service,
+        ) else {
+            return Ok(());
+        };
+
+        for preferences in self
+            .database_manager
+            .list_automated_trading_preferences()
+            .await?
+        {
+            let Some(profile) = user_profile_service
+                .get_user_profile(&preferences.user_id)
+                .await?
+            else {
+                continue;
+            };
+            let credentials: Vec<_> = if preferences.paper_trading {
+                paper_credentials(
+                    &profile.user_id,
+                    &self.exchange_service.supported_exchanges(),
+                )
+            } else {
+                match api_key_service.get_user_api_keys(&profile.user_id).await {
+                    Ok(keys) => keys
+                        .into_iter()
+                        .map(|(_, credentials)| credentials)
+                        .collect(),
+                    Err(e) => {
+                        worker::console_log!(
+                            "⚠️ Skipping automation for {} - API keys unavailable: {}",
+                            profile.user_id,
+                            e
+                        );
+                        continue;
+                    }
+                }
+            };
+            let executor = self.arbitrage_executor.for_preferences(&preferences);
+            let has_key = |exchange| credentials.iter().any(|c| c.exchange == exchange);
+
+            for opportunity in opportunities
+                .iter()
+                .filter(|o| has_key(o.long_exchange) && has_key(o.short_exchange))
+            {
+                let message = match executor
+                    .handle_opportunity(
+                        &preferences,
+                        opportunity,
+                        &credentials,
+                        profile.risk_profile.max_position_size_usd,
+                    )
+                    .await
+                {
+                    Ok(ExecutionOutcome::Executed(position)) => format_execution_result(&position),
+                    Ok(ExecutionOutcome::AwaitingApproval(pending)) => {
+                        format_pending_execution(&pending)
+                    }
+                    Ok(ExecutionOutcome::Skipped(_)) => continue,
+                    Err(e) => {
+                        worker::console_error!(
+                            "Automated execution of {} for {} failed: {}",
+                            opportunity.id,
+                            profile.user_id,
+                            e
+                        );
+                        continue;
+                    }
+                };
+                if let Some(chat_id) = profile.telegram_user_id {
+                    NotificationSender::send_message(
+                        telegram_service.as_ref(),
+                        &chat_id.to_string(),
+                        &escape_markdown_v2(&message),
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Health status of all services in the container
@@ -201,6 +201,7 @@ impl AccessManager {
                             default_leverage: 1,
                             exchange_type: "spot".to_string(),
                             is_testnet: api_key.is_testnet,
+                            user_id: Some(user_id.to_string()),
                         };
                         exchanges.push((exchange_id, credentials));
                     }
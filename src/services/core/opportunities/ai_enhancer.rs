@@ -551,6 +551,7 @@ impl AIEnhancer {
                 tech_opp.signal_type, tech_opp.confidence
             )),
             min_exchanges_required: 1, // Technical only needs one exchange
+            volume_24h: None,
         }
     }
 }
@@ -598,6 +599,7 @@ mod tests {
             r#type: ArbitrageType::CrossExchange,
             details: Some("Test opportunity".to_string()),
             min_exchanges_required: 2,
+            volume_24h: None,
         }
     }
 
@@ -673,6 +675,7 @@ mod tests {
                 tech_opp.signal_type, tech_opp.confidence
             )),
             min_exchanges_required: 2, // Arbitrage typically requires 2
+            volume_24h: None,
         };
 
         assert_eq!(converted.pair, "ETHUSDT");
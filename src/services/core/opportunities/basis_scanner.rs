@@ -0,0 +1,317 @@
+// src/services/core/opportunities/basis_scanner.rs
+
+//! Spot–futures basis (cash-and-carry) scanner.
+//!
+//! For every spot market with a perpetual or dated future on the same exchange, the scanner
+//! prices buying spot at the ask and shorting the future at the bid. Dated futures converge
+//! at delivery, so their basis is annualized over the days left to expiry. Perpetuals never
+//! deliver: their basis is annualized over an assumed holding period and the short leg also
+//! collects (or pays) funding, which is added to give the funding-adjusted carry.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::core::analysis::market_analysis::{
+    OpportunityType, RiskLevel, TimeHorizon, TradingOpportunity,
+};
+use crate::services::core::opportunities::intra_exchange::{book_key, MarketBooks};
+use crate::types::{ExchangeIdEnum, FundingRateInfo, Market};
+
+const DAY_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Indicator tag that routes a `TradingOpportunity` into the basis carry category
+pub const BASIS_CARRY_INDICATOR: &str = "basis_carry";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasisScanConfig {
+    /// Days a perpetual carry is assumed to be held before its basis converges
+    pub perpetual_holding_days: f64,
+    /// Smallest funding-adjusted carry, annualized, worth reporting
+    pub min_annualized_carry: f64,
+}
+
+impl Default for BasisScanConfig {
+    fn default() -> Self {
+        Self {
+            perpetual_holding_days: 30.0,
+            min_annualized_carry: 0.05,
+        }
+    }
+}
+
+/// Long spot / short futures carry on one exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasisCarry {
+    pub exchange: ExchangeIdEnum,
+    pub spot_symbol: String,
+    pub futures_symbol: String,
+    /// Delivery time of a dated future; `None` for perpetuals
+    pub expiry: Option<u64>,
+    pub spot_price: f64,
+    pub futures_price: f64,
+    /// Futures premium over spot as a fraction of the spot price
+    pub basis: f64,
+    pub annualized_basis: f64,
+    /// Latest funding rate per settlement; perpetuals only
+    pub funding_rate: Option<f64>,
+    /// Funding the short leg collects per year at the latest rate
+    pub annualized_funding: f64,
+    /// Round-trip taker fees on both legs as a fraction of notional
+    pub fees: f64,
+    /// Basis net of fees plus funding, annualized
+    pub funding_adjusted_carry: f64,
+    pub days_to_expiry: Option<f64>,
+    /// Days the carry is expected to be held: to expiry, or the configured perpetual horizon
+    pub horizon_days: f64,
+    /// Base quantity both best levels can fill
+    pub quantity: f64,
+}
+
+impl BasisCarry {
+    pub fn is_perpetual(&self) -> bool {
+        self.expiry.is_none()
+    }
+
+    /// Dated carries lock in their basis at delivery; perpetual ones depend on funding staying
+    /// positive
+    pub fn risk_level(&self) -> RiskLevel {
+        if self.is_perpetual() {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        }
+    }
+
+    /// Express the carry as a `TradingOpportunity` so it can be categorized for users
+    pub fn trading_opportunity(&self, created_at: u64) -> TradingOpportunity {
+        TradingOpportunity {
+            opportunity_id: format!(
+                "basis_{}_{}_{}",
+                self.exchange.as_str(),
+                self.futures_symbol,
+                created_at
+            ),
+            opportunity_type: OpportunityType::Arbitrage,
+            trading_pair: self.spot_symbol.clone(),
+            exchanges: vec![self.exchange.to_string()],
+            entry_price: self.spot_price,
+            target_price: Some(self.futures_price),
+            stop_loss: None,
+            confidence_score: if self.is_perpetual() { 0.75 } else { 0.9 },
+            risk_level: self.risk_level(),
+            expected_return: self.funding_adjusted_carry * 100.0,
+            time_horizon: TimeHorizon::Long,
+            indicators_used: vec![BASIS_CARRY_INDICATOR.to_string()],
+            analysis_data: serde_json::to_value(self).unwrap_or_default(),
+            created_at,
+            expires_at: self.expiry,
+        }
+    }
+}
+
+/// Every spot market paired with a same-quote perpetual or unexpired dated future whose
+/// funding-adjusted carry clears `config.min_annualized_carry`, best first
+///
+/// `funding` holds the latest funding info keyed by perpetual market symbol.
+pub fn scan_basis_carry(
+    exchange: ExchangeIdEnum,
+    markets: &[Market],
+    books: &MarketBooks,
+    funding: &HashMap<String, FundingRateInfo>,
+    now: u64,
+    config: &BasisScanConfig,
+) -> Vec<BasisCarry> {
+    let spots: HashMap<(&str, &str), &Market> = markets
+        .iter()
+        .filter(|m| m.spot && m.active)
+        .map(|m| ((m.base.as_str(), m.quote.as_str()), m))
+        .collect();
+
+    let mut carries = Vec::new();
+    for futures in markets.iter().filter(|m| m.contract && m.active) {
+        let Some(spot) = spots.get(&(futures.base.as_str(), futures.quote.as_str())) else {
+            continue;
+        };
+        let (Some(spot_book), Some(futures_book)) =
+            (books.get(&book_key(spot)), books.get(&book_key(futures)))
+        else {
+            continue;
+        };
+        let (Some([spot_price, ask_size]), Some([futures_price, bid_size])) = (
+            spot_book.asks.first().copied(),
+            futures_book.bids.first().copied(),
+        ) else {
+            continue;
+        };
+        if spot_price <= 0.0 {
+            continue;
+        }
+
+        let (days_to_expiry, horizon_days) = match futures.expiry {
+            Some(expiry) if expiry <= now => continue,
+            Some(expiry) => {
+                let days = (expiry - now) as f64 / DAY_MS;
+                (Some(days), days)
+            }
+            None if futures.type_ == "swap" => (None, config.perpetual_holding_days),
+            None => continue,
+        };
+
+        let basis = (futures_price - spot_price) / spot_price;
+        let fees = 2.0 * (spot.taker + futures.taker);
+        let annualize = DAYS_PER_YEAR / horizon_days;
+        let funding_info = funding
+            .get(&futures.symbol)
+            .filter(|_| futures.expiry.is_none());
+        let annualized_funding = funding_info
+            .map(|info| {
+                let settlements_per_day = 24.0 / f64::from(info.funding_interval_hours.max(1));
+                info.funding_rate * settlements_per_day * DAYS_PER_YEAR
+            })
+            .unwrap_or(0.0);
+        let funding_adjusted_carry = (basis - fees) * annualize + annualized_funding;
+        if funding_adjusted_carry < config.min_annualized_carry {
+            continue;
+        }
+
+        carries.push(BasisCarry {
+            exchange,
+            spot_symbol: spot.symbol.clone(),
+            futures_symbol: futures.symbol.clone(),
+            expiry: futures.expiry,
+            spot_price,
+            futures_price,
+            basis,
+            annualized_basis: basis * annualize,
+            funding_rate: funding_info.map(|info| info.funding_rate),
+            annualized_funding,
+            fees,
+            funding_adjusted_carry,
+            days_to_expiry,
+            horizon_days,
+            quantity: ask_size.min(bid_size * futures.contract_size.unwrap_or(1.0)),
+        });
+    }
+
+    carries.sort_by(|a, b| {
+        b.funding_adjusted_carry
+            .total_cmp(&a.funding_adjusted_carry)
+    });
+    carries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::adapters::test_support::fixture;
+    use crate::services::core::trading::adapters::BinanceAdapter;
+    use crate::types::OrderBook;
+
+    const BINANCE_EXCHANGE_INFO_SPOT: &str =
+        include_str!("../../../test_utils/fixtures/binance/exchange_info_spot.json");
+    const BINANCE_EXCHANGE_INFO_FUTURES: &str =
+        include_str!("../../../test_utils/fixtures/binance/exchange_info_futures.json");
+
+    fn binance_markets() -> Vec<Market> {
+        let mut markets =
+            BinanceAdapter::parse_markets(&fixture(BINANCE_EXCHANGE_INFO_SPOT), false);
+        markets.extend(BinanceAdapter::parse_markets(
+            &fixture(BINANCE_EXCHANGE_INFO_FUTURES),
+            true,
+        ));
+        markets
+    }
+
+    fn book(market: &Market, bid: f64, ask: f64, size: f64) -> ((String, String), OrderBook) {
+        (
+            book_key(market),
+            OrderBook {
+                symbol: market.symbol.clone(),
+                bids: vec![[bid, size]],
+                asks: vec![[ask, size]],
+                timestamp: 0,
+                datetime: String::new(),
+                nonce: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_dated_and_perpetual_carry_are_annualized_over_their_horizons() {
+        let markets = binance_markets();
+        let find = |symbol: &str, type_: &str| {
+            markets
+                .iter()
+                .find(|m| m.symbol == symbol && m.type_ == type_)
+                .unwrap()
+        };
+        // Binance lists the spot pair and the perpetual under the same symbol
+        let (spot, perp, quarter) = (
+            find("BTCUSDT", "spot"),
+            find("BTCUSDT", "swap"),
+            find("BTCUSDT_250328", "future"),
+        );
+        let expiry = quarter.expiry.unwrap();
+        let now = expiry - 73 * DAY_MS as u64;
+        let books: MarketBooks = [
+            book(spot, 59_990.0, 60_000.0, 1.0),
+            book(perp, 60_060.0, 60_070.0, 0.5),
+            book(quarter, 61_200.0, 61_210.0, 2.0),
+        ]
+        .into_iter()
+        .collect();
+        let funding: HashMap<String, FundingRateInfo> = [(
+            "BTCUSDT".to_string(),
+            FundingRateInfo {
+                symbol: "BTCUSDT".to_string(),
+                funding_rate: 0.0001,
+                funding_interval_hours: 8,
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect();
+
+        let scan = |now| {
+            scan_basis_carry(
+                ExchangeIdEnum::Binance,
+                &markets,
+                &books,
+                &funding,
+                now,
+                &BasisScanConfig::default(),
+            )
+        };
+        let carries = scan(now);
+        let symbols: Vec<&str> = carries.iter().map(|c| c.futures_symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BTCUSDT", "BTCUSDT_250328"]);
+
+        // Opening and closing 0.1% spot and 0.05% futures taker fills cost 0.3%;
+        // 2% basis over 73 days nets (0.02 - 0.003) * 5 = 8.5% a year, no funding
+        let dated = &carries[1];
+        assert!((dated.days_to_expiry.unwrap() - 73.0).abs() < 1e-9);
+        assert!((dated.fees - 0.003).abs() < 1e-12);
+        assert!((dated.annualized_basis - 0.1).abs() < 1e-9);
+        assert!((dated.funding_adjusted_carry - 0.085).abs() < 1e-9);
+        assert_eq!(dated.risk_level(), RiskLevel::Low);
+
+        // 0.1% basis does not cover fees, but 0.01% three times a day adds 10.95% a year
+        let perpetual = &carries[0];
+        assert!(perpetual.is_perpetual());
+        assert_eq!(perpetual.days_to_expiry, None);
+        assert!((perpetual.annualized_funding - 0.1095).abs() < 1e-9);
+        let expected = (0.001 - 0.003) * 365.0 / 30.0 + 0.1095;
+        assert!((perpetual.funding_adjusted_carry - expected).abs() < 1e-9);
+        assert_eq!(perpetual.quantity, 0.5);
+
+        let categorized = perpetual.trading_opportunity(now);
+        assert_eq!(categorized.indicators_used, vec![BASIS_CARRY_INDICATOR]);
+        assert_eq!(categorized.risk_level, RiskLevel::Medium);
+
+        // Once delivered, the quarterly drops out
+        let symbols: Vec<String> = scan(expiry).into_iter().map(|c| c.futures_symbol).collect();
+        assert_eq!(symbols, vec!["BTCUSDT"]);
+    }
+}
@@ -617,6 +617,7 @@ mod tests {
             r#type: ArbitrageType::CrossExchange,
             details: Some("Test arbitrage opportunity".to_string()),
             min_exchanges_required: 2,
+            volume_24h: None,
         }
     }
 
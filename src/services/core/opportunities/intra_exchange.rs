@@ -0,0 +1,355 @@
+// src/services/core/opportunities/intra_exchange.rs
+
+//! Arbitrage inside a single exchange.
+//!
+//! Triangular cycles are three spot trades that start and end in the same asset, e.g.
+//! USDT → BTC → ETH → USDT through BTC/USDT, ETH/BTC and ETH/USDT. Every leg is priced at the
+//! top of the order book and charged the market's taker fee, so a reported edge is what
+//! crossing the books right now would return after fees.
+//!
+//! The scan inputs also carry the exchange's contract markets and books, which the
+//! spot–futures basis scanner in `basis_scanner` prices from the same fetch.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::core::trading::exchange::ExchangeInterface;
+use crate::types::{ExchangeIdEnum, Market, OrderBook};
+use crate::utils::ArbitrageResult;
+
+/// Levels requested per book; the detectors only price the best one
+const SCAN_BOOK_DEPTH: u32 = 5;
+
+/// Order books keyed by market symbol and type, since an exchange's spot market and its
+/// perpetual usually share a symbol
+pub type MarketBooks = HashMap<(String, String), OrderBook>;
+
+/// Key of `market`'s book in [`MarketBooks`]
+pub fn book_key(market: &Market) -> (String, String) {
+    (market.symbol.clone(), market.type_.clone())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntraExchangeConfig {
+    /// Assets triangular cycles start and end in
+    pub start_assets: Vec<String>,
+    /// Smallest edge after fees worth reporting
+    pub min_net_edge: f64,
+}
+
+impl Default for IntraExchangeConfig {
+    fn default() -> Self {
+        Self {
+            start_assets: vec!["USDT".to_string(), "USDC".to_string()],
+            min_net_edge: 0.0,
+        }
+    }
+}
+
+/// One conversion in a triangular cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleLeg {
+    pub symbol: String,
+    /// "buy" spends the quote asset for base; "sell" spends base for quote
+    pub side: String,
+    pub price: f64,
+    pub fee_rate: f64,
+    pub from_asset: String,
+    pub to_asset: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriangularCycle {
+    pub exchange: ExchangeIdEnum,
+    pub start_asset: String,
+    pub legs: Vec<CycleLeg>,
+    /// Return of the three conversions before fees
+    pub gross_edge: f64,
+    pub net_edge: f64,
+    /// Most of the start asset the cycle can take before a leg exhausts its best level
+    pub capacity: f64,
+}
+
+impl TriangularCycle {
+    /// Assets in trading order, e.g. `USDT → BTC → ETH → USDT`
+    pub fn path(&self) -> String {
+        let mut path = vec![self.start_asset.as_str()];
+        path.extend(self.legs.iter().map(|leg| leg.to_asset.as_str()));
+        path.join(" → ")
+    }
+}
+
+/// A directed conversion between two assets through one market's best level
+struct Edge<'a> {
+    market: &'a Market,
+    buy: bool,
+    price: f64,
+    /// Units of the target asset received per unit spent, after fees
+    rate: f64,
+    /// Most of the source asset the best level accepts
+    max_input: f64,
+}
+
+fn conversions<'a>(
+    markets: &'a [Market],
+    books: &MarketBooks,
+) -> HashMap<&'a str, Vec<(&'a str, Edge<'a>)>> {
+    let mut graph: HashMap<&str, Vec<(&str, Edge)>> = HashMap::new();
+    for market in markets.iter().filter(|m| m.spot && m.active) {
+        let Some(book) = books.get(&book_key(market)) else {
+            continue;
+        };
+        if let Some([ask, size]) = book.asks.first().copied().filter(|level| level[0] > 0.0) {
+            graph.entry(market.quote.as_str()).or_default().push((
+                market.base.as_str(),
+                Edge {
+                    market,
+                    buy: true,
+                    price: ask,
+                    rate: (1.0 - market.taker) / ask,
+                    max_input: size * ask,
+                },
+            ));
+        }
+        if let Some([bid, size]) = book.bids.first().copied().filter(|level| level[0] > 0.0) {
+            graph.entry(market.base.as_str()).or_default().push((
+                market.quote.as_str(),
+                Edge {
+                    market,
+                    buy: false,
+                    price: bid,
+                    rate: bid * (1.0 - market.taker),
+                    max_input: size,
+                },
+            ));
+        }
+    }
+    graph
+}
+
+/// Every three-leg spot cycle from a start asset whose edge after fees clears
+/// `config.min_net_edge`, best first
+pub fn find_triangular_cycles(
+    exchange: ExchangeIdEnum,
+    markets: &[Market],
+    books: &MarketBooks,
+    config: &IntraExchangeConfig,
+) -> Vec<TriangularCycle> {
+    let graph = conversions(markets, books);
+    let empty = Vec::new();
+    let mut cycles = Vec::new();
+
+    for start in &config.start_assets {
+        let start = start.as_str();
+        for (second, first_edge) in graph.get(start).unwrap_or(&empty) {
+            for (third, second_edge) in graph.get(second).unwrap_or(&empty) {
+                if *third == start {
+                    continue;
+                }
+                for (end, third_edge) in graph.get(third).unwrap_or(&empty) {
+                    if *end != start {
+                        continue;
+                    }
+                    let edges = [first_edge, second_edge, third_edge];
+                    let product: f64 = edges.iter().map(|edge| edge.rate).product();
+                    let net_edge = product - 1.0;
+                    if net_edge <= config.min_net_edge {
+                        continue;
+                    }
+                    let gross: f64 = edges
+                        .iter()
+                        .map(|edge| edge.rate / (1.0 - edge.market.taker))
+                        .product();
+
+                    // Scale each leg's limit back to units of the start asset
+                    let mut reach = 1.0;
+                    let mut capacity = f64::INFINITY;
+                    for edge in edges {
+                        capacity = capacity.min(edge.max_input / reach);
+                        reach *= edge.rate;
+                    }
+
+                    let assets = [start, *second, *third, start];
+                    cycles.push(TriangularCycle {
+                        exchange,
+                        start_asset: start.to_string(),
+                        legs: edges
+                            .iter()
+                            .zip(assets.windows(2))
+                            .map(|(edge, pair)| CycleLeg {
+                                symbol: edge.market.symbol.clone(),
+                                side: if edge.buy { "buy" } else { "sell" }.to_string(),
+                                price: edge.price,
+                                fee_rate: edge.market.taker,
+                                from_asset: pair[0].to_string(),
+                                to_asset: pair[1].to_string(),
+                            })
+                            .collect(),
+                        gross_edge: gross - 1.0,
+                        net_edge,
+                        capacity,
+                    });
+                }
+            }
+        }
+    }
+
+    cycles.sort_by(|a, b| b.net_edge.total_cmp(&a.net_edge));
+    cycles
+}
+
+/// Fetch one exchange's markets and books for a scan
+///
+/// The scan covers the assets of `pairs` plus the configured start assets, so cross pairs such
+/// as ETH/BTC are included whenever both sides are monitored. With `contracts`, perpetuals and
+/// dated futures are included too and their books come from the futures endpoint. Books that
+/// fail to load are skipped so one delisted market does not abort the scan.
+pub async fn fetch_scan_inputs<E: ExchangeInterface>(
+    exchange: &E,
+    exchange_id: ExchangeIdEnum,
+    pairs: &[String],
+    config: &IntraExchangeConfig,
+    contracts: bool,
+) -> ArbitrageResult<(Vec<Market>, MarketBooks)> {
+    let all_markets = exchange.get_markets(exchange_id.as_str()).await?;
+    let compact = |symbol: &str| symbol.replace(['/', '-', '_'], "").to_uppercase();
+    let wanted: HashSet<String> = pairs.iter().map(|p| compact(p)).collect();
+
+    let mut assets: HashSet<&str> = config.start_assets.iter().map(String::as_str).collect();
+    for market in all_markets.iter().filter(|m| m.spot) {
+        if wanted.contains(&compact(&market.symbol)) {
+            assets.insert(&market.base);
+            assets.insert(&market.quote);
+        }
+    }
+
+    let markets: Vec<Market> = all_markets
+        .iter()
+        .filter(|m| {
+            m.active && assets.contains(m.base.as_str()) && assets.contains(m.quote.as_str())
+        })
+        .filter(|m| m.spot || (contracts && m.contract))
+        .cloned()
+        .collect();
+
+    let mut books = MarketBooks::new();
+    for market in &markets {
+        if let Ok(book) = exchange
+            .get_market_orderbook(
+                exchange_id.as_str(),
+                &market.symbol,
+                Some(SCAN_BOOK_DEPTH),
+                &market.type_,
+            )
+            .await
+        {
+            books.insert(book_key(market), book);
+        }
+    }
+    Ok((markets, books))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::opportunities::{
+        OpportunityBuilder, OpportunityConfig, OpportunityContext,
+    };
+    use crate::types::ArbitrageType;
+
+    fn market(symbol: &str, base: &str, quote: &str) -> Market {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "base": base,
+            "quote": quote,
+            "active": true,
+            "type_": "spot",
+            "spot": true,
+            "margin": false,
+            "future": false,
+            "option": false,
+            "contract": false,
+            "taker": 0.001,
+            "maker": 0.001,
+            "percentage": true,
+            "tier_based": false,
+            "limits": {},
+            "precision": {},
+            "info": null
+        }))
+        .unwrap()
+    }
+
+    fn book(
+        symbol: &str,
+        type_: &str,
+        bid: [f64; 2],
+        ask: [f64; 2],
+    ) -> ((String, String), OrderBook) {
+        (
+            (symbol.to_string(), type_.to_string()),
+            OrderBook {
+                symbol: symbol.to_string(),
+                bids: vec![bid],
+                asks: vec![ask],
+                timestamp: 0,
+                datetime: String::new(),
+                nonce: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_triangular_cycle_found_only_when_edge_beats_fees() {
+        let markets = vec![
+            market("BTCUSDT", "BTC", "USDT"),
+            market("ETHBTC", "ETH", "BTC"),
+            market("ETHUSDT", "ETH", "USDT"),
+        ];
+        // ETH is cheap in BTC terms: 0.05 BTC buys 1 ETH worth 3,030 USDT
+        let mut books: MarketBooks = [
+            book("BTCUSDT", "spot", [59_990.0, 1.0], [60_000.0, 0.5]),
+            book("ETHBTC", "spot", [0.0499, 20.0], [0.05, 4.0]),
+            book("ETHUSDT", "spot", [3_030.0, 2.0], [3_031.0, 10.0]),
+        ]
+        .into_iter()
+        .collect();
+
+        let cycles = find_triangular_cycles(
+            ExchangeIdEnum::Binance,
+            &markets,
+            &books,
+            &IntraExchangeConfig::default(),
+        );
+        assert_eq!(cycles.len(), 1);
+        let cycle = &cycles[0];
+        assert_eq!(cycle.path(), "USDT → BTC → ETH → USDT");
+        let sides: Vec<&str> = cycle.legs.iter().map(|leg| leg.side.as_str()).collect();
+        assert_eq!(sides, vec!["buy", "buy", "sell"]);
+        let gross = 3_030.0 / (60_000.0 * 0.05);
+        assert!((cycle.gross_edge - (gross - 1.0)).abs() < 1e-12);
+        assert!((cycle.net_edge - (gross * 0.999_f64.powi(3) - 1.0)).abs() < 1e-12);
+        // Selling 2 ETH at the ETH/USDT bid is the tightest leg
+        let eth_per_usdt = 0.999 / 60_000.0 * 0.999 / 0.05;
+        assert!((cycle.capacity - 2.0 / eth_per_usdt).abs() < 1e-6);
+
+        let opportunity = OpportunityBuilder::new(OpportunityConfig::default())
+            .build_triangular_arbitrage(cycle, &OpportunityContext::Global { system_level: true })
+            .unwrap();
+        assert_eq!(opportunity.r#type, ArbitrageType::Triangular);
+        assert_eq!(opportunity.min_exchanges_required, 1);
+        assert_eq!(opportunity.volume, cycle.capacity);
+
+        // A 0.3% mispricing does not cover three 0.1% fees
+        let (key, cheaper) = book("ETHUSDT", "spot", [3_009.0, 2.0], [3_010.0, 10.0]);
+        books.insert(key, cheaper);
+        assert!(find_triangular_cycles(
+            ExchangeIdEnum::Binance,
+            &markets,
+            &books,
+            &IntraExchangeConfig::default(),
+        )
+        .is_empty());
+    }
+}
@@ -5,8 +5,9 @@ use crate::services::core::opportunities::opportunity_core::{
 };
 use crate::services::core::trading::exchange::{ExchangeInterface, ExchangeService};
 use crate::types::{
-    ArbitrageOpportunity, ArbitrageType, ExchangeIdEnum, FundingRateInfo, TechnicalRiskLevel,
-    TechnicalSignalStrength, TechnicalSignalType, Ticker,
+    ArbitrageOpportunity, ArbitrageType, ExchangeCredentials, ExchangeIdEnum, FundingRateInfo,
+    Market, OrderBook, TechnicalRiskLevel, TechnicalSignalStrength, TechnicalSignalType, Ticker,
+    TradingFeeRates,
 };
 use crate::utils::{ArbitrageError, ArbitrageResult};
 use chrono::Utc;
@@ -103,6 +104,44 @@ impl MarketAnalyzer {
         }
     }
 
+    /// Exchange service market data is read through
+    pub fn exchange_service(&self) -> &Arc<ExchangeService> {
+        &self.exchange_service
+    }
+
+    /// Fetch market metadata (lot sizes, minimums, base fees) for each exchange. Exchanges
+    /// whose markets cannot be loaded are left out, so their legs go unchecked.
+    pub async fn fetch_markets(
+        &self,
+        exchanges: &[ExchangeIdEnum],
+    ) -> Vec<(ExchangeIdEnum, Vec<Market>)> {
+        let market_tasks = exchanges.iter().map(|exchange_id| {
+            let exchange_service = Arc::clone(&self.exchange_service);
+            async move {
+                let result = exchange_service.get_markets(exchange_id.as_str()).await;
+                (*exchange_id, result)
+            }
+        });
+
+        join_all(market_tasks)
+            .await
+            .into_iter()
+            .filter_map(|(exchange_id, result)| match result {
+                Ok(markets) => Some((exchange_id, markets)),
+                Err(e) => {
+                    log_info!(
+                        "Failed to fetch exchange markets",
+                        serde_json::json!({
+                            "exchange": exchange_id.as_str(),
+                            "error": e.to_string()
+                        })
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Fetch market data for multiple symbols across multiple exchanges
     pub async fn fetch_market_data(
         &self,
@@ -915,6 +954,89 @@ impl MarketAnalyzer {
         (avg_volume / OpportunityConstants::HIGH_VOLUME_THRESHOLD).min(1.0)
     }
 
+    /// Fetch the fees each of the user's accounts pays on `symbol` (perpetual market when
+    /// `futures`). Accounts whose fees cannot be fetched are left out.
+    pub async fn fetch_trading_fees(
+        &self,
+        symbol: &str,
+        user_exchanges: &[(ExchangeIdEnum, ExchangeCredentials)],
+        futures: bool,
+    ) -> Vec<(ExchangeIdEnum, TradingFeeRates)> {
+        let fee_tasks = user_exchanges.iter().map(|(exchange_id, credentials)| {
+            let exchange_service = Arc::clone(&self.exchange_service);
+            let mut credentials = credentials.clone();
+            credentials.exchange_type = if futures { "futures" } else { "spot" }.to_string();
+            async move {
+                let result = exchange_service
+                    .get_trading_fees(exchange_id.as_str(), &credentials, symbol)
+                    .await;
+                (*exchange_id, result)
+            }
+        });
+
+        join_all(fee_tasks)
+            .await
+            .into_iter()
+            .filter_map(|(exchange_id, result)| match result {
+                Ok(fees) => Some((exchange_id, fees.trading)),
+                Err(e) => {
+                    log_info!(
+                        "Failed to fetch account trading fees",
+                        serde_json::json!({
+                            "exchange": exchange_id.as_str(),
+                            "symbol": symbol,
+                            "error": e.to_string()
+                        })
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Fetch the perpetual's funding schedule and order book for `symbol` on each exchange.
+    /// Exchanges whose data cannot be fetched are left out, so their legs are valued
+    /// without settlement timing or slippage.
+    pub async fn fetch_funding_inputs(
+        &self,
+        symbol: &str,
+        exchanges: &[ExchangeIdEnum],
+    ) -> (Vec<FundingRateInfo>, Vec<(ExchangeIdEnum, OrderBook)>) {
+        let input_tasks = exchanges.iter().map(|exchange_id| {
+            let exchange_service = Arc::clone(&self.exchange_service);
+            async move {
+                let funding = exchange_service
+                    .get_funding_rate_direct(exchange_id.as_str(), symbol)
+                    .await;
+                let book = exchange_service
+                    .get_market_orderbook(exchange_id.as_str(), symbol, None, "futures")
+                    .await;
+                (*exchange_id, funding, book)
+            }
+        });
+
+        let mut funding_info = Vec::new();
+        let mut order_books = Vec::new();
+        for (exchange_id, funding, book) in join_all(input_tasks).await {
+            for error in [funding.as_ref().err(), book.as_ref().err()]
+                .into_iter()
+                .flatten()
+            {
+                log_info!(
+                    "Failed to fetch perpetual funding inputs",
+                    serde_json::json!({
+                        "exchange": exchange_id.as_str(),
+                        "symbol": symbol,
+                        "error": error.to_string()
+                    })
+                );
+            }
+            funding_info.extend(funding.ok());
+            order_books.extend(book.ok().map(|book| (exchange_id, book)));
+        }
+        (funding_info, order_books)
+    }
+
     /// Detect arbitrage opportunities across multiple exchanges
     pub async fn detect_arbitrage_opportunities(
         &self,
@@ -981,6 +1103,9 @@ impl MarketAnalyzer {
                                     exchange_a, exchange_b
                                 )),
                                 min_exchanges_required: 2,
+                                volume_24h: OpportunityUtils::quote_volume_24h(&ticker_a)
+                                    .zip(OpportunityUtils::quote_volume_24h(&ticker_b))
+                                    .map(|(volume_a, volume_b)| volume_a.min(volume_b)),
                             };
                             opportunities.push(opportunity);
                         }
@@ -1191,6 +1316,25 @@ mod tests {
         assert!(analysis.price_difference_percent > 0.0);
     }
 
+    #[test]
+    fn test_quote_volume_24h_falls_back_to_base_volume() {
+        let mut ticker = create_test_ticker("BTCUSDT", 50000.0, 1000.0, 1.0);
+        assert_eq!(
+            OpportunityUtils::quote_volume_24h(&ticker),
+            Some(50_000_000.0)
+        );
+
+        ticker.quote_volume = None;
+        ticker.base_volume = Some(200.0);
+        assert_eq!(
+            OpportunityUtils::quote_volume_24h(&ticker),
+            Some(10_000_000.0)
+        );
+
+        ticker.last = None;
+        assert_eq!(OpportunityUtils::quote_volume_24h(&ticker), None);
+    }
+
     #[allow(dead_code)] // #[test]
     fn test_risk_assessment() {
         let analyzer = MarketAnalyzer::new_without_exchange();
@@ -3,12 +3,16 @@
 // Core modular components (new unified architecture)
 pub mod access_manager;
 pub mod ai_enhancer;
+pub mod basis_scanner;
 pub mod cache_manager;
+pub mod intra_exchange;
 pub mod market_analyzer;
 pub mod opportunity_builders;
 pub mod opportunity_categorization;
 pub mod opportunity_core;
 pub mod opportunity_engine; // Renamed from opportunity_models
+pub mod opportunity_filter;
+pub mod opportunity_lifecycle;
 
 // Legacy services (still needed)
 pub mod opportunity_distribution;
@@ -16,13 +20,20 @@ pub mod opportunity_distribution;
 // Re-export core components for easy access
 pub use access_manager::AccessManager;
 pub use ai_enhancer::AIEnhancer;
+pub use basis_scanner::{scan_basis_carry, BasisCarry, BasisScanConfig};
 pub use cache_manager::{CachePrefixes, OpportunityDataCache};
+pub use intra_exchange::{find_triangular_cycles, IntraExchangeConfig, TriangularCycle};
 pub use market_analyzer::MarketAnalyzer;
 pub use opportunity_builders::OpportunityBuilder;
 pub use opportunity_categorization::*;
 pub use opportunity_core::*;
 pub use opportunity_core::{OpportunityConfig, OpportunityContext, OpportunityUtils};
 pub use opportunity_engine::OpportunityEngine; // Renamed from opportunity_models
+pub use opportunity_filter::{FilterExpr, FilterRecord};
+pub use opportunity_lifecycle::{
+    LifecycleConfig, LifecycleStage, LifecycleUpdate, OpportunityLifecycleTracker,
+    TrackedOpportunity,
+};
 
 // Re-export remaining legacy service for backward compatibility
 pub use opportunity_distribution::OpportunityDistributionService;
@@ -1,33 +1,108 @@
 // src/services/core/opportunities/opportunity_builders.rs
 
 use crate::log_info;
+use crate::services::core::analysis::market_analysis::RiskLevel;
+use crate::services::core::opportunities::basis_scanner::BasisCarry;
+use crate::services::core::opportunities::intra_exchange::TriangularCycle;
 use crate::services::core::opportunities::opportunity_core::{
     OpportunityConfig, OpportunityContext,
 };
+use crate::services::core::opportunities::opportunity_lifecycle::stable_id;
+use crate::services::core::trading::paper_exchange::walk_book;
 use crate::types::{
-    ArbitrageOpportunity, ArbitrageType, DistributionStrategy, ExchangeIdEnum, GlobalOpportunity,
-    OpportunityData, OpportunitySource, TechnicalOpportunity, TechnicalRiskLevel,
-    TechnicalSignalStrength, TechnicalSignalType,
+    ArbitrageOpportunity, ArbitrageType, DistributionStrategy, ExchangeIdEnum, FundingRateInfo,
+    GlobalOpportunity, Market, OpportunityData, OpportunitySource, OrderBook, TechnicalOpportunity,
+    TechnicalRiskLevel, TechnicalSignalStrength, TechnicalSignalType, TradingFeeRates,
 };
 use crate::utils::{ArbitrageError, ArbitrageResult};
 use chrono::Utc;
 use serde_json;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Base quantity quoted on arbitrage opportunities when no price is known to size them
+const DEFAULT_ARBITRAGE_VOLUME: f64 = 1000.0;
+
+/// Funding rates are compared per this many hours, the most common settlement interval
+const STANDARD_FUNDING_INTERVAL_HOURS: f64 = 8.0;
+
 /// Unified opportunity builder for all opportunity services
 /// Consolidates opportunity creation logic and provides consistent building patterns
+#[derive(Clone)]
 pub struct OpportunityBuilder {
     config: OpportunityConfig,
+    markets: HashMap<ExchangeIdEnum, Vec<Market>>,
+    /// Account fee rates keyed by (exchange, compact symbol, futures)
+    trading_fees: HashMap<(ExchangeIdEnum, String, bool), TradingFeeRates>,
+    /// Funding schedules and order books keyed by (exchange, compact symbol)
+    funding_info: HashMap<(ExchangeIdEnum, String), FundingRateInfo>,
+    order_books: HashMap<(ExchangeIdEnum, String), OrderBook>,
+    /// Evaluation time in milliseconds; the wall clock when unset
+    now: Option<u64>,
 }
 
 impl OpportunityBuilder {
     pub fn new(config: OpportunityConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            markets: HashMap::new(),
+            trading_fees: HashMap::new(),
+            funding_info: HashMap::new(),
+            order_books: HashMap::new(),
+            now: None,
+        }
+    }
+
+    /// Register an exchange's market metadata so arbitrage volumes are rounded to its lot
+    /// sizes and rejected when below its minimums
+    pub fn with_markets(mut self, exchange: ExchangeIdEnum, markets: Vec<Market>) -> Self {
+        self.markets.insert(exchange, markets);
+        self
+    }
+
+    /// Register the fees a user's account pays on `pair` so net spreads reflect their tier.
+    /// Without them, the registered market's base taker rate is used.
+    pub fn with_trading_fees(
+        mut self,
+        exchange: ExchangeIdEnum,
+        pair: &str,
+        futures: bool,
+        fees: TradingFeeRates,
+    ) -> Self {
+        self.trading_fees
+            .insert((exchange, Self::compact_symbol(pair), futures), fees);
+        self
+    }
+
+    /// Register a perpetual's funding interval, next settlement and mark price on one
+    /// exchange. Without it, funding settles every 8 hours at an unknown time.
+    pub fn with_funding_info(mut self, info: FundingRateInfo) -> Self {
+        self.funding_info
+            .insert((info.exchange, Self::compact_symbol(&info.symbol)), info);
+        self
+    }
+
+    /// Register a perpetual's order book so funding edges are net of the slippage of
+    /// trading `trade_size_usd` through it
+    pub fn with_order_book(mut self, exchange: ExchangeIdEnum, book: OrderBook) -> Self {
+        self.order_books
+            .insert((exchange, Self::compact_symbol(&book.symbol)), book);
+        self
+    }
+
+    /// Evaluate settlements and timestamps as of `timestamp_ms`, e.g. when replaying
+    pub fn at_time(mut self, timestamp_ms: u64) -> Self {
+        self.now = Some(timestamp_ms);
+        self
     }
 
     // Arbitrage Opportunity Builders
 
-    /// Build funding rate arbitrage opportunity
+    /// Build funding rate arbitrage opportunity. `long_rate` and `short_rate` are each
+    /// exchange's rate per settlement; the opportunity's `rate_difference` compares them per
+    /// 8 hours. The net edge is the funding both legs collect over the configured horizon,
+    /// less four taker fills and the slippage of entering and exiting at the registered
+    /// order books' depth. Opportunities without a positive net edge are rejected.
     pub fn build_funding_rate_arbitrage(
         &self,
         pair: String,
@@ -37,7 +112,11 @@ impl OpportunityBuilder {
         short_rate: f64,
         context: &OpportunityContext,
     ) -> ArbitrageResult<ArbitrageOpportunity> {
-        let rate_difference = (short_rate - long_rate).abs();
+        let long_interval = self.funding_interval_hours(long_exchange, &pair);
+        let short_interval = self.funding_interval_hours(short_exchange, &pair);
+        let rate_difference = (short_rate * STANDARD_FUNDING_INTERVAL_HOURS / short_interval
+            - long_rate * STANDARD_FUNDING_INTERVAL_HOURS / long_interval)
+            .abs();
 
         // Validate rate difference meets minimum threshold
         if rate_difference < self.config.min_rate_difference {
@@ -48,44 +127,101 @@ impl OpportunityBuilder {
             )));
         }
 
-        // Calculate potential profit value
-        let potential_profit_value =
-            self.calculate_arbitrage_profit_value(rate_difference, context);
+        // The short leg receives its rate at each of its settlements and the long leg pays
+        let funding_edge = short_rate * self.settlements_in_horizon(short_exchange, &pair)
+            - long_rate * self.settlements_in_horizon(long_exchange, &pair);
+        let fee_cost = 2.0 * self.taker_fees(&pair, &[long_exchange, short_exchange], true);
+
+        let reference_price = self.reference_price(&pair, &[long_exchange, short_exchange]);
+        let mut volume = match reference_price {
+            Some(price) => self.config.trade_size_usd / price,
+            None => DEFAULT_ARBITRAGE_VOLUME,
+        };
+        let mut slippage_cost = 0.0;
+        let mut books_known = true;
+        for exchange in [long_exchange, short_exchange] {
+            match self.order_book(exchange, &pair) {
+                Some(book) => {
+                    let (fillable, slippage) = Self::round_trip_slippage(book, volume);
+                    volume = volume.min(fillable);
+                    slippage_cost += slippage;
+                }
+                None => books_known = false,
+            }
+        }
+        if volume <= 0.0 {
+            return Err(ArbitrageError::validation_error(format!(
+                "Order books for {} have no depth to trade",
+                pair
+            )));
+        }
+        let net_edge = funding_edge - fee_cost - slippage_cost;
+        if net_edge <= 0.0 {
+            return Err(ArbitrageError::validation_error(format!(
+                "Net edge {:.4}% is not positive after {:.4}% fees and {:.4}% slippage",
+                net_edge * 100.0,
+                fee_cost * 100.0,
+                slippage_cost * 100.0
+            )));
+        }
+
+        let volume = self.conform_volume_to_markets(
+            &pair,
+            &[long_exchange, short_exchange],
+            true,
+            volume,
+            reference_price,
+        )?;
+        let notional = reference_price
+            .map(|price| volume * price)
+            .unwrap_or(self.config.trade_size_usd);
+        let potential_profit_value = net_edge * notional;
+        let schedules_known = [long_exchange, short_exchange]
+            .iter()
+            .all(|exchange| self.funding_info(*exchange, &pair).is_some());
+        let confidence =
+            Self::funding_confidence(net_edge, funding_edge, books_known, schedules_known);
+        let now = self.now();
 
         let opportunity = ArbitrageOpportunity {
             id: Uuid::new_v4().to_string(),
             trading_pair: pair.clone(),
             exchanges: vec![long_exchange.to_string(), short_exchange.to_string()],
             profit_percentage: rate_difference,
-            confidence_score: 0.8, // Default confidence for funding rate arbitrage
+            confidence_score: confidence,
             risk_level: "medium".to_string(),
             buy_exchange: long_exchange.to_string(),
             sell_exchange: short_exchange.to_string(),
             buy_price: 0.0,
             sell_price: 0.0,
-            volume: 1000.0, // Default volume
-            created_at: chrono::Utc::now().timestamp_millis() as u64,
-            expires_at: Some(chrono::Utc::now().timestamp_millis() as u64 + (15 * 60 * 1000)), // 15 minutes
+            volume,
+            created_at: now,
+            expires_at: Some(now + (15 * 60 * 1000)), // 15 minutes
             pair: pair.clone(),
             long_exchange,
             short_exchange,
-            long_rate: None,
-            short_rate: None,
+            long_rate: Some(long_rate),
+            short_rate: Some(short_rate),
             rate_difference,
-            net_rate_difference: Some(rate_difference),
-            potential_profit_value: None,
-            confidence: 0.8,
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            detected_at: chrono::Utc::now().timestamp_millis() as u64,
+            net_rate_difference: Some(net_edge),
+            potential_profit_value: Some(potential_profit_value),
+            confidence,
+            timestamp: now,
+            detected_at: now,
             r#type: ArbitrageType::FundingRate,
             details: Some(format!(
-                "Funding rate arbitrage: Long {} at {:.4}%, Short {} at {:.4}%",
+                "Funding rate arbitrage: Long {} at {:.4}%/{}h, Short {} at {:.4}%/{}h, net {:.4}% over {}h",
                 long_exchange,
                 long_rate * 100.0,
+                long_interval,
                 short_exchange,
-                short_rate * 100.0
+                short_rate * 100.0,
+                short_interval,
+                net_edge * 100.0,
+                self.config.funding_horizon_hours
             )),
             min_exchanges_required: 2,
+            volume_24h: None,
         };
 
         log_info!(
@@ -93,6 +229,7 @@ impl OpportunityBuilder {
             serde_json::json!({
                 "pair": opportunity.pair,
                 "rate_difference": rate_difference,
+                "net_edge": net_edge,
                 "potential_profit": potential_profit_value,
                 "context": format!("{:?}", context)
             })
@@ -124,6 +261,13 @@ impl OpportunityBuilder {
 
         let potential_profit_value =
             self.calculate_arbitrage_profit_value(price_difference, context);
+        let volume = self.conform_volume_to_markets(
+            &pair,
+            &[long_exchange, short_exchange],
+            false,
+            DEFAULT_ARBITRAGE_VOLUME,
+            Some(long_price),
+        )?;
 
         let opportunity = ArbitrageOpportunity {
             id: Uuid::new_v4().to_string(),
@@ -136,7 +280,7 @@ impl OpportunityBuilder {
             sell_exchange: short_exchange.to_string(),
             buy_price: 0.0,
             sell_price: 0.0,
-            volume: 1000.0, // Default volume
+            volume,
             created_at: chrono::Utc::now().timestamp_millis() as u64,
             expires_at: Some(chrono::Utc::now().timestamp_millis() as u64 + (15 * 60 * 1000)), // 15 minutes
             pair: pair.clone(),
@@ -145,7 +289,9 @@ impl OpportunityBuilder {
             long_rate: None, // Not applicable for price arbitrage
             short_rate: None,
             rate_difference: price_difference,
-            net_rate_difference: Some(price_difference),
+            net_rate_difference: Some(
+                price_difference - self.taker_fees(&pair, &[long_exchange, short_exchange], false),
+            ),
             potential_profit_value: None,
             confidence: 0.8,
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
@@ -159,6 +305,7 @@ impl OpportunityBuilder {
                 short_price
             )),
             min_exchanges_required: 2,
+            volume_24h: None,
         };
 
         log_info!(
@@ -209,6 +356,17 @@ impl OpportunityBuilder {
         }
 
         let potential_profit_value = self.calculate_arbitrage_profit_value(difference, context);
+        // Funding legs trade perpetuals; the compared values are only prices for price arbitrage
+        let is_funding = arbitrage_type == ArbitrageType::FundingRate;
+        let volume = self.conform_volume_to_markets(
+            &pair,
+            &[*min_exchange, *max_exchange],
+            is_funding,
+            DEFAULT_ARBITRAGE_VOLUME,
+            (arbitrage_type == ArbitrageType::Price).then_some(*min_value),
+        )?;
+        let legs_fee = self.taker_fees(&pair, &[*min_exchange, *max_exchange], is_funding);
+        let fee_cost = if is_funding { 2.0 * legs_fee } else { legs_fee };
 
         let opportunity = ArbitrageOpportunity {
             id: Uuid::new_v4().to_string(),
@@ -221,7 +379,7 @@ impl OpportunityBuilder {
             sell_exchange: max_exchange.to_string(),
             buy_price: 0.0,
             sell_price: 0.0,
-            volume: 1000.0, // Default volume
+            volume,
             created_at: chrono::Utc::now().timestamp_millis() as u64,
             expires_at: Some(chrono::Utc::now().timestamp_millis() as u64 + (15 * 60 * 1000)), // 15 minutes
             pair: pair.clone(),
@@ -230,7 +388,7 @@ impl OpportunityBuilder {
             long_rate: Some(*min_value),
             short_rate: Some(*max_value),
             rate_difference: difference,
-            net_rate_difference: Some(difference),
+            net_rate_difference: Some(difference - fee_cost),
             potential_profit_value: None,
             confidence: 0.8,
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
@@ -242,6 +400,7 @@ impl OpportunityBuilder {
                 difference * 100.0
             )),
             min_exchanges_required: 2,
+            volume_24h: None,
         };
 
         log_info!(
@@ -257,6 +416,186 @@ impl OpportunityBuilder {
         Ok(opportunity)
     }
 
+    /// Build a triangular opportunity from a cycle found on one exchange's spot books
+    pub fn build_triangular_arbitrage(
+        &self,
+        cycle: &TriangularCycle,
+        context: &OpportunityContext,
+    ) -> ArbitrageResult<ArbitrageOpportunity> {
+        if cycle.legs.len() != 3 {
+            return Err(ArbitrageError::validation_error(format!(
+                "Triangular cycle needs 3 legs, got {}",
+                cycle.legs.len()
+            )));
+        }
+        if cycle.net_edge <= 0.0 {
+            return Err(ArbitrageError::validation_error(format!(
+                "Net edge {:.4}% is not positive after fees",
+                cycle.net_edge * 100.0
+            )));
+        }
+
+        // Sized in the start asset, which is a stablecoin for the default start assets
+        let volume = cycle.capacity.min(self.config.trade_size_usd);
+        let now = self.now();
+        let path = cycle.path();
+        let exchange = cycle.exchange;
+
+        let opportunity = ArbitrageOpportunity {
+            id: Uuid::new_v4().to_string(),
+            trading_pair: path.clone(),
+            exchanges: vec![exchange.to_string()],
+            profit_percentage: cycle.net_edge * 100.0,
+            confidence_score: 0.7,
+            risk_level: "medium".to_string(),
+            buy_exchange: exchange.to_string(),
+            sell_exchange: exchange.to_string(),
+            buy_price: cycle.legs[0].price,
+            sell_price: cycle.legs[2].price,
+            volume,
+            created_at: now,
+            // Top-of-book mispricings rarely survive long
+            expires_at: Some(now + 60 * 1000),
+            pair: path,
+            long_exchange: exchange,
+            short_exchange: exchange,
+            long_rate: None,
+            short_rate: None,
+            rate_difference: cycle.gross_edge,
+            net_rate_difference: Some(cycle.net_edge),
+            potential_profit_value: Some(cycle.net_edge * volume),
+            confidence: 0.7,
+            timestamp: now,
+            detected_at: now,
+            r#type: ArbitrageType::Triangular,
+            details: Some(
+                cycle
+                    .legs
+                    .iter()
+                    .map(|leg| format!("{} {} @ {}", leg.side, leg.symbol, leg.price))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            min_exchanges_required: 1,
+            volume_24h: None,
+        };
+
+        log_info!(
+            "Built triangular arbitrage opportunity",
+            serde_json::json!({
+                "exchange": exchange.as_str(),
+                "path": opportunity.pair,
+                "net_edge": cycle.net_edge,
+                "volume": volume,
+                "context": format!("{:?}", context)
+            })
+        );
+
+        Ok(opportunity)
+    }
+
+    /// Build a cash-and-carry opportunity: long spot, short a perpetual or dated future
+    pub fn build_basis_carry_opportunity(
+        &self,
+        carry: &BasisCarry,
+        context: &OpportunityContext,
+    ) -> ArbitrageResult<ArbitrageOpportunity> {
+        if carry.funding_adjusted_carry <= 0.0 {
+            return Err(ArbitrageError::validation_error(format!(
+                "Funding-adjusted carry {:.2}% a year is not positive",
+                carry.funding_adjusted_carry * 100.0
+            )));
+        }
+        let volume = carry
+            .quantity
+            .min(self.config.trade_size_usd / carry.spot_price);
+        if volume <= 0.0 {
+            return Err(ArbitrageError::validation_error(format!(
+                "No depth at the best levels of {} and {}",
+                carry.spot_symbol, carry.futures_symbol
+            )));
+        }
+
+        let now = self.now();
+        let exchange = carry.exchange;
+        let risk_level = match carry.risk_level() {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+        };
+        let confidence = if carry.is_perpetual() { 0.75 } else { 0.9 };
+        // Name both instruments when they differ so each dated contract is its own opportunity
+        let pair = if carry.futures_symbol == carry.spot_symbol {
+            carry.spot_symbol.clone()
+        } else {
+            format!("{}/{}", carry.spot_symbol, carry.futures_symbol)
+        };
+        let leg = match carry.days_to_expiry {
+            Some(days) => format!("future {} ({:.0}d to expiry)", carry.futures_symbol, days),
+            None => format!("perpetual {}", carry.futures_symbol),
+        };
+
+        let opportunity = ArbitrageOpportunity {
+            id: Uuid::new_v4().to_string(),
+            trading_pair: carry.spot_symbol.clone(),
+            exchanges: vec![exchange.to_string()],
+            profit_percentage: carry.funding_adjusted_carry * 100.0,
+            confidence_score: confidence,
+            risk_level: risk_level.to_string(),
+            buy_exchange: exchange.to_string(),
+            sell_exchange: exchange.to_string(),
+            buy_price: carry.spot_price,
+            sell_price: carry.futures_price,
+            volume,
+            created_at: now,
+            expires_at: Some(now + self.config.opportunity_ttl_minutes as u64 * 60 * 1000),
+            pair,
+            long_exchange: exchange,
+            short_exchange: exchange,
+            long_rate: None,
+            short_rate: carry.funding_rate,
+            rate_difference: carry.basis,
+            net_rate_difference: Some(carry.basis - carry.fees),
+            // Expected over the holding horizon rather than a full year
+            potential_profit_value: Some(
+                carry.funding_adjusted_carry * carry.horizon_days / 365.0
+                    * volume
+                    * carry.spot_price,
+            ),
+            confidence,
+            timestamp: now,
+            detected_at: now,
+            r#type: ArbitrageType::SpotFutures,
+            details: Some(format!(
+                "Carry: Buy spot {} (${:.2}) vs Sell {} (${:.2}); basis {:.2}%/yr, funding {:.2}%/yr, net carry {:.2}%/yr",
+                carry.spot_symbol,
+                carry.spot_price,
+                leg,
+                carry.futures_price,
+                carry.annualized_basis * 100.0,
+                carry.annualized_funding * 100.0,
+                carry.funding_adjusted_carry * 100.0
+            )),
+            min_exchanges_required: 1,
+            volume_24h: None,
+        };
+
+        log_info!(
+            "Built basis carry opportunity",
+            serde_json::json!({
+                "exchange": exchange.as_str(),
+                "spot": carry.spot_symbol,
+                "futures": carry.futures_symbol,
+                "annualized_basis": carry.annualized_basis,
+                "funding_adjusted_carry": carry.funding_adjusted_carry,
+                "days_to_expiry": carry.days_to_expiry,
+                "context": format!("{:?}", context)
+            })
+        );
+
+        Ok(opportunity)
+    }
+
     // Technical Opportunity Builders
 
     /// Build technical analysis opportunity
@@ -449,15 +788,9 @@ impl OpportunityBuilder {
         let priority_score = self.calculate_priority_score(&arbitrage_opportunity);
         let now = chrono::Utc::now().timestamp_millis() as u64;
 
+        // Same pair, legs and type across scans share one id
         let global_opportunity = GlobalOpportunity {
-            id: format!(
-                "global_arb_{}",
-                Uuid::new_v4()
-                    .to_string()
-                    .split('-')
-                    .next()
-                    .unwrap_or("unknown")
-            ),
+            id: format!("global_arb_{}", stable_id(&arbitrage_opportunity)),
             source: source.clone(),
             opportunity_type: source.clone(),
             target_users: Vec::new(),
@@ -546,6 +879,164 @@ impl OpportunityBuilder {
 
     // Helper Methods
 
+    fn compact_symbol(pair: &str) -> String {
+        pair.replace(['/', '-', '_'], "").to_uppercase()
+    }
+
+    /// Sum of the taker rates paid to fill one side on each exchange. Funding positions are
+    /// opened and later closed, so callers charge them twice. Legs with no known fee
+    /// (no account fees and no registered market) add nothing.
+    fn taker_fees(&self, pair: &str, exchanges: &[ExchangeIdEnum], futures: bool) -> f64 {
+        let symbol = Self::compact_symbol(pair);
+        exchanges
+            .iter()
+            .filter_map(|exchange| {
+                self.trading_fees
+                    .get(&(*exchange, symbol.clone(), futures))
+                    .map(|fees| fees.taker)
+                    .or_else(|| {
+                        self.markets
+                            .get(exchange)?
+                            .iter()
+                            .find(|m| m.symbol == symbol && m.contract == futures)
+                            .map(|m| m.taker)
+                    })
+            })
+            .sum()
+    }
+
+    /// Round `volume` down to every leg's lot size and check it clears each leg's minimum
+    /// amount and notional. Exchanges without registered markets are left unchecked.
+    fn conform_volume_to_markets(
+        &self,
+        pair: &str,
+        exchanges: &[ExchangeIdEnum],
+        contract: bool,
+        volume: f64,
+        reference_price: Option<f64>,
+    ) -> ArbitrageResult<f64> {
+        let symbol = Self::compact_symbol(pair);
+        let mut legs = Vec::new();
+        for exchange in exchanges {
+            let Some(markets) = self.markets.get(exchange) else {
+                continue;
+            };
+            let market = markets
+                .iter()
+                .find(|m| m.symbol == symbol && m.contract == contract)
+                .filter(|m| m.active)
+                .ok_or_else(|| {
+                    ArbitrageError::validation_error(format!(
+                        "{} is not tradable on {}",
+                        pair, exchange
+                    ))
+                })?;
+            legs.push((exchange, market));
+        }
+
+        // Round on every leg first so both sides trade the same final size
+        let volume = legs
+            .iter()
+            .fold(volume, |volume, (_, market)| market.round_amount(volume));
+        for (exchange, market) in &legs {
+            market
+                .validate_order(volume, reference_price)
+                .map_err(|e| {
+                    ArbitrageError::validation_error(format!("{} on {}: {}", pair, exchange, e))
+                })?;
+        }
+
+        Ok(volume)
+    }
+
+    fn now(&self) -> u64 {
+        self.now
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64)
+    }
+
+    fn funding_info(&self, exchange: ExchangeIdEnum, pair: &str) -> Option<&FundingRateInfo> {
+        self.funding_info
+            .get(&(exchange, Self::compact_symbol(pair)))
+    }
+
+    fn order_book(&self, exchange: ExchangeIdEnum, pair: &str) -> Option<&OrderBook> {
+        self.order_books
+            .get(&(exchange, Self::compact_symbol(pair)))
+    }
+
+    fn funding_interval_hours(&self, exchange: ExchangeIdEnum, pair: &str) -> f64 {
+        self.funding_info(exchange, pair)
+            .map(|info| info.funding_interval_hours)
+            .filter(|hours| *hours > 0)
+            .map(f64::from)
+            .unwrap_or(STANDARD_FUNDING_INTERVAL_HOURS)
+    }
+
+    /// Settlements a position opened now collects within the funding horizon. With a known
+    /// next settlement time they are counted exactly; otherwise the expected share is used.
+    fn settlements_in_horizon(&self, exchange: ExchangeIdEnum, pair: &str) -> f64 {
+        let horizon = self.config.funding_horizon_hours;
+        let interval = self.funding_interval_hours(exchange, pair);
+        let Some(next) = self
+            .funding_info(exchange, pair)
+            .and_then(|info| info.next_funding_time)
+        else {
+            return horizon / interval;
+        };
+        let until_next = next.saturating_sub(self.now()) as f64 / 3_600_000.0;
+        if until_next > horizon {
+            return 0.0;
+        }
+        ((horizon - until_next) / interval).floor() + 1.0
+    }
+
+    fn reference_price(&self, pair: &str, exchanges: &[ExchangeIdEnum]) -> Option<f64> {
+        exchanges
+            .iter()
+            .find_map(|exchange| self.funding_info(*exchange, pair)?.mark_price)
+            .or_else(|| {
+                exchanges
+                    .iter()
+                    .find_map(|exchange| self.order_book(*exchange, pair)?.mid_price())
+            })
+            .filter(|price| *price > 0.0)
+    }
+
+    /// Quantity both sides of `book` can fill up to `quantity`, and the cost of crossing
+    /// into and back out of that position as a fraction of the mid price
+    fn round_trip_slippage(book: &OrderBook, quantity: f64) -> (f64, f64) {
+        let Some(mid) = book.mid_price() else {
+            return (0.0, 0.0);
+        };
+        let (bought, buy_price) = walk_book(book, true, quantity, None, 1.0);
+        let (sold, sell_price) = walk_book(book, false, quantity, None, 1.0);
+        let impact = |price: Option<f64>| price.map(|p| (p - mid).abs() / mid).unwrap_or(0.0);
+        (bought.min(sold), impact(buy_price) + impact(sell_price))
+    }
+
+    /// Confidence rises with the share of the funding edge left after costs and with how
+    /// much of the cost model rests on registered schedules and order books
+    fn funding_confidence(
+        net_edge: f64,
+        funding_edge: f64,
+        books_known: bool,
+        schedules_known: bool,
+    ) -> f64 {
+        let retained = if funding_edge > 0.0 {
+            (net_edge / funding_edge).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let mut confidence = 0.4 + 0.3 * retained;
+        if books_known {
+            confidence += 0.15;
+        }
+        if schedules_known {
+            confidence += 0.15;
+        }
+        confidence.min(1.0)
+    }
+
     /// Calculate arbitrage profit value based on context
     fn calculate_arbitrage_profit_value(
         &self,
@@ -688,6 +1179,207 @@ mod tests {
         assert!(matches!(opportunity.r#type, ArbitrageType::FundingRate));
     }
 
+    fn test_market(symbol: &str, contract: bool, lot_size: f64, min_notional: f64) -> Market {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "base": "BTC",
+            "quote": "USDT",
+            "active": true,
+            "type_": if contract { "swap" } else { "spot" },
+            "spot": !contract,
+            "margin": false,
+            "future": contract,
+            "option": false,
+            "contract": contract,
+            "taker": 0.001,
+            "maker": 0.001,
+            "percentage": true,
+            "tier_based": false,
+            "limits": {"cost": {"min": min_notional}},
+            "precision": {"lot_size": lot_size},
+            "info": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_price_arbitrage_volume_respects_market_limits() {
+        let context = OpportunityContext::Personal {
+            user_id: "test_user".to_string(),
+        };
+        let builder = OpportunityBuilder::new(create_test_config())
+            .with_markets(
+                ExchangeIdEnum::Binance,
+                vec![test_market("BTCUSDT", false, 0.001, 5.0)],
+            )
+            .with_markets(
+                ExchangeIdEnum::Bybit,
+                vec![test_market("BTCUSDT", false, 10.0, 5.0)],
+            );
+
+        let opportunity = builder
+            .build_price_arbitrage(
+                "BTC/USDT".to_string(),
+                ExchangeIdEnum::Binance,
+                ExchangeIdEnum::Bybit,
+                50000.0,
+                50100.0,
+                &context,
+            )
+            .unwrap();
+        assert_eq!(opportunity.volume, DEFAULT_ARBITRAGE_VOLUME);
+
+        // Funding legs need perpetual markets, which are not registered here
+        let unlisted = builder.build_funding_rate_arbitrage(
+            "BTCUSDT".to_string(),
+            ExchangeIdEnum::Binance,
+            ExchangeIdEnum::Bybit,
+            0.0005,
+            0.0015,
+            &context,
+        );
+        assert!(unlisted.is_err());
+
+        // A lot size larger than the volume rounds it to zero
+        let coarse = OpportunityBuilder::new(create_test_config()).with_markets(
+            ExchangeIdEnum::Binance,
+            vec![test_market("BTCUSDT", false, 5000.0, 5.0)],
+        );
+        let error = coarse
+            .build_price_arbitrage(
+                "BTCUSDT".to_string(),
+                ExchangeIdEnum::Binance,
+                ExchangeIdEnum::Bybit,
+                50000.0,
+                50100.0,
+                &context,
+            )
+            .unwrap_err();
+        assert!(error.message.contains("rounds to zero"));
+    }
+
+    #[test]
+    fn test_net_spread_uses_account_fees_over_market_defaults() {
+        let fees = |taker: f64| TradingFeeRates {
+            maker: 0.0,
+            taker,
+            percentage: true,
+            tier_based: true,
+        };
+        // Binance has a VIP account rate; Bybit falls back to the market's base taker fee
+        let builder = OpportunityBuilder::new(create_test_config())
+            .with_markets(
+                ExchangeIdEnum::Bybit,
+                vec![
+                    test_market("BTCUSDT", false, 0.001, 5.0),
+                    test_market("BTCUSDT", true, 0.001, 5.0),
+                ],
+            )
+            .with_trading_fees(ExchangeIdEnum::Binance, "BTC/USDT", false, fees(0.0002));
+        let context = OpportunityContext::Personal {
+            user_id: "test_user".to_string(),
+        };
+
+        let price = builder
+            .build_price_arbitrage(
+                "BTCUSDT".to_string(),
+                ExchangeIdEnum::Binance,
+                ExchangeIdEnum::Bybit,
+                100.0,
+                101.0,
+                &context,
+            )
+            .unwrap();
+        assert!((price.net_rate_difference.unwrap() - (0.01 - 0.0012)).abs() < 1e-12);
+
+        // Funding legs are opened and later closed, so both takers are paid twice
+        let funding = builder
+            .clone()
+            .with_trading_fees(ExchangeIdEnum::Binance, "BTCUSDT", true, fees(0.0004))
+            .build_funding_rate_arbitrage(
+                "BTCUSDT".to_string(),
+                ExchangeIdEnum::Binance,
+                ExchangeIdEnum::Bybit,
+                0.0005,
+                0.0105,
+                &context,
+            )
+            .unwrap();
+        assert!((funding.net_rate_difference.unwrap() - (0.01 - 2.0 * 0.0014)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_funding_net_edge_normalizes_intervals_and_drops_unprofitable() {
+        const NOW: u64 = 1_700_000_000_000;
+        const HOUR: u64 = 3_600_000;
+        let fees = TradingFeeRates {
+            maker: 0.0,
+            taker: 0.0004,
+            percentage: true,
+            tier_based: false,
+        };
+        let schedule = |exchange, interval, next| FundingRateInfo {
+            symbol: "BTC/USDT".to_string(),
+            exchange,
+            funding_interval_hours: interval,
+            next_funding_time: Some(next),
+            mark_price: Some(50_000.0),
+            ..FundingRateInfo::default()
+        };
+        let book = OrderBook {
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![[49_990.0, 1.0]],
+            asks: vec![[50_010.0, 1.0]],
+            timestamp: NOW,
+            datetime: String::new(),
+            nonce: None,
+        };
+        let builder = |binance_next: u64| {
+            OpportunityBuilder::new(create_test_config())
+                .at_time(NOW)
+                .with_trading_fees(ExchangeIdEnum::Binance, "BTCUSDT", true, fees.clone())
+                .with_trading_fees(ExchangeIdEnum::Bybit, "BTCUSDT", true, fees.clone())
+                .with_funding_info(schedule(ExchangeIdEnum::Binance, 1, binance_next))
+                .with_funding_info(schedule(ExchangeIdEnum::Bybit, 8, NOW + 15 * HOUR / 2))
+                .with_order_book(ExchangeIdEnum::Binance, book.clone())
+        };
+        let context = OpportunityContext::Global { system_level: true };
+        let build = |builder: OpportunityBuilder| {
+            builder.build_funding_rate_arbitrage(
+                "BTCUSDT".to_string(),
+                ExchangeIdEnum::Bybit,
+                ExchangeIdEnum::Binance,
+                0.0001,
+                0.0003,
+                &context,
+            )
+        };
+
+        // Binance settles hourly from 30 minutes out, so it pays 8 times in the horizon
+        // while Bybit settles once; the edge is net of 4 fills and crossing Binance's spread
+        let opportunity = build(builder(NOW + HOUR / 2)).unwrap();
+        assert!((opportunity.rate_difference - (0.0024 - 0.0001)).abs() < 1e-12);
+        let net = 0.0023 - 4.0 * 0.0004 - 2.0 * 10.0 / 50_000.0;
+        assert!((opportunity.net_rate_difference.unwrap() - net).abs() < 1e-12);
+        assert!((opportunity.volume - 0.2).abs() < 1e-12);
+        assert!((opportunity.potential_profit_value.unwrap() - net * 10_000.0).abs() < 1e-9);
+        assert!(opportunity.confidence > 0.5 && opportunity.confidence < 0.85);
+        assert_eq!(opportunity.short_rate, Some(0.0003));
+
+        // With Binance's next settlement almost 8 hours away, fees outweigh the funding
+        let error = build(builder(NOW + 79 * HOUR / 10)).unwrap_err();
+        assert!(error.message.contains("Net edge"));
+
+        // A thin book caps the quantity at what it can fill
+        let thin = OrderBook {
+            asks: vec![[50_010.0, 0.05]],
+            ..book.clone()
+        };
+        let capped =
+            build(builder(NOW + HOUR / 2).with_order_book(ExchangeIdEnum::Binance, thin)).unwrap();
+        assert!((capped.volume - 0.05).abs() < 1e-12);
+    }
+
     #[test]
     fn test_technical_opportunity_builder() {
         let config = create_test_config();
@@ -789,6 +1481,7 @@ mod tests {
             r#type: ArbitrageType::FundingRate,
             details: Some("Test arbitrage".to_string()),
             min_exchanges_required: 2,
+            volume_24h: None,
         };
 
         let result = builder.build_global_opportunity_from_arbitrage(
@@ -1,6 +1,8 @@
 // Opportunity Categorization Service
 // Task 9.5: User Experience & Opportunity Categorization
 
+use crate::services::core::opportunities::basis_scanner::BASIS_CARRY_INDICATOR;
+use crate::services::core::opportunities::opportunity_filter::{FilterExpr, FilterRecord};
 use crate::services::{
     core::analysis::market_analysis::{
         OpportunityType, RiskLevel, TimeHorizon, TradingOpportunity,
@@ -41,6 +43,8 @@ pub enum OpportunityCategory {
     BeginnerFriendly, // Simple, low-risk opportunities for beginners
     #[serde(rename = "advanced_strategies")]
     AdvancedStrategies, // Complex strategies for experienced traders
+    #[serde(rename = "basis_carry")]
+    BasisCarry, // Delta-neutral spot vs futures carry
 }
 
 impl OpportunityCategory {
@@ -57,6 +61,7 @@ impl OpportunityCategory {
             OpportunityCategory::AiRecommended => "AI Recommended",
             OpportunityCategory::BeginnerFriendly => "Beginner Friendly",
             OpportunityCategory::AdvancedStrategies => "Advanced Strategies",
+            OpportunityCategory::BasisCarry => "Basis Carry",
         }
     }
 
@@ -89,6 +94,9 @@ impl OpportunityCategory {
             OpportunityCategory::AdvancedStrategies => {
                 "Complex strategies requiring trading experience"
             }
+            OpportunityCategory::BasisCarry => {
+                "Long spot, short perpetual or dated futures to earn the basis and funding"
+            }
         }
     }
 
@@ -98,6 +106,7 @@ impl OpportunityCategory {
             OpportunityCategory::LowRiskArbitrage => RiskLevel::Low,
             OpportunityCategory::HighConfidenceArbitrage => RiskLevel::Low,
             OpportunityCategory::BeginnerFriendly => RiskLevel::Low,
+            OpportunityCategory::BasisCarry => RiskLevel::Low,
             OpportunityCategory::TechnicalSignals => RiskLevel::Medium,
             OpportunityCategory::HybridEnhanced => RiskLevel::Medium,
             OpportunityCategory::AiRecommended => RiskLevel::Medium,
@@ -111,6 +120,7 @@ impl OpportunityCategory {
     /// Check if category is suitable for user's experience level
     pub fn is_suitable_for_experience(&self, experience: &ExperienceLevel) -> bool {
         match experience {
+            // Basis carry is left out: it needs a margined futures leg held for days
             ExperienceLevel::Beginner => {
                 matches!(
                     self,
@@ -250,6 +260,9 @@ pub struct UserOpportunityPreferences {
     pub alert_configs: Vec<CategoryAlertConfig>,
     pub global_alert_settings: GlobalAlertSettings,
     pub personalization_settings: PersonalizationSettings,
+    /// Normalized filter expression, see [`FilterExpr`]
+    #[serde(default)]
+    pub custom_filter: Option<String>,
     pub created_at: u64,
     pub updated_at: u64,
 }
@@ -266,6 +279,7 @@ impl UserOpportunityPreferences {
             alert_configs: vec![],
             global_alert_settings: GlobalAlertSettings::default(),
             personalization_settings: PersonalizationSettings::default(),
+            custom_filter: None,
             created_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
@@ -276,6 +290,49 @@ impl UserOpportunityPreferences {
                 .as_millis() as u64,
         }
     }
+
+    /// Preferences as stored in D1, where `preferences_data` may come back as a JSON string
+    pub fn from_stored(value: serde_json::Value) -> ArbitrageResult<Self> {
+        let value = match value {
+            serde_json::Value::String(json) => serde_json::from_str(&json).map_err(|e| {
+                ArbitrageError::parse_error(format!("Failed to parse user preferences: {}", e))
+            })?,
+            value => value,
+        };
+        serde_json::from_value(value).map_err(|e| {
+            ArbitrageError::parse_error(format!("Failed to parse user preferences: {}", e))
+        })
+    }
+
+    /// Validate and store a custom filter in normalized form; `None` or blank clears it
+    pub fn set_custom_filter(
+        &mut self,
+        source: Option<&str>,
+    ) -> ArbitrageResult<Option<FilterExpr>> {
+        let filter = source
+            .map(str::trim)
+            .filter(|source| !source.is_empty())
+            .map(FilterExpr::parse)
+            .transpose()?;
+        self.custom_filter = filter.as_ref().map(ToString::to_string);
+        Ok(filter)
+    }
+
+    /// Parsed custom filter; a stored filter that no longer parses is treated as absent
+    pub fn custom_filter_expr(&self) -> Option<FilterExpr> {
+        self.custom_filter
+            .as_deref()
+            .and_then(|source| FilterExpr::parse(source).ok())
+    }
+
+    /// Whether an opportunity passes the custom filter, if one is set
+    pub fn passes_custom_filter<'a, T>(&self, opportunity: &'a T) -> bool
+    where
+        FilterRecord: From<&'a T>,
+    {
+        self.custom_filter_expr()
+            .is_none_or(|filter| filter.matches(&FilterRecord::from(opportunity)))
+    }
 }
 
 /// Global alert settings
@@ -492,12 +549,7 @@ impl OpportunityCategorizationService {
             .get_user_opportunity_preferences(user_id)
             .await?
         {
-            Some(prefs) => {
-                // Convert serde_json::Value to UserOpportunityPreferences
-                serde_json::from_value::<UserOpportunityPreferences>(prefs).map_err(|e| {
-                    ArbitrageError::parse_error(format!("Failed to parse user preferences: {}", e))
-                })?
-            }
+            Some(prefs) => UserOpportunityPreferences::from_stored(prefs)?,
             None => {
                 // Create default preferences
                 let default_prefs =
@@ -619,6 +671,20 @@ impl OpportunityCategorizationService {
         Ok(())
     }
 
+    /// Validate and persist a user's custom filter expression; `None` clears it
+    pub async fn update_user_custom_filter(
+        &self,
+        user_id: &str,
+        source: Option<&str>,
+    ) -> ArbitrageResult<Option<FilterExpr>> {
+        let mut preferences = self.get_user_opportunity_preferences(user_id).await?;
+        let filter = preferences.set_custom_filter(source)?;
+        preferences.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        self.update_user_opportunity_preferences(&preferences)
+            .await?;
+        Ok(filter)
+    }
+
     /// Add or update alert configuration for a specific category
     pub async fn update_category_alert_config(
         &self,
@@ -683,7 +749,15 @@ impl OpportunityCategorizationService {
                 if opportunity.risk_level == RiskLevel::Low {
                     categories.push(OpportunityCategory::LowRiskArbitrage);
                 }
-                if opportunity.confidence_score >= 0.8 && opportunity.risk_level == RiskLevel::Low {
+                if opportunity
+                    .indicators_used
+                    .iter()
+                    .any(|indicator| indicator == BASIS_CARRY_INDICATOR)
+                {
+                    categories.insert(0, OpportunityCategory::BasisCarry);
+                } else if opportunity.confidence_score >= 0.8
+                    && opportunity.risk_level == RiskLevel::Low
+                {
                     categories.push(OpportunityCategory::BeginnerFriendly);
                 }
             }
@@ -736,6 +810,7 @@ impl OpportunityCategorizationService {
         // Priority based on user's trading focus
         let priority_order = match user_prefs.trading_focus {
             TradingFocus::Arbitrage => vec![
+                OpportunityCategory::BasisCarry,
                 OpportunityCategory::HighConfidenceArbitrage,
                 OpportunityCategory::LowRiskArbitrage,
                 OpportunityCategory::HybridEnhanced,
@@ -756,6 +831,7 @@ impl OpportunityCategorizationService {
                 OpportunityCategory::HighConfidenceArbitrage,
                 OpportunityCategory::TechnicalSignals,
                 OpportunityCategory::LowRiskArbitrage,
+                OpportunityCategory::BasisCarry,
                 OpportunityCategory::MomentumTrading,
             ],
         };
@@ -959,6 +1035,11 @@ impl OpportunityCategorizationService {
             return Ok(false);
         }
 
+        // Check the user's custom filter expression
+        if !user_opp_prefs.passes_custom_filter(&categorized_opp.base_opportunity) {
+            return Ok(false);
+        }
+
         Ok(true)
     }
 
@@ -1039,6 +1120,7 @@ pub struct CategoryStatistics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{ArbitrageOpportunity, ExchangeIdEnum};
     use crate::utils::logger::{LogLevel, Logger};
 
     #[allow(dead_code)]
@@ -1095,6 +1177,10 @@ mod tests {
         assert!(beginner_suitable.is_suitable_for_experience(&ExperienceLevel::Beginner));
         assert!(!advanced_only.is_suitable_for_experience(&ExperienceLevel::Beginner));
         assert!(advanced_only.is_suitable_for_experience(&ExperienceLevel::Advanced));
+
+        let carry = OpportunityCategory::BasisCarry;
+        assert!(!carry.is_suitable_for_experience(&ExperienceLevel::Beginner));
+        assert!(carry.is_suitable_for_experience(&ExperienceLevel::Intermediate));
     }
 
     #[test]
@@ -1137,4 +1223,48 @@ mod tests {
         assert!(settings.max_simultaneous_opportunities > 0);
         assert!(settings.diversity_preference >= 0.0 && settings.diversity_preference <= 1.0);
     }
+
+    #[test]
+    fn test_custom_filter_is_normalized_and_survives_storage() {
+        let mut prefs = UserOpportunityPreferences::default_for_user("user".to_string());
+        assert!(prefs.set_custom_filter(Some("net_edge > 0.1 AND")).is_err());
+        assert_eq!(prefs.custom_filter, None);
+
+        prefs
+            .set_custom_filter(Some("PAIR in [btc,eth] && exchange != 'mexc'"))
+            .unwrap();
+        assert_eq!(
+            prefs.custom_filter.as_deref(),
+            Some("pair in [btc, eth] and exchange != mexc")
+        );
+
+        // D1 hands preferences_data back as the JSON text it was stored as
+        let stored = serde_json::Value::String(serde_json::to_string(&prefs).unwrap());
+        let loaded = UserOpportunityPreferences::from_stored(stored).unwrap();
+        assert!(loaded.custom_filter_expr().is_some());
+        let mexc = ArbitrageOpportunity {
+            pair: "BTC/USDT".to_string(),
+            long_exchange: ExchangeIdEnum::Mexc,
+            ..Default::default()
+        };
+        assert!(!loaded.passes_custom_filter(&mexc));
+        assert!(loaded.passes_custom_filter(&ArbitrageOpportunity::default()));
+
+        // 24h volume comes from the tickers the opportunity was detected on
+        prefs.set_custom_filter(Some("volume_24h > 10M")).unwrap();
+        let thin = ArbitrageOpportunity {
+            volume_24h: Some(2e6),
+            ..Default::default()
+        };
+        let liquid = ArbitrageOpportunity {
+            volume_24h: Some(25e6),
+            ..Default::default()
+        };
+        assert!(!prefs.passes_custom_filter(&thin));
+        assert!(prefs.passes_custom_filter(&liquid));
+        assert!(!prefs.passes_custom_filter(&ArbitrageOpportunity::default()));
+
+        prefs.set_custom_filter(Some("  ")).unwrap();
+        assert_eq!(prefs.custom_filter, None);
+    }
 }
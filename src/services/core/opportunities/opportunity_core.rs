@@ -1,5 +1,7 @@
 // src/services/core/opportunities/opportunity_core.rs
 
+use crate::services::core::opportunities::basis_scanner::BasisScanConfig;
+use crate::services::core::opportunities::intra_exchange::IntraExchangeConfig;
 use crate::types::{
     ArbitrageOpportunity, ChatContext, ExchangeCredentials, ExchangeIdEnum, FundingRateInfo,
     TechnicalOpportunity, Ticker,
@@ -47,6 +49,26 @@ pub struct OpportunityConfig {
     pub monitored_exchanges: Vec<ExchangeIdEnum>,
     pub opportunity_ttl_minutes: u32,
     pub max_participants_per_opportunity: u32,
+    /// Notional each leg is sized and slippage-estimated at
+    #[serde(default = "default_trade_size_usd")]
+    pub trade_size_usd: f64,
+    /// Holding period funding edges are measured over
+    #[serde(default = "default_funding_horizon_hours")]
+    pub funding_horizon_hours: f64,
+    /// Triangular scan inside each monitored exchange
+    #[serde(default)]
+    pub intra_exchange: IntraExchangeConfig,
+    /// Spot vs perpetual and dated futures carry scan on each monitored exchange
+    #[serde(default)]
+    pub basis_scan: BasisScanConfig,
+}
+
+fn default_trade_size_usd() -> f64 {
+    10_000.0
+}
+
+fn default_funding_horizon_hours() -> f64 {
+    8.0
 }
 
 impl Default for OpportunityConfig {
@@ -64,6 +86,10 @@ impl Default for OpportunityConfig {
             monitored_exchanges: vec![ExchangeIdEnum::Binance, ExchangeIdEnum::Bybit],
             opportunity_ttl_minutes: 15,
             max_participants_per_opportunity: 10,
+            trade_size_usd: default_trade_size_usd(),
+            funding_horizon_hours: default_funding_horizon_hours(),
+            intra_exchange: IntraExchangeConfig::default(),
+            basis_scan: BasisScanConfig::default(),
         }
     }
 }
@@ -176,6 +202,15 @@ impl OpportunityUtils {
         }
     }
 
+    /// 24h quote volume from ticker data, estimated from base volume and last price when the
+    /// exchange does not report it
+    pub fn quote_volume_24h(ticker: &Ticker) -> Option<f64> {
+        ticker.quote_volume.or_else(|| {
+            let base_volume = ticker.base_volume.or(ticker.volume)?;
+            Some(base_volume * ticker.last?)
+        })
+    }
+
     /// Determine if price difference is significant for arbitrage
     pub fn is_arbitrage_significant(price_diff_percent: f64) -> bool {
         // value already expressed in percent
@@ -4,6 +4,7 @@ use crate::services::core::infrastructure::data_ingestion_module::{MessagePriori
 use crate::services::core::infrastructure::{
     database_repositories::DatabaseManager, DataAccessLayer, DataIngestionModule,
 };
+use crate::services::core::opportunities::opportunity_categorization::UserOpportunityPreferences;
 use crate::services::core::user::session_management::SessionManagementService;
 
 use crate::types::{
@@ -248,6 +249,9 @@ impl OpportunityDistributionService {
                                 &chat_context,
                             )
                             .await?
+                            && self
+                                .passes_custom_filter(user_id_str, arbitrage_opp)
+                                .await?
                         {
                             eligible_users.push(user_id_str.to_string());
                         }
@@ -259,6 +263,27 @@ impl OpportunityDistributionService {
         Ok(eligible_users)
     }
 
+    /// Whether the opportunity passes the custom `/filter` stored under the user's telegram id
+    async fn passes_custom_filter(
+        &self,
+        telegram_id: &str,
+        opportunity: &ArbitrageOpportunity,
+    ) -> ArbitrageResult<bool> {
+        let Some(stored) = self
+            .database_repositories
+            .get_user_opportunity_preferences(telegram_id)
+            .await?
+        else {
+            return Ok(true);
+        };
+        // One user's unreadable preferences must not fail the whole distribution
+        Ok(
+            UserOpportunityPreferences::from_stored(stored).map_or(true, |preferences| {
+                preferences.passes_custom_filter(opportunity)
+            }),
+        )
+    }
+
     /// Apply fairness algorithm to select users for distribution
     async fn apply_fairness_algorithm(
         &self,
@@ -1200,6 +1225,7 @@ impl OpportunityDistributionService {
             detected_at: created_at_from_row, // Use same value as created_at for now
             r#type: ArbitrageType::CrossExchange,
             min_exchanges_required: 2,
+            volume_24h: None,
             details,
         })
     }
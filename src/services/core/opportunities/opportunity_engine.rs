@@ -1,24 +1,29 @@
 // src/services/core/opportunities/opportunity_engine.rs
 
-use crate::log_info;
 use crate::services::core::ai::ai_beta_integration::AiBetaIntegrationService;
 use crate::services::core::opportunities::{
     access_manager::AccessManager,
     ai_enhancer::AIEnhancer,
+    basis_scanner::{self, BasisScanConfig},
+    intra_exchange::{self, IntraExchangeConfig},
     market_analyzer::MarketAnalyzer,
     opportunity_builders::OpportunityBuilder,
     opportunity_core::{OpportunityConfig, OpportunityContext},
+    opportunity_lifecycle::stable_id,
 };
+use crate::services::core::trading::exchange::{ExchangeInterface, ExchangeService};
 use crate::services::core::user::user_access::UserAccessService;
 use crate::services::core::user::UserProfileService;
 use crate::services::CacheManager;
 use crate::types::{
-    ArbitrageOpportunity, ChatContext, DistributionStrategy, GlobalOpportunity, OpportunitySource,
-    TechnicalOpportunity,
+    ArbitrageOpportunity, ChatContext, DistributionStrategy, ExchangeIdEnum, FundingRateInfo,
+    GlobalOpportunity, OpportunitySource, TechnicalOpportunity,
 };
 use crate::utils::{ArbitrageError, ArbitrageResult};
+use crate::{log_debug, log_info};
 use chrono::Utc;
 use serde_json;
+use std::collections::HashMap;
 use std::sync::Arc;
 use worker::kv::KvStore;
 
@@ -56,8 +61,7 @@ impl OpportunityEngine {
             Arc::new(kv_store.clone()),
         ));
 
-        // Create market analyzer without exchange service for now
-        // TODO: Properly inject ExchangeService when available
+        // Placeholder exchange service until one is injected with `with_exchange_service`
         let market_analyzer = Arc::new(MarketAnalyzer::new_without_exchange());
         let ai_enhancer = Arc::new(AIEnhancer::new(ai_service, access_manager.clone()));
         let cache_manager = Arc::new(CacheManager::new(kv_store.clone()));
@@ -75,6 +79,85 @@ impl OpportunityEngine {
         })
     }
 
+    /// Read tickers, markets and order books through `exchange_service` instead of the
+    /// placeholder service the engine starts with
+    pub fn with_exchange_service(mut self, exchange_service: Arc<ExchangeService>) -> Self {
+        self.market_analyzer = Arc::new(MarketAnalyzer::new(exchange_service));
+        self
+    }
+
+    /// The engine's builder with each exchange's market metadata registered, so volumes are
+    /// rounded to lot sizes and legs below a minimum amount or notional are rejected
+    async fn builder_with_markets(&self, exchanges: &[ExchangeIdEnum]) -> OpportunityBuilder {
+        self.market_analyzer
+            .fetch_markets(exchanges)
+            .await
+            .into_iter()
+            .fold(
+                (*self.opportunity_builder).clone(),
+                |builder, (exchange, markets)| builder.with_markets(exchange, markets),
+            )
+    }
+
+    /// Register each exchange's perpetual funding schedule and order book for `pair`, so
+    /// funding edges account for settlement timing and the slippage of entering
+    async fn with_funding_inputs(
+        &self,
+        builder: OpportunityBuilder,
+        pair: &str,
+        exchanges: &[ExchangeIdEnum],
+    ) -> OpportunityBuilder {
+        let (funding_info, order_books) = self
+            .market_analyzer
+            .fetch_funding_inputs(pair, exchanges)
+            .await;
+        let builder = funding_info
+            .into_iter()
+            .fold(builder, |builder, info| builder.with_funding_info(info));
+        order_books
+            .into_iter()
+            .fold(builder, |builder, (exchange, book)| {
+                builder.with_order_book(exchange, book)
+            })
+    }
+
+    /// Price a detected spread as a funding rate opportunity, keeping the 24h volume read
+    /// with it. Spreads below the threshold, on untradable markets, or with no edge left
+    /// after fees and slippage are skipped.
+    fn build_funding_opportunity(
+        builder: &OpportunityBuilder,
+        detected: ArbitrageOpportunity,
+        context: &OpportunityContext,
+    ) -> Option<ArbitrageOpportunity> {
+        let pair = detected.pair.clone();
+        let volume_24h = detected.volume_24h;
+        builder
+            .build_funding_rate_arbitrage(
+                detected.pair,
+                detected.long_exchange,
+                detected.short_exchange,
+                detected.long_rate.unwrap_or(0.0),
+                detected.short_rate.unwrap_or(0.0),
+                context,
+            )
+            .map(|opportunity| ArbitrageOpportunity {
+                volume_24h,
+                ..opportunity
+            })
+            .map_err(|e| {
+                log_debug!(
+                    "Rejected funding rate opportunity",
+                    serde_json::json!({
+                        "pair": pair,
+                        "long_exchange": detected.long_exchange.as_str(),
+                        "short_exchange": detected.short_exchange.as_str(),
+                        "reason": e.to_string()
+                    })
+                );
+            })
+            .ok()
+    }
+
     // Personal Opportunity Generation (replaces PersonalOpportunityService)
 
     /// Generate personal arbitrage opportunities for a user
@@ -124,32 +207,38 @@ impl OpportunityEngine {
 
         // Use default pairs if none provided
         let trading_pairs = pairs.unwrap_or_else(|| self.config.default_pairs.clone());
+        let exchanges: Vec<ExchangeIdEnum> = user_exchanges.iter().map(|(ex, _)| *ex).collect();
+        let market_builder = self.builder_with_markets(&exchanges).await;
 
         // Analyze market data and detect opportunities
         let mut opportunities = Vec::new();
         for pair in &trading_pairs {
             let pair_opportunities = self
                 .market_analyzer
-                .detect_arbitrage_opportunities(
-                    pair,
-                    &user_exchanges.iter().map(|(ex, _)| *ex).collect::<Vec<_>>(),
-                    &self.config,
-                )
+                .detect_arbitrage_opportunities(pair, &exchanges, &self.config)
                 .await?;
 
-            for market_opp in pair_opportunities {
-                let opportunity = self.opportunity_builder.build_funding_rate_arbitrage(
-                    market_opp.pair,
-                    market_opp.long_exchange,
-                    market_opp.short_exchange,
-                    market_opp.long_rate.unwrap_or(0.0),
-                    market_opp.short_rate.unwrap_or(0.0),
-                    &OpportunityContext::Personal {
-                        user_id: user_id.to_string(),
-                    },
-                )?;
-                opportunities.push(opportunity);
+            if pair_opportunities.is_empty() {
+                continue;
             }
+
+            // Net spreads use the fees this user's own accounts pay
+            let builder = self
+                .market_analyzer
+                .fetch_trading_fees(pair, &user_exchanges, true)
+                .await
+                .into_iter()
+                .fold(market_builder.clone(), |builder, (exchange, fees)| {
+                    builder.with_trading_fees(exchange, pair, true, fees)
+                });
+            let builder = self.with_funding_inputs(builder, pair, &exchanges).await;
+
+            let context = OpportunityContext::Personal {
+                user_id: user_id.to_string(),
+            };
+            opportunities.extend(pair_opportunities.into_iter().filter_map(|market_opp| {
+                Self::build_funding_opportunity(&builder, market_opp, &context)
+            }));
         }
 
         // Apply subscription-based filtering
@@ -348,42 +437,31 @@ impl OpportunityEngine {
         }
 
         let trading_pairs = pairs.unwrap_or_else(|| self.config.default_pairs.clone());
+        let exchanges: Vec<ExchangeIdEnum> = admin_exchanges.iter().map(|(ex, _)| *ex).collect();
+        let market_builder = self.builder_with_markets(&exchanges).await;
+        let context = OpportunityContext::Group {
+            admin_id: group_admin_id.to_string(),
+            chat_context: chat_context.clone(),
+        };
 
         // Generate opportunities using admin's APIs
         let mut opportunities = Vec::new();
         for pair in &trading_pairs {
             let pair_opportunities = self
                 .market_analyzer
-                .detect_arbitrage_opportunities(
-                    pair,
-                    &admin_exchanges
-                        .iter()
-                        .map(|(ex, _)| *ex)
-                        .collect::<Vec<_>>(),
-                    &self.config,
-                )
+                .detect_arbitrage_opportunities(pair, &exchanges, &self.config)
                 .await?;
 
-            for market_opp in pair_opportunities {
-                let mut opportunity = self.opportunity_builder.build_funding_rate_arbitrage(
-                    market_opp.pair,
-                    market_opp.long_exchange,
-                    market_opp.short_exchange,
-                    market_opp.long_rate.unwrap_or(0.0),
-                    market_opp.short_rate.unwrap_or(0.0),
-                    &OpportunityContext::Group {
-                        admin_id: group_admin_id.to_string(),
-                        chat_context: chat_context.clone(),
-                    },
-                )?;
-
-                // Apply group multiplier (2x opportunities)
-                if let Some(profit) = opportunity.potential_profit_value {
-                    opportunity.potential_profit_value = Some(profit * 2.0);
-                }
-
-                opportunities.push(opportunity);
+            if pair_opportunities.is_empty() {
+                continue;
             }
+
+            let builder = self
+                .with_funding_inputs(market_builder.clone(), pair, &exchanges)
+                .await;
+            opportunities.extend(pair_opportunities.into_iter().filter_map(|market_opp| {
+                Self::build_funding_opportunity(&builder, market_opp, &context)
+            }));
         }
 
         // Enhance with AI using admin's access level
@@ -404,8 +482,7 @@ impl OpportunityEngine {
             serde_json::json!({
                 "group_admin_id": group_admin_id,
                 "group_id": group_id,
-                "count": opportunities.len(),
-                "multiplier_applied": true
+                "count": opportunities.len()
             })
         );
 
@@ -430,42 +507,90 @@ impl OpportunityEngine {
 
         let trading_pairs = pairs.unwrap_or_else(|| self.config.default_pairs.clone());
         let monitored_exchanges = self.config.monitored_exchanges.clone();
+        let market_builder = self.builder_with_markets(&monitored_exchanges).await;
+        let context = OpportunityContext::Global { system_level: true };
 
         // Generate arbitrage opportunities across all monitored exchanges
-        let mut global_opportunities = Vec::new();
+        let mut scanned = Vec::new();
         for pair in &trading_pairs {
             let arbitrage_opportunities = self
                 .market_analyzer
                 .detect_arbitrage_opportunities(pair, &monitored_exchanges, &self.config)
                 .await?;
 
-            for arb_opp in arbitrage_opportunities {
-                let opportunity = self.opportunity_builder.build_funding_rate_arbitrage(
-                    arb_opp.pair,
-                    arb_opp.long_exchange,
-                    arb_opp.short_exchange,
-                    arb_opp.long_rate.unwrap_or(0.0),
-                    arb_opp.short_rate.unwrap_or(0.0),
-                    &OpportunityContext::Global { system_level: true },
-                )?;
+            if arbitrage_opportunities.is_empty() {
+                continue;
+            }
 
-                // Convert to global opportunity
-                let expires_at = Utc::now().timestamp_millis() as u64
-                    + (self.config.opportunity_ttl_minutes as u64 * 60 * 1000);
-                let global_opp = self
-                    .opportunity_builder
-                    .build_global_opportunity_from_arbitrage(
-                        opportunity,
-                        OpportunitySource::SystemGenerated,
-                        expires_at,
-                        Some(self.config.max_participants_per_opportunity),
-                        DistributionStrategy::RoundRobin,
-                    )?;
-
-                global_opportunities.push(global_opp);
+            let builder = self
+                .with_funding_inputs(market_builder.clone(), pair, &monitored_exchanges)
+                .await;
+            scanned.extend(arbitrage_opportunities.into_iter().filter_map(|arb_opp| {
+                Self::build_funding_opportunity(&builder, arb_opp, &context)
+            }));
+        }
+
+        // Then inside each monitored exchange; one exchange failing does not stop the others
+        let exchange_service = self.market_analyzer.exchange_service().as_ref();
+        for exchange_id in &monitored_exchanges {
+            let scans = [
+                (
+                    "intra_exchange",
+                    self.generate_intra_exchange_opportunities(
+                        exchange_service,
+                        *exchange_id,
+                        &self.config.intra_exchange,
+                    )
+                    .await,
+                ),
+                (
+                    "basis_carry",
+                    self.generate_basis_carry_opportunities(
+                        exchange_service,
+                        *exchange_id,
+                        &self.config.basis_scan,
+                    )
+                    .await,
+                ),
+            ];
+            for (scan, result) in scans {
+                match result {
+                    Ok(opportunities) => scanned.extend(opportunities),
+                    Err(e) => log_info!(
+                        "Exchange scan failed",
+                        serde_json::json!({
+                            "scan": scan,
+                            "exchange": exchange_id.as_str(),
+                            "error": e.to_string()
+                        })
+                    ),
+                }
             }
         }
 
+        let expires_at = Utc::now().timestamp_millis() as u64
+            + (self.config.opportunity_ttl_minutes as u64 * 60 * 1000);
+        let mut global_opportunities = Vec::new();
+        for opportunity in scanned {
+            let opportunity = ArbitrageOpportunity {
+                id: stable_id(&opportunity),
+                ..opportunity
+            };
+
+            // Convert to global opportunity
+            let global_opp = self
+                .opportunity_builder
+                .build_global_opportunity_from_arbitrage(
+                    opportunity,
+                    OpportunitySource::SystemGenerated,
+                    expires_at,
+                    Some(self.config.max_participants_per_opportunity),
+                    DistributionStrategy::RoundRobin,
+                )?;
+
+            global_opportunities.push(global_opp);
+        }
+
         // Enhance with system-level AI
         let arbitrage_opportunities: Vec<ArbitrageOpportunity> = global_opportunities
             .iter()
@@ -516,6 +641,117 @@ impl OpportunityEngine {
         Ok(global_opportunities)
     }
 
+    // Intra-Exchange Opportunity Generation
+
+    /// Scan one exchange for triangular cycles
+    ///
+    /// The result is ready for `ServiceContainer::distribute_opportunities`.
+    pub async fn generate_intra_exchange_opportunities<E: ExchangeInterface>(
+        &self,
+        exchange: &E,
+        exchange_id: ExchangeIdEnum,
+        intra_config: &IntraExchangeConfig,
+    ) -> ArbitrageResult<Vec<ArbitrageOpportunity>> {
+        let (markets, books) = intra_exchange::fetch_scan_inputs(
+            exchange,
+            exchange_id,
+            &self.config.default_pairs,
+            intra_config,
+            false,
+        )
+        .await?;
+        let context = OpportunityContext::Global { system_level: true };
+
+        let mut opportunities = Vec::new();
+        for cycle in
+            intra_exchange::find_triangular_cycles(exchange_id, &markets, &books, intra_config)
+        {
+            if let Ok(opportunity) = self
+                .opportunity_builder
+                .build_triangular_arbitrage(&cycle, &context)
+            {
+                opportunities.push(opportunity);
+            }
+        }
+
+        log_info!(
+            "Generated intra-exchange opportunities",
+            serde_json::json!({
+                "exchange": exchange_id.as_str(),
+                "markets": markets.len(),
+                "books": books.len(),
+                "count": opportunities.len()
+            })
+        );
+
+        Ok(opportunities)
+    }
+
+    /// Scan one exchange for spot vs perpetual and dated futures carry on the monitored pairs
+    pub async fn generate_basis_carry_opportunities<E: ExchangeInterface>(
+        &self,
+        exchange: &E,
+        exchange_id: ExchangeIdEnum,
+        scan_config: &BasisScanConfig,
+    ) -> ArbitrageResult<Vec<ArbitrageOpportunity>> {
+        // Only the monitored pairs' own assets; no stablecoin cross pairs are needed
+        let universe = IntraExchangeConfig {
+            start_assets: Vec::new(),
+            ..Default::default()
+        };
+        let (markets, books) = intra_exchange::fetch_scan_inputs(
+            exchange,
+            exchange_id,
+            &self.config.default_pairs,
+            &universe,
+            true,
+        )
+        .await?;
+
+        let mut funding = HashMap::new();
+        for market in markets.iter().filter(|m| m.contract && m.expiry.is_none()) {
+            let info = exchange
+                .fetch_funding_rates(exchange_id.as_str(), Some(&market.symbol))
+                .await
+                .ok()
+                .and_then(|rates| rates.into_iter().next())
+                .and_then(|rate| serde_json::from_value::<FundingRateInfo>(rate).ok());
+            if let Some(info) = info {
+                funding.insert(market.symbol.clone(), info);
+            }
+        }
+
+        let now = Utc::now().timestamp_millis() as u64;
+        let context = OpportunityContext::Global { system_level: true };
+        let opportunities: Vec<ArbitrageOpportunity> = basis_scanner::scan_basis_carry(
+            exchange_id,
+            &markets,
+            &books,
+            &funding,
+            now,
+            scan_config,
+        )
+        .iter()
+        .filter_map(|carry| {
+            self.opportunity_builder
+                .build_basis_carry_opportunity(carry, &context)
+                .ok()
+        })
+        .collect();
+
+        log_info!(
+            "Generated basis carry opportunities",
+            serde_json::json!({
+                "exchange": exchange_id.as_str(),
+                "markets": markets.len(),
+                "funding_rates": funding.len(),
+                "count": opportunities.len()
+            })
+        );
+
+        Ok(opportunities)
+    }
+
     // Legacy Compatibility (replaces OpportunityService)
 
     /// Generate opportunities with legacy compatibility
@@ -0,0 +1,962 @@
+// src/services/core/opportunities/opportunity_filter.rs
+
+//! User-defined opportunity filters.
+//!
+//! A small expression language over opportunity fields, e.g.
+//!
+//! ```text
+//! pair in [BTC, ETH] and net_edge > 0.05% and exchange != mexc and volume_24h > 10M
+//! ```
+//!
+//! Expressions combine comparisons (`==`, `!=`, `>`, `>=`, `<`, `<=`, `in [..]`,
+//! `not in [..]`) with `and`, `or`, `not` and parentheses. Numbers accept `%` (divided by
+//! 100) and `k`, `M`, `B` suffixes. Evaluation only reads values copied out of the
+//! opportunity into a [`FilterRecord`], and parsing enforces limits on length, nesting
+//! and list size, so a stored filter cannot do anything but answer true or false.
+//!
+//! Fields holding several values, such as the exchanges of a two-leg opportunity, match
+//! `==` and `in` when any value matches, and `!=` and `not in` only when none does. A
+//! comparison on a field the opportunity does not carry is false.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::services::core::analysis::market_analysis::{RiskLevel, TradingOpportunity};
+use crate::services::core::opportunities::opportunity_lifecycle::type_slug;
+use crate::types::{ArbitrageOpportunity, TechnicalOpportunity};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+const MAX_EXPRESSION_LENGTH: usize = 512;
+const MAX_NESTING: usize = 16;
+const MAX_LIST_LENGTH: usize = 50;
+const MAX_COMPARISONS: usize = 32;
+
+/// Quote assets stripped from compact symbols to find the base asset, longest first
+const KNOWN_QUOTES: &[&str] = &["FDUSD", "USDT", "USDC", "BUSD", "USD", "EUR", "BTC", "ETH"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Number,
+    Text,
+}
+
+struct FieldSpec {
+    name: &'static str,
+    kind: FieldKind,
+    description: &'static str,
+}
+
+const FIELDS: &[FieldSpec] = &[
+    FieldSpec {
+        name: "pair",
+        kind: FieldKind::Text,
+        description: "trading pair or its base asset, e.g. BTCUSDT or BTC",
+    },
+    FieldSpec {
+        name: "base",
+        kind: FieldKind::Text,
+        description: "base asset, e.g. BTC",
+    },
+    FieldSpec {
+        name: "quote",
+        kind: FieldKind::Text,
+        description: "quote asset, e.g. USDT",
+    },
+    FieldSpec {
+        name: "exchange",
+        kind: FieldKind::Text,
+        description: "any exchange involved, e.g. binance",
+    },
+    FieldSpec {
+        name: "type",
+        kind: FieldKind::Text,
+        description: "funding_rate, cross_exchange, price, spot_futures, triangular or technical",
+    },
+    FieldSpec {
+        name: "risk",
+        kind: FieldKind::Text,
+        description: "low, medium or high",
+    },
+    FieldSpec {
+        name: "signal",
+        kind: FieldKind::Text,
+        description: "technical signal, e.g. buy, sell, rsioversold",
+    },
+    FieldSpec {
+        name: "net_edge",
+        kind: FieldKind::Number,
+        description: "edge after costs, or expected return for technical signals, e.g. 0.05%",
+    },
+    FieldSpec {
+        name: "gross_edge",
+        kind: FieldKind::Number,
+        description: "rate or price difference before costs",
+    },
+    FieldSpec {
+        name: "confidence",
+        kind: FieldKind::Number,
+        description: "confidence between 0 and 1",
+    },
+    FieldSpec {
+        name: "profit",
+        kind: FieldKind::Number,
+        description: "estimated profit in USD",
+    },
+    FieldSpec {
+        name: "volume",
+        kind: FieldKind::Number,
+        description: "tradable size in base units",
+    },
+    FieldSpec {
+        name: "volume_24h",
+        kind: FieldKind::Number,
+        description: "24h quote volume in USD, e.g. 10M",
+    },
+];
+
+fn field_spec(name: &str) -> Option<&'static FieldSpec> {
+    FIELDS.iter().find(|spec| spec.name == name)
+}
+
+/// One line per field, for help messages
+pub fn field_help() -> String {
+    FIELDS
+        .iter()
+        .map(|spec| format!("{} - {}", spec.name, spec.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CompareOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+        }
+    }
+}
+
+/// A literal as written, with its numeric value when it reads as a number
+#[derive(Debug, Clone, PartialEq)]
+pub struct Literal {
+    pub raw: String,
+    pub number: Option<f64>,
+}
+
+impl Literal {
+    fn text(&self) -> String {
+        normalize_text(&self.raw)
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bare = self
+            .raw
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '%' | '/' | '-'));
+        if bare && !self.raw.is_empty() {
+            write!(f, "{}", self.raw)
+        } else {
+            write!(f, "\"{}\"", self.raw)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare {
+        field: &'static str,
+        op: CompareOp,
+        value: Literal,
+    },
+    In {
+        field: &'static str,
+        values: Vec<Literal>,
+        negated: bool,
+    },
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterExpr::And(left, right) => {
+                for (i, side) in [left, right].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " and ")?;
+                    }
+                    match side.as_ref() {
+                        FilterExpr::Or(..) => write!(f, "({})", side)?,
+                        _ => write!(f, "{}", side)?,
+                    }
+                }
+                Ok(())
+            }
+            FilterExpr::Or(left, right) => write!(f, "{} or {}", left, right),
+            FilterExpr::Not(inner) => match inner.as_ref() {
+                FilterExpr::And(..) | FilterExpr::Or(..) => write!(f, "not ({})", inner),
+                _ => write!(f, "not {}", inner),
+            },
+            FilterExpr::Compare { field, op, value } => {
+                write!(f, "{} {} {}", field, op.as_str(), value)
+            }
+            FilterExpr::In {
+                field,
+                values,
+                negated,
+            } => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                let op = if *negated { "not in" } else { "in" };
+                write!(f, "{} {} [{}]", field, op, values.join(", "))
+            }
+        }
+    }
+}
+
+/// Field values of one opportunity, copied out for evaluation
+#[derive(Debug, Clone, Default)]
+pub struct FilterRecord {
+    numbers: HashMap<&'static str, f64>,
+    texts: HashMap<&'static str, Vec<String>>,
+}
+
+fn normalize_text(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Base and quote of "BTC/USDT", "BTC-USDT" or "BTCUSDT"
+fn split_pair(pair: &str) -> Option<(String, String)> {
+    let first = pair.split([' ', '→']).next().unwrap_or(pair);
+    let mut parts = first.split(['/', '-']);
+    if let (Some(base), Some(quote)) = (parts.next(), parts.next()) {
+        if KNOWN_QUOTES.contains(&quote.to_uppercase().as_str()) {
+            return Some((base.to_uppercase(), quote.to_uppercase()));
+        }
+    }
+    let compact = normalize_text(base_segment(first)).to_uppercase();
+    KNOWN_QUOTES.iter().find_map(|quote| {
+        compact
+            .strip_suffix(quote)
+            .filter(|base| !base.is_empty())
+            .map(|base| (base.to_string(), quote.to_string()))
+    })
+}
+
+fn base_segment(symbol: &str) -> &str {
+    symbol.split(['/', '-', '_']).next().unwrap_or(symbol)
+}
+
+fn risk_name(risk: &RiskLevel) -> &'static str {
+    match risk {
+        RiskLevel::Low => "low",
+        RiskLevel::Medium => "medium",
+        RiskLevel::High => "high",
+    }
+}
+
+impl FilterRecord {
+    pub fn with_number(mut self, field: &'static str, value: f64) -> Self {
+        self.numbers.insert(field, value);
+        self
+    }
+
+    fn set_number(&mut self, field: &'static str, value: Option<f64>) {
+        if let Some(value) = value.filter(|v| v.is_finite()) {
+            self.numbers.insert(field, value);
+        }
+    }
+
+    fn set_text<I, S>(&mut self, field: &'static str, values: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|v| normalize_text(v.as_ref()))
+            .filter(|v| !v.is_empty())
+            .collect();
+        if !values.is_empty() {
+            self.texts.insert(field, values);
+        }
+    }
+
+    fn set_pair(&mut self, pair: &str) {
+        match split_pair(pair) {
+            Some((base, quote)) => {
+                self.set_text("pair", [format!("{}{}", base, quote), base.clone()]);
+                self.set_text("base", [base]);
+                self.set_text("quote", [quote]);
+            }
+            None => self.set_text("pair", [pair]),
+        }
+    }
+
+    fn set_metadata_number(&mut self, field: &'static str, metadata: &serde_json::Value) {
+        self.set_number(
+            field,
+            metadata.get(field).and_then(serde_json::Value::as_f64),
+        );
+    }
+}
+
+impl From<&ArbitrageOpportunity> for FilterRecord {
+    fn from(opportunity: &ArbitrageOpportunity) -> Self {
+        let mut record = FilterRecord::default();
+        record.set_pair(&opportunity.pair);
+        let mut exchanges = vec![
+            opportunity.long_exchange.as_str().to_string(),
+            opportunity.short_exchange.as_str().to_string(),
+        ];
+        exchanges.extend(opportunity.exchanges.iter().cloned());
+        record.set_text("exchange", exchanges);
+        record.set_text("type", [type_slug(&opportunity.r#type)]);
+        record.set_text("risk", [opportunity.risk_level.as_str()]);
+        record.set_number(
+            "net_edge",
+            Some(
+                opportunity
+                    .net_rate_difference
+                    .unwrap_or(opportunity.rate_difference),
+            ),
+        );
+        record.set_number("gross_edge", Some(opportunity.rate_difference));
+        record.set_number("confidence", Some(opportunity.confidence_score));
+        record.set_number("profit", opportunity.potential_profit_value);
+        record.set_number("volume", Some(opportunity.volume));
+        record.set_number("volume_24h", opportunity.volume_24h);
+        record
+    }
+}
+
+impl From<&TechnicalOpportunity> for FilterRecord {
+    fn from(opportunity: &TechnicalOpportunity) -> Self {
+        let mut record = FilterRecord::default();
+        record.set_pair(&opportunity.pair);
+        let mut exchanges = opportunity.exchanges.clone();
+        exchanges.push(opportunity.exchange.clone());
+        record.set_text("exchange", exchanges);
+        record.set_text("type", ["technical"]);
+        record.set_text("risk", [opportunity.risk_level.as_str()]);
+        if let Ok(serde_json::Value::String(signal)) =
+            serde_json::to_value(&opportunity.signal_type)
+        {
+            record.set_text("signal", [signal]);
+        }
+        record.set_number(
+            "net_edge",
+            Some(opportunity.expected_return_percentage / 100.0),
+        );
+        record.set_number("confidence", Some(opportunity.confidence));
+        record.set_metadata_number("volume_24h", &opportunity.metadata);
+        record
+    }
+}
+
+impl From<&TradingOpportunity> for FilterRecord {
+    fn from(opportunity: &TradingOpportunity) -> Self {
+        let mut record = FilterRecord::default();
+        record.set_pair(&opportunity.trading_pair);
+        record.set_text("exchange", &opportunity.exchanges);
+        record.set_text("type", [opportunity.opportunity_type.to_string()]);
+        record.set_text("risk", [risk_name(&opportunity.risk_level)]);
+        record.set_number("net_edge", Some(opportunity.expected_return / 100.0));
+        record.set_number("confidence", Some(opportunity.confidence_score));
+        record.set_metadata_number("volume_24h", &opportunity.analysis_data);
+        record
+    }
+}
+
+impl FilterExpr {
+    /// Parse and validate an expression; errors name the position and what was expected
+    pub fn parse(source: &str) -> ArbitrageResult<Self> {
+        if source.chars().count() > MAX_EXPRESSION_LENGTH {
+            return Err(ArbitrageError::validation_error(format!(
+                "Filter is longer than {} characters",
+                MAX_EXPRESSION_LENGTH
+            )));
+        }
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+            comparisons: 0,
+        };
+        let expression = parser.or_expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(filter_error(
+                token.position,
+                format!(
+                    "expected 'and', 'or' or the end of the filter, found '{}'",
+                    token.text
+                ),
+            ));
+        }
+        Ok(expression)
+    }
+
+    pub fn matches(&self, record: &FilterRecord) -> bool {
+        match self {
+            FilterExpr::And(left, right) => left.matches(record) && right.matches(record),
+            FilterExpr::Or(left, right) => left.matches(record) || right.matches(record),
+            FilterExpr::Not(inner) => !inner.matches(record),
+            FilterExpr::Compare { field, op, value } => match field_spec(field).map(|s| s.kind) {
+                Some(FieldKind::Number) => {
+                    let (Some(actual), Some(expected)) = (record.numbers.get(field), value.number)
+                    else {
+                        return false;
+                    };
+                    match op {
+                        CompareOp::Eq => (actual - expected).abs() < f64::EPSILON,
+                        CompareOp::Ne => (actual - expected).abs() >= f64::EPSILON,
+                        CompareOp::Gt => *actual > expected,
+                        CompareOp::Ge => *actual >= expected,
+                        CompareOp::Lt => *actual < expected,
+                        CompareOp::Le => *actual <= expected,
+                    }
+                }
+                Some(FieldKind::Text) => {
+                    let Some(actual) = record.texts.get(field) else {
+                        return false;
+                    };
+                    let expected = value.text();
+                    let any = actual.contains(&expected);
+                    match op {
+                        CompareOp::Ne => !any,
+                        _ => any,
+                    }
+                }
+                None => false,
+            },
+            FilterExpr::In {
+                field,
+                values,
+                negated,
+            } => {
+                let any = match field_spec(field).map(|s| s.kind) {
+                    Some(FieldKind::Number) => {
+                        let Some(actual) = record.numbers.get(field) else {
+                            return false;
+                        };
+                        values
+                            .iter()
+                            .filter_map(|v| v.number)
+                            .any(|expected| (actual - expected).abs() < f64::EPSILON)
+                    }
+                    Some(FieldKind::Text) => {
+                        let Some(actual) = record.texts.get(field) else {
+                            return false;
+                        };
+                        values.iter().any(|v| actual.contains(&v.text()))
+                    }
+                    None => return false,
+                };
+                any != *negated
+            }
+        }
+    }
+}
+
+fn filter_error(position: usize, message: String) -> ArbitrageError {
+    ArbitrageError::validation_error(format!(
+        "Filter error at position {}: {}",
+        position + 1,
+        message
+    ))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word,
+    Quoted,
+    Op(CompareOp),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    AndSymbol,
+    OrSymbol,
+    Bang,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    position: usize,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+}
+
+fn tokenize(source: &str) -> ArbitrageResult<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+        let (kind, len) = match (c, two.as_str()) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            (_, "==") => (TokenKind::Op(CompareOp::Eq), 2),
+            (_, "!=") => (TokenKind::Op(CompareOp::Ne), 2),
+            (_, ">=") => (TokenKind::Op(CompareOp::Ge), 2),
+            (_, "<=") => (TokenKind::Op(CompareOp::Le), 2),
+            (_, "&&") => (TokenKind::AndSymbol, 2),
+            (_, "||") => (TokenKind::OrSymbol, 2),
+            ('=', _) => (TokenKind::Op(CompareOp::Eq), 1),
+            ('>', _) => (TokenKind::Op(CompareOp::Gt), 1),
+            ('<', _) => (TokenKind::Op(CompareOp::Lt), 1),
+            ('!', _) => (TokenKind::Bang, 1),
+            ('(', _) => (TokenKind::LParen, 1),
+            (')', _) => (TokenKind::RParen, 1),
+            ('[', _) => (TokenKind::LBracket, 1),
+            (']', _) => (TokenKind::RBracket, 1),
+            (',', _) => (TokenKind::Comma, 1),
+            ('"' | '\'', _) => {
+                let Some(end) = chars[i + 1..].iter().position(|&q| q == c) else {
+                    return Err(filter_error(start, format!("unclosed {} quote", c)));
+                };
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                tokens.push(Token {
+                    kind: TokenKind::Quoted,
+                    text,
+                    position: start,
+                });
+                i += end + 2;
+                continue;
+            }
+            (c, _) if c.is_ascii_alphanumeric() || matches!(c, '_' | '.') => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|&&c| {
+                        c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '%' | '/' | '-')
+                    })
+                    .count();
+                (TokenKind::Word, len)
+            }
+            (c, _) => return Err(filter_error(start, format!("unexpected character '{}'", c))),
+        };
+        tokens.push(Token {
+            kind,
+            text: chars[i..i + len].iter().collect(),
+            position: start,
+        });
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Numeric value of `0.05%`, `10M`, `2.5k`, `-3`; `None` when the word is not a number
+fn parse_number(word: &str) -> Option<f64> {
+    let (digits, scale) = match word.chars().last()? {
+        '%' => (&word[..word.len() - 1], 0.01),
+        'k' | 'K' => (&word[..word.len() - 1], 1e3),
+        'm' | 'M' => (&word[..word.len() - 1], 1e6),
+        'b' | 'B' => (&word[..word.len() - 1], 1e9),
+        _ => (word, 1.0),
+    };
+    if digits.is_empty()
+        || !digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+    {
+        return None;
+    }
+    digits
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .map(|v| v * scale)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+    comparisons: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn end_position(&self) -> usize {
+        self.tokens
+            .last()
+            .map(|t| t.position + t.text.chars().count())
+            .unwrap_or(0)
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> ArbitrageResult<Token> {
+        match self.next() {
+            Some(token) if token.kind == kind => Ok(token),
+            Some(token) => Err(filter_error(
+                token.position,
+                format!("expected {}, found '{}'", what, token.text),
+            )),
+            None => Err(filter_error(
+                self.end_position(),
+                format!("expected {} before the end of the filter", what),
+            )),
+        }
+    }
+
+    fn or_expression(&mut self) -> ArbitrageResult<FilterExpr> {
+        let mut left = self.and_expression()?;
+        while self
+            .peek()
+            .is_some_and(|t| t.is_keyword("or") || t.kind == TokenKind::OrSymbol)
+        {
+            self.next();
+            let right = self.and_expression()?;
+            left = FilterExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and_expression(&mut self) -> ArbitrageResult<FilterExpr> {
+        let mut left = self.unary()?;
+        while self
+            .peek()
+            .is_some_and(|t| t.is_keyword("and") || t.kind == TokenKind::AndSymbol)
+        {
+            self.next();
+            let right = self.unary()?;
+            left = FilterExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> ArbitrageResult<FilterExpr> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            let position = self.peek().map(|t| t.position).unwrap_or(0);
+            return Err(filter_error(
+                position,
+                format!("filter nests deeper than {} levels", MAX_NESTING),
+            ));
+        }
+        let expression = match self.peek() {
+            Some(t) if t.is_keyword("not") || t.kind == TokenKind::Bang => {
+                self.next();
+                FilterExpr::Not(Box::new(self.unary()?))
+            }
+            Some(t) if t.kind == TokenKind::LParen => {
+                self.next();
+                let inner = self.or_expression()?;
+                self.expect(TokenKind::RParen, "')'")?;
+                inner
+            }
+            _ => self.comparison()?,
+        };
+        self.depth -= 1;
+        Ok(expression)
+    }
+
+    fn field(&mut self) -> ArbitrageResult<&'static FieldSpec> {
+        let token = match self.next() {
+            Some(token) if token.kind == TokenKind::Word => token,
+            Some(token) => {
+                return Err(filter_error(
+                    token.position,
+                    format!("expected a field name, found '{}'", token.text),
+                ))
+            }
+            None => {
+                return Err(filter_error(
+                    self.end_position(),
+                    "expected a field name before the end of the filter".to_string(),
+                ))
+            }
+        };
+        let name = token.text.to_lowercase();
+        field_spec(&name).ok_or_else(|| {
+            let suggestion = FIELDS
+                .iter()
+                .map(|spec| (edit_distance(&name, spec.name), spec.name))
+                .filter(|(distance, _)| *distance <= 3)
+                .min()
+                .map(|(_, field)| format!("did you mean '{}'? ", field))
+                .unwrap_or_default();
+            let known: Vec<&str> = FIELDS.iter().map(|spec| spec.name).collect();
+            filter_error(
+                token.position,
+                format!(
+                    "unknown field '{}'; {}fields are: {}",
+                    token.text,
+                    suggestion,
+                    known.join(", ")
+                ),
+            )
+        })
+    }
+
+    fn literal(&mut self, field: &FieldSpec) -> ArbitrageResult<Literal> {
+        let token = match self.next() {
+            Some(token) if matches!(token.kind, TokenKind::Word | TokenKind::Quoted) => token,
+            Some(token) => {
+                return Err(filter_error(
+                    token.position,
+                    format!(
+                        "expected a value for {}, found '{}'",
+                        field.name, token.text
+                    ),
+                ))
+            }
+            None => {
+                return Err(filter_error(
+                    self.end_position(),
+                    format!("expected a value for {} before the end", field.name),
+                ))
+            }
+        };
+        let number = match token.kind {
+            TokenKind::Word => parse_number(&token.text),
+            _ => None,
+        };
+        if field.kind == FieldKind::Number && number.is_none() {
+            return Err(filter_error(
+                token.position,
+                format!(
+                    "{} is a number; use a value like 0.05%, 2500 or 10M instead of '{}'",
+                    field.name, token.text
+                ),
+            ));
+        }
+        Ok(Literal {
+            raw: token.text,
+            number,
+        })
+    }
+
+    fn comparison(&mut self) -> ArbitrageResult<FilterExpr> {
+        self.comparisons += 1;
+        if self.comparisons > MAX_COMPARISONS {
+            let position = self.peek().map(|t| t.position).unwrap_or(0);
+            return Err(filter_error(
+                position,
+                format!("filter has more than {} comparisons", MAX_COMPARISONS),
+            ));
+        }
+
+        let field = self.field()?;
+        let negated = self.peek().is_some_and(|t| t.is_keyword("not"));
+        if negated {
+            self.next();
+        }
+
+        match self.next() {
+            Some(token) if token.is_keyword("in") => {
+                self.expect(TokenKind::LBracket, "'[' to start the list")?;
+                let mut values = vec![self.literal(field)?];
+                while self.peek().is_some_and(|t| t.kind == TokenKind::Comma) {
+                    self.next();
+                    values.push(self.literal(field)?);
+                    if values.len() > MAX_LIST_LENGTH {
+                        return Err(filter_error(
+                            token.position,
+                            format!("lists hold at most {} values", MAX_LIST_LENGTH),
+                        ));
+                    }
+                }
+                self.expect(TokenKind::RBracket, "',' or ']'")?;
+                Ok(FilterExpr::In {
+                    field: field.name,
+                    values,
+                    negated,
+                })
+            }
+            Some(token) if negated => Err(filter_error(
+                token.position,
+                format!("expected 'in' after 'not', found '{}'", token.text),
+            )),
+            Some(Token {
+                kind: TokenKind::Op(op),
+                position,
+                ..
+            }) => {
+                if field.kind == FieldKind::Text && !matches!(op, CompareOp::Eq | CompareOp::Ne) {
+                    return Err(filter_error(
+                        position,
+                        format!(
+                            "{} is text and only supports ==, != and in [..]",
+                            field.name
+                        ),
+                    ));
+                }
+                Ok(FilterExpr::Compare {
+                    field: field.name,
+                    op,
+                    value: self.literal(field)?,
+                })
+            }
+            Some(token) => Err(filter_error(
+                token.position,
+                format!(
+                    "expected a comparison (==, !=, >, >=, <, <=, in) after {}, found '{}'",
+                    field.name, token.text
+                ),
+            )),
+            None => Err(filter_error(
+                self.end_position(),
+                format!("expected a comparison after {}", field.name),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ArbitrageType, ExchangeIdEnum};
+
+    fn opportunity(
+        pair: &str,
+        long: ExchangeIdEnum,
+        short: ExchangeIdEnum,
+    ) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            pair: pair.to_string(),
+            long_exchange: long,
+            short_exchange: short,
+            exchanges: vec![long.to_string(), short.to_string()],
+            rate_difference: 0.0012,
+            net_rate_difference: Some(0.0008),
+            r#type: ArbitrageType::FundingRate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_pairs_edges_exchanges_and_volume() {
+        let filter = FilterExpr::parse(
+            "pair in [BTC,ETH] and net_edge > 0.05% and exchange != mexc and volume_24h > 10M",
+        )
+        .unwrap();
+        assert_eq!(
+            filter.to_string(),
+            "pair in [BTC, ETH] and net_edge > 0.05% and exchange != mexc and volume_24h > 10M"
+        );
+
+        let mut btc = opportunity("BTC/USDT", ExchangeIdEnum::Binance, ExchangeIdEnum::Bybit);
+        // Without 24h volume the volume comparison, and so the filter, is false
+        assert!(!filter.matches(&FilterRecord::from(&btc)));
+        btc.volume_24h = Some(25e6);
+        assert!(filter.matches(&FilterRecord::from(&btc)));
+        btc.volume_24h = Some(5e6);
+        assert!(!filter.matches(&FilterRecord::from(&btc)));
+
+        let mut mexc = opportunity("ETHUSDT", ExchangeIdEnum::Mexc, ExchangeIdEnum::Bybit);
+        mexc.volume_24h = Some(25e6);
+        assert!(!filter.matches(&FilterRecord::from(&mexc)));
+        let mut sol = opportunity("SOL/USDT", ExchangeIdEnum::Binance, ExchangeIdEnum::Bybit);
+        sol.volume_24h = Some(25e6);
+        assert!(!filter.matches(&FilterRecord::from(&sol)));
+
+        let technical = TechnicalOpportunity {
+            pair: "ETH/USDT".to_string(),
+            exchange: "okx".to_string(),
+            exchanges: vec!["okx".to_string()],
+            expected_return_percentage: 2.0,
+            metadata: serde_json::json!({"volume_24h": 2e9}),
+            ..Default::default()
+        };
+        assert!(filter.matches(&FilterRecord::from(&technical)));
+        let grouped =
+            FilterExpr::parse("not (type == technical or risk = high) && base == eth").unwrap();
+        assert_eq!(
+            grouped.to_string(),
+            "not (type == technical or risk == high) and base == eth"
+        );
+        assert!(!grouped.matches(&FilterRecord::from(&technical)));
+    }
+
+    #[test]
+    fn test_filter_errors_point_at_the_problem() {
+        let message = |source: &str| FilterExpr::parse(source).unwrap_err().message;
+
+        assert_eq!(
+            message("volum > 10M"),
+            "Filter error at position 1: unknown field 'volum'; did you mean 'volume'? fields are: \
+             pair, base, quote, exchange, type, risk, signal, net_edge, gross_edge, confidence, \
+             profit, volume, volume_24h"
+        );
+        assert_eq!(
+            message("net_edge > mexc"),
+            "Filter error at position 12: net_edge is a number; use a value like 0.05%, 2500 or \
+             10M instead of 'mexc'"
+        );
+        assert_eq!(
+            message("exchange > binance"),
+            "Filter error at position 10: exchange is text and only supports ==, != and in [..]"
+        );
+        assert_eq!(
+            message("pair in [BTC, ETH"),
+            "Filter error at position 18: expected ',' or ']' before the end of the filter"
+        );
+        assert_eq!(
+            message("pair == BTC volume > 1"),
+            "Filter error at position 13: expected 'and', 'or' or the end of the filter, found \
+             'volume'"
+        );
+        assert!(message(&"(".repeat(40)).contains("nests deeper than 16 levels"));
+        assert!(message(&"x".repeat(600)).contains("longer than 512 characters"));
+    }
+}
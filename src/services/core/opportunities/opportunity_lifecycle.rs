@@ -0,0 +1,447 @@
+// src/services/core/opportunities/opportunity_lifecycle.rs
+
+//! Stable identity and lifecycle for opportunities seen across scans.
+//!
+//! Every scan mints fresh opportunity ids, so the same spread would otherwise look new each
+//! cycle. Here an opportunity is identified by its type, pair and legs, and each sighting
+//! moves its tracked record through detected → updated / peaked / decayed → expired while
+//! appending to a capped time series of the spread. Distribution only needs to notify when
+//! a record is (re)detected or its edge has moved materially since the last notification.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::core::trading::KvOperations;
+use crate::types::{ArbitrageOpportunity, ArbitrageType};
+use crate::utils::ArbitrageResult;
+
+const RECORD_PREFIX: &str = "opportunity_lifecycle:";
+/// Shared key index written by earlier releases, cleared by the next sweep
+const LEGACY_INDEX_KEY: &str = "opportunity_lifecycle:index";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleStage {
+    Detected,
+    Updated,
+    Peaked,
+    Decayed,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleConfig {
+    /// Relative edge change since the last notification that warrants another one
+    pub material_change: f64,
+    /// Absolute edge change below which nothing is re-notified, whatever the relative change
+    pub min_absolute_change: f64,
+    /// Fraction of the peak edge lost before a record counts as decayed
+    pub decay_fraction: f64,
+    /// A record not seen for this long expires
+    pub stale_after_ms: u64,
+    /// Expired records are kept this long for analysis, then deleted
+    pub retention_ms: u64,
+    /// Spread samples kept per record, oldest dropped first
+    pub max_samples: usize,
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            material_change: 0.25,
+            min_absolute_change: 0.0005,
+            decay_fraction: 0.5,
+            stale_after_ms: 15 * 60 * 1000,
+            retention_ms: 7 * 24 * 60 * 60 * 1000,
+            // A day of five-minute scans
+            max_samples: 288,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpreadSample {
+    pub timestamp: u64,
+    pub rate_difference: f64,
+    pub net_edge: Option<f64>,
+}
+
+/// One opportunity identity and everything seen of it so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedOpportunity {
+    pub key: String,
+    pub id: String,
+    pub stage: LifecycleStage,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub peak_edge: f64,
+    pub peak_at: u64,
+    pub last_notified_edge: Option<f64>,
+    pub last_notified_at: Option<u64>,
+    pub observations: u32,
+    pub samples: Vec<SpreadSample>,
+    /// Latest sighting, carrying the stable id
+    pub latest: ArbitrageOpportunity,
+}
+
+/// Result of recording one sighting
+#[derive(Debug, Clone)]
+pub struct LifecycleUpdate {
+    pub opportunity: ArbitrageOpportunity,
+    pub stage: LifecycleStage,
+    pub notify: bool,
+}
+
+pub(crate) fn type_slug(arbitrage_type: &ArbitrageType) -> &'static str {
+    match arbitrage_type {
+        ArbitrageType::FundingRate => "funding_rate",
+        ArbitrageType::SpotFutures => "spot_futures",
+        ArbitrageType::CrossExchange => "cross_exchange",
+        ArbitrageType::Price => "price",
+        ArbitrageType::Triangular => "triangular",
+    }
+}
+
+/// Identity of an opportunity across scans: type, compact pair and legs
+pub fn opportunity_key(opportunity: &ArbitrageOpportunity) -> String {
+    let pair: String = opportunity
+        .pair
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    format!(
+        "{}:{}:{}:{}",
+        type_slug(&opportunity.r#type),
+        pair.to_uppercase(),
+        opportunity.long_exchange.as_str(),
+        opportunity.short_exchange.as_str()
+    )
+}
+
+/// Deterministic opportunity id derived from [`opportunity_key`]
+pub fn stable_id(opportunity: &ArbitrageOpportunity) -> String {
+    format!("opp_{}", opportunity_key(opportunity).replace(':', "_")).to_lowercase()
+}
+
+/// Edge used to compare sightings: net of costs when the builder priced them
+fn edge(opportunity: &ArbitrageOpportunity) -> f64 {
+    opportunity
+        .net_rate_difference
+        .unwrap_or(opportunity.rate_difference)
+}
+
+impl TrackedOpportunity {
+    pub fn new(mut opportunity: ArbitrageOpportunity, now: u64) -> Self {
+        let key = opportunity_key(&opportunity);
+        let id = stable_id(&opportunity);
+        opportunity.id = id.clone();
+        let edge = edge(&opportunity);
+        Self {
+            key,
+            id,
+            stage: LifecycleStage::Detected,
+            first_seen: now,
+            last_seen: now,
+            peak_edge: edge,
+            peak_at: now,
+            last_notified_edge: Some(edge),
+            last_notified_at: Some(now),
+            observations: 1,
+            samples: vec![SpreadSample {
+                timestamp: now,
+                rate_difference: opportunity.rate_difference,
+                net_edge: opportunity.net_rate_difference,
+            }],
+            latest: opportunity,
+        }
+    }
+
+    /// Record another sighting; returns whether users should be notified again
+    pub fn observe(
+        &mut self,
+        mut opportunity: ArbitrageOpportunity,
+        now: u64,
+        config: &LifecycleConfig,
+    ) -> bool {
+        if self.stage == LifecycleStage::Expired {
+            let first_seen = self.first_seen;
+            let samples = std::mem::take(&mut self.samples);
+            *self = Self::new(opportunity, now);
+            self.first_seen = first_seen;
+            self.samples.splice(0..0, samples);
+            self.trim_samples(config);
+            return true;
+        }
+
+        opportunity.id = self.id.clone();
+        let edge = edge(&opportunity);
+        self.stage = if edge > self.peak_edge {
+            self.peak_edge = edge;
+            self.peak_at = now;
+            LifecycleStage::Peaked
+        } else if edge < self.peak_edge * (1.0 - config.decay_fraction) {
+            LifecycleStage::Decayed
+        } else {
+            LifecycleStage::Updated
+        };
+        self.last_seen = now;
+        self.observations += 1;
+        self.samples.push(SpreadSample {
+            timestamp: now,
+            rate_difference: opportunity.rate_difference,
+            net_edge: opportunity.net_rate_difference,
+        });
+        self.trim_samples(config);
+        self.latest = opportunity;
+
+        let notify = match self.last_notified_edge {
+            Some(previous) => {
+                let change = (edge - previous).abs();
+                change >= config.min_absolute_change
+                    && change >= config.material_change * previous.abs()
+            }
+            None => true,
+        };
+        if notify {
+            self.last_notified_edge = Some(edge);
+            self.last_notified_at = Some(now);
+        }
+        notify
+    }
+
+    /// Mark the record expired when it has not been seen recently or its quote lapsed
+    pub fn expire_if_stale(&mut self, now: u64, config: &LifecycleConfig) -> bool {
+        if self.stage == LifecycleStage::Expired {
+            return false;
+        }
+        let unseen = now.saturating_sub(self.last_seen) > config.stale_after_ms;
+        let lapsed = self.latest.expires_at.is_some_and(|at| at < now);
+        if unseen || lapsed {
+            self.stage = LifecycleStage::Expired;
+        }
+        self.stage == LifecycleStage::Expired
+    }
+
+    fn trim_samples(&mut self, config: &LifecycleConfig) {
+        let excess = self.samples.len().saturating_sub(config.max_samples);
+        self.samples.drain(..excess);
+    }
+}
+
+/// KV-backed lifecycle records, one per opportunity identity
+///
+/// Records are found by listing their key prefix rather than through a shared index, so a
+/// scan and the expiry sweep running at once only ever race on the same record.
+pub struct OpportunityLifecycleTracker<K: KvOperations> {
+    store: Arc<K>,
+    config: LifecycleConfig,
+}
+
+impl<K: KvOperations> OpportunityLifecycleTracker<K> {
+    pub fn new(store: Arc<K>) -> Self {
+        Self {
+            store,
+            config: LifecycleConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: LifecycleConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    fn record_key(key: &str) -> String {
+        format!("{}{}", RECORD_PREFIX, key)
+    }
+
+    /// Tracked record for an opportunity identity, including its spread history
+    pub async fn get(&self, key: &str) -> ArbitrageResult<Option<TrackedOpportunity>> {
+        Ok(self.store.get(&Self::record_key(key)).await?)
+    }
+
+    /// Record one scan's opportunities, returning each with its stable id, stage and whether
+    /// it should be distributed
+    pub async fn observe(
+        &self,
+        opportunities: &[ArbitrageOpportunity],
+        now: u64,
+    ) -> ArbitrageResult<Vec<LifecycleUpdate>> {
+        let mut updates: Vec<LifecycleUpdate> = Vec::with_capacity(opportunities.len());
+
+        for opportunity in opportunities {
+            let key = opportunity_key(opportunity);
+            let (tracked, notify) = match self.get(&key).await? {
+                Some(mut tracked) => {
+                    let notify = tracked.observe(opportunity.clone(), now, &self.config);
+                    (tracked, notify)
+                }
+                None => (TrackedOpportunity::new(opportunity.clone(), now), true),
+            };
+            self.store.put(&Self::record_key(&key), &tracked).await?;
+
+            // The same identity twice in one scan is one sighting for distribution
+            match updates.iter_mut().find(|u| u.opportunity.id == tracked.id) {
+                Some(update) => {
+                    update.notify |= notify;
+                    update.stage = tracked.stage;
+                    update.opportunity = tracked.latest;
+                }
+                None => updates.push(LifecycleUpdate {
+                    opportunity: tracked.latest,
+                    stage: tracked.stage,
+                    notify,
+                }),
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Expire records that stopped appearing and delete those past retention; returns the
+    /// records expired by this call
+    pub async fn expire_stale(&self, now: u64) -> ArbitrageResult<Vec<TrackedOpportunity>> {
+        let mut expired = Vec::new();
+
+        for record_key in self.store.list_keys(RECORD_PREFIX).await? {
+            if record_key == LEGACY_INDEX_KEY {
+                self.store.delete(&record_key).await?;
+                continue;
+            }
+            let Some(mut tracked) = self.store.get::<TrackedOpportunity>(&record_key).await? else {
+                continue;
+            };
+            if tracked.stage == LifecycleStage::Expired
+                && now.saturating_sub(tracked.last_seen) > self.config.retention_ms
+            {
+                self.store.delete(&record_key).await?;
+                continue;
+            }
+            if tracked.expire_if_stale(now, &self.config) {
+                self.store.put(&record_key, &tracked).await?;
+                expired.push(tracked);
+            }
+        }
+
+        Ok(expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::types::ExchangeIdEnum;
+
+    const MINUTE: u64 = 60 * 1000;
+
+    fn spread(net_edge: f64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: uuid::Uuid::new_v4().to_string(),
+            pair: "BTC/USDT".to_string(),
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            rate_difference: net_edge + 0.001,
+            net_rate_difference: Some(net_edge),
+            r#type: ArbitrageType::CrossExchange,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_spread_keeps_identity_and_notifies_on_material_change() {
+        let tracker = OpportunityLifecycleTracker::new(Arc::new(MockKvStore::new()));
+        let t0 = 1_700_000_000_000;
+
+        let first = tracker.observe(&[spread(0.004)], t0).await.unwrap();
+        assert_eq!(first[0].stage, LifecycleStage::Detected);
+        assert!(first[0].notify);
+        let id = first[0].opportunity.id.clone();
+        assert_eq!(id, "opp_cross_exchange_btcusdt_binance_bybit");
+
+        // A fresh scan with a new UUID and a 10% wiggle is the same, unannounced opportunity
+        let second = tracker
+            .observe(&[spread(0.0044)], t0 + 5 * MINUTE)
+            .await
+            .unwrap();
+        assert_eq!(second[0].opportunity.id, id);
+        assert_eq!(second[0].stage, LifecycleStage::Peaked);
+        assert!(!second[0].notify);
+
+        let third = tracker
+            .observe(&[spread(0.0042)], t0 + 10 * MINUTE)
+            .await
+            .unwrap();
+        assert_eq!(third[0].stage, LifecycleStage::Updated);
+        assert!(!third[0].notify);
+
+        // Half the peak gone, and far from the 0.4% last announced
+        let fourth = tracker
+            .observe(&[spread(0.0015)], t0 + 15 * MINUTE)
+            .await
+            .unwrap();
+        assert_eq!(fourth[0].stage, LifecycleStage::Decayed);
+        assert!(fourth[0].notify);
+
+        let key = opportunity_key(&spread(0.0));
+        let tracked = tracker.get(&key).await.unwrap().unwrap();
+        assert_eq!(tracked.observations, 4);
+        assert_eq!(tracked.peak_edge, 0.0044);
+        let edges: Vec<Option<f64>> = tracked.samples.iter().map(|s| s.net_edge).collect();
+        assert_eq!(
+            edges,
+            vec![Some(0.004), Some(0.0044), Some(0.0042), Some(0.0015)]
+        );
+
+        // Unseen past the stale window: expired, then announced again when it reappears
+        let expired = tracker.expire_stale(t0 + 31 * MINUTE).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].stage, LifecycleStage::Expired);
+
+        let back = tracker
+            .observe(&[spread(0.0016)], t0 + 40 * MINUTE)
+            .await
+            .unwrap();
+        assert_eq!(back[0].stage, LifecycleStage::Detected);
+        assert!(back[0].notify);
+        let tracked = tracker.get(&key).await.unwrap().unwrap();
+        assert_eq!(tracked.first_seen, t0);
+        assert_eq!(tracked.samples.len(), 5);
+
+        // Past retention the record is dropped entirely
+        tracker.expire_stale(t0 + 60 * MINUTE).await.unwrap();
+        let week = 7 * 24 * 60 * MINUTE;
+        tracker
+            .expire_stale(t0 + 40 * MINUTE + week + 1)
+            .await
+            .unwrap();
+        assert!(tracker.get(&key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_keeps_records_observed_alongside_it() {
+        let store = Arc::new(MockKvStore::new());
+        let tracker = OpportunityLifecycleTracker::new(store.clone());
+        let t0 = 1_700_000_000_000;
+
+        tracker.observe(&[spread(0.004)], t0).await.unwrap();
+        store
+            .put(
+                LEGACY_INDEX_KEY,
+                &vec!["cross_exchange:btcusdt:binance:bybit"],
+            )
+            .await
+            .unwrap();
+
+        // A second scanner records another spread; nothing shared can be overwritten
+        let mut other = spread(0.003);
+        other.short_exchange = ExchangeIdEnum::OKX;
+        tracker.observe(&[other], t0 + MINUTE).await.unwrap();
+
+        let expired = tracker.expire_stale(t0 + 2 * MINUTE).await.unwrap();
+        assert!(expired.is_empty());
+        let keys = store.list_keys(RECORD_PREFIX).await.unwrap();
+        assert_eq!(keys.len(), 2);
+        assert!(!keys.iter().any(|key| key == LEGACY_INDEX_KEY));
+    }
+}
@@ -0,0 +1,1439 @@
+// src/services/core/trading/adapters/binance.rs
+
+//! Binance spot (`api.binance.com`) and USDⓈ-M futures (`fapi.binance.com`) adapter
+
+use async_trait::async_trait;
+use reqwest::Method;
+use serde_json::Value;
+
+use super::{
+    account_fee_rates, dated_future, empty_ticker, ignore_unchanged, min_max, new_funding_rate,
+    new_market, new_position, normalize_orderbook, parse_levels, validate_leverage,
+    ExchangeAdapter,
+};
+use crate::services::core::trading::exchange_rest::{
+    compact_symbol, datetime_from_millis, format_decimal, is_futures_market, json_f64, json_string,
+    json_u64, ExchangeRestClient,
+};
+use crate::services::core::user::user_exchange_api::ApiKeyPermissions;
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, FundingRateInfo, MarginMode, Market,
+    Order, OrderBook, OrderRequest, OrderStatus, OrderType, Position, Ticker, TimeInForce, Trade,
+    TradingFee, TradingFeeRates,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+#[derive(Clone)]
+pub struct BinanceAdapter {
+    rest: ExchangeRestClient,
+}
+
+impl BinanceAdapter {
+    pub fn new(rest: ExchangeRestClient) -> Self {
+        Self { rest }
+    }
+
+    fn fee_rates(futures: bool) -> (f64, f64) {
+        if futures {
+            (0.0002, 0.0005)
+        } else {
+            (0.001, 0.001)
+        }
+    }
+
+    /// Smallest depth Binance accepts that still covers `limit` levels
+    fn depth_limit(limit: u32, futures: bool) -> u32 {
+        let allowed: &[u32] = if futures {
+            &[5, 10, 20, 50, 100, 500, 1000]
+        } else {
+            &[5, 10, 20, 50, 100, 500, 1000, 5000]
+        };
+        allowed
+            .iter()
+            .copied()
+            .find(|depth| *depth >= limit.max(1))
+            .unwrap_or(allowed[allowed.len() - 1])
+    }
+
+    fn order_params(
+        request: &OrderRequest,
+        futures: bool,
+    ) -> ArbitrageResult<Vec<(String, String)>> {
+        let post_only = request.time_in_force == Some(TimeInForce::PostOnly);
+        let order_type = match (request.order_type, futures) {
+            (OrderType::Market, _) => "MARKET",
+            (OrderType::Limit, false) if post_only => "LIMIT_MAKER",
+            (OrderType::Limit, _) => "LIMIT",
+            (OrderType::StopLoss, false) => "STOP_LOSS",
+            (OrderType::StopLoss, true) => "STOP_MARKET",
+            (OrderType::TakeProfit, false) => "TAKE_PROFIT",
+            (OrderType::TakeProfit, true) => "TAKE_PROFIT_MARKET",
+            (OrderType::StopLossLimit, false) => "STOP_LOSS_LIMIT",
+            (OrderType::StopLossLimit, true) => "STOP",
+            (OrderType::TakeProfitLimit, false) => "TAKE_PROFIT_LIMIT",
+            (OrderType::TakeProfitLimit, true) => "TAKE_PROFIT",
+            (OrderType::TrailingStop, true) => "TRAILING_STOP_MARKET",
+            (OrderType::TrailingStop, false) => {
+                return Err(ArbitrageError::validation_error(
+                    "Trailing stop orders are only supported on Binance futures",
+                ))
+            }
+        };
+
+        let mut params = vec![
+            ("symbol".to_string(), compact_symbol(&request.symbol)),
+            ("side".to_string(), request.side.to_uppercase()),
+            ("type".to_string(), order_type.to_string()),
+            ("quantity".to_string(), format_decimal(request.amount)),
+            (
+                "newOrderRespType".to_string(),
+                if futures { "RESULT" } else { "FULL" }.to_string(),
+            ),
+        ];
+
+        let has_limit_price = matches!(
+            request.order_type,
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit
+        );
+        if has_limit_price {
+            if let Some(price) = request.price {
+                params.push(("price".to_string(), format_decimal(price)));
+            }
+        }
+
+        // LIMIT_MAKER takes no timeInForce; futures express post-only as GTX
+        if has_limit_price && order_type != "LIMIT_MAKER" {
+            let tif = match request.time_in_force.unwrap_or_default() {
+                TimeInForce::PostOnly => "GTX",
+                other => other.as_str(),
+            };
+            params.push(("timeInForce".to_string(), tif.to_string()));
+        }
+
+        if let Some(stop_price) = request.stop_price {
+            match request.order_type {
+                OrderType::TrailingStop => {
+                    params.push(("activationPrice".to_string(), format_decimal(stop_price)))
+                }
+                OrderType::Market | OrderType::Limit => {}
+                _ => params.push(("stopPrice".to_string(), format_decimal(stop_price))),
+            }
+        }
+        if let Some(callback_rate) = request.trailing_percent {
+            params.push(("callbackRate".to_string(), format_decimal(callback_rate)));
+        }
+        if futures && request.reduce_only {
+            params.push(("reduceOnly".to_string(), "true".to_string()));
+        }
+        if let Some(client_id) = &request.client_order_id {
+            params.push(("newClientOrderId".to_string(), client_id.clone()));
+        }
+        Ok(params)
+    }
+
+    /// Map a Binance spot or USDⓈ-M futures order payload into `Order`
+    pub fn parse_order(data: &Value) -> ArbitrageResult<Order> {
+        let id = json_string(data, "orderId")
+            .ok_or_else(|| ArbitrageError::parse_error("Binance order response missing orderId"))?;
+        let status = OrderStatus::from_exchange_status(
+            data.get("status").and_then(|s| s.as_str()).unwrap_or("NEW"),
+        );
+        let amount = json_f64(data, "origQty").unwrap_or(0.0);
+        let filled = json_f64(data, "executedQty").unwrap_or(0.0);
+        let cost = json_f64(data, "cummulativeQuoteQty")
+            .or_else(|| json_f64(data, "cumQuote"))
+            .unwrap_or(0.0);
+        let timestamp = json_u64(data, "transactTime")
+            .or_else(|| json_u64(data, "updateTime"))
+            .or_else(|| json_u64(data, "time"))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+        let symbol = data["symbol"].as_str().unwrap_or_default().to_string();
+        let side = data["side"].as_str().unwrap_or_default().to_lowercase();
+
+        let trades: Vec<Trade> = data["fills"]
+            .as_array()
+            .map(|fills| {
+                fills
+                    .iter()
+                    .enumerate()
+                    .map(|(index, fill)| {
+                        let price = json_f64(fill, "price").unwrap_or(0.0);
+                        let qty = json_f64(fill, "qty").unwrap_or(0.0);
+                        Trade {
+                            id: json_string(fill, "tradeId")
+                                .unwrap_or_else(|| format!("{}-{}", id, index)),
+                            order: Some(id.clone()),
+                            info: fill.clone(),
+                            timestamp,
+                            datetime: datetime_from_millis(timestamp),
+                            symbol: symbol.clone(),
+                            type_: None,
+                            side: side.clone(),
+                            amount: qty,
+                            price,
+                            cost: price * qty,
+                            fee: json_f64(fill, "commission").map(|commission| TradingFee {
+                                currency: fill["commissionAsset"]
+                                    .as_str()
+                                    .unwrap_or_default()
+                                    .to_string(),
+                                cost: commission,
+                                rate: None,
+                            }),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let fee = trades.iter().filter_map(|t| t.fee.as_ref()).fold(
+            None,
+            |acc: Option<TradingFee>, f| match acc {
+                Some(mut total) => {
+                    total.cost += f.cost;
+                    Some(total)
+                }
+                None => Some(f.clone()),
+            },
+        );
+
+        let average = json_f64(data, "avgPrice")
+            .filter(|p| *p > 0.0)
+            .or_else(|| (filled > 0.0 && cost > 0.0).then(|| cost / filled));
+
+        Ok(Order {
+            id,
+            client_order_id: json_string(data, "clientOrderId"),
+            datetime: datetime_from_millis(timestamp),
+            timestamp,
+            last_trade_timestamp: (filled > 0.0).then_some(timestamp),
+            status: status.as_order_status_str().to_string(),
+            symbol,
+            type_: data["type"].as_str().unwrap_or("limit").to_lowercase(),
+            time_in_force: json_string(data, "timeInForce"),
+            side,
+            amount,
+            price: json_f64(data, "price").filter(|p| *p > 0.0),
+            average,
+            filled,
+            remaining: (amount - filled).max(0.0),
+            cost,
+            trades,
+            fee,
+            info: data.clone(),
+        })
+    }
+
+    fn filter<'a>(symbol: &'a Value, filter_type: &str) -> Option<&'a Value> {
+        symbol["filters"]
+            .as_array()?
+            .iter()
+            .find(|filter| filter["filterType"] == filter_type)
+    }
+
+    /// Parse `/api/v3/exchangeInfo` or `/fapi/v1/exchangeInfo`, keeping tradable spot pairs
+    /// and USDⓈ-M perpetuals and delivery contracts
+    pub fn parse_markets(data: &Value, futures: bool) -> Vec<Market> {
+        let Some(symbols) = data["symbols"].as_array() else {
+            return Vec::new();
+        };
+
+        symbols
+            .iter()
+            .filter(|s| s["status"] == "TRADING")
+            .filter_map(|s| {
+                let base = s["baseAsset"].as_str()?;
+                let quote = s["quoteAsset"].as_str()?;
+                let mut market = new_market(base, quote, futures, Self::fee_rates(futures), s);
+                if futures {
+                    market = match s["contractType"].as_str()? {
+                        "PERPETUAL" => market,
+                        "CURRENT_QUARTER" | "NEXT_QUARTER" | "CURRENT_MONTH" | "NEXT_MONTH" => {
+                            dated_future(
+                                market,
+                                s["symbol"].as_str()?,
+                                json_u64(s, "deliveryDate")?,
+                            )
+                        }
+                        _ => return None,
+                    };
+                }
+                market.margin = s["isMarginTradingAllowed"].as_bool().unwrap_or(false);
+                if futures {
+                    market.settle = json_string(s, "marginAsset");
+                    market.settle_id = market.settle.clone();
+                    market.precision.price = json_u64(s, "pricePrecision").map(|p| p as i32);
+                    market.precision.amount = json_u64(s, "quantityPrecision").map(|p| p as i32);
+                } else {
+                    market.precision.base = json_u64(s, "baseAssetPrecision").map(|p| p as i32);
+                    market.precision.quote = json_u64(s, "quoteAssetPrecision").map(|p| p as i32);
+                }
+
+                if let Some(filter) = Self::filter(s, "PRICE_FILTER") {
+                    market.precision.tick_size = json_f64(filter, "tickSize");
+                    market.limits.price = min_max(
+                        json_f64(filter, "minPrice").filter(|v| *v > 0.0),
+                        json_f64(filter, "maxPrice").filter(|v| *v > 0.0),
+                    );
+                }
+                if let Some(filter) = Self::filter(s, "LOT_SIZE") {
+                    market.precision.lot_size = json_f64(filter, "stepSize");
+                    market.limits.amount =
+                        min_max(json_f64(filter, "minQty"), json_f64(filter, "maxQty"));
+                }
+                let min_notional = Self::filter(s, "NOTIONAL")
+                    .and_then(|f| json_f64(f, "minNotional"))
+                    .or_else(|| {
+                        Self::filter(s, "MIN_NOTIONAL").and_then(|f| {
+                            json_f64(f, "minNotional").or_else(|| json_f64(f, "notional"))
+                        })
+                    });
+                let max_notional =
+                    Self::filter(s, "NOTIONAL").and_then(|f| json_f64(f, "maxNotional"));
+                market.limits.cost = min_max(min_notional, max_notional);
+                Some(market)
+            })
+            .collect()
+    }
+
+    /// Map a `/fapi/v2/positionRisk` entry; flat positions yield `None`
+    pub fn parse_position(data: &Value) -> Option<Position> {
+        let signed_amount = json_f64(data, "positionAmt")?;
+        if signed_amount == 0.0 {
+            return None;
+        }
+        // Hedge mode reports LONG/SHORT explicitly; one-way mode ("BOTH") uses the sign
+        let position_side = data["positionSide"].as_str().unwrap_or("BOTH");
+        let side = match position_side {
+            "LONG" => "long",
+            "SHORT" => "short",
+            _ if signed_amount > 0.0 => "long",
+            _ => "short",
+        };
+        let amount = signed_amount.abs();
+        let mut position = new_position(
+            data["symbol"].as_str()?,
+            side,
+            amount,
+            json_u64(data, "updateTime").filter(|t| *t > 0),
+            data,
+        );
+        let isolated = data["marginType"].as_str() == Some("isolated");
+        position.isolated = Some(isolated);
+        position.hedged = Some(position_side != "BOTH");
+        position.contracts = Some(amount);
+        position.contract_size = Some(1.0);
+        position.entry_price = json_f64(data, "entryPrice").filter(|p| *p > 0.0);
+        position.mark_price = json_f64(data, "markPrice").filter(|p| *p > 0.0);
+        position.notional = json_f64(data, "notional").map(f64::abs);
+        position.leverage = json_f64(data, "leverage");
+        if isolated {
+            position.collateral = json_f64(data, "isolatedMargin");
+        }
+        if let (Some(notional), Some(leverage)) = (position.notional, position.leverage) {
+            if leverage > 0.0 {
+                position.initial_margin = Some(notional / leverage);
+                position.initial_margin_percentage = Some(1.0 / leverage);
+            }
+        }
+        position.unrealized_pnl = json_f64(data, "unRealizedProfit");
+        Some(position)
+    }
+
+    /// Map a `/ticker/24hr` payload (spot or futures) into `Ticker`
+    pub fn parse_ticker(symbol: &str, data: &Value) -> Ticker {
+        let mut ticker = empty_ticker(symbol, json_u64(data, "closeTime"), data.clone());
+        ticker.high = json_f64(data, "highPrice");
+        ticker.low = json_f64(data, "lowPrice");
+        ticker.bid = json_f64(data, "bidPrice");
+        ticker.bid_volume = json_f64(data, "bidQty");
+        ticker.ask = json_f64(data, "askPrice");
+        ticker.ask_volume = json_f64(data, "askQty");
+        ticker.vwap = json_f64(data, "weightedAvgPrice");
+        ticker.open = json_f64(data, "openPrice");
+        ticker.close = json_f64(data, "lastPrice");
+        ticker.last = json_f64(data, "lastPrice");
+        ticker.previous_close = json_f64(data, "prevClosePrice");
+        ticker.change = json_f64(data, "priceChange");
+        ticker.percentage = json_f64(data, "priceChangePercent");
+        ticker.base_volume = json_f64(data, "volume");
+        ticker.quote_volume = json_f64(data, "quoteVolume");
+        ticker.volume = ticker.base_volume;
+        ticker
+    }
+
+    /// Map `/fapi/v1/premiumIndex` into `FundingRateInfo`
+    pub fn parse_funding_rate(symbol: &str, data: &Value) -> FundingRateInfo {
+        let rate = json_f64(data, "lastFundingRate").unwrap_or(0.0);
+        let mut info = new_funding_rate(ExchangeIdEnum::Binance, symbol, rate, data.clone());
+        info.next_funding_time = json_u64(data, "nextFundingTime").filter(|t| *t > 0);
+        info.mark_price = json_f64(data, "markPrice");
+        info.index_price = json_f64(data, "indexPrice");
+        info.estimated_settle_price = json_f64(data, "estimatedSettlePrice");
+        if let (Some(next), Some(now)) = (info.next_funding_time, json_u64(data, "time")) {
+            info.funding_countdown = Some(next.saturating_sub(now));
+        }
+        info
+    }
+
+    /// Effective (maker, taker) from `/api/v3/account/commission`: standard plus tax
+    /// commission, with the standard part scaled by `discount` when the BNB discount is on
+    pub fn parse_spot_commission(response: &Value) -> (Option<f64>, Option<f64>) {
+        let discount = &response["discount"];
+        let multiplier =
+            if discount["enabledForAccount"] == true && discount["enabledForSymbol"] == true {
+                json_f64(discount, "discount").unwrap_or(1.0)
+            } else {
+                1.0
+            };
+        let rate = |side: &str| {
+            let standard = json_f64(&response["standardCommission"], side)?;
+            let tax = json_f64(&response["taxCommission"], side).unwrap_or(0.0);
+            Some(standard * multiplier + tax)
+        };
+        (rate("maker"), rate("taker"))
+    }
+
+    /// Map `/fapi/v1/income` FUNDING_FEE records; `income` is already signed
+    pub fn parse_funding_payments(symbol: &str, data: &Value) -> Vec<FundingPayment> {
+        let mut payments: Vec<FundingPayment> = data
+            .as_array()
+            .map(|records| {
+                records
+                    .iter()
+                    .filter_map(|record| {
+                        Some(FundingPayment {
+                            exchange: ExchangeIdEnum::Binance,
+                            symbol: symbol.to_string(),
+                            amount: json_f64(record, "income")?,
+                            asset: json_string(record, "asset").unwrap_or_else(|| "USDT".into()),
+                            timestamp: json_u64(record, "time")?,
+                            payment_id: json_string(record, "tranId")?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        payments.sort_by_key(|payment| payment.timestamp);
+        payments
+    }
+
+    /// Map `/sapi/v1/account/apiRestrictions` flags
+    pub fn parse_api_restrictions(response: &Value) -> ApiKeyPermissions {
+        let flag = |name: &str| response[name].as_bool().unwrap_or(false);
+        ApiKeyPermissions {
+            can_read: flag("enableReading"),
+            can_trade_spot: flag("enableSpotAndMarginTrading"),
+            can_trade_futures: flag("enableFutures"),
+            can_withdraw: flag("enableWithdrawals"),
+            ip_restricted: response["ipRestrict"].as_bool(),
+            rate_limit: None,
+        }
+    }
+
+    /// Highest `initialLeverage` across the symbol's `/fapi/v1/leverageBracket` tiers.
+    /// Binance answers with an array, or a single object when `symbol` is sent.
+    pub fn parse_max_leverage(response: &Value, symbol: &str) -> Option<f64> {
+        let entry = match response {
+            Value::Array(entries) => entries.iter().find(|e| e["symbol"] == symbol)?,
+            other => other,
+        };
+        entry["brackets"]
+            .as_array()?
+            .iter()
+            .filter_map(|bracket| json_f64(bracket, "initialLeverage"))
+            .reduce(f64::max)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ExchangeAdapter for BinanceAdapter {
+    fn exchange_id(&self) -> ExchangeIdEnum {
+        ExchangeIdEnum::Binance
+    }
+
+    fn market_symbol(&self, symbol: &str, _futures: bool) -> ArbitrageResult<String> {
+        Ok(compact_symbol(symbol))
+    }
+
+    fn base_fee_rates(&self, futures: bool) -> (f64, f64) {
+        Self::fee_rates(futures)
+    }
+
+    async fn get_ticker(&self, symbol: &str, futures: bool) -> ArbitrageResult<Ticker> {
+        let endpoints = self.rest.endpoints(false);
+        let (base_url, path) = if futures {
+            (&endpoints.binance_futures, "/fapi/v1/ticker/24hr")
+        } else {
+            (&endpoints.binance_spot, "/api/v3/ticker/24hr")
+        };
+        let data = self
+            .rest
+            .public_get(
+                "binance",
+                base_url,
+                path,
+                &[("symbol", compact_symbol(symbol))],
+            )
+            .await?;
+        Ok(Self::parse_ticker(symbol, &data))
+    }
+
+    async fn get_orderbook(
+        &self,
+        symbol: &str,
+        limit: u32,
+        futures: bool,
+    ) -> ArbitrageResult<OrderBook> {
+        let (base_url, path) = if futures {
+            (
+                &self.rest.endpoints(false).binance_futures,
+                "/fapi/v1/depth",
+            )
+        } else {
+            (&self.rest.endpoints(false).binance_spot, "/api/v3/depth")
+        };
+        let query = [
+            ("symbol", compact_symbol(symbol)),
+            ("limit", Self::depth_limit(limit, futures).to_string()),
+        ];
+        let data = self
+            .rest
+            .public_get("binance", base_url, path, &query)
+            .await?;
+
+        Ok(normalize_orderbook(
+            symbol,
+            parse_levels(&data["bids"]),
+            parse_levels(&data["asks"]),
+            limit,
+            // Spot depth carries no timestamp; futures reports the engine time as "T"
+            json_u64(&data, "T"),
+            json_u64(&data, "lastUpdateId"),
+        ))
+    }
+
+    async fn get_markets(&self, futures: bool) -> ArbitrageResult<Vec<Market>> {
+        let endpoints = self.rest.endpoints(false);
+        let (base_url, path) = if futures {
+            (&endpoints.binance_futures, "/fapi/v1/exchangeInfo")
+        } else {
+            (&endpoints.binance_spot, "/api/v3/exchangeInfo")
+        };
+        let data = self.rest.public_get("binance", base_url, path, &[]).await?;
+        Ok(Self::parse_markets(&data, futures))
+    }
+
+    async fn get_funding_rate(&self, symbol: &str) -> ArbitrageResult<FundingRateInfo> {
+        let data = self
+            .rest
+            .public_get(
+                "binance",
+                &self.rest.endpoints(false).binance_futures,
+                "/fapi/v1/premiumIndex",
+                &[("symbol", compact_symbol(symbol))],
+            )
+            .await?;
+        Ok(Self::parse_funding_rate(symbol, &data))
+    }
+
+    async fn get_balance(&self, credentials: &ExchangeCredentials) -> ArbitrageResult<Value> {
+        let futures = is_futures_market(&credentials.exchange_type);
+        let endpoints = self.rest.endpoints(credentials.is_testnet);
+        let (base_url, path) = if futures {
+            (endpoints.binance_futures.as_str(), "/fapi/v2/balance")
+        } else {
+            (endpoints.binance_spot.as_str(), "/api/v3/account")
+        };
+        self.rest
+            .binance_signed(Method::GET, base_url, path, Vec::new(), credentials)
+            .await
+    }
+
+    async fn place_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        request
+            .validate()
+            .map_err(ArbitrageError::validation_error)?;
+
+        let futures = is_futures_market(ExchangeRestClient::market_type(
+            credentials,
+            request.market_type.as_deref(),
+        ));
+        let params = Self::order_params(request, futures)?;
+        let endpoints = self.rest.endpoints(credentials.is_testnet);
+        let (base_url, path) = if futures {
+            (endpoints.binance_futures.as_str(), "/fapi/v1/order")
+        } else {
+            (endpoints.binance_spot.as_str(), "/api/v3/order")
+        };
+
+        let response = self
+            .rest
+            .binance_signed(Method::POST, base_url, path, params, credentials)
+            .await?;
+        Self::parse_order(&response)
+    }
+
+    async fn cancel_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let endpoints = self.rest.endpoints(credentials.is_testnet);
+        let (base_url, path) = if futures {
+            (endpoints.binance_futures.as_str(), "/fapi/v1/order")
+        } else {
+            (endpoints.binance_spot.as_str(), "/api/v3/order")
+        };
+        let params = vec![
+            ("symbol".to_string(), compact_symbol(symbol)),
+            ("orderId".to_string(), order_id.to_string()),
+        ];
+
+        let response = self
+            .rest
+            .binance_signed(Method::DELETE, base_url, path, params, credentials)
+            .await?;
+        Self::parse_order(&response)
+    }
+
+    async fn get_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let endpoints = self.rest.endpoints(credentials.is_testnet);
+        let (base_url, path) = if futures {
+            (endpoints.binance_futures.as_str(), "/fapi/v1/order")
+        } else {
+            (endpoints.binance_spot.as_str(), "/api/v3/order")
+        };
+        let params = vec![
+            ("symbol".to_string(), compact_symbol(symbol)),
+            ("orderId".to_string(), order_id.to_string()),
+        ];
+
+        let response = self
+            .rest
+            .binance_signed(Method::GET, base_url, path, params, credentials)
+            .await?;
+        Self::parse_order(&response)
+    }
+
+    async fn get_open_positions(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        let params: Vec<(String, String)> = symbol
+            .map(|symbol| vec![("symbol".to_string(), compact_symbol(symbol))])
+            .unwrap_or_default();
+        let response = self
+            .rest
+            .binance_signed(
+                Method::GET,
+                &self.rest.endpoints(credentials.is_testnet).binance_futures,
+                "/fapi/v2/positionRisk",
+                params,
+                credentials,
+            )
+            .await?;
+        Ok(response
+            .as_array()
+            .map(|entries| entries.iter().filter_map(Self::parse_position).collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_open_orders(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let endpoints = self.rest.endpoints(credentials.is_testnet);
+        let (base_url, path) = if futures {
+            (endpoints.binance_futures.as_str(), "/fapi/v1/openOrders")
+        } else {
+            (endpoints.binance_spot.as_str(), "/api/v3/openOrders")
+        };
+        let params: Vec<(String, String)> = symbol
+            .map(|symbol| vec![("symbol".to_string(), compact_symbol(symbol))])
+            .unwrap_or_default();
+
+        let response = self
+            .rest
+            .binance_signed(Method::GET, base_url, path, params, credentials)
+            .await?;
+        response
+            .as_array()
+            .map(|orders| orders.iter().map(Self::parse_order).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    async fn get_max_leverage(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+    ) -> ArbitrageResult<Option<f64>> {
+        let symbol = compact_symbol(symbol);
+        let response = self
+            .rest
+            .binance_signed(
+                Method::GET,
+                &self.rest.endpoints(credentials.is_testnet).binance_futures,
+                "/fapi/v1/leverageBracket",
+                vec![("symbol".to_string(), symbol.clone())],
+                credentials,
+            )
+            .await?;
+        Ok(Self::parse_max_leverage(&response, &symbol))
+    }
+
+    async fn set_leverage(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
+    ) -> ArbitrageResult<()> {
+        let max_leverage = self.get_max_leverage(credentials, symbol).await?;
+        validate_leverage(self.exchange_id(), symbol, leverage, max_leverage)?;
+
+        self.rest
+            .binance_signed(
+                Method::POST,
+                &self.rest.endpoints(credentials.is_testnet).binance_futures,
+                "/fapi/v1/leverage",
+                vec![
+                    ("symbol".to_string(), compact_symbol(symbol)),
+                    ("leverage".to_string(), leverage.to_string()),
+                ],
+                credentials,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_margin_mode(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        // -4046: "No need to change margin type."
+        const MARGIN_TYPE_UNCHANGED: i64 = -4046;
+        let margin_type = match mode {
+            MarginMode::Isolated => "ISOLATED",
+            MarginMode::Cross => "CROSSED",
+        };
+        let result = self
+            .rest
+            .binance_signed(
+                Method::POST,
+                &self.rest.endpoints(credentials.is_testnet).binance_futures,
+                "/fapi/v1/marginType",
+                vec![
+                    ("symbol".to_string(), compact_symbol(symbol)),
+                    ("marginType".to_string(), margin_type.to_string()),
+                ],
+                credentials,
+            )
+            .await;
+        ignore_unchanged(result, &[MARGIN_TYPE_UNCHANGED])
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        // type 1 adds margin, 2 reduces it
+        let margin_type = if amount >= 0.0 { "1" } else { "2" };
+        self.rest
+            .binance_signed(
+                Method::POST,
+                &self.rest.endpoints(credentials.is_testnet).binance_futures,
+                "/fapi/v1/positionMargin",
+                vec![
+                    ("symbol".to_string(), compact_symbol(symbol)),
+                    ("amount".to_string(), format_decimal(amount.abs())),
+                    ("type".to_string(), margin_type.to_string()),
+                ],
+                credentials,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_trading_fees(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        futures: bool,
+    ) -> ArbitrageResult<TradingFeeRates> {
+        let endpoints = self.rest.endpoints(credentials.is_testnet);
+        let params = vec![("symbol".to_string(), compact_symbol(symbol))];
+        let (maker, taker) = if futures {
+            let response = self
+                .rest
+                .binance_signed(
+                    Method::GET,
+                    &endpoints.binance_futures,
+                    "/fapi/v1/commissionRate",
+                    params,
+                    credentials,
+                )
+                .await?;
+            (
+                json_f64(&response, "makerCommissionRate"),
+                json_f64(&response, "takerCommissionRate"),
+            )
+        } else {
+            let response = self
+                .rest
+                .binance_signed(
+                    Method::GET,
+                    &endpoints.binance_spot,
+                    "/api/v3/account/commission",
+                    params,
+                    credentials,
+                )
+                .await?;
+            Self::parse_spot_commission(&response)
+        };
+        account_fee_rates(self.exchange_id(), symbol, maker, taker)
+    }
+
+    async fn get_funding_payments(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        let mut params = vec![
+            ("symbol".to_string(), compact_symbol(symbol)),
+            ("incomeType".to_string(), "FUNDING_FEE".to_string()),
+            ("limit".to_string(), "1000".to_string()),
+        ];
+        if let Some(since) = since {
+            params.push(("startTime".to_string(), since.to_string()));
+        }
+        let response = self
+            .rest
+            .binance_signed(
+                Method::GET,
+                &self.rest.endpoints(credentials.is_testnet).binance_futures,
+                "/fapi/v1/income",
+                params,
+                credentials,
+            )
+            .await?;
+        Ok(Self::parse_funding_payments(symbol, &response))
+    }
+
+    async fn probe_api_key(
+        &self,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        let (response, rate_limit) = self
+            .rest
+            .binance_signed_with_limits(
+                Method::GET,
+                &self.rest.endpoints(credentials.is_testnet).binance_spot,
+                "/sapi/v1/account/apiRestrictions",
+                Vec::new(),
+                credentials,
+            )
+            .await?;
+        Ok(ApiKeyPermissions {
+            rate_limit,
+            ..Self::parse_api_restrictions(&response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::adapters::test_support::{
+        assert_best_first, credentials, fixture, rest_for,
+    };
+    use crate::services::core::trading::exchange_rest::ExchangeEndpoints;
+    use crate::services::core::trading::signing::hmac_sha256_hex;
+    use crate::test_utils::mock_http_server::{MockHttpServer, MockRoute};
+    use crate::utils::error::ErrorKind;
+    use serde_json::json;
+
+    const BINANCE_SPOT_ORDER_FILLED: &str =
+        include_str!("../../../../test_utils/fixtures/binance/spot_order_filled.json");
+    const BINANCE_FUTURES_ORDER_NEW: &str =
+        include_str!("../../../../test_utils/fixtures/binance/futures_order_new.json");
+    const BINANCE_SPOT_CANCEL: &str =
+        include_str!("../../../../test_utils/fixtures/binance/spot_order_canceled.json");
+    const BINANCE_INSUFFICIENT_BALANCE: &str =
+        include_str!("../../../../test_utils/fixtures/binance/error_insufficient_balance.json");
+    const BINANCE_DEPTH: &str = include_str!("../../../../test_utils/fixtures/binance/depth.json");
+    const BINANCE_EXCHANGE_INFO_SPOT: &str =
+        include_str!("../../../../test_utils/fixtures/binance/exchange_info_spot.json");
+    const BINANCE_EXCHANGE_INFO_FUTURES: &str =
+        include_str!("../../../../test_utils/fixtures/binance/exchange_info_futures.json");
+    const BINANCE_TICKER: &str =
+        include_str!("../../../../test_utils/fixtures/binance/ticker_24hr.json");
+    const BINANCE_PREMIUM_INDEX: &str =
+        include_str!("../../../../test_utils/fixtures/binance/premium_index.json");
+    const BINANCE_POSITION_RISK: &str =
+        include_str!("../../../../test_utils/fixtures/binance/position_risk.json");
+    const BINANCE_FUTURES_OPEN_ORDERS: &str =
+        include_str!("../../../../test_utils/fixtures/binance/futures_open_orders.json");
+    const BINANCE_LEVERAGE_BRACKET: &str =
+        include_str!("../../../../test_utils/fixtures/binance/leverage_bracket.json");
+    const BINANCE_MARGIN_TYPE_UNCHANGED: &str =
+        include_str!("../../../../test_utils/fixtures/binance/error_margin_type_unchanged.json");
+    const BINANCE_ACCOUNT_COMMISSION: &str =
+        include_str!("../../../../test_utils/fixtures/binance/account_commission.json");
+    const BINANCE_COMMISSION_RATE: &str =
+        include_str!("../../../../test_utils/fixtures/binance/commission_rate.json");
+    const BINANCE_API_RESTRICTIONS: &str =
+        include_str!("../../../../test_utils/fixtures/binance/api_restrictions.json");
+    const BINANCE_INCOME_FUNDING_FEE: &str =
+        include_str!("../../../../test_utils/fixtures/binance/income_funding_fee.json");
+    const BINANCE_POSITION_MARGIN: &str =
+        include_str!("../../../../test_utils/fixtures/binance/position_margin.json");
+
+    #[tokio::test]
+    async fn test_binance_spot_market_order_filled() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "POST",
+            "/api/v3/order",
+            200,
+            BINANCE_SPOT_ORDER_FILLED,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "spot");
+
+        let order = adapter
+            .place_order(&creds, &OrderRequest::market("BTC/USDT", "buy", 0.01))
+            .await
+            .unwrap();
+
+        assert_eq!(order.id, "28");
+        assert_eq!(order.status, "closed");
+        assert_eq!(order.side, "buy");
+        assert_eq!(order.filled, 0.01);
+        assert_eq!(order.remaining, 0.0);
+        assert_eq!(order.trades.len(), 2);
+        let fee = order.fee.unwrap();
+        assert_eq!(fee.currency, "BNB");
+        assert!((fee.cost - 0.0000075).abs() < 1e-12);
+        assert!((order.average.unwrap() - 65010.0).abs() < 1e-6);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.header("x-mbx-apikey"), Some("test-api-key"));
+        assert!(request.query.contains("symbol=BTCUSDT"));
+        assert!(request.query.contains("type=MARKET"));
+        assert!(!request.query.contains("timeInForce"));
+
+        // Signature must be the HMAC of everything before it in the query string
+        let (payload, signature) = request.query.rsplit_once("&signature=").unwrap();
+        assert_eq!(signature, hmac_sha256_hex("test-secret", payload).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_binance_futures_limit_order_honors_time_in_force() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "POST",
+            "/fapi/v1/order",
+            200,
+            BINANCE_FUTURES_ORDER_NEW,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+        let mut request = OrderRequest::limit("BTCUSDT", "sell", 0.5, 70000.0);
+        request.time_in_force = Some(TimeInForce::PostOnly);
+        request.reduce_only = true;
+
+        let order = adapter.place_order(&creds, &request).await.unwrap();
+
+        assert_eq!(order.status, "open");
+        assert_eq!(order.remaining, 0.5);
+        assert_eq!(order.price, Some(70000.0));
+
+        let recorded = &server.requests()[0];
+        assert!(recorded.query.contains("type=LIMIT"));
+        assert!(recorded.query.contains("timeInForce=GTX"));
+        assert!(recorded.query.contains("reduceOnly=true"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_cancel_order() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "DELETE",
+            "/api/v3/order",
+            200,
+            BINANCE_SPOT_CANCEL,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "spot");
+
+        let order = adapter
+            .cancel_order(&creds, "LTCBTC", "4", None)
+            .await
+            .unwrap();
+
+        assert_eq!(order.id, "4");
+        assert_eq!(order.status, "canceled");
+        assert!(server.requests()[0].query.contains("orderId=4"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_error_maps_to_exchange_error() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "POST",
+            "/api/v3/order",
+            400,
+            BINANCE_INSUFFICIENT_BALANCE,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "spot");
+
+        let error = adapter
+            .place_order(&creds, &OrderRequest::market("BTCUSDT", "buy", 1.0))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::ExchangeError);
+        assert!(error.message.contains("insufficient balance"));
+        let details = error.details.unwrap();
+        assert_eq!(details["exchange_code"], json!(-2010));
+        assert_eq!(details["http_status"], json!(400));
+    }
+
+    #[test]
+    fn test_order_type_mapping_rejects_unsupported_combinations() {
+        let mut trailing = OrderRequest::market("BTCUSDT", "sell", 1.0);
+        trailing.order_type = OrderType::TrailingStop;
+        trailing.trailing_percent = Some(1.0);
+
+        assert!(BinanceAdapter::order_params(&trailing, false).is_err());
+        let futures = BinanceAdapter::order_params(&trailing, true).unwrap();
+        assert!(futures.contains(&("type".to_string(), "TRAILING_STOP_MARKET".to_string())));
+        assert!(futures.contains(&("callbackRate".to_string(), "1".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_is_rejected_before_sending() {
+        let adapter = BinanceAdapter::new(ExchangeRestClient::with_endpoints(
+            ExchangeEndpoints::uniform("http://127.0.0.1:9"),
+        ));
+        let creds = credentials(ExchangeIdEnum::Binance, "spot");
+        let mut request = OrderRequest::limit("BTCUSDT", "buy", 1.0, 1.0);
+        request.price = None;
+
+        let error = adapter.place_order(&creds, &request).await.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+    }
+
+    #[tokio::test]
+    async fn test_binance_orderbook_sorted_and_truncated() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v3/depth",
+            200,
+            BINANCE_DEPTH,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+
+        let book = adapter.get_orderbook("BNB/BTC", 2, false).await.unwrap();
+
+        assert_eq!(book.symbol, "BNB/BTC");
+        assert_eq!(book.bids, vec![[4.000002, 12.0], [4.0, 431.0]]);
+        assert_eq!(book.asks, vec![[4.0000025, 3.0], [4.000003, 12.0]]);
+        assert_eq!(book.nonce, Some(1027024));
+        assert_best_first(&book);
+
+        // A limit of 2 is rounded up to Binance's smallest accepted depth
+        let query = &server.requests()[0].query;
+        assert!(query.contains("symbol=BNBBTC"));
+        assert!(query.contains("limit=5"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_spot_markets_from_exchange_info() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v3/exchangeInfo",
+            200,
+            BINANCE_EXCHANGE_INFO_SPOT,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+
+        let markets = adapter.get_markets(false).await.unwrap();
+
+        // Symbols in BREAK status are not tradable
+        assert_eq!(markets.len(), 2);
+        let btc = markets.iter().find(|m| m.symbol == "BTCUSDT").unwrap();
+        assert!(btc.spot && !btc.contract);
+        assert_eq!(btc.tick_size(), Some(0.01));
+        assert_eq!(btc.lot_size(), Some(0.00001));
+        assert_eq!(btc.min_notional(), Some(5.0));
+        assert_eq!(btc.limits.amount.as_ref().unwrap().max, Some(9000.0));
+    }
+
+    #[test]
+    fn test_binance_futures_markets_include_delivery_contracts() {
+        let markets = BinanceAdapter::parse_markets(&fixture(BINANCE_EXCHANGE_INFO_FUTURES), true);
+
+        assert_eq!(markets.len(), 2);
+        let btc = &markets[0];
+        assert_eq!(btc.type_, "swap");
+        assert_eq!(btc.expiry, None);
+        assert_eq!(btc.settle.as_deref(), Some("USDT"));
+        assert_eq!(btc.tick_size(), Some(0.1));
+        assert_eq!(btc.lot_size(), Some(0.001));
+        assert_eq!(btc.min_notional(), Some(100.0));
+
+        // The quarterly keeps its own symbol so it does not shadow the perpetual
+        let quarter = &markets[1];
+        assert_eq!(quarter.symbol, "BTCUSDT_250328");
+        assert_eq!(quarter.type_, "future");
+        assert_eq!(quarter.expiry, Some(1_743_148_800_000));
+        assert_eq!(
+            (quarter.base.as_str(), quarter.quote.as_str()),
+            ("BTC", "USDT")
+        );
+    }
+
+    #[test]
+    fn test_order_request_conforms_to_market_limits() {
+        let markets = BinanceAdapter::parse_markets(&fixture(BINANCE_EXCHANGE_INFO_FUTURES), true);
+        let btc = &markets[0];
+
+        let request = OrderRequest::limit("BTCUSDT", "buy", 0.12345, 65000.07);
+        let conformed = request.conform_to_market(btc, None).unwrap();
+        assert_eq!(conformed.amount, 0.123);
+        assert_eq!(conformed.price, Some(65000.1));
+
+        // 0.001 BTC at 65k is below the 100 USDT minimum notional
+        let small = OrderRequest::limit("BTCUSDT", "buy", 0.001, 65000.0);
+        let error = small.conform_to_market(btc, None).unwrap_err();
+        assert!(error.contains("minimum notional"));
+
+        // Reduce-only exits are exempt from the notional floor
+        let mut exit = OrderRequest::market("BTCUSDT", "sell", 0.001);
+        exit.reduce_only = true;
+        assert!(exit.conform_to_market(btc, Some(65000.0)).is_ok());
+
+        // Rounds down to zero below one lot
+        let dust = OrderRequest::market("BTCUSDT", "buy", 0.0004);
+        assert!(dust.conform_to_market(btc, Some(65000.0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_binance_ticker_maps_24hr_stats() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v3/ticker/24hr",
+            200,
+            BINANCE_TICKER,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+
+        let ticker = adapter.get_ticker("BTC/USDT", false).await.unwrap();
+
+        assert_eq!(ticker.symbol, "BTC/USDT");
+        assert_eq!(ticker.bid, Some(65004.99));
+        assert_eq!(ticker.ask, Some(65005.0));
+        assert_eq!(ticker.percentage, Some(-0.146));
+        assert_eq!(ticker.volume, Some(18250.5));
+        assert_eq!(ticker.timestamp, 1716863999999);
+        assert!(server.requests()[0].query.contains("symbol=BTCUSDT"));
+    }
+
+    #[test]
+    fn test_binance_funding_rate_from_premium_index() {
+        let info = BinanceAdapter::parse_funding_rate("BTCUSDT", &fixture(BINANCE_PREMIUM_INDEX));
+
+        assert_eq!(info.exchange, ExchangeIdEnum::Binance);
+        assert_eq!(info.funding_rate, 0.0001);
+        assert_eq!(info.mark_price, Some(65012.3));
+        assert_eq!(info.next_funding_time, Some(1716883200000));
+        assert_eq!(info.funding_countdown, Some(14_400_000));
+    }
+
+    #[tokio::test]
+    async fn test_binance_open_positions_skip_flat_entries() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/fapi/v2/positionRisk",
+            200,
+            BINANCE_POSITION_RISK,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        let positions = adapter.get_open_positions(&creds, None).await.unwrap();
+
+        assert_eq!(positions.len(), 2);
+        let btc = &positions[0];
+        assert_eq!(
+            (btc.symbol.as_str(), btc.side.as_str()),
+            ("BTCUSDT", "long")
+        );
+        assert_eq!(btc.amount, 0.15);
+        assert_eq!(btc.isolated, Some(false));
+        assert_eq!(btc.initial_margin, Some(1950.0));
+        let eth = &positions[1];
+        assert_eq!((eth.side.as_str(), eth.amount), ("short", 1.0));
+        assert_eq!(eth.hedged, Some(true));
+        assert_eq!(eth.collateral, Some(350.0));
+        assert_eq!(eth.notional, Some(3450.0));
+    }
+
+    #[tokio::test]
+    async fn test_binance_futures_open_orders() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/fapi/v1/openOrders",
+            200,
+            BINANCE_FUTURES_OPEN_ORDERS,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        let orders = adapter
+            .get_open_orders(&creds, Some("BTC/USDT"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].id, "1917641");
+        assert_eq!(orders[0].status, "open");
+        assert_eq!(orders[0].remaining, 0.4);
+        assert!(server.requests()[0].query.contains("symbol=BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_leverage_above_bracket_max_is_rejected() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/fapi/v1/leverageBracket",
+            200,
+            BINANCE_LEVERAGE_BRACKET,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        let error = adapter
+            .set_leverage(&creds, "BTC/USDT", 150)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::ExchangeError);
+        assert_eq!(
+            error.message,
+            "Leverage 150x exceeds the 125x maximum for BTC/USDT on binance"
+        );
+        let details = error.details.unwrap();
+        assert_eq!(details["max_leverage"], json!(125.0));
+        assert_eq!(details["requested_leverage"], json!(150));
+        // Nothing is sent once validation fails
+        assert!(server.requests().iter().all(|r| r.method == "GET"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_set_leverage_posts_symbol_and_value() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/fapi/v1/leverageBracket",
+                200,
+                BINANCE_LEVERAGE_BRACKET,
+            ),
+            MockRoute::new(
+                "POST",
+                "/fapi/v1/leverage",
+                200,
+                r#"{"leverage":20,"maxNotionalValue":"3000000","symbol":"BTCUSDT"}"#,
+            ),
+        ]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        adapter.set_leverage(&creds, "BTCUSDT", 20).await.unwrap();
+
+        let post = &server.requests()[1];
+        assert_eq!(post.path, "/fapi/v1/leverage");
+        assert!(post.query.starts_with("symbol=BTCUSDT&leverage=20&"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_unchanged_margin_type_is_not_an_error() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "POST",
+                "/fapi/v1/marginType",
+                400,
+                BINANCE_MARGIN_TYPE_UNCHANGED,
+            ),
+            MockRoute::new(
+                "POST",
+                "/fapi/v1/marginType",
+                400,
+                BINANCE_INSUFFICIENT_BALANCE,
+            ),
+        ]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        adapter
+            .set_margin_mode(&creds, "BTCUSDT", MarginMode::Isolated)
+            .await
+            .unwrap();
+        assert!(server.requests()[0].query.contains("marginType=ISOLATED"));
+
+        let error = adapter
+            .set_margin_mode(&creds, "BTCUSDT", MarginMode::Cross)
+            .await
+            .unwrap_err();
+        assert_eq!(error.details.unwrap()["exchange_code"], json!(-2010));
+    }
+
+    #[test]
+    fn test_binance_spot_commission_applies_bnb_discount() {
+        let (maker, taker) =
+            BinanceAdapter::parse_spot_commission(&fixture(BINANCE_ACCOUNT_COMMISSION));
+
+        assert!((maker.unwrap() - 0.000675).abs() < 1e-12);
+        assert!((taker.unwrap() - 0.00075).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_binance_futures_fees_from_commission_rate() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/fapi/v1/commissionRate",
+            200,
+            BINANCE_COMMISSION_RATE,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        let fees = adapter
+            .get_trading_fees(&creds, "BTC/USDT", true)
+            .await
+            .unwrap();
+
+        assert_eq!((fees.maker, fees.taker), (0.00018, 0.00045));
+        assert!(fees.tier_based);
+        assert!(server.requests()[0].query.contains("symbol=BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_binance_probe_reads_restrictions_and_weight() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/sapi/v1/account/apiRestrictions",
+            200,
+            BINANCE_API_RESTRICTIONS,
+        )
+        .with_header("x-sapi-used-ip-weight-1m", "150")]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "spot");
+
+        let permissions = adapter.probe_api_key(&creds).await.unwrap();
+
+        assert!(permissions.can_read);
+        assert!(permissions.can_trade_spot);
+        assert!(permissions.can_trade_futures);
+        assert!(!permissions.can_withdraw);
+        assert_eq!(permissions.ip_restricted, Some(true));
+        let rate_limit = permissions.rate_limit.unwrap();
+        assert_eq!(rate_limit.requests_per_minute, 12000);
+        assert_eq!(rate_limit.requests_remaining, 11850);
+    }
+
+    #[tokio::test]
+    async fn test_binance_get_order_queries_futures_endpoint() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/fapi/v1/order",
+            200,
+            BINANCE_FUTURES_ORDER_NEW,
+        )]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "spot");
+
+        let order = adapter
+            .get_order(&creds, "BTC/USDT", "22542179", Some("futures"))
+            .await
+            .unwrap();
+
+        assert_eq!(order.status, "open");
+        assert_eq!(order.filled, 0.0);
+        assert!(server.requests()[0].query.contains("orderId=22542179"));
+    }
+
+    #[test]
+    fn test_parse_funding_payments_keeps_income_sign() {
+        let data = fixture(BINANCE_INCOME_FUNDING_FEE);
+        let payments = BinanceAdapter::parse_funding_payments("BTC/USDT", &data);
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].amount, -0.375);
+        assert_eq!(payments[0].payment_id, "9689322392");
+        assert_eq!(payments[1].amount, 0.12);
+        assert_eq!(payments[1].timestamp, 1727798400000);
+        assert_eq!(payments[1].symbol, "BTC/USDT");
+    }
+
+    #[tokio::test]
+    async fn test_binance_isolated_margin_add_and_reduce() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "POST",
+                "/fapi/v1/positionMargin",
+                200,
+                BINANCE_POSITION_MARGIN,
+            ),
+            MockRoute::new(
+                "POST",
+                "/fapi/v1/positionMargin",
+                200,
+                BINANCE_POSITION_MARGIN,
+            ),
+        ]);
+        let adapter = BinanceAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Binance, "futures");
+
+        adapter
+            .adjust_isolated_margin(&creds, "BTC/USDT", 100.0)
+            .await
+            .unwrap();
+        adapter
+            .adjust_isolated_margin(&creds, "BTC/USDT", -25.5)
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert!(requests[0]
+            .query
+            .starts_with("symbol=BTCUSDT&amount=100&type=1&"));
+        assert!(requests[1]
+            .query
+            .starts_with("symbol=BTCUSDT&amount=25.5&type=2&"));
+    }
+}
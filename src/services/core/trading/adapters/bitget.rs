@@ -0,0 +1,1152 @@
+// src/services/core/trading/adapters/bitget.rs
+
+//! Bitget v2 adapter (spot and USDT-M futures)
+
+use async_trait::async_trait;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use super::{
+    account_fee_rates, acknowledged_order, empty_ticker, min_max, new_funding_rate, new_market,
+    normalize_orderbook, parse_levels, unsupported, ExchangeAdapter,
+};
+use crate::services::core::trading::exchange_rest::{
+    compact_symbol, datetime_from_millis, format_decimal, is_futures_market, json_f64, json_string,
+    json_u64, ExchangeRestClient,
+};
+use crate::services::core::user::user_exchange_api::ApiKeyPermissions;
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, FundingRateInfo, MarginMode, Market,
+    Order, OrderBook, OrderRequest, OrderStatus, OrderType, Position, Ticker, TimeInForce,
+    TradingFee, TradingFeeRates,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+/// Bitget product type for USDT-margined perpetuals
+const USDT_FUTURES: &str = "USDT-FUTURES";
+
+/// Trigger order types searched when an order id is not a regular order
+const PLAN_TYPES: [&str; 2] = ["normal_plan", "track_plan"];
+
+#[derive(Clone)]
+pub struct BitgetAdapter {
+    rest: ExchangeRestClient,
+}
+
+impl BitgetAdapter {
+    pub fn new(rest: ExchangeRestClient) -> Self {
+        Self { rest }
+    }
+
+    fn fee_rates(futures: bool) -> (f64, f64) {
+        if futures {
+            (0.0002, 0.0006)
+        } else {
+            (0.001, 0.001)
+        }
+    }
+
+    /// Decoded `{code, msg, data}` response, erroring on a non-success code
+    async fn public_data(&self, path: &str, query: &[(&str, String)]) -> ArbitrageResult<Value> {
+        let response = self
+            .rest
+            .public_get("bitget", &self.rest.endpoints(false).bitget, path, query)
+            .await?;
+        ExchangeRestClient::check_coded_response("bitget", &response, "00000")?;
+        Ok(response)
+    }
+
+    /// Parse Bitget v2 spot symbols or USDT-M contracts
+    pub fn parse_markets(data: &Value, futures: bool) -> Vec<Market> {
+        let Some(symbols) = data["data"].as_array() else {
+            return Vec::new();
+        };
+
+        symbols
+            .iter()
+            .filter(|s| {
+                if futures {
+                    s["symbolStatus"] == "normal"
+                } else {
+                    s["status"] == "online"
+                }
+            })
+            .filter_map(|s| {
+                let base = s["baseCoin"].as_str()?;
+                let quote = s["quoteCoin"].as_str()?;
+                let mut market = new_market(base, quote, futures, Self::fee_rates(futures), s);
+
+                if futures {
+                    let price_place = json_u64(s, "pricePlace").unwrap_or(0) as i32;
+                    let end_step = json_f64(s, "priceEndStep").unwrap_or(1.0);
+                    market.precision.price = Some(price_place);
+                    market.precision.tick_size = Some(end_step * 10f64.powi(-price_place));
+                    market.precision.amount = json_u64(s, "volumePlace").map(|p| p as i32);
+                    market.precision.lot_size = json_f64(s, "sizeMultiplier");
+                    market.limits.amount = min_max(json_f64(s, "minTradeNum"), None);
+                    market.limits.leverage =
+                        min_max(json_f64(s, "minLever"), json_f64(s, "maxLever"));
+                } else {
+                    market.precision.price = json_u64(s, "pricePrecision").map(|p| p as i32);
+                    market.precision.amount = json_u64(s, "quantityPrecision").map(|p| p as i32);
+                    market.limits.amount = min_max(
+                        json_f64(s, "minTradeAmount").filter(|v| *v > 0.0),
+                        json_f64(s, "maxTradeAmount").filter(|v| *v > 0.0),
+                    );
+                }
+                market.limits.cost = min_max(json_f64(s, "minTradeUSDT"), None);
+                Some(market)
+            })
+            .collect()
+    }
+
+    /// Map a spot or futures ticker entry into `Ticker`
+    pub fn parse_ticker(symbol: &str, data: &Value) -> Ticker {
+        let mut ticker = empty_ticker(symbol, json_u64(data, "ts"), data.clone());
+        ticker.high = json_f64(data, "high24h");
+        ticker.low = json_f64(data, "low24h");
+        ticker.bid = json_f64(data, "bidPr");
+        ticker.bid_volume = json_f64(data, "bidSz");
+        ticker.ask = json_f64(data, "askPr");
+        ticker.ask_volume = json_f64(data, "askSz");
+        ticker.open = json_f64(data, "open").or_else(|| json_f64(data, "open24h"));
+        ticker.close = json_f64(data, "lastPr");
+        ticker.last = json_f64(data, "lastPr");
+        if let (Some(last), Some(open)) = (ticker.last, ticker.open) {
+            ticker.change = Some(last - open);
+        }
+        // Bitget reports the 24h change as a fraction
+        ticker.percentage = json_f64(data, "change24h").map(|p| p * 100.0);
+        ticker.base_volume = json_f64(data, "baseVolume");
+        ticker.quote_volume = json_f64(data, "quoteVolume");
+        ticker.volume = ticker.base_volume;
+        ticker
+    }
+
+    /// Map `/api/v2/spot/account/info`. Authorities ending in "ow" grant write access
+    /// ("stow" spot orders, "coow"/"cpow" futures orders/positions, "wtow" wallet); older keys
+    /// report plain "trade"/"readonly". Any wallet write access counts as withdrawal.
+    pub fn parse_account_info(data: &Value) -> ApiKeyPermissions {
+        let authorities: Vec<&str> = data["authorities"]
+            .as_array()
+            .map(|list| list.iter().filter_map(|a| a.as_str()).collect())
+            .unwrap_or_default();
+        let has = |names: &[&str]| names.iter().any(|name| authorities.contains(name));
+        let legacy_trade = has(&["trade"]);
+        ApiKeyPermissions {
+            can_read: true,
+            can_trade_spot: legacy_trade || has(&["stow"]),
+            can_trade_futures: legacy_trade || has(&["coow", "cpow"]),
+            can_withdraw: has(&["wtow", "withdraw"]),
+            ip_restricted: data["ips"].as_str().map(|ips| !ips.trim().is_empty()),
+            rate_limit: None,
+        }
+    }
+
+    /// Map a futures `/api/v2/mix/order/detail` or spot `/api/v2/spot/trade/orderInfo`
+    /// record into `Order`
+    pub fn parse_order(data: &Value) -> ArbitrageResult<Order> {
+        let id = json_string(data, "orderId")
+            .ok_or_else(|| ArbitrageError::parse_error("Bitget order record missing orderId"))?;
+        let status = OrderStatus::from_exchange_status(
+            data["state"]
+                .as_str()
+                .or_else(|| data["status"].as_str())
+                .unwrap_or("live"),
+        );
+        let amount = json_f64(data, "size").unwrap_or(0.0);
+        let filled = json_f64(data, "baseVolume").unwrap_or(0.0);
+        let average = json_f64(data, "priceAvg").filter(|p| *p > 0.0);
+        let timestamp = json_u64(data, "uTime")
+            .or_else(|| json_u64(data, "cTime"))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+        // Futures fees are reported as negative amounts
+        let fee = json_f64(data, "fee")
+            .filter(|f| *f != 0.0)
+            .map(|fee| TradingFee {
+                currency: data["marginCoin"].as_str().unwrap_or("USDT").to_string(),
+                cost: fee.abs(),
+                rate: None,
+            });
+
+        Ok(Order {
+            id,
+            client_order_id: json_string(data, "clientOid").filter(|id| !id.is_empty()),
+            datetime: datetime_from_millis(timestamp),
+            timestamp,
+            last_trade_timestamp: (filled > 0.0).then_some(timestamp),
+            status: status.as_order_status_str().to_string(),
+            symbol: data["symbol"].as_str().unwrap_or_default().to_string(),
+            type_: data["orderType"].as_str().unwrap_or("limit").to_string(),
+            time_in_force: json_string(data, "force").map(|force| force.to_uppercase()),
+            side: data["side"].as_str().unwrap_or_default().to_lowercase(),
+            amount,
+            price: json_f64(data, "price").filter(|p| *p > 0.0),
+            average,
+            filled,
+            remaining: (amount - filled).max(0.0),
+            cost: filled * average.unwrap_or(0.0),
+            trades: vec![],
+            fee,
+            info: data.clone(),
+        })
+    }
+
+    /// Map an entry of the futures trigger order lists (`orders-plan-pending` and
+    /// `orders-plan-history`). An executed plan has sent its order to the book.
+    pub fn parse_plan_order(data: &Value) -> ArbitrageResult<Order> {
+        let id = json_string(data, "orderId")
+            .ok_or_else(|| ArbitrageError::parse_error("Bitget plan order missing orderId"))?;
+        let status = match data["planStatus"].as_str().unwrap_or("live") {
+            "executed" => OrderStatus::Filled,
+            "fail" => OrderStatus::Rejected,
+            state => OrderStatus::from_exchange_status(state),
+        };
+        let amount = json_f64(data, "size").unwrap_or(0.0);
+        let timestamp = json_u64(data, "uTime")
+            .or_else(|| json_u64(data, "cTime"))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+        let type_ = if data["planType"] == "track_plan" {
+            "trailingstop"
+        } else {
+            "stoploss"
+        };
+
+        Ok(Order {
+            id,
+            client_order_id: json_string(data, "clientOid").filter(|id| !id.is_empty()),
+            datetime: datetime_from_millis(timestamp),
+            timestamp,
+            last_trade_timestamp: None,
+            status: status.as_order_status_str().to_string(),
+            symbol: data["symbol"].as_str().unwrap_or_default().to_string(),
+            type_: type_.to_string(),
+            time_in_force: None,
+            side: data["side"].as_str().unwrap_or_default().to_lowercase(),
+            amount,
+            price: json_f64(data, "triggerPrice").filter(|p| *p > 0.0),
+            average: None,
+            filled: 0.0,
+            remaining: amount,
+            cost: 0.0,
+            trades: vec![],
+            fee: None,
+            info: data.clone(),
+        })
+    }
+
+    fn force(time_in_force: Option<TimeInForce>) -> &'static str {
+        match time_in_force.unwrap_or_default() {
+            TimeInForce::GTC => "gtc",
+            TimeInForce::IOC => "ioc",
+            TimeInForce::FOK => "fok",
+            TimeInForce::PostOnly => "post_only",
+        }
+    }
+
+    /// Stops, take-profits and trailing stops are futures plan (trigger) orders
+    fn is_plan(request: &OrderRequest) -> bool {
+        !matches!(request.order_type, OrderType::Market | OrderType::Limit)
+    }
+
+    /// Body for `/api/v2/mix/order/place-order`, or `place-plan-order` for trigger orders.
+    /// Sizes are in base coin; positions are one-way and cross margined. A trailing stop
+    /// activates at `stop_price`, which Bitget requires.
+    pub fn futures_order_body(request: &OrderRequest) -> ArbitrageResult<Value> {
+        let limit = matches!(
+            request.order_type,
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit
+        );
+        let mut body = json!({
+            "symbol": compact_symbol(&request.symbol),
+            "productType": USDT_FUTURES,
+            "marginMode": "crossed",
+            "marginCoin": "USDT",
+            "size": format_decimal(request.amount),
+            "side": request.side.to_lowercase(),
+            "orderType": if limit { "limit" } else { "market" },
+        });
+        if limit {
+            body["price"] = json!(request.price.map(format_decimal));
+            body["force"] = json!(Self::force(request.time_in_force));
+        }
+        match request.order_type {
+            OrderType::Market | OrderType::Limit => {}
+            OrderType::TrailingStop => {
+                let activation = request.stop_price.ok_or_else(|| {
+                    ArbitrageError::validation_error(
+                        "Bitget trailing stops need an activation price in stop_price",
+                    )
+                })?;
+                body["planType"] = json!("track_plan");
+                body["triggerPrice"] = json!(format_decimal(activation));
+                body["triggerType"] = json!("mark_price");
+                body["callbackRatio"] = json!(request.trailing_percent.map(format_decimal));
+            }
+            _ => {
+                body["planType"] = json!("normal_plan");
+                body["triggerPrice"] = json!(request.stop_price.map(format_decimal));
+                body["triggerType"] = json!("mark_price");
+            }
+        }
+        if request.reduce_only {
+            body["reduceOnly"] = json!("YES");
+        }
+        if let Some(client_id) = &request.client_order_id {
+            body["clientOid"] = json!(client_id);
+        }
+        Ok(body)
+    }
+
+    /// Body for `/api/v2/spot/trade/place-order`. `size` is the quote amount for market
+    /// buys and the base amount otherwise.
+    pub fn spot_order_body(request: &OrderRequest, size: f64) -> ArbitrageResult<Value> {
+        if Self::is_plan(request) {
+            return Err(ArbitrageError::validation_error(format!(
+                "{:?} orders are only supported on Bitget futures",
+                request.order_type
+            )));
+        }
+        let mut body = json!({
+            "symbol": compact_symbol(&request.symbol),
+            "side": request.side.to_lowercase(),
+            "orderType": if request.order_type == OrderType::Limit { "limit" } else { "market" },
+            "force": Self::force(request.time_in_force),
+            "size": format_decimal(size),
+        });
+        if request.order_type == OrderType::Limit {
+            body["price"] = json!(request.price.map(format_decimal));
+        }
+        if let Some(client_id) = &request.client_order_id {
+            body["clientOid"] = json!(client_id);
+        }
+        Ok(body)
+    }
+
+    /// Look up a futures trigger order among pending and past plans of every type
+    async fn fetch_plan_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+    ) -> ArbitrageResult<Option<Order>> {
+        for path in [
+            "/api/v2/mix/order/orders-plan-pending",
+            "/api/v2/mix/order/orders-plan-history",
+        ] {
+            for plan_type in PLAN_TYPES {
+                let response = self
+                    .rest
+                    .bitget_signed(
+                        Method::GET,
+                        path,
+                        json!({
+                            "symbol": symbol,
+                            "productType": USDT_FUTURES,
+                            "planType": plan_type,
+                            "orderId": order_id,
+                        }),
+                        credentials,
+                    )
+                    .await?;
+                let found = response["data"]["entrustedList"]
+                    .as_array()
+                    .and_then(|plans| plans.iter().find(|plan| plan["orderId"] == order_id));
+                if let Some(plan) = found {
+                    return Ok(Some(Self::parse_plan_order(plan)?));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Map a `/api/v2/mix/market/current-fund-rate` entry into `FundingRateInfo`
+    /// Map `contract_settle_fee` entries of the mix account bill; `amount` is signed
+    pub fn parse_funding_payments(symbol: &str, data: &Value) -> Vec<FundingPayment> {
+        let mut payments: Vec<FundingPayment> = data["bills"]
+            .as_array()
+            .map(|bills| {
+                bills
+                    .iter()
+                    .filter_map(|bill| {
+                        Some(FundingPayment {
+                            exchange: ExchangeIdEnum::Bitget,
+                            symbol: symbol.to_string(),
+                            amount: json_f64(bill, "amount")?,
+                            asset: json_string(bill, "coin").unwrap_or_else(|| "USDT".into()),
+                            timestamp: json_u64(bill, "cTime")?,
+                            payment_id: json_string(bill, "billId")?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        payments.sort_by_key(|payment| payment.timestamp);
+        payments
+    }
+
+    pub fn parse_funding_rate(symbol: &str, data: &Value) -> FundingRateInfo {
+        let rate = json_f64(data, "fundingRate").unwrap_or(0.0);
+        let mut info = new_funding_rate(ExchangeIdEnum::Bitget, symbol, rate, data.clone());
+        info.next_funding_time = json_u64(data, "nextUpdate").filter(|t| *t > 0);
+        if let Some(hours) = json_u64(data, "fundingRateInterval").filter(|h| *h > 0) {
+            info.funding_interval_hours = hours as u32;
+        }
+        info
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ExchangeAdapter for BitgetAdapter {
+    fn exchange_id(&self) -> ExchangeIdEnum {
+        ExchangeIdEnum::Bitget
+    }
+
+    fn market_symbol(&self, symbol: &str, _futures: bool) -> ArbitrageResult<String> {
+        Ok(compact_symbol(symbol))
+    }
+
+    fn base_fee_rates(&self, futures: bool) -> (f64, f64) {
+        Self::fee_rates(futures)
+    }
+
+    async fn get_ticker(&self, symbol: &str, futures: bool) -> ArbitrageResult<Ticker> {
+        let mut query = vec![("symbol", compact_symbol(symbol))];
+        let path = if futures {
+            query.push(("productType", USDT_FUTURES.to_string()));
+            "/api/v2/mix/market/ticker"
+        } else {
+            "/api/v2/spot/market/tickers"
+        };
+        let response = self.public_data(path, &query).await?;
+        let entry = response["data"]
+            .get(0)
+            .ok_or_else(|| ArbitrageError::not_found(format!("No Bitget ticker for {}", symbol)))?;
+        Ok(Self::parse_ticker(symbol, entry))
+    }
+
+    async fn get_orderbook(
+        &self,
+        symbol: &str,
+        limit: u32,
+        futures: bool,
+    ) -> ArbitrageResult<OrderBook> {
+        let mut query = vec![
+            ("symbol", compact_symbol(symbol)),
+            (
+                "limit",
+                limit.clamp(1, if futures { 100 } else { 150 }).to_string(),
+            ),
+        ];
+        let path = if futures {
+            query.push(("productType", "USDT-FUTURES".to_string()));
+            "/api/v2/mix/market/merge-depth"
+        } else {
+            query.push(("type", "step0".to_string()));
+            "/api/v2/spot/market/orderbook"
+        };
+        let response = self
+            .rest
+            .public_get("bitget", &self.rest.endpoints(false).bitget, path, &query)
+            .await?;
+        ExchangeRestClient::check_coded_response("bitget", &response, "00000")?;
+        let book = &response["data"];
+
+        Ok(normalize_orderbook(
+            symbol,
+            parse_levels(&book["bids"]),
+            parse_levels(&book["asks"]),
+            limit,
+            json_u64(book, "ts"),
+            None,
+        ))
+    }
+
+    async fn get_markets(&self, futures: bool) -> ArbitrageResult<Vec<Market>> {
+        let data = if futures {
+            self.public_data(
+                "/api/v2/mix/market/contracts",
+                &[("productType", USDT_FUTURES.to_string())],
+            )
+            .await?
+        } else {
+            self.public_data("/api/v2/spot/public/symbols", &[]).await?
+        };
+        Ok(Self::parse_markets(&data, futures))
+    }
+
+    async fn get_funding_rate(&self, symbol: &str) -> ArbitrageResult<FundingRateInfo> {
+        let query = [
+            ("symbol", compact_symbol(symbol)),
+            ("productType", USDT_FUTURES.to_string()),
+        ];
+        let response = self
+            .public_data("/api/v2/mix/market/current-fund-rate", &query)
+            .await?;
+        let entry = response["data"].get(0).ok_or_else(|| {
+            ArbitrageError::not_found(format!("No Bitget funding rate for {}", symbol))
+        })?;
+        Ok(Self::parse_funding_rate(symbol, entry))
+    }
+
+    async fn get_balance(&self, credentials: &ExchangeCredentials) -> ArbitrageResult<Value> {
+        let (path, params) = if is_futures_market(&credentials.exchange_type) {
+            (
+                "/api/v2/mix/account/accounts",
+                json!({ "productType": USDT_FUTURES }),
+            )
+        } else {
+            ("/api/v2/spot/account/assets", json!({}))
+        };
+        self.rest
+            .bitget_signed(Method::GET, path, params, credentials)
+            .await
+    }
+
+    async fn place_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        request
+            .validate()
+            .map_err(ArbitrageError::validation_error)?;
+
+        let futures = is_futures_market(ExchangeRestClient::market_type(
+            credentials,
+            request.market_type.as_deref(),
+        ));
+        let (path, body) = if futures {
+            let path = if Self::is_plan(request) {
+                "/api/v2/mix/order/place-plan-order"
+            } else {
+                "/api/v2/mix/order/place-order"
+            };
+            (path, Self::futures_order_body(request)?)
+        } else {
+            // Spot market buys are sized in quote currency
+            let size = if request.order_type == OrderType::Market && request.is_buy() {
+                let ticker = self.get_ticker(&request.symbol, false).await?;
+                let price = ticker.ask.or(ticker.last).ok_or_else(|| {
+                    ArbitrageError::not_found(format!("No Bitget price for {}", request.symbol))
+                })?;
+                request.amount * price
+            } else {
+                request.amount
+            };
+            (
+                "/api/v2/spot/trade/place-order",
+                Self::spot_order_body(request, size)?,
+            )
+        };
+
+        let response = self
+            .rest
+            .bitget_signed(Method::POST, path, body, credentials)
+            .await?;
+        let order_id = json_string(&response["data"], "orderId")
+            .ok_or_else(|| ArbitrageError::parse_error("Bitget order response missing orderId"))?;
+
+        // The order is placed either way; fall back to the acknowledgement if the lookup fails
+        match self
+            .get_order(
+                credentials,
+                &request.symbol,
+                &order_id,
+                request.market_type.as_deref(),
+            )
+            .await
+        {
+            Ok(order) => Ok(order),
+            Err(_) => Ok(acknowledged_order(
+                order_id,
+                json_string(&response["data"], "clientOid"),
+                compact_symbol(&request.symbol),
+                request,
+                response,
+            )),
+        }
+    }
+
+    async fn cancel_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let compact = compact_symbol(symbol);
+        if futures {
+            let cancelled = self
+                .rest
+                .bitget_signed(
+                    Method::POST,
+                    "/api/v2/mix/order/cancel-order",
+                    json!({
+                        "symbol": compact,
+                        "productType": USDT_FUTURES,
+                        "marginCoin": "USDT",
+                        "orderId": order_id,
+                    }),
+                    credentials,
+                )
+                .await;
+            if let Err(e) = cancelled {
+                // Not a regular order; trigger orders have their own cancel endpoint
+                self.rest
+                    .bitget_signed(
+                        Method::POST,
+                        "/api/v2/mix/order/cancel-plan-order",
+                        json!({
+                            "symbol": compact,
+                            "productType": USDT_FUTURES,
+                            "marginCoin": "USDT",
+                            "orderIdList": [{ "orderId": order_id }],
+                        }),
+                        credentials,
+                    )
+                    .await
+                    .map_err(|_| e)?;
+            }
+        } else {
+            self.rest
+                .bitget_signed(
+                    Method::POST,
+                    "/api/v2/spot/trade/cancel-order",
+                    json!({ "symbol": compact, "orderId": order_id }),
+                    credentials,
+                )
+                .await?;
+        }
+        self.get_order(credentials, symbol, order_id, market_type)
+            .await
+    }
+
+    async fn get_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let compact = compact_symbol(symbol);
+        if !futures {
+            let response = self
+                .rest
+                .bitget_signed(
+                    Method::GET,
+                    "/api/v2/spot/trade/orderInfo",
+                    json!({ "orderId": order_id }),
+                    credentials,
+                )
+                .await?;
+            let record = response["data"].get(0).ok_or_else(|| {
+                ArbitrageError::not_found(format!("Bitget order {} not found", order_id))
+            })?;
+            return Self::parse_order(record);
+        }
+
+        let detail = self
+            .rest
+            .bitget_signed(
+                Method::GET,
+                "/api/v2/mix/order/detail",
+                json!({ "symbol": compact, "productType": USDT_FUTURES, "orderId": order_id }),
+                credentials,
+            )
+            .await;
+        match detail {
+            Ok(response) => Self::parse_order(&response["data"]),
+            Err(e) => self
+                .fetch_plan_order(credentials, &compact, order_id)
+                .await
+                .ok()
+                .flatten()
+                .ok_or(e),
+        }
+    }
+
+    async fn get_open_positions(
+        &self,
+        _credentials: &ExchangeCredentials,
+        _symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        Err(unsupported(ExchangeIdEnum::Bitget, "Position sync"))
+    }
+
+    async fn get_open_orders(
+        &self,
+        _credentials: &ExchangeCredentials,
+        _symbol: Option<&str>,
+        _market_type: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        Err(unsupported(ExchangeIdEnum::Bitget, "Open order sync"))
+    }
+
+    async fn get_max_leverage(
+        &self,
+        _credentials: &ExchangeCredentials,
+        symbol: &str,
+    ) -> ArbitrageResult<Option<f64>> {
+        let symbol = compact_symbol(symbol);
+        Ok(self
+            .get_markets(true)
+            .await?
+            .into_iter()
+            .find(|market| market.symbol == symbol)
+            .and_then(|market| market.limits.leverage)
+            .and_then(|leverage| leverage.max))
+    }
+
+    async fn set_leverage(
+        &self,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _leverage: u32,
+    ) -> ArbitrageResult<()> {
+        Err(unsupported(ExchangeIdEnum::Bitget, "Leverage control"))
+    }
+
+    async fn set_margin_mode(
+        &self,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        Err(unsupported(ExchangeIdEnum::Bitget, "Margin mode control"))
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _amount: f64,
+    ) -> ArbitrageResult<()> {
+        Err(unsupported(
+            ExchangeIdEnum::Bitget,
+            "Isolated margin adjustment",
+        ))
+    }
+
+    async fn get_trading_fees(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        futures: bool,
+    ) -> ArbitrageResult<TradingFeeRates> {
+        let response = self
+            .rest
+            .bitget_signed(
+                Method::GET,
+                "/api/v2/common/trade-rate",
+                json!({
+                    "symbol": compact_symbol(symbol),
+                    "businessType": if futures { "mix" } else { "spot" },
+                }),
+                credentials,
+            )
+            .await?;
+        let data = &response["data"];
+        account_fee_rates(
+            self.exchange_id(),
+            symbol,
+            json_f64(data, "makerFeeRate"),
+            json_f64(data, "takerFeeRate"),
+        )
+    }
+
+    async fn get_funding_payments(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        let mut params = json!({
+            "productType": "USDT-FUTURES",
+            "symbol": compact_symbol(symbol),
+            "businessType": "contract_settle_fee",
+        });
+        if let Some(since) = since {
+            params["startTime"] = json!(since.to_string());
+        }
+        let response = self
+            .rest
+            .bitget_signed(Method::GET, "/api/v2/mix/account/bill", params, credentials)
+            .await?;
+        Ok(Self::parse_funding_payments(symbol, &response["data"]))
+    }
+
+    async fn probe_api_key(
+        &self,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        let response = self
+            .rest
+            .bitget_signed(
+                Method::GET,
+                "/api/v2/spot/account/info",
+                json!({}),
+                credentials,
+            )
+            .await?;
+        Ok(Self::parse_account_info(&response["data"]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::adapters::test_support::{credentials, fixture, rest_for};
+    use crate::services::core::trading::signing::hmac_sha256_base64;
+    use crate::test_utils::mock_http_server::{MockHttpServer, MockRoute};
+    use crate::utils::error::ErrorKind;
+
+    const BITGET_ORDERBOOK: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/orderbook.json");
+    const BITGET_SYMBOLS_SPOT: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/symbols_spot.json");
+    const BITGET_CONTRACTS: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/contracts.json");
+    const BITGET_TICKER_SPOT: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/ticker_spot.json");
+    const BITGET_CURRENT_FUND_RATE: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/current_fund_rate.json");
+    const BITGET_MIX_ACCOUNTS: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/mix_accounts.json");
+    const BITGET_TRADE_RATE: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/trade_rate.json");
+    const BITGET_ACCOUNT_INFO: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/account_info.json");
+    const BITGET_MIX_BILL_SETTLE_FEE: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/mix_bill_settle_fee.json");
+    const BITGET_PLACE_ORDER: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/place_order.json");
+    const BITGET_MIX_ORDER_DETAIL: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/mix_order_detail.json");
+    const BITGET_ORDERS_PLAN_PENDING: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/orders_plan_pending.json");
+    const BITGET_SPOT_ORDER_INFO: &str =
+        include_str!("../../../../test_utils/fixtures/bitget/spot_order_info.json");
+
+    #[tokio::test]
+    async fn test_bitget_orderbook_spot() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v2/spot/market/orderbook",
+            200,
+            BITGET_ORDERBOOK,
+        )]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+
+        let book = adapter.get_orderbook("BTC/USDT", 1, false).await.unwrap();
+
+        assert_eq!(book.bids, vec![[34567.14, 1.0]]);
+        assert_eq!(book.asks, vec![[34567.15, 0.0131]]);
+        assert!(server.requests()[0].query.contains("symbol=BTCUSDT"));
+    }
+
+    #[test]
+    fn test_bitget_markets_derive_tick_size() {
+        let spot = BitgetAdapter::parse_markets(&fixture(BITGET_SYMBOLS_SPOT), false);
+        assert_eq!(spot.len(), 1);
+        assert_eq!(spot[0].tick_size(), Some(0.01));
+        assert_eq!(spot[0].lot_size(), Some(0.000001));
+        assert_eq!(spot[0].min_notional(), Some(1.0));
+
+        let futures = BitgetAdapter::parse_markets(&fixture(BITGET_CONTRACTS), true);
+        assert_eq!(futures[0].tick_size(), Some(0.1));
+        assert_eq!(futures[0].lot_size(), Some(0.0001));
+        assert_eq!(futures[0].min_notional(), Some(5.0));
+    }
+
+    #[tokio::test]
+    async fn test_bitget_spot_ticker() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v2/spot/market/tickers",
+            200,
+            BITGET_TICKER_SPOT,
+        )]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+
+        let ticker = adapter.get_ticker("BTC/USDT", false).await.unwrap();
+
+        assert_eq!(ticker.last, Some(34413.1));
+        assert_eq!(ticker.ask, Some(34413.11));
+        assert!((ticker.percentage.unwrap() - 0.069).abs() < 1e-9);
+        assert!(server.requests()[0].query.contains("symbol=BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_bitget_funding_rate_uses_usdt_futures() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v2/mix/market/current-fund-rate",
+            200,
+            BITGET_CURRENT_FUND_RATE,
+        )]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+
+        let info = adapter.get_funding_rate("BTC/USDT").await.unwrap();
+
+        assert_eq!(info.exchange, ExchangeIdEnum::Bitget);
+        assert_eq!(info.funding_rate, 0.000068);
+        assert_eq!(info.funding_interval_hours, 8);
+        assert_eq!(info.next_funding_time, Some(1743062400000));
+        assert!(server.requests()[0]
+            .query
+            .contains("productType=USDT-FUTURES"));
+    }
+
+    #[tokio::test]
+    async fn test_bitget_leverage_control_not_implemented() {
+        let adapter = BitgetAdapter::new(ExchangeRestClient::new());
+        let creds = credentials(ExchangeIdEnum::Bitget, "futures");
+
+        let error = adapter
+            .set_leverage(&creds, "BTCUSDT", 5)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::NotImplemented);
+    }
+
+    #[tokio::test]
+    async fn test_bitget_futures_balance_is_signed() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v2/mix/account/accounts",
+            200,
+            BITGET_MIX_ACCOUNTS,
+        )]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::Bitget, "futures");
+        creds.passphrase = Some("bitget-passphrase".to_string());
+
+        let balance = adapter.get_balance(&creds).await.unwrap();
+        assert_eq!(balance["data"][0]["available"], "1200.5");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.query, "productType=USDT-FUTURES");
+        assert_eq!(
+            request.header("access-passphrase"),
+            Some("bitget-passphrase")
+        );
+        let timestamp = request.header("access-timestamp").unwrap();
+        let expected = hmac_sha256_base64(
+            "test-secret",
+            &format!(
+                "{}GET/api/v2/mix/account/accounts?productType=USDT-FUTURES",
+                timestamp
+            ),
+        )
+        .unwrap();
+        assert_eq!(request.header("access-sign"), Some(expected.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_bitget_balance_requires_passphrase() {
+        let adapter = BitgetAdapter::new(ExchangeRestClient::new());
+        let creds = credentials(ExchangeIdEnum::Bitget, "spot");
+
+        let error = adapter.get_balance(&creds).await.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+    }
+
+    #[tokio::test]
+    async fn test_bitget_trade_rate_for_futures() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v2/common/trade-rate",
+            200,
+            BITGET_TRADE_RATE,
+        )]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::Bitget, "futures");
+        creds.passphrase = Some("bitget-passphrase".to_string());
+
+        let fees = adapter
+            .get_trading_fees(&creds, "BTC/USDT", true)
+            .await
+            .unwrap();
+
+        assert_eq!((fees.maker, fees.taker), (0.0002, 0.0006));
+        assert!(server.requests()[0].query.contains("businessType=mix"));
+    }
+
+    #[test]
+    fn test_bitget_account_info_permissions() {
+        let permissions = BitgetAdapter::parse_account_info(&fixture(BITGET_ACCOUNT_INFO)["data"]);
+
+        assert!(permissions.can_trade_spot && permissions.can_trade_futures);
+        assert!(!permissions.can_withdraw);
+        assert_eq!(permissions.ip_restricted, Some(false));
+    }
+
+    #[test]
+    fn test_parse_funding_payments_from_mix_bills() {
+        let data = fixture(BITGET_MIX_BILL_SETTLE_FEE);
+        let payments = BitgetAdapter::parse_funding_payments("BTC/USDT", &data["data"]);
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].amount, 0.0807);
+        assert_eq!(payments[1].amount, -0.0512);
+        assert_eq!(payments[1].payment_id, "1220289012519190529");
+    }
+
+    fn bitget_credentials(market_type: &str) -> ExchangeCredentials {
+        let mut creds = credentials(ExchangeIdEnum::Bitget, market_type);
+        creds.passphrase = Some("bitget-passphrase".to_string());
+        creds
+    }
+
+    #[tokio::test]
+    async fn test_bitget_futures_market_order() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "POST",
+                "/api/v2/mix/order/place-order",
+                200,
+                BITGET_PLACE_ORDER,
+            ),
+            MockRoute::new(
+                "GET",
+                "/api/v2/mix/order/detail",
+                200,
+                BITGET_MIX_ORDER_DETAIL,
+            ),
+        ]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+        let request = OrderRequest {
+            client_order_id: Some("arbS1".to_string()),
+            ..OrderRequest::market("BTC/USDT", "sell", 0.15)
+        };
+
+        let order = adapter
+            .place_order(&bitget_credentials("futures"), &request)
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(body["symbol"], "BTCUSDT");
+        assert_eq!(body["productType"], "USDT-FUTURES");
+        assert_eq!(body["orderType"], "market");
+        assert_eq!(body["size"], "0.15");
+        assert_eq!(body["clientOid"], "arbS1");
+        assert!(body.get("reduceOnly").is_none());
+        assert_eq!(order.id, "1193484212836106240");
+        assert_eq!(order.status, "closed");
+        assert_eq!(order.filled, 0.15);
+        assert_eq!(order.average, Some(65010.0));
+        assert!((order.fee.unwrap().cost - 5.8509).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_bitget_stop_loss_is_a_plan_order() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "POST",
+                "/api/v2/mix/order/place-plan-order",
+                200,
+                &BITGET_PLACE_ORDER.replace("1193484212836106240", "1193484599185043456"),
+            ),
+            MockRoute::new(
+                "GET",
+                "/api/v2/mix/order/detail",
+                200,
+                r#"{"code":"40109","msg":"The data of the order cannot be found","data":null}"#,
+            ),
+            MockRoute::new(
+                "GET",
+                "/api/v2/mix/order/orders-plan-pending",
+                200,
+                BITGET_ORDERS_PLAN_PENDING,
+            ),
+        ]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+        let request = OrderRequest {
+            order_type: OrderType::StopLoss,
+            stop_price: Some(68_000.0),
+            reduce_only: true,
+            ..OrderRequest::market("BTCUSDT", "buy", 0.15)
+        };
+
+        let order = adapter
+            .place_order(&bitget_credentials("futures"), &request)
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(body["planType"], "normal_plan");
+        assert_eq!(body["triggerPrice"], "68000");
+        assert_eq!(body["triggerType"], "mark_price");
+        assert_eq!(body["reduceOnly"], "YES");
+        assert_eq!(order.id, "1193484599185043456");
+        assert_eq!(order.status, "open");
+        assert_eq!(order.price, Some(68_000.0));
+    }
+
+    #[tokio::test]
+    async fn test_bitget_spot_market_buy_is_sized_in_quote() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/api/v2/spot/market/tickers",
+                200,
+                BITGET_TICKER_SPOT,
+            ),
+            MockRoute::new(
+                "POST",
+                "/api/v2/spot/trade/place-order",
+                200,
+                &BITGET_PLACE_ORDER.replace("1193484212836106240", "1193485001234567890"),
+            ),
+            MockRoute::new(
+                "GET",
+                "/api/v2/spot/trade/orderInfo",
+                200,
+                BITGET_SPOT_ORDER_INFO,
+            ),
+        ]);
+        let adapter = BitgetAdapter::new(rest_for(&server));
+
+        let order = adapter
+            .place_order(
+                &bitget_credentials("spot"),
+                &OrderRequest::market("BTCUSDT", "buy", 0.002),
+            )
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body["orderType"], "market");
+        // 0.002 BTC at the 34,413.11 ask
+        let quote: f64 = body["size"].as_str().unwrap().parse().unwrap();
+        assert!((quote - 68.82622).abs() < 1e-9);
+        assert_eq!(order.status, "closed");
+        assert_eq!(order.filled, 0.002);
+    }
+
+    #[test]
+    fn test_bitget_trailing_stop_needs_activation_price() {
+        let request = OrderRequest {
+            order_type: OrderType::TrailingStop,
+            trailing_percent: Some(2.0),
+            reduce_only: true,
+            ..OrderRequest::market("BTCUSDT", "sell", 0.1)
+        };
+        let error = BitgetAdapter::futures_order_body(&request).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+
+        let body = BitgetAdapter::futures_order_body(&OrderRequest {
+            stop_price: Some(70_000.0),
+            ..request
+        })
+        .unwrap();
+        assert_eq!(body["planType"], "track_plan");
+        assert_eq!(body["callbackRatio"], "2");
+        assert_eq!(body["triggerPrice"], "70000");
+    }
+}
@@ -0,0 +1,1297 @@
+// src/services/core/trading/adapters/bybit.rs
+
+//! Bybit v5 unified API adapter (spot and linear perpetuals)
+
+use async_trait::async_trait;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use super::{
+    account_fee_rates, dated_future, empty_ticker, ignore_unchanged, min_max, new_funding_rate,
+    new_market, new_position, normalize_orderbook, parse_levels, validate_leverage,
+    ExchangeAdapter,
+};
+use crate::services::core::trading::exchange_rest::{
+    compact_symbol, datetime_from_millis, format_decimal, is_futures_market, json_f64, json_string,
+    json_u64, ExchangeRestClient,
+};
+use crate::services::core::user::user_exchange_api::ApiKeyPermissions;
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, FundingRateInfo, MarginMode, Market,
+    Order, OrderBook, OrderRequest, OrderStatus, OrderType, Position, Ticker, TimeInForce,
+    TradingFee, TradingFeeRates,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+#[derive(Clone)]
+pub struct BybitAdapter {
+    rest: ExchangeRestClient,
+}
+
+impl BybitAdapter {
+    pub fn new(rest: ExchangeRestClient) -> Self {
+        Self { rest }
+    }
+
+    fn fee_rates(futures: bool) -> (f64, f64) {
+        if futures {
+            (0.0002, 0.00055)
+        } else {
+            (0.001, 0.001)
+        }
+    }
+
+    fn depth_limit(limit: u32, futures: bool) -> u32 {
+        limit.clamp(1, if futures { 500 } else { 200 })
+    }
+
+    fn category(futures: bool) -> &'static str {
+        if futures {
+            "linear"
+        } else {
+            "spot"
+        }
+    }
+
+    fn order_body(request: &OrderRequest, futures: bool) -> ArbitrageResult<Value> {
+        let side = if request.is_buy() { "Buy" } else { "Sell" };
+        let order_type = match request.order_type {
+            OrderType::Market | OrderType::StopLoss | OrderType::TakeProfit => "Market",
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit => "Limit",
+            OrderType::TrailingStop => {
+                return Err(ArbitrageError::validation_error(
+                    "Bybit trailing stops are position-level settings, not orders",
+                ))
+            }
+        };
+
+        let mut body = json!({
+            "category": Self::category(futures),
+            "symbol": compact_symbol(&request.symbol),
+            "side": side,
+            "orderType": order_type,
+            "qty": format_decimal(request.amount),
+        });
+
+        if order_type == "Limit" {
+            if let Some(price) = request.price {
+                body["price"] = json!(format_decimal(price));
+            }
+            let tif = match request.time_in_force.unwrap_or_default() {
+                TimeInForce::PostOnly => "PostOnly",
+                other => other.as_str(),
+            };
+            body["timeInForce"] = json!(tif);
+        }
+
+        let conditional = !matches!(request.order_type, OrderType::Market | OrderType::Limit);
+        if let Some(trigger_price) = request.stop_price.filter(|_| conditional) {
+            // 1 = triggered when price rises to triggerPrice, 2 = when it falls
+            let rising = match request.order_type {
+                OrderType::StopLoss | OrderType::StopLossLimit => request.is_buy(),
+                _ => !request.is_buy(),
+            };
+            body["triggerPrice"] = json!(format_decimal(trigger_price));
+            body["triggerDirection"] = json!(if rising { 1 } else { 2 });
+        }
+        if futures && request.reduce_only {
+            body["reduceOnly"] = json!(true);
+        }
+        if let Some(client_id) = &request.client_order_id {
+            body["orderLinkId"] = json!(client_id);
+        }
+        Ok(body)
+    }
+
+    /// Map a Bybit v5 order record (from /v5/order/realtime or /history) into `Order`
+    pub fn parse_order(data: &Value) -> ArbitrageResult<Order> {
+        let id = json_string(data, "orderId")
+            .ok_or_else(|| ArbitrageError::parse_error("Bybit order record missing orderId"))?;
+        let status = OrderStatus::from_exchange_status(
+            data.get("orderStatus")
+                .and_then(|s| s.as_str())
+                .unwrap_or("New"),
+        );
+        let amount = json_f64(data, "qty").unwrap_or(0.0);
+        let filled = json_f64(data, "cumExecQty").unwrap_or(0.0);
+        let cost = json_f64(data, "cumExecValue").unwrap_or(0.0);
+        let timestamp = json_u64(data, "updatedTime")
+            .or_else(|| json_u64(data, "createdTime"))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+        let fee = json_f64(data, "cumExecFee")
+            .filter(|f| *f > 0.0)
+            .map(|cost| TradingFee {
+                currency: data["feeCurrency"].as_str().unwrap_or("USDT").to_string(),
+                cost,
+                rate: None,
+            });
+
+        Ok(Order {
+            id,
+            client_order_id: json_string(data, "orderLinkId"),
+            datetime: datetime_from_millis(timestamp),
+            timestamp,
+            last_trade_timestamp: (filled > 0.0).then_some(timestamp),
+            status: status.as_order_status_str().to_string(),
+            symbol: data["symbol"].as_str().unwrap_or_default().to_string(),
+            type_: data["orderType"].as_str().unwrap_or("Limit").to_lowercase(),
+            time_in_force: json_string(data, "timeInForce"),
+            side: data["side"].as_str().unwrap_or_default().to_lowercase(),
+            amount,
+            price: json_f64(data, "price").filter(|p| *p > 0.0),
+            average: json_f64(data, "avgPrice").filter(|p| *p > 0.0),
+            filled,
+            remaining: json_f64(data, "leavesQty").unwrap_or((amount - filled).max(0.0)),
+            cost,
+            trades: vec![],
+            fee,
+            info: data.clone(),
+        })
+    }
+
+    /// Parse Bybit `/v5/market/instruments-info` (spot, or linear perpetuals and futures)
+    pub fn parse_markets(result: &Value, futures: bool) -> Vec<Market> {
+        let Some(list) = result["list"].as_array() else {
+            return Vec::new();
+        };
+
+        list.iter()
+            .filter(|i| i["status"] == "Trading")
+            .filter_map(|i| {
+                let base = i["baseCoin"].as_str()?;
+                let quote = i["quoteCoin"].as_str()?;
+                let mut market = new_market(base, quote, futures, Self::fee_rates(futures), i);
+                if futures {
+                    market = match i["contractType"].as_str()? {
+                        "LinearPerpetual" => market,
+                        "LinearFutures" => dated_future(
+                            market,
+                            i["symbol"].as_str()?,
+                            json_u64(i, "deliveryTime").filter(|t| *t > 0)?,
+                        ),
+                        _ => return None,
+                    };
+                }
+                let lot = &i["lotSizeFilter"];
+                let price = &i["priceFilter"];
+
+                market.precision.tick_size = json_f64(price, "tickSize");
+                market.limits.price =
+                    min_max(json_f64(price, "minPrice"), json_f64(price, "maxPrice"));
+                market.limits.amount =
+                    min_max(json_f64(lot, "minOrderQty"), json_f64(lot, "maxOrderQty"));
+                if futures {
+                    market.settle = json_string(i, "settleCoin");
+                    market.settle_id = market.settle.clone();
+                    market.precision.lot_size = json_f64(lot, "qtyStep");
+                    market.limits.cost = min_max(json_f64(lot, "minNotionalValue"), None);
+                    market.limits.leverage = min_max(
+                        json_f64(&i["leverageFilter"], "minLeverage"),
+                        json_f64(&i["leverageFilter"], "maxLeverage"),
+                    );
+                } else {
+                    market.margin = i["marginTrading"].as_str().is_some_and(|m| m != "none");
+                    market.precision.lot_size = json_f64(lot, "basePrecision");
+                    market.limits.cost =
+                        min_max(json_f64(lot, "minOrderAmt"), json_f64(lot, "maxOrderAmt"));
+                }
+                Some(market)
+            })
+            .collect()
+    }
+
+    /// Map a `/v5/position/list` entry; flat positions yield `None`
+    pub fn parse_position(data: &Value) -> Option<Position> {
+        let amount = json_f64(data, "size").filter(|size| *size > 0.0)?;
+        let side = match data["side"].as_str()? {
+            "Buy" => "long",
+            "Sell" => "short",
+            _ => return None,
+        };
+        let mut position = new_position(
+            data["symbol"].as_str()?,
+            side,
+            amount,
+            json_u64(data, "updatedTime"),
+            data,
+        );
+        // tradeMode 1 is isolated margin; positionIdx 1/2 are the hedge-mode legs
+        position.isolated = Some(json_u64(data, "tradeMode") == Some(1));
+        position.hedged = Some(json_u64(data, "positionIdx").unwrap_or(0) != 0);
+        position.contracts = Some(amount);
+        position.contract_size = Some(1.0);
+        position.entry_price = json_f64(data, "avgPrice").filter(|p| *p > 0.0);
+        position.mark_price = json_f64(data, "markPrice").filter(|p| *p > 0.0);
+        position.notional = json_f64(data, "positionValue");
+        position.leverage = json_f64(data, "leverage");
+        position.initial_margin = json_f64(data, "positionIM");
+        position.maintenance_margin = json_f64(data, "positionMM");
+        position.collateral = json_f64(data, "positionBalance");
+        position.unrealized_pnl = json_f64(data, "unrealisedPnl");
+        position.realized_pnl = json_f64(data, "cumRealisedPnl");
+        Some(position)
+    }
+
+    /// Map a `/v5/market/tickers` entry into `Ticker`
+    pub fn parse_ticker(symbol: &str, data: &Value, timestamp: Option<u64>) -> Ticker {
+        let mut ticker = empty_ticker(symbol, timestamp, data.clone());
+        ticker.high = json_f64(data, "highPrice24h");
+        ticker.low = json_f64(data, "lowPrice24h");
+        ticker.bid = json_f64(data, "bid1Price");
+        ticker.bid_volume = json_f64(data, "bid1Size");
+        ticker.ask = json_f64(data, "ask1Price");
+        ticker.ask_volume = json_f64(data, "ask1Size");
+        ticker.open = json_f64(data, "prevPrice24h");
+        ticker.close = json_f64(data, "lastPrice");
+        ticker.last = json_f64(data, "lastPrice");
+        ticker.previous_close = json_f64(data, "prevPrice24h");
+        if let (Some(last), Some(open)) = (ticker.last, ticker.open) {
+            ticker.change = Some(last - open);
+        }
+        // Bybit reports the 24h change as a fraction
+        ticker.percentage = json_f64(data, "price24hPcnt").map(|p| p * 100.0);
+        ticker.base_volume = json_f64(data, "volume24h");
+        ticker.quote_volume = json_f64(data, "turnover24h");
+        ticker.volume = ticker.base_volume;
+        ticker
+    }
+
+    /// Map a linear `/v5/market/tickers` entry into `FundingRateInfo`
+    pub fn parse_funding_rate(symbol: &str, data: &Value) -> FundingRateInfo {
+        let rate = json_f64(data, "fundingRate").unwrap_or(0.0);
+        let mut info = new_funding_rate(ExchangeIdEnum::Bybit, symbol, rate, data.clone());
+        info.next_funding_time = json_u64(data, "nextFundingTime").filter(|t| *t > 0);
+        info.mark_price = json_f64(data, "markPrice");
+        info.index_price = json_f64(data, "indexPrice");
+        if let Some(hours) = json_u64(data, "fundingIntervalHour").filter(|h| *h > 0) {
+            info.funding_interval_hours = hours as u32;
+        }
+        info
+    }
+
+    async fn ticker_entry(
+        &self,
+        symbol: &str,
+        futures: bool,
+    ) -> ArbitrageResult<(Value, Option<u64>)> {
+        let query = [
+            ("category", Self::category(futures).to_string()),
+            ("symbol", compact_symbol(symbol)),
+        ];
+        let response = self
+            .rest
+            .public_get(
+                "bybit",
+                &self.rest.endpoints(false).bybit,
+                "/v5/market/tickers",
+                &query,
+            )
+            .await?;
+        let response = ExchangeRestClient::check_bybit_response(response)?;
+        let entry = response["result"]["list"]
+            .get(0)
+            .cloned()
+            .ok_or_else(|| ArbitrageError::not_found(format!("No Bybit ticker for {}", symbol)))?;
+        Ok((entry, json_u64(&response, "time")))
+    }
+
+    /// Map SETTLEMENT rows of `/v5/account/transaction-log`. Bybit reports `funding` as a
+    /// cost, so a positive value was paid and is negated here.
+    pub fn parse_funding_payments(symbol: &str, result: &Value) -> Vec<FundingPayment> {
+        let mut payments: Vec<FundingPayment> = result["list"]
+            .as_array()
+            .map(|records| {
+                records
+                    .iter()
+                    .filter_map(|record| {
+                        Some(FundingPayment {
+                            exchange: ExchangeIdEnum::Bybit,
+                            symbol: symbol.to_string(),
+                            amount: -json_f64(record, "funding")?,
+                            asset: json_string(record, "currency").unwrap_or_else(|| "USDT".into()),
+                            timestamp: json_u64(record, "transactionTime")?,
+                            payment_id: json_string(record, "id")?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        payments.sort_by_key(|payment| payment.timestamp);
+        payments
+    }
+
+    /// Map `/v5/user/query-api`. A read-only key has no trade permissions whatever it lists.
+    pub fn parse_api_key_info(result: &Value) -> ApiKeyPermissions {
+        let read_only = result["readOnly"].as_i64() == Some(1);
+        let granted = |group: &str, permission: Option<&str>| {
+            result["permissions"][group]
+                .as_array()
+                .is_some_and(|list| match permission {
+                    Some(permission) => list.iter().any(|p| p == permission),
+                    None => !list.is_empty(),
+                })
+        };
+        let ips: Vec<&str> = result["ips"]
+            .as_array()
+            .map(|ips| ips.iter().filter_map(|ip| ip.as_str()).collect())
+            .unwrap_or_default();
+
+        ApiKeyPermissions {
+            can_read: true,
+            can_trade_spot: !read_only && granted("Spot", Some("SpotTrade")),
+            can_trade_futures: !read_only
+                && (granted("ContractTrade", Some("Order"))
+                    || granted("Derivatives", Some("DerivativesTrade"))),
+            can_withdraw: granted("Wallet", Some("Withdraw")),
+            ip_restricted: Some(!ips.is_empty() && ips != ["*"]),
+            rate_limit: None,
+        }
+    }
+
+    /// Linear list endpoints require either a symbol or a settle coin
+    fn list_params(category: &str, symbol: Option<&str>) -> Value {
+        match symbol {
+            Some(symbol) => json!({ "category": category, "symbol": compact_symbol(symbol) }),
+            None if category == "linear" => json!({ "category": category, "settleCoin": "USDT" }),
+            None => json!({ "category": category }),
+        }
+    }
+
+    /// Fetch the current state of a Bybit order; create/cancel responses only echo the id
+    async fn fetch_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        category: &str,
+        symbol: &str,
+        order_id: &str,
+    ) -> ArbitrageResult<Option<Order>> {
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/order/realtime",
+                json!({ "category": category, "symbol": symbol, "orderId": order_id }),
+                credentials,
+            )
+            .await?;
+
+        match response["result"]["list"]
+            .as_array()
+            .and_then(|l| l.first())
+        {
+            Some(record) => Ok(Some(Self::parse_order(record)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ExchangeAdapter for BybitAdapter {
+    fn exchange_id(&self) -> ExchangeIdEnum {
+        ExchangeIdEnum::Bybit
+    }
+
+    fn market_symbol(&self, symbol: &str, _futures: bool) -> ArbitrageResult<String> {
+        Ok(compact_symbol(symbol))
+    }
+
+    fn base_fee_rates(&self, futures: bool) -> (f64, f64) {
+        Self::fee_rates(futures)
+    }
+
+    async fn get_ticker(&self, symbol: &str, futures: bool) -> ArbitrageResult<Ticker> {
+        let (entry, timestamp) = self.ticker_entry(symbol, futures).await?;
+        Ok(Self::parse_ticker(symbol, &entry, timestamp))
+    }
+
+    async fn get_orderbook(
+        &self,
+        symbol: &str,
+        limit: u32,
+        futures: bool,
+    ) -> ArbitrageResult<OrderBook> {
+        let query = [
+            ("category", Self::category(futures).to_string()),
+            ("symbol", compact_symbol(symbol)),
+            ("limit", Self::depth_limit(limit, futures).to_string()),
+        ];
+        let response = self
+            .rest
+            .public_get(
+                "bybit",
+                &self.rest.endpoints(false).bybit,
+                "/v5/market/orderbook",
+                &query,
+            )
+            .await?;
+        let result = &ExchangeRestClient::check_bybit_response(response)?["result"];
+
+        Ok(normalize_orderbook(
+            symbol,
+            parse_levels(&result["b"]),
+            parse_levels(&result["a"]),
+            limit,
+            json_u64(result, "ts"),
+            json_u64(result, "u"),
+        ))
+    }
+
+    async fn get_markets(&self, futures: bool) -> ArbitrageResult<Vec<Market>> {
+        // Bybit pages instruments with a cursor; cap pages so a bad cursor cannot loop forever
+        const MAX_PAGES: usize = 10;
+        let mut markets = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let mut query = vec![
+                ("category", Self::category(futures).to_string()),
+                ("limit", "1000".to_string()),
+            ];
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor.clone()));
+            }
+            let response = self
+                .rest
+                .public_get(
+                    "bybit",
+                    &self.rest.endpoints(false).bybit,
+                    "/v5/market/instruments-info",
+                    &query,
+                )
+                .await?;
+            let result = &ExchangeRestClient::check_bybit_response(response)?["result"];
+            markets.extend(Self::parse_markets(result, futures));
+
+            cursor = json_string(result, "nextPageCursor").filter(|c| !c.is_empty());
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(markets)
+    }
+
+    async fn get_funding_rate(&self, symbol: &str) -> ArbitrageResult<FundingRateInfo> {
+        let (entry, _) = self.ticker_entry(symbol, true).await?;
+        Ok(Self::parse_funding_rate(symbol, &entry))
+    }
+
+    async fn get_balance(&self, credentials: &ExchangeCredentials) -> ArbitrageResult<Value> {
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        self.rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/account/wallet-balance",
+                json!({ "accountType": "UNIFIED" }),
+                credentials,
+            )
+            .await
+    }
+
+    async fn place_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        request
+            .validate()
+            .map_err(ArbitrageError::validation_error)?;
+
+        let futures = is_futures_market(ExchangeRestClient::market_type(
+            credentials,
+            request.market_type.as_deref(),
+        ));
+        let body = Self::order_body(request, futures)?;
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::POST,
+                &base_url,
+                "/v5/order/create",
+                body,
+                credentials,
+            )
+            .await?;
+        let order_id = json_string(&response["result"], "orderId")
+            .ok_or_else(|| ArbitrageError::parse_error("Bybit create response missing orderId"))?;
+
+        let category = Self::category(futures);
+        let symbol = compact_symbol(&request.symbol);
+        if let Some(order) = self
+            .fetch_order(credentials, category, &symbol, &order_id)
+            .await?
+        {
+            return Ok(order);
+        }
+
+        // Order already left the realtime book; report what the exchange acknowledged
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        Ok(Order {
+            id: order_id,
+            client_order_id: json_string(&response["result"], "orderLinkId"),
+            datetime: datetime_from_millis(now),
+            timestamp: now,
+            last_trade_timestamp: None,
+            status: OrderStatus::PendingNew.as_order_status_str().to_string(),
+            symbol,
+            type_: format!("{:?}", request.order_type).to_lowercase(),
+            time_in_force: request.time_in_force.map(|t| t.as_str().to_string()),
+            side: request.side.to_lowercase(),
+            amount: request.amount,
+            price: request.price,
+            average: None,
+            filled: 0.0,
+            remaining: request.amount,
+            cost: 0.0,
+            trades: vec![],
+            fee: None,
+            info: response,
+        })
+    }
+
+    async fn cancel_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let category = Self::category(futures);
+        let symbol = compact_symbol(symbol);
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::POST,
+                &base_url,
+                "/v5/order/cancel",
+                json!({ "category": category, "symbol": symbol, "orderId": order_id }),
+                credentials,
+            )
+            .await?;
+
+        if let Some(order) = self
+            .fetch_order(credentials, category, &symbol, order_id)
+            .await?
+        {
+            return Ok(order);
+        }
+
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        Ok(Order {
+            id: order_id.to_string(),
+            client_order_id: json_string(&response["result"], "orderLinkId"),
+            datetime: datetime_from_millis(now),
+            timestamp: now,
+            last_trade_timestamp: None,
+            status: OrderStatus::Canceled.as_order_status_str().to_string(),
+            symbol,
+            type_: "limit".to_string(),
+            time_in_force: None,
+            side: String::new(),
+            amount: 0.0,
+            price: None,
+            average: None,
+            filled: 0.0,
+            remaining: 0.0,
+            cost: 0.0,
+            trades: vec![],
+            fee: None,
+            info: response,
+        })
+    }
+
+    async fn get_open_positions(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/position/list",
+                Self::list_params("linear", symbol),
+                credentials,
+            )
+            .await?;
+        Ok(response["result"]["list"]
+            .as_array()
+            .map(|entries| entries.iter().filter_map(Self::parse_position).collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        self.fetch_order(
+            credentials,
+            Self::category(futures),
+            &compact_symbol(symbol),
+            order_id,
+        )
+        .await?
+        .ok_or_else(|| ArbitrageError::not_found(format!("bybit order {} not found", order_id)))
+    }
+
+    async fn get_open_orders(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        let category = Self::category(is_futures_market(ExchangeRestClient::market_type(
+            credentials,
+            market_type,
+        )));
+        let mut params = Self::list_params(category, symbol);
+        params["openOnly"] = json!(0);
+        params["limit"] = json!(50);
+
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/order/realtime",
+                params,
+                credentials,
+            )
+            .await?;
+        response["result"]["list"]
+            .as_array()
+            .map(|orders| orders.iter().map(Self::parse_order).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    async fn get_max_leverage(
+        &self,
+        _credentials: &ExchangeCredentials,
+        symbol: &str,
+    ) -> ArbitrageResult<Option<f64>> {
+        let response = self
+            .rest
+            .public_get(
+                "bybit",
+                &self.rest.endpoints(false).bybit,
+                "/v5/market/instruments-info",
+                &[
+                    ("category", "linear".to_string()),
+                    ("symbol", compact_symbol(symbol)),
+                ],
+            )
+            .await?;
+        let result = &ExchangeRestClient::check_bybit_response(response)?["result"];
+        Ok(Self::parse_markets(result, true)
+            .first()
+            .and_then(|market| market.limits.leverage.as_ref())
+            .and_then(|leverage| leverage.max))
+    }
+
+    async fn set_leverage(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
+    ) -> ArbitrageResult<()> {
+        // 110043: "leverage not modified"
+        const LEVERAGE_UNCHANGED: i64 = 110043;
+        let max_leverage = self.get_max_leverage(credentials, symbol).await?;
+        validate_leverage(self.exchange_id(), symbol, leverage, max_leverage)?;
+
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let result = self
+            .rest
+            .bybit_signed(
+                Method::POST,
+                &base_url,
+                "/v5/position/set-leverage",
+                json!({
+                    "category": "linear",
+                    "symbol": compact_symbol(symbol),
+                    "buyLeverage": leverage.to_string(),
+                    "sellLeverage": leverage.to_string(),
+                }),
+                credentials,
+            )
+            .await;
+        ignore_unchanged(result, &[LEVERAGE_UNCHANGED])
+    }
+
+    async fn set_margin_mode(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        // 110026: "Cross/isolated margin mode is not modified"
+        const MARGIN_MODE_UNCHANGED: i64 = 110026;
+        let symbol = compact_symbol(symbol);
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+
+        // switch-isolated requires leverage alongside the mode; keep the current setting
+        let positions = self
+            .rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/position/list",
+                Self::list_params("linear", Some(&symbol)),
+                credentials,
+            )
+            .await?;
+        let leverage = json_string(&positions["result"]["list"][0], "leverage")
+            .filter(|leverage| !leverage.is_empty())
+            .ok_or_else(|| {
+                ArbitrageError::exchange_error(
+                    "bybit",
+                    format!("Bybit did not report the current leverage for {}", symbol),
+                )
+            })?;
+
+        let result = self
+            .rest
+            .bybit_signed(
+                Method::POST,
+                &base_url,
+                "/v5/position/switch-isolated",
+                json!({
+                    "category": "linear",
+                    "symbol": symbol,
+                    "tradeMode": if mode == MarginMode::Isolated { 1 } else { 0 },
+                    "buyLeverage": leverage,
+                    "sellLeverage": leverage,
+                }),
+                credentials,
+            )
+            .await;
+        ignore_unchanged(result, &[MARGIN_MODE_UNCHANGED])
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        // A negative margin withdraws; positionIdx 0 is the one-way position
+        let sign = if amount < 0.0 { "-" } else { "" };
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        self.rest
+            .bybit_signed(
+                Method::POST,
+                &base_url,
+                "/v5/position/add-margin",
+                json!({
+                    "category": "linear",
+                    "symbol": compact_symbol(symbol),
+                    "margin": format!("{}{}", sign, format_decimal(amount.abs())),
+                    "positionIdx": 0,
+                }),
+                credentials,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_trading_fees(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        futures: bool,
+    ) -> ArbitrageResult<TradingFeeRates> {
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/account/fee-rate",
+                Self::list_params(Self::category(futures), Some(symbol)),
+                credentials,
+            )
+            .await?;
+        let entry = &response["result"]["list"][0];
+        account_fee_rates(
+            self.exchange_id(),
+            symbol,
+            json_f64(entry, "makerFeeRate"),
+            json_f64(entry, "takerFeeRate"),
+        )
+    }
+
+    async fn get_funding_payments(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let mut params = json!({
+            "accountType": "UNIFIED",
+            "category": "linear",
+            "symbol": compact_symbol(symbol),
+            "type": "SETTLEMENT",
+            "limit": 50,
+        });
+        if let Some(since) = since {
+            params["startTime"] = json!(since);
+        }
+        let response = self
+            .rest
+            .bybit_signed(
+                Method::GET,
+                &base_url,
+                "/v5/account/transaction-log",
+                params,
+                credentials,
+            )
+            .await?;
+        Ok(Self::parse_funding_payments(symbol, &response["result"]))
+    }
+
+    async fn probe_api_key(
+        &self,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        let base_url = self.rest.endpoints(credentials.is_testnet).bybit.clone();
+        let (response, rate_limit) = self
+            .rest
+            .bybit_signed_with_limits(
+                Method::GET,
+                &base_url,
+                "/v5/user/query-api",
+                json!({}),
+                credentials,
+            )
+            .await?;
+        Ok(ApiKeyPermissions {
+            rate_limit,
+            ..Self::parse_api_key_info(&response["result"])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::adapters::test_support::{
+        assert_best_first, credentials, fixture, rest_for,
+    };
+    use crate::services::core::trading::signing::hmac_sha256_hex;
+    use crate::test_utils::mock_http_server::{MockHttpServer, MockRoute};
+    use crate::utils::error::ErrorKind;
+
+    const BYBIT_CREATE: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/order_create.json");
+    const BYBIT_CANCEL: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/order_cancel.json");
+    const BYBIT_REALTIME_NEW: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/order_realtime_new.json");
+    const BYBIT_REALTIME_CANCELLED: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/order_realtime_cancelled.json");
+    const BYBIT_INSUFFICIENT_BALANCE: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/error_insufficient_balance.json");
+    const BYBIT_ORDERBOOK: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/orderbook.json");
+    const BYBIT_INSTRUMENTS_PAGE1: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/instruments_linear_page1.json");
+    const BYBIT_INSTRUMENTS_PAGE2: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/instruments_linear_page2.json");
+    const BYBIT_TICKERS_LINEAR: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/tickers_linear.json");
+    const BYBIT_POSITION_LIST: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/position_list.json");
+    const BYBIT_SET_LEVERAGE: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/set_leverage.json");
+    const BYBIT_LEVERAGE_NOT_MODIFIED: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/error_leverage_not_modified.json");
+    const BYBIT_FEE_RATE: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/fee_rate.json");
+    const BYBIT_QUERY_API: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/query_api.json");
+    const BYBIT_TRANSACTION_LOG_SETTLEMENT: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/transaction_log_settlement.json");
+    const BYBIT_ADD_MARGIN: &str =
+        include_str!("../../../../test_utils/fixtures/bybit/add_margin.json");
+
+    #[tokio::test]
+    async fn test_bybit_limit_order_create_and_fetch() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new("POST", "/v5/order/create", 200, BYBIT_CREATE),
+            MockRoute::new("GET", "/v5/order/realtime", 200, BYBIT_REALTIME_NEW),
+        ]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+        let mut request = OrderRequest::limit("ETHUSDT", "buy", 0.1, 1600.0);
+        request.time_in_force = Some(TimeInForce::IOC);
+
+        let order = adapter.place_order(&creds, &request).await.unwrap();
+
+        assert_eq!(order.id, "fd4300ae-7847-404e-b947-b46980a4d140");
+        assert_eq!(order.status, "open");
+        assert_eq!(order.side, "buy");
+        assert_eq!(order.remaining, 0.1);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let create = &requests[0];
+        let body: Value = serde_json::from_str(&create.body).unwrap();
+        assert_eq!(body["category"], "linear");
+        assert_eq!(body["orderType"], "Limit");
+        assert_eq!(body["timeInForce"], "IOC");
+        assert_eq!(body["price"], "1600");
+
+        let timestamp = create.header("x-bapi-timestamp").unwrap();
+        let expected = hmac_sha256_hex(
+            "test-secret",
+            &format!("{}test-api-key5000{}", timestamp, create.body),
+        )
+        .unwrap();
+        assert_eq!(create.header("x-bapi-sign"), Some(expected.as_str()));
+        assert!(requests[1].query.contains("orderId="));
+    }
+
+    #[tokio::test]
+    async fn test_bybit_cancel_order() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new("POST", "/v5/order/cancel", 200, BYBIT_CANCEL),
+            MockRoute::new("GET", "/v5/order/realtime", 200, BYBIT_REALTIME_CANCELLED),
+        ]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        let order = adapter
+            .cancel_order(
+                &creds,
+                "ETHUSDT",
+                "fd4300ae-7847-404e-b947-b46980a4d140",
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(order.status, "canceled");
+    }
+
+    #[tokio::test]
+    async fn test_bybit_ret_code_maps_to_exchange_error() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "POST",
+            "/v5/order/create",
+            200,
+            BYBIT_INSUFFICIENT_BALANCE,
+        )]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "spot");
+
+        let error = adapter
+            .place_order(&creds, &OrderRequest::market("BTCUSDT", "buy", 1.0))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::ExchangeError);
+        assert_eq!(error.details.unwrap()["exchange_code"], json!(110007));
+    }
+
+    #[test]
+    fn test_order_type_mapping_rejects_unsupported_combinations() {
+        let mut trailing = OrderRequest::market("BTCUSDT", "sell", 1.0);
+        trailing.order_type = OrderType::TrailingStop;
+        trailing.trailing_percent = Some(1.0);
+        assert!(BybitAdapter::order_body(&trailing, true).is_err());
+
+        let mut stop = OrderRequest::market("BTCUSDT", "sell", 1.0);
+        stop.order_type = OrderType::StopLoss;
+        stop.stop_price = Some(60000.0);
+        let body = BybitAdapter::order_body(&stop, true).unwrap();
+        assert_eq!(body["orderType"], "Market");
+        assert_eq!(body["triggerDirection"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_bybit_orderbook_uses_category() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/v5/market/orderbook",
+            200,
+            BYBIT_ORDERBOOK,
+        )]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+
+        let book = adapter.get_orderbook("BTCUSDT", 25, true).await.unwrap();
+
+        assert_eq!(book.bids.len(), 3);
+        assert_eq!(book.best_bid(), Some(65485.47));
+        assert_eq!(book.best_ask(), Some(65485.48));
+        assert_eq!(book.timestamp, 1716863719031);
+        assert_best_first(&book);
+
+        let query = &server.requests()[0].query;
+        assert!(query.contains("category=linear"));
+        assert!(query.contains("limit=25"));
+    }
+
+    #[tokio::test]
+    async fn test_bybit_markets_follow_cursor() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/v5/market/instruments-info",
+                200,
+                BYBIT_INSTRUMENTS_PAGE1,
+            ),
+            MockRoute::new(
+                "GET",
+                "/v5/market/instruments-info",
+                200,
+                BYBIT_INSTRUMENTS_PAGE2,
+            ),
+        ]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+
+        let markets = adapter.get_markets(true).await.unwrap();
+
+        // Dated LinearFutures keep their own symbol and delivery time
+        let symbols: Vec<&str> = markets.iter().map(|m| m.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BTCUSDT", "ETHUSDT", "BTC-28MAR25"]);
+        assert_eq!(markets[0].expiry, None);
+        assert_eq!(markets[2].type_, "future");
+        assert_eq!(markets[2].expiry, Some(1_743_148_800_000));
+        assert_eq!(markets[1].lot_size(), Some(0.01));
+        assert_eq!(
+            markets[0].limits.leverage.as_ref().unwrap().max,
+            Some(100.0)
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].query.contains("category=linear"));
+        assert!(requests[1].query.contains("cursor="));
+    }
+
+    #[tokio::test]
+    async fn test_bybit_funding_rate_from_linear_ticker() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/v5/market/tickers",
+            200,
+            BYBIT_TICKERS_LINEAR,
+        )]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+
+        let info = adapter.get_funding_rate("BTC/USDT").await.unwrap();
+
+        assert_eq!(info.exchange, ExchangeIdEnum::Bybit);
+        assert_eq!(info.funding_rate, -0.00012);
+        assert_eq!(info.funding_interval_hours, 4);
+        assert_eq!(info.next_funding_time, Some(1716883200000));
+        assert_eq!(info.mark_price, Some(65011.0));
+
+        let query = &server.requests()[0].query;
+        assert!(query.contains("category=linear"));
+        assert!(query.contains("symbol=BTCUSDT"));
+    }
+
+    #[test]
+    fn test_bybit_ticker_reports_percentage_change() {
+        let response = fixture(BYBIT_TICKERS_LINEAR);
+        let ticker = BybitAdapter::parse_ticker("BTCUSDT", &response["result"]["list"][0], Some(1));
+
+        assert_eq!(ticker.bid, Some(65010.4));
+        assert_eq!(ticker.ask, Some(65010.5));
+        assert!((ticker.percentage.unwrap() - 1.5789).abs() < 1e-9);
+        assert_eq!(ticker.quote_volume, Some(2100000000.5));
+    }
+
+    #[tokio::test]
+    async fn test_bybit_open_positions_use_settle_coin() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/v5/position/list",
+            200,
+            BYBIT_POSITION_LIST,
+        )]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        let positions = adapter.get_open_positions(&creds, None).await.unwrap();
+
+        // The flat ETHUSDT entry is dropped
+        assert_eq!(positions.len(), 1);
+        let btc = &positions[0];
+        assert_eq!((btc.side.as_str(), btc.amount), ("short", 0.15));
+        assert_eq!(btc.entry_price, Some(64100.0));
+        assert_eq!(btc.unrealized_pnl, Some(-135.0));
+        assert_eq!(btc.isolated, Some(false));
+
+        let query = &server.requests()[0].query;
+        assert!(query.contains("category=linear"));
+        assert!(query.contains("settleCoin=USDT"));
+    }
+
+    #[tokio::test]
+    async fn test_bybit_set_leverage_validates_and_tolerates_no_change() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/v5/market/instruments-info",
+                200,
+                BYBIT_INSTRUMENTS_PAGE2,
+            ),
+            MockRoute::new("POST", "/v5/position/set-leverage", 200, BYBIT_SET_LEVERAGE),
+            MockRoute::new(
+                "POST",
+                "/v5/position/set-leverage",
+                200,
+                BYBIT_LEVERAGE_NOT_MODIFIED,
+            ),
+        ]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        adapter.set_leverage(&creds, "ETHUSDT", 25).await.unwrap();
+        adapter.set_leverage(&creds, "ETHUSDT", 25).await.unwrap();
+        let error = adapter
+            .set_leverage(&creds, "ETHUSDT", 101)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::ExchangeError);
+        assert_eq!(error.error_code.as_deref(), Some("INVALID_LEVERAGE"));
+        let posts: Vec<_> = server
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == "POST")
+            .collect();
+        assert_eq!(posts.len(), 2);
+        let body: Value = serde_json::from_str(&posts[0].body).unwrap();
+        assert_eq!(body["buyLeverage"], "25");
+        assert_eq!(body["sellLeverage"], "25");
+        assert_eq!(body["category"], "linear");
+    }
+
+    #[tokio::test]
+    async fn test_bybit_margin_mode_keeps_current_leverage() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new("GET", "/v5/position/list", 200, BYBIT_POSITION_LIST),
+            MockRoute::new(
+                "POST",
+                "/v5/position/switch-isolated",
+                200,
+                BYBIT_SET_LEVERAGE,
+            ),
+        ]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        adapter
+            .set_margin_mode(&creds, "BTCUSDT", MarginMode::Isolated)
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body["tradeMode"], 1);
+        assert_eq!(body["buyLeverage"], "5");
+        assert_eq!(body["symbol"], "BTCUSDT");
+    }
+
+    #[tokio::test]
+    async fn test_bybit_account_fee_rate() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/v5/account/fee-rate",
+            200,
+            BYBIT_FEE_RATE,
+        )]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        let fees = adapter
+            .get_trading_fees(&creds, "BTCUSDT", true)
+            .await
+            .unwrap();
+
+        assert_eq!((fees.maker, fees.taker), (0.00016, 0.00044));
+        let query = &server.requests()[0].query;
+        assert!(query.contains("category=linear"));
+        assert!(query.contains("symbol=BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_bybit_probe_flags_withdrawal_and_rate_limit() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/v5/user/query-api",
+            200,
+            BYBIT_QUERY_API,
+        )
+        .with_header("x-bapi-limit", "10")
+        .with_header("x-bapi-limit-status", "9")
+        .with_header("x-bapi-limit-reset-timestamp", "1697525991000")]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        let permissions = adapter.probe_api_key(&creds).await.unwrap();
+
+        assert!(permissions.can_trade_spot && permissions.can_trade_futures);
+        assert!(permissions.can_withdraw);
+        assert_eq!(permissions.ip_restricted, Some(false));
+        assert!(permissions
+            .ensure_no_withdrawal("bybit")
+            .is_err_and(|e| e.error_code.as_deref() == Some("WITHDRAWAL_PERMISSION")));
+        let rate_limit = permissions.rate_limit.unwrap();
+        assert_eq!(rate_limit.requests_per_minute, 600);
+        assert_eq!(rate_limit.requests_remaining, 9);
+        assert_eq!(rate_limit.reset_time, 1697525991000);
+    }
+
+    #[test]
+    fn test_parse_funding_payments_negates_funding_cost_and_sorts() {
+        let data = fixture(BYBIT_TRANSACTION_LOG_SETTLEMENT);
+        let payments = BybitAdapter::parse_funding_payments("BTC/USDT", &data["result"]);
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].timestamp, 1727769600000);
+        assert_eq!(payments[0].amount, -0.3152);
+        assert_eq!(payments[1].amount, 0.6321);
+        assert_eq!(payments[1].payment_id, "592324_BTCUSDT_161611");
+    }
+
+    #[tokio::test]
+    async fn test_bybit_isolated_margin_withdrawal_is_negative() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "POST",
+            "/v5/position/add-margin",
+            200,
+            BYBIT_ADD_MARGIN,
+        )]);
+        let adapter = BybitAdapter::new(rest_for(&server));
+        let creds = credentials(ExchangeIdEnum::Bybit, "futures");
+
+        adapter
+            .adjust_isolated_margin(&creds, "BTCUSDT", -40.0)
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(body["margin"], "-40");
+        assert_eq!(body["positionIdx"], 0);
+        assert_eq!(body["category"], "linear");
+    }
+}
@@ -0,0 +1,605 @@
+// src/services/core/trading/adapters/mod.rs
+
+//! Per-exchange adapters. Each adapter owns URL building, symbol mapping and
+//! response parsing for one exchange on top of the shared `ExchangeRestClient`
+//! transport. `ExchangeService` dispatches through `ExchangeAdapterRegistry`, so
+//! supporting another exchange means registering a new adapter.
+
+mod binance;
+mod bitget;
+mod bybit;
+mod okx;
+
+pub use binance::BinanceAdapter;
+pub use bitget::BitgetAdapter;
+pub use bybit::BybitAdapter;
+pub use okx::OkxAdapter;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::services::core::trading::exchange_rest::{
+    compact_symbol, datetime_from_millis, ExchangeRestClient,
+};
+use crate::services::core::user::user_exchange_api::ApiKeyPermissions;
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, FundingRateInfo, MarginMode, Market,
+    MarketLimits, MarketPrecision, MinMax, Order, OrderBook, OrderRequest, OrderStatus, Position,
+    Ticker, TradingFeeRates,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+/// Funding interval assumed when an exchange does not report one
+pub const DEFAULT_FUNDING_INTERVAL_HOURS: u32 = 8;
+
+/// Exchange-specific REST integration. `futures` selects the linear perpetual market
+/// instead of spot wherever an exchange serves both from different endpoints.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ExchangeAdapter: Send + Sync {
+    fn exchange_id(&self) -> ExchangeIdEnum;
+
+    /// Exchange-native instrument id for a unified ("BTC/USDT") or compact ("BTCUSDT") symbol
+    fn market_symbol(&self, symbol: &str, futures: bool) -> ArbitrageResult<String>;
+
+    /// Base-tier (maker, taker) fee rates from the public fee schedule
+    fn base_fee_rates(&self, futures: bool) -> (f64, f64);
+
+    async fn get_ticker(&self, symbol: &str, futures: bool) -> ArbitrageResult<Ticker>;
+
+    /// Order book with bids and asks best-first, at most `limit` levels per side
+    async fn get_orderbook(
+        &self,
+        symbol: &str,
+        limit: u32,
+        futures: bool,
+    ) -> ArbitrageResult<OrderBook>;
+
+    /// Tradable markets with precision and limits filled in
+    async fn get_markets(&self, futures: bool) -> ArbitrageResult<Vec<Market>>;
+
+    /// Current funding rate of the linear perpetual for `symbol`
+    async fn get_funding_rate(&self, symbol: &str) -> ArbitrageResult<FundingRateInfo>;
+
+    async fn get_balance(&self, credentials: &ExchangeCredentials) -> ArbitrageResult<Value>;
+
+    /// Validates `request` before anything is sent
+    async fn place_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order>;
+
+    async fn cancel_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order>;
+
+    /// Current state of a single order, including how much of it has filled
+    async fn get_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order>;
+
+    /// Open linear-perpetual positions, optionally for one symbol. Sizes are in base units.
+    async fn get_open_positions(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>>;
+
+    /// Resting orders; `market_type` defaults to the credentials' exchange type
+    async fn get_open_orders(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>>;
+
+    /// Highest leverage the exchange allows for the `symbol` perpetual, if it reports one
+    async fn get_max_leverage(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+    ) -> ArbitrageResult<Option<f64>>;
+
+    /// Validates `leverage` against the symbol's maximum before anything is sent
+    async fn set_leverage(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
+    ) -> ArbitrageResult<()>;
+
+    /// Switch the `symbol` perpetual between isolated and cross margin. Already being in
+    /// `mode` is not an error.
+    async fn set_margin_mode(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()>;
+
+    /// Move `amount` of quote currency into (positive) or out of (negative) the isolated
+    /// margin of the `symbol` perpetual position
+    async fn adjust_isolated_margin(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()>;
+
+    /// Maker/taker rates the account actually pays on `symbol`, including VIP tiers and any
+    /// discount the exchange reports
+    async fn get_trading_fees(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        futures: bool,
+    ) -> ArbitrageResult<TradingFeeRates>;
+
+    /// Funding settled on the account's `symbol` perpetual since `since` (ms), oldest first
+    async fn get_funding_payments(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>>;
+
+    /// Ask the exchange what the key may do. Fails when the key is rejected outright.
+    async fn probe_api_key(
+        &self,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions>;
+}
+
+/// Adapters keyed by exchange id
+#[derive(Clone, Default)]
+pub struct ExchangeAdapterRegistry {
+    adapters: HashMap<ExchangeIdEnum, Arc<dyn ExchangeAdapter>>,
+}
+
+impl ExchangeAdapterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in adapters sharing one REST transport
+    pub fn with_defaults(rest: ExchangeRestClient) -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(BinanceAdapter::new(rest.clone())));
+        registry.register(Arc::new(BybitAdapter::new(rest.clone())));
+        registry.register(Arc::new(OkxAdapter::new(rest.clone())));
+        registry.register(Arc::new(BitgetAdapter::new(rest)));
+        registry
+    }
+
+    /// Add or replace the adapter for its exchange
+    pub fn register(&mut self, adapter: Arc<dyn ExchangeAdapter>) {
+        self.adapters.insert(adapter.exchange_id(), adapter);
+    }
+
+    pub fn get(&self, exchange: ExchangeIdEnum) -> ArbitrageResult<&dyn ExchangeAdapter> {
+        self.adapters
+            .get(&exchange)
+            .map(|adapter| adapter.as_ref())
+            .ok_or_else(|| {
+                ArbitrageError::not_implemented(format!(
+                    "No adapter registered for exchange: {}",
+                    exchange
+                ))
+            })
+    }
+
+    /// Look up an adapter by exchange id string ("binance", "okx", ...)
+    pub fn resolve(&self, exchange_id: &str) -> ArbitrageResult<&dyn ExchangeAdapter> {
+        let exchange = exchange_id
+            .parse::<ExchangeIdEnum>()
+            .map_err(ArbitrageError::validation_error)?;
+        self.get(exchange)
+    }
+
+    pub fn supported_exchanges(&self) -> Vec<ExchangeIdEnum> {
+        let mut exchanges: Vec<ExchangeIdEnum> = self.adapters.keys().copied().collect();
+        exchanges.sort();
+        exchanges
+    }
+}
+
+/// Error for operations an adapter does not support yet
+pub(crate) fn unsupported(exchange: ExchangeIdEnum, operation: &str) -> ArbitrageError {
+    ArbitrageError::not_implemented(format!(
+        "{} not implemented for exchange: {}",
+        operation, exchange
+    ))
+}
+
+/// Order as the exchange acknowledged it, for when the follow-up lookup has no record yet
+pub(crate) fn acknowledged_order(
+    id: String,
+    client_order_id: Option<String>,
+    symbol: String,
+    request: &OrderRequest,
+    info: Value,
+) -> Order {
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    Order {
+        id,
+        client_order_id,
+        datetime: datetime_from_millis(now),
+        timestamp: now,
+        last_trade_timestamp: None,
+        status: OrderStatus::PendingNew.as_order_status_str().to_string(),
+        symbol,
+        type_: format!("{:?}", request.order_type).to_lowercase(),
+        time_in_force: request.time_in_force.map(|t| t.as_str().to_string()),
+        side: request.side.to_lowercase(),
+        amount: request.amount,
+        price: request.price,
+        average: None,
+        filled: 0.0,
+        remaining: request.amount,
+        cost: 0.0,
+        trades: vec![],
+        fee: None,
+        info,
+    }
+}
+
+/// Parse `[[price, size, ...], ...]` levels, dropping malformed or empty ones
+pub(crate) fn parse_levels(levels: &Value) -> Vec<[f64; 2]> {
+    let parse = |value: &Value| match value {
+        Value::String(s) => s.parse::<f64>().ok(),
+        Value::Number(n) => n.as_f64(),
+        _ => None,
+    };
+    levels
+        .as_array()
+        .map(|levels| {
+            levels
+                .iter()
+                .filter_map(|level| {
+                    let price = parse(level.get(0)?)?;
+                    let size = parse(level.get(1)?)?;
+                    (price > 0.0 && size > 0.0).then_some([price, size])
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build an `OrderBook` with bids best (highest) first, asks best (lowest) first,
+/// each truncated to `limit` levels
+pub(crate) fn normalize_orderbook(
+    symbol: &str,
+    mut bids: Vec<[f64; 2]>,
+    mut asks: Vec<[f64; 2]>,
+    limit: u32,
+    timestamp: Option<u64>,
+    nonce: Option<u64>,
+) -> OrderBook {
+    bids.sort_by(|a, b| b[0].total_cmp(&a[0]));
+    asks.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    bids.truncate(limit as usize);
+    asks.truncate(limit as usize);
+
+    let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+    OrderBook {
+        symbol: symbol.to_string(),
+        bids,
+        asks,
+        timestamp,
+        datetime: datetime_from_millis(timestamp),
+        nonce,
+    }
+}
+
+/// Ticker with only the symbol and timestamp set; adapters fill in what the exchange reports
+pub(crate) fn empty_ticker(symbol: &str, timestamp: Option<u64>, info: Value) -> Ticker {
+    let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+    Ticker {
+        symbol: symbol.to_string(),
+        timestamp,
+        datetime: datetime_from_millis(timestamp),
+        high: None,
+        low: None,
+        bid: None,
+        bid_volume: None,
+        ask: None,
+        ask_volume: None,
+        vwap: None,
+        open: None,
+        close: None,
+        last: None,
+        previous_close: None,
+        change: None,
+        percentage: None,
+        average: None,
+        base_volume: None,
+        quote_volume: None,
+        volume: None,
+        info,
+    }
+}
+
+/// Funding snapshot with only the rate set; adapters fill in timing and prices
+pub(crate) fn new_funding_rate(
+    exchange: ExchangeIdEnum,
+    symbol: &str,
+    funding_rate: f64,
+    info: Value,
+) -> FundingRateInfo {
+    let now = chrono::Utc::now();
+    FundingRateInfo {
+        symbol: symbol.to_string(),
+        funding_rate,
+        timestamp: now.timestamp_millis() as u64,
+        datetime: now.to_rfc3339(),
+        info,
+        next_funding_time: None,
+        estimated_rate: Some(funding_rate),
+        estimated_settle_price: None,
+        exchange,
+        funding_interval_hours: DEFAULT_FUNDING_INTERVAL_HOURS,
+        mark_price: None,
+        index_price: None,
+        funding_countdown: None,
+    }
+}
+
+/// Turn a contract skeleton into a dated future delivering at `expiry` (ms). It keeps the
+/// exchange's symbol, since it shares base and quote with the perpetual.
+pub(crate) fn dated_future(mut market: Market, symbol: &str, expiry: u64) -> Market {
+    market.symbol = compact_symbol(symbol);
+    market.type_ = "future".to_string();
+    market.expiry = Some(expiry);
+    market
+}
+
+/// Skeleton market with no limits; loaders fill in whatever the exchange reports
+pub(crate) fn new_market(
+    base: &str,
+    quote: &str,
+    futures: bool,
+    (maker, taker): (f64, f64),
+    info: &Value,
+) -> Market {
+    Market {
+        symbol: format!("{}{}", base, quote).to_uppercase(),
+        base: base.to_uppercase(),
+        quote: quote.to_uppercase(),
+        active: true,
+        type_: if futures { "swap" } else { "spot" }.to_string(),
+        spot: !futures,
+        margin: false,
+        future: futures,
+        option: false,
+        contract: futures,
+        settle: futures.then(|| quote.to_uppercase()),
+        settle_id: futures.then(|| quote.to_uppercase()),
+        contract_size: futures.then_some(1.0),
+        expiry: None,
+        linear: futures.then_some(true),
+        inverse: futures.then_some(false),
+        taker,
+        maker,
+        percentage: true,
+        tier_based: true,
+        limits: MarketLimits {
+            amount: None,
+            price: None,
+            cost: None,
+            leverage: None,
+        },
+        precision: MarketPrecision {
+            amount: None,
+            price: None,
+            base: None,
+            quote: None,
+            tick_size: None,
+            lot_size: None,
+        },
+        info: info.clone(),
+    }
+}
+
+/// Position with only symbol, side ("long"/"short") and size set; adapters fill in the rest
+pub(crate) fn new_position(
+    symbol: &str,
+    side: &str,
+    amount: f64,
+    timestamp: Option<u64>,
+    info: &Value,
+) -> Position {
+    let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+    Position {
+        info: info.clone(),
+        id: None,
+        symbol: symbol.to_string(),
+        timestamp,
+        datetime: datetime_from_millis(timestamp),
+        isolated: None,
+        hedged: None,
+        side: side.to_string(),
+        amount,
+        contracts: None,
+        contract_size: None,
+        entry_price: None,
+        mark_price: None,
+        notional: None,
+        leverage: None,
+        collateral: None,
+        initial_margin: None,
+        initial_margin_percentage: None,
+        maintenance_margin: None,
+        maintenance_margin_percentage: None,
+        unrealized_pnl: None,
+        realized_pnl: None,
+        percentage: None,
+    }
+}
+
+/// Account fee rates from a commission endpoint; a parse error if either side is missing
+pub(crate) fn account_fee_rates(
+    exchange: ExchangeIdEnum,
+    symbol: &str,
+    maker: Option<f64>,
+    taker: Option<f64>,
+) -> ArbitrageResult<TradingFeeRates> {
+    match (maker, taker) {
+        (Some(maker), Some(taker)) => Ok(TradingFeeRates {
+            maker,
+            taker,
+            percentage: true,
+            tier_based: true,
+        }),
+        _ => Err(ArbitrageError::parse_error(format!(
+            "{} returned no fee rates for {}",
+            exchange.as_str(),
+            symbol
+        ))),
+    }
+}
+
+/// Reject leverage outside `1..=max_leverage` with an exchange error that names the limit
+pub(crate) fn validate_leverage(
+    exchange: ExchangeIdEnum,
+    symbol: &str,
+    leverage: u32,
+    max_leverage: Option<f64>,
+) -> ArbitrageResult<()> {
+    let message = match max_leverage {
+        _ if leverage == 0 => format!("Leverage must be at least 1x, got {}x", leverage),
+        Some(max) if f64::from(leverage) > max => format!(
+            "Leverage {}x exceeds the {}x maximum for {} on {}",
+            leverage,
+            max,
+            symbol,
+            exchange.as_str()
+        ),
+        _ => return Ok(()),
+    };
+
+    let mut error = ArbitrageError::exchange_error(exchange.as_str(), message)
+        .with_status(400)
+        .with_code("INVALID_LEVERAGE");
+    if let Some(details) = error.details.as_mut() {
+        details.insert("symbol".to_string(), json!(symbol));
+        details.insert("requested_leverage".to_string(), json!(leverage));
+        if let Some(max) = max_leverage {
+            details.insert("max_leverage".to_string(), json!(max));
+        }
+    }
+    Err(error)
+}
+
+/// Exchanges answer an unchanged leverage or margin mode with an error code; treat those as success
+pub(crate) fn ignore_unchanged(
+    result: ArbitrageResult<Value>,
+    unchanged_codes: &[i64],
+) -> ArbitrageResult<()> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(error) => {
+            let code = error
+                .details
+                .as_ref()
+                .and_then(|details| details.get("exchange_code"))
+                .and_then(|code| {
+                    code.as_i64()
+                        .or_else(|| code.as_str().and_then(|s| s.parse().ok()))
+                });
+            match code {
+                Some(code) if unchanged_codes.contains(&code) => Ok(()),
+                _ => Err(error),
+            }
+        }
+    }
+}
+
+/// `MinMax` from optional bounds, `None` when neither side is known
+pub(crate) fn min_max(min: Option<f64>, max: Option<f64>) -> Option<MinMax> {
+    (min.is_some() || max.is_some()).then_some(MinMax { min, max })
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use crate::services::core::trading::exchange_rest::{ExchangeEndpoints, ExchangeRestClient};
+    use crate::test_utils::mock_http_server::MockHttpServer;
+    use crate::types::{ExchangeCredentials, ExchangeIdEnum, OrderBook};
+
+    pub fn credentials(exchange: ExchangeIdEnum, exchange_type: &str) -> ExchangeCredentials {
+        let mut creds = ExchangeCredentials::new(
+            exchange,
+            "test-api-key".to_string(),
+            "test-secret".to_string(),
+            None,
+            false,
+        );
+        creds.exchange_type = exchange_type.to_string();
+        creds
+    }
+
+    pub fn rest_for(server: &MockHttpServer) -> ExchangeRestClient {
+        ExchangeRestClient::with_endpoints(ExchangeEndpoints::uniform(server.url()))
+    }
+
+    pub fn fixture(raw: &str) -> serde_json::Value {
+        serde_json::from_str(raw).unwrap()
+    }
+
+    pub fn assert_best_first(book: &OrderBook) {
+        assert!(book.bids.windows(2).all(|w| w[0][0] > w[1][0]));
+        assert!(book.asks.windows(2).all(|w| w[0][0] < w[1][0]));
+        assert!(book.best_bid().unwrap() < book.best_ask().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_resolves_default_adapters() {
+        let registry = ExchangeAdapterRegistry::with_defaults(ExchangeRestClient::new());
+
+        assert_eq!(
+            registry.supported_exchanges(),
+            vec![
+                ExchangeIdEnum::Binance,
+                ExchangeIdEnum::Bybit,
+                ExchangeIdEnum::OKX,
+                ExchangeIdEnum::Bitget
+            ]
+        );
+        assert_eq!(
+            registry.resolve("okx").unwrap().exchange_id(),
+            ExchangeIdEnum::OKX
+        );
+        assert!(registry.resolve("not-an-exchange").is_err());
+        assert!(registry.get(ExchangeIdEnum::Kucoin).is_err());
+    }
+
+    #[test]
+    fn test_normalize_orderbook_sorts_and_truncates() {
+        let book = normalize_orderbook(
+            "BTCUSDT",
+            vec![[99.0, 1.0], [100.0, 2.0], [98.0, 1.0]],
+            vec![[102.0, 1.0], [101.0, 1.0]],
+            2,
+            Some(1),
+            None,
+        );
+
+        assert_eq!(book.bids, vec![[100.0, 2.0], [99.0, 1.0]]);
+        assert_eq!(book.asks, vec![[101.0, 1.0], [102.0, 1.0]]);
+    }
+}
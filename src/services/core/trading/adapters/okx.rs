@@ -0,0 +1,1464 @@
+// src/services/core/trading/adapters/okx.rs
+
+//! OKX v5 adapter. Instruments use dashed ids: "BTC-USDT" spot, "BTC-USDT-SWAP" perpetual.
+
+use async_trait::async_trait;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use super::{
+    account_fee_rates, acknowledged_order, empty_ticker, min_max, new_funding_rate, new_market,
+    new_position, normalize_orderbook, parse_levels, validate_leverage, ExchangeAdapter,
+};
+use crate::services::core::trading::exchange_rest::{
+    datetime_from_millis, format_decimal, is_futures_market, json_f64, json_string, json_u64,
+    split_symbol, ExchangeRestClient,
+};
+use crate::services::core::user::user_exchange_api::ApiKeyPermissions;
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, FundingRateInfo, MarginMode, Market,
+    Order, OrderBook, OrderRequest, OrderStatus, OrderType, Position, Ticker, TimeInForce,
+    TradingFee, TradingFeeRates,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct OkxAdapter {
+    rest: ExchangeRestClient,
+}
+
+impl OkxAdapter {
+    pub fn new(rest: ExchangeRestClient) -> Self {
+        Self { rest }
+    }
+
+    fn fee_rates(futures: bool) -> (f64, f64) {
+        if futures {
+            (0.0002, 0.0005)
+        } else {
+            (0.0008, 0.001)
+        }
+    }
+
+    /// First entry of an OKX `{code, msg, data: [...]}` response
+    async fn public_entry(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        symbol: &str,
+    ) -> ArbitrageResult<Value> {
+        let response = self
+            .rest
+            .public_get("okx", &self.rest.endpoints(false).okx, path, query)
+            .await?;
+        ExchangeRestClient::check_coded_response("okx", &response, "0")?;
+        response["data"].get(0).cloned().ok_or_else(|| {
+            ArbitrageError::parse_error(format!("OKX returned no data for {}", symbol))
+        })
+    }
+
+    /// Parse OKX `/api/v5/public/instruments` (SPOT or SWAP). Swap sizes are in contracts
+    /// of `ctVal` base units.
+    pub fn parse_markets(data: &Value, futures: bool) -> Vec<Market> {
+        let Some(instruments) = data["data"].as_array() else {
+            return Vec::new();
+        };
+
+        instruments
+            .iter()
+            .filter(|i| i["state"] == "live")
+            .filter_map(|i| {
+                let (base, quote) = if futures {
+                    if i["ctType"] != "linear" {
+                        return None;
+                    }
+                    split_symbol(i["uly"].as_str().or_else(|| i["instFamily"].as_str())?)?
+                } else {
+                    (
+                        i["baseCcy"].as_str()?.to_string(),
+                        i["quoteCcy"].as_str()?.to_string(),
+                    )
+                };
+                let mut market = new_market(&base, &quote, futures, Self::fee_rates(futures), i);
+
+                market.precision.tick_size = json_f64(i, "tickSz");
+                market.precision.lot_size = json_f64(i, "lotSz");
+                market.limits.amount = min_max(json_f64(i, "minSz"), json_f64(i, "maxLmtSz"));
+                if futures {
+                    market.settle = json_string(i, "settleCcy");
+                    market.settle_id = market.settle.clone();
+                    market.contract_size = json_f64(i, "ctVal");
+                    market.limits.leverage = min_max(Some(1.0), json_f64(i, "lever"));
+                }
+                Some(market)
+            })
+            .collect()
+    }
+
+    /// Compact symbol for an instrument id: "BTC-USDT-SWAP" -> "BTCUSDT"
+    fn symbol_from_inst_id(inst_id: &str) -> String {
+        split_symbol(inst_id)
+            .map(|(base, quote)| format!("{}{}", base, quote))
+            .unwrap_or_else(|| inst_id.to_string())
+    }
+
+    /// Map an `/api/v5/trade/order` style record into `Order`. Swap sizes stay in contracts.
+    pub fn parse_order(data: &Value) -> ArbitrageResult<Order> {
+        let id = json_string(data, "ordId")
+            .ok_or_else(|| ArbitrageError::parse_error("OKX order record missing ordId"))?;
+        let status = OrderStatus::from_exchange_status(data["state"].as_str().unwrap_or("live"));
+        let amount = json_f64(data, "sz").unwrap_or(0.0);
+        let filled = json_f64(data, "accFillSz").unwrap_or(0.0);
+        let average = json_f64(data, "avgPx").filter(|p| *p > 0.0);
+        let timestamp = json_u64(data, "uTime")
+            .or_else(|| json_u64(data, "cTime"))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+        let (type_, time_in_force) = match data["ordType"].as_str().unwrap_or("limit") {
+            "market" => ("market", None),
+            "post_only" => ("limit", Some("PO")),
+            "fok" => ("limit", Some("FOK")),
+            "ioc" | "optimal_limit_ioc" => ("limit", Some("IOC")),
+            _ => ("limit", Some("GTC")),
+        };
+        // OKX reports fees as negative amounts (rebates are positive)
+        let fee = json_f64(data, "fee")
+            .filter(|f| *f < 0.0)
+            .map(|fee| TradingFee {
+                currency: data["feeCcy"].as_str().unwrap_or("USDT").to_string(),
+                cost: -fee,
+                rate: None,
+            });
+
+        Ok(Order {
+            id,
+            client_order_id: json_string(data, "clOrdId").filter(|id| !id.is_empty()),
+            datetime: datetime_from_millis(timestamp),
+            timestamp,
+            last_trade_timestamp: json_u64(data, "fillTime").filter(|t| *t > 0),
+            status: status.as_order_status_str().to_string(),
+            symbol: Self::symbol_from_inst_id(data["instId"].as_str().unwrap_or_default()),
+            type_: type_.to_string(),
+            time_in_force: time_in_force.map(|tif| tif.to_string()),
+            side: data["side"].as_str().unwrap_or_default().to_lowercase(),
+            amount,
+            price: json_f64(data, "px").filter(|p| *p > 0.0),
+            average,
+            filled,
+            remaining: (amount - filled).max(0.0),
+            cost: filled * average.unwrap_or(0.0),
+            trades: vec![],
+            fee,
+            info: data.clone(),
+        })
+    }
+
+    /// Map an `/api/v5/trade/order-algo` record into `Order`. "effective" means the trigger
+    /// fired and the order was sent to the book.
+    pub fn parse_algo_order(data: &Value) -> ArbitrageResult<Order> {
+        let id = json_string(data, "algoId")
+            .ok_or_else(|| ArbitrageError::parse_error("OKX algo order record missing algoId"))?;
+        let status = match data["state"].as_str().unwrap_or("live") {
+            "effective" => OrderStatus::Filled,
+            "order_failed" => OrderStatus::Rejected,
+            state => OrderStatus::from_exchange_status(state),
+        };
+        let stop_trigger = json_f64(data, "slTriggerPx").filter(|p| *p > 0.0);
+        let type_ = match (data["ordType"].as_str(), stop_trigger) {
+            (Some("move_order_stop"), _) => "trailingstop",
+            (_, Some(_)) => "stoploss",
+            _ => "takeprofit",
+        };
+        let amount = json_f64(data, "sz").unwrap_or(0.0);
+        let timestamp = json_u64(data, "uTime")
+            .or_else(|| json_u64(data, "cTime"))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+
+        Ok(Order {
+            id,
+            client_order_id: json_string(data, "algoClOrdId").filter(|id| !id.is_empty()),
+            datetime: datetime_from_millis(timestamp),
+            timestamp,
+            last_trade_timestamp: None,
+            status: status.as_order_status_str().to_string(),
+            symbol: Self::symbol_from_inst_id(data["instId"].as_str().unwrap_or_default()),
+            type_: type_.to_string(),
+            time_in_force: None,
+            side: data["side"].as_str().unwrap_or_default().to_lowercase(),
+            amount,
+            price: stop_trigger.or_else(|| json_f64(data, "tpTriggerPx").filter(|p| *p > 0.0)),
+            average: None,
+            filled: 0.0,
+            remaining: amount,
+            cost: 0.0,
+            trades: vec![],
+            fee: None,
+            info: data.clone(),
+        })
+    }
+
+    /// Stops, take-profits and trailing stops go through the algo order endpoints
+    fn is_algo(request: &OrderRequest) -> bool {
+        !matches!(request.order_type, OrderType::Market | OrderType::Limit)
+    }
+
+    /// Body for `/api/v5/trade/order`, or `/api/v5/trade/order-algo` for conditional and
+    /// trailing orders. `size` is in contracts for swaps and base units for spot.
+    pub fn order_body(request: &OrderRequest, inst_id: &str, futures: bool, size: f64) -> Value {
+        let mut body = json!({
+            "instId": inst_id,
+            "tdMode": if futures { "cross" } else { "cash" },
+            "side": request.side.to_lowercase(),
+            "sz": format_decimal(size),
+        });
+        // An order price of -1 executes at market once the trigger fires
+        let order_price = request
+            .price
+            .filter(|_| {
+                matches!(
+                    request.order_type,
+                    OrderType::StopLossLimit | OrderType::TakeProfitLimit
+                )
+            })
+            .map(format_decimal)
+            .unwrap_or_else(|| "-1".to_string());
+        match request.order_type {
+            OrderType::Market => body["ordType"] = json!("market"),
+            OrderType::Limit => {
+                body["ordType"] = json!(match request.time_in_force.unwrap_or_default() {
+                    TimeInForce::GTC => "limit",
+                    TimeInForce::PostOnly => "post_only",
+                    TimeInForce::IOC => "ioc",
+                    TimeInForce::FOK => "fok",
+                });
+                body["px"] = json!(request.price.map(format_decimal));
+            }
+            OrderType::StopLoss | OrderType::StopLossLimit => {
+                body["ordType"] = json!("conditional");
+                body["slTriggerPx"] = json!(request.stop_price.map(format_decimal));
+                body["slOrdPx"] = json!(order_price);
+            }
+            OrderType::TakeProfit | OrderType::TakeProfitLimit => {
+                body["ordType"] = json!("conditional");
+                body["tpTriggerPx"] = json!(request.stop_price.map(format_decimal));
+                body["tpOrdPx"] = json!(order_price);
+            }
+            OrderType::TrailingStop => {
+                body["ordType"] = json!("move_order_stop");
+                body["callbackRatio"] = json!(request
+                    .trailing_percent
+                    .map(|percent| format_decimal(percent / 100.0)));
+            }
+        }
+        // Spot market buys are otherwise sized in quote currency
+        if !futures && request.order_type != OrderType::Limit {
+            body["tgtCcy"] = json!("base_ccy");
+        }
+        if futures && request.reduce_only {
+            body["reduceOnly"] = json!(true);
+        }
+        if let Some(client_id) = &request.client_order_id {
+            let field = if Self::is_algo(request) {
+                "algoClOrdId"
+            } else {
+                "clOrdId"
+            };
+            body[field] = json!(client_id);
+        }
+        body
+    }
+
+    /// Swap order size in contracts, rounded down to the instrument's lot size
+    pub fn contracts_for(amount: f64, market: &Market) -> ArbitrageResult<f64> {
+        let contract_size = market.contract_size.filter(|c| *c > 0.0).unwrap_or(1.0);
+        let lot = market
+            .precision
+            .lot_size
+            .filter(|l| *l > 0.0)
+            .unwrap_or(1.0);
+        let decimals = (-lot.log10()).ceil().max(0.0) as i32;
+        let scale = 10f64.powi(decimals);
+        let lots = (amount / contract_size / lot + 1e-9).floor();
+        let contracts = (lots * lot * scale).round() / scale;
+        if contracts <= 0.0 {
+            return Err(ArbitrageError::validation_error(format!(
+                "{} {} is below one OKX lot of {} contracts",
+                amount, market.symbol, lot
+            )));
+        }
+        Ok(contracts)
+    }
+
+    /// Convert a swap order's contract sizes into base units
+    fn in_base_units(mut order: Order, contract_size: Option<f64>) -> Order {
+        if let Some(contract_size) = contract_size {
+            order.amount *= contract_size;
+            order.filled *= contract_size;
+            order.remaining *= contract_size;
+            order.cost = order.filled * order.average.unwrap_or(0.0);
+        }
+        order
+    }
+
+    /// Linear swap instrument for `symbol`
+    async fn swap_market(&self, symbol: &str) -> ArbitrageResult<Market> {
+        let data = self
+            .rest
+            .public_get(
+                "okx",
+                &self.rest.endpoints(false).okx,
+                "/api/v5/public/instruments",
+                &[
+                    ("instType", "SWAP".to_string()),
+                    ("instId", self.market_symbol(symbol, true)?),
+                ],
+            )
+            .await?;
+        ExchangeRestClient::check_coded_response("okx", &data, "0")?;
+        Self::parse_markets(&data, true)
+            .into_iter()
+            .next()
+            .ok_or_else(|| ArbitrageError::not_found(format!("No OKX linear swap for {}", symbol)))
+    }
+
+    /// Look up a regular (`algo == false`) or algo order; `None` when OKX has no record
+    async fn fetch_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        inst_id: &str,
+        order_id: &str,
+        algo: bool,
+    ) -> ArbitrageResult<Option<Order>> {
+        let (path, params) = if algo {
+            ("/api/v5/trade/order-algo", json!({ "algoId": order_id }))
+        } else {
+            (
+                "/api/v5/trade/order",
+                json!({ "instId": inst_id, "ordId": order_id }),
+            )
+        };
+        let response = self
+            .rest
+            .okx_signed(Method::GET, path, params, credentials)
+            .await?;
+        match response["data"].get(0) {
+            Some(record) if algo => Ok(Some(Self::parse_algo_order(record)?)),
+            Some(record) => Ok(Some(Self::parse_order(record)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Map an `/api/v5/account/positions` entry. `pos` is in contracts, so `contract_size`
+    /// (the instrument's ctVal) converts it to base units. Flat positions yield `None`.
+    pub fn parse_position(data: &Value, contract_size: Option<f64>) -> Option<Position> {
+        let signed_contracts = json_f64(data, "pos").filter(|pos| *pos != 0.0)?;
+        let side = match data["posSide"].as_str().unwrap_or("net") {
+            "long" => "long",
+            "short" => "short",
+            _ if signed_contracts > 0.0 => "long",
+            _ => "short",
+        };
+        let contracts = signed_contracts.abs();
+        let contract_size = contract_size.unwrap_or(1.0);
+        let mut position = new_position(
+            &Self::symbol_from_inst_id(data["instId"].as_str()?),
+            side,
+            contracts * contract_size,
+            json_u64(data, "uTime"),
+            data,
+        );
+        let isolated = data["mgnMode"].as_str() == Some("isolated");
+        position.id = json_string(data, "posId");
+        position.isolated = Some(isolated);
+        position.hedged = Some(data["posSide"].as_str() != Some("net"));
+        position.contracts = Some(contracts);
+        position.contract_size = Some(contract_size);
+        position.entry_price = json_f64(data, "avgPx").filter(|p| *p > 0.0);
+        position.mark_price = json_f64(data, "markPx").filter(|p| *p > 0.0);
+        position.notional = json_f64(data, "notionalUsd");
+        position.leverage = json_f64(data, "lever");
+        position.initial_margin = json_f64(data, "imr").filter(|m| *m > 0.0);
+        position.maintenance_margin = json_f64(data, "mmr");
+        if isolated {
+            position.collateral = json_f64(data, "margin");
+        }
+        position.unrealized_pnl = json_f64(data, "upl");
+        position.realized_pnl = json_f64(data, "realizedPnl");
+        position.percentage = json_f64(data, "uplRatio").map(|ratio| ratio * 100.0);
+        Some(position)
+    }
+
+    /// Map `/api/v5/account/config`: `perm` lists "read_only", "trade" and "withdraw", and
+    /// `ip` the whitelisted addresses. OKX does not report rate-limit usage.
+    pub fn parse_account_config(data: &Value) -> ApiKeyPermissions {
+        let perms: Vec<&str> = data["perm"]
+            .as_str()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .collect();
+        let can_trade = perms.contains(&"trade");
+        ApiKeyPermissions {
+            can_read: perms.contains(&"read_only") || can_trade,
+            can_trade_spot: can_trade,
+            can_trade_futures: can_trade,
+            can_withdraw: perms.contains(&"withdraw"),
+            ip_restricted: data["ip"].as_str().map(|ip| !ip.trim().is_empty()),
+            rate_limit: None,
+        }
+    }
+
+    /// Map a `/api/v5/market/ticker` entry into `Ticker`
+    /// Map funding-fee bills (type 8); `balChg` is signed
+    pub fn parse_funding_payments(symbol: &str, data: &Value) -> Vec<FundingPayment> {
+        let mut payments: Vec<FundingPayment> = data
+            .as_array()
+            .map(|bills| {
+                bills
+                    .iter()
+                    .filter_map(|bill| {
+                        Some(FundingPayment {
+                            exchange: ExchangeIdEnum::OKX,
+                            symbol: symbol.to_string(),
+                            amount: json_f64(bill, "balChg")?,
+                            asset: json_string(bill, "ccy").unwrap_or_else(|| "USDT".into()),
+                            timestamp: json_u64(bill, "ts")?,
+                            payment_id: json_string(bill, "billId")?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        payments.sort_by_key(|payment| payment.timestamp);
+        payments
+    }
+
+    pub fn parse_ticker(symbol: &str, data: &Value, futures: bool) -> Ticker {
+        let mut ticker = empty_ticker(symbol, json_u64(data, "ts"), data.clone());
+        ticker.high = json_f64(data, "high24h");
+        ticker.low = json_f64(data, "low24h");
+        ticker.bid = json_f64(data, "bidPx");
+        ticker.bid_volume = json_f64(data, "bidSz");
+        ticker.ask = json_f64(data, "askPx");
+        ticker.ask_volume = json_f64(data, "askSz");
+        ticker.open = json_f64(data, "open24h");
+        ticker.close = json_f64(data, "last");
+        ticker.last = json_f64(data, "last");
+        if let (Some(last), Some(open)) = (ticker.last, ticker.open) {
+            ticker.change = Some(last - open);
+            if open > 0.0 {
+                ticker.percentage = Some((last - open) / open * 100.0);
+            }
+        }
+        // Spot: vol24h is base, volCcy24h quote. Swaps: vol24h is contracts, volCcy24h base.
+        if futures {
+            ticker.base_volume = json_f64(data, "volCcy24h");
+        } else {
+            ticker.base_volume = json_f64(data, "vol24h");
+            ticker.quote_volume = json_f64(data, "volCcy24h");
+        }
+        ticker.volume = ticker.base_volume;
+        ticker
+    }
+
+    /// Map a `/api/v5/public/funding-rate` entry into `FundingRateInfo`
+    pub fn parse_funding_rate(symbol: &str, data: &Value) -> FundingRateInfo {
+        let rate = json_f64(data, "fundingRate").unwrap_or(0.0);
+        let mut info = new_funding_rate(ExchangeIdEnum::OKX, symbol, rate, data.clone());
+        // OKX "fundingTime" is the upcoming settlement; "nextFundingTime" the one after it
+        let settlement = json_u64(data, "fundingTime").filter(|t| *t > 0);
+        info.next_funding_time = settlement;
+        info.estimated_rate = json_f64(data, "nextFundingRate").or(Some(rate));
+        if let (Some(current), Some(next)) = (settlement, json_u64(data, "nextFundingTime")) {
+            let hours = next.saturating_sub(current) / 3_600_000;
+            if hours > 0 {
+                info.funding_interval_hours = hours as u32;
+            }
+        }
+        info
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ExchangeAdapter for OkxAdapter {
+    fn exchange_id(&self) -> ExchangeIdEnum {
+        ExchangeIdEnum::OKX
+    }
+
+    fn market_symbol(&self, symbol: &str, futures: bool) -> ArbitrageResult<String> {
+        let (base, quote) = split_symbol(symbol).ok_or_else(|| {
+            ArbitrageError::validation_error(format!(
+                "Cannot map symbol {} to an OKX instrument",
+                symbol
+            ))
+        })?;
+        Ok(if futures {
+            format!("{}-{}-SWAP", base, quote)
+        } else {
+            format!("{}-{}", base, quote)
+        })
+    }
+
+    fn base_fee_rates(&self, futures: bool) -> (f64, f64) {
+        Self::fee_rates(futures)
+    }
+
+    async fn get_ticker(&self, symbol: &str, futures: bool) -> ArbitrageResult<Ticker> {
+        let query = [("instId", self.market_symbol(symbol, futures)?)];
+        let data = self
+            .public_entry("/api/v5/market/ticker", &query, symbol)
+            .await?;
+        Ok(Self::parse_ticker(symbol, &data, futures))
+    }
+
+    async fn get_orderbook(
+        &self,
+        symbol: &str,
+        limit: u32,
+        futures: bool,
+    ) -> ArbitrageResult<OrderBook> {
+        let query = [
+            ("instId", self.market_symbol(symbol, futures)?),
+            ("sz", limit.clamp(1, 400).to_string()),
+        ];
+        let response = self
+            .rest
+            .public_get(
+                "okx",
+                &self.rest.endpoints(false).okx,
+                "/api/v5/market/books",
+                &query,
+            )
+            .await?;
+        ExchangeRestClient::check_coded_response("okx", &response, "0")?;
+        let book = response["data"].get(0).ok_or_else(|| {
+            ArbitrageError::parse_error(format!("OKX returned no order book for {}", symbol))
+        })?;
+
+        Ok(normalize_orderbook(
+            symbol,
+            parse_levels(&book["bids"]),
+            parse_levels(&book["asks"]),
+            limit,
+            json_u64(book, "ts"),
+            json_u64(book, "seqId"),
+        ))
+    }
+
+    async fn get_markets(&self, futures: bool) -> ArbitrageResult<Vec<Market>> {
+        let inst_type = if futures { "SWAP" } else { "SPOT" };
+        let data = self
+            .rest
+            .public_get(
+                "okx",
+                &self.rest.endpoints(false).okx,
+                "/api/v5/public/instruments",
+                &[("instType", inst_type.to_string())],
+            )
+            .await?;
+        ExchangeRestClient::check_coded_response("okx", &data, "0")?;
+        Ok(Self::parse_markets(&data, futures))
+    }
+
+    async fn get_funding_rate(&self, symbol: &str) -> ArbitrageResult<FundingRateInfo> {
+        let query = [("instId", self.market_symbol(symbol, true)?)];
+        let data = self
+            .public_entry("/api/v5/public/funding-rate", &query, symbol)
+            .await?;
+        Ok(Self::parse_funding_rate(symbol, &data))
+    }
+
+    async fn get_balance(&self, credentials: &ExchangeCredentials) -> ArbitrageResult<Value> {
+        // The unified trading account holds spot and swap margin together
+        self.rest
+            .okx_signed(
+                Method::GET,
+                "/api/v5/account/balance",
+                json!({}),
+                credentials,
+            )
+            .await
+    }
+
+    async fn place_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        request
+            .validate()
+            .map_err(ArbitrageError::validation_error)?;
+
+        let futures = is_futures_market(ExchangeRestClient::market_type(
+            credentials,
+            request.market_type.as_deref(),
+        ));
+        let inst_id = self.market_symbol(&request.symbol, futures)?;
+        let (size, contract_size) = if futures {
+            let market = self.swap_market(&request.symbol).await?;
+            (
+                Self::contracts_for(request.amount, &market)?,
+                market.contract_size,
+            )
+        } else {
+            (request.amount, None)
+        };
+        let algo = Self::is_algo(request);
+        let path = if algo {
+            "/api/v5/trade/order-algo"
+        } else {
+            "/api/v5/trade/order"
+        };
+
+        let response = self
+            .rest
+            .okx_signed(
+                Method::POST,
+                path,
+                Self::order_body(request, &inst_id, futures, size),
+                credentials,
+            )
+            .await?;
+        let ack = &response["data"][0];
+        let order_id = json_string(ack, if algo { "algoId" } else { "ordId" })
+            .ok_or_else(|| ArbitrageError::parse_error("OKX order response missing its id"))?;
+
+        if let Some(order) = self
+            .fetch_order(credentials, &inst_id, &order_id, algo)
+            .await?
+        {
+            return Ok(Self::in_base_units(order, contract_size));
+        }
+        let client_order_id = json_string(ack, if algo { "algoClOrdId" } else { "clOrdId" })
+            .filter(|id| !id.is_empty());
+        Ok(acknowledged_order(
+            order_id,
+            client_order_id,
+            Self::symbol_from_inst_id(&inst_id),
+            request,
+            response,
+        ))
+    }
+
+    async fn cancel_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let inst_id = self.market_symbol(symbol, futures)?;
+        let cancelled = self
+            .rest
+            .okx_signed(
+                Method::POST,
+                "/api/v5/trade/cancel-order",
+                json!({ "instId": inst_id, "ordId": order_id }),
+                credentials,
+            )
+            .await;
+        if let Err(e) = cancelled {
+            // Not a regular order; conditional and trailing orders cancel as algo orders
+            self.rest
+                .okx_signed(
+                    Method::POST,
+                    "/api/v5/trade/cancel-algos",
+                    json!([{ "algoId": order_id, "instId": inst_id }]),
+                    credentials,
+                )
+                .await
+                .map_err(|_| e)?;
+        }
+        self.get_order(credentials, symbol, order_id, market_type)
+            .await
+    }
+
+    async fn get_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        order_id: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let inst_id = self.market_symbol(symbol, futures)?;
+        let order = match self
+            .fetch_order(credentials, &inst_id, order_id, false)
+            .await
+        {
+            Ok(Some(order)) => order,
+            regular => match self
+                .fetch_order(credentials, &inst_id, order_id, true)
+                .await
+            {
+                Ok(Some(order)) => order,
+                _ => {
+                    return Err(regular.err().unwrap_or_else(|| {
+                        ArbitrageError::not_found(format!("OKX order {} not found", order_id))
+                    }))
+                }
+            },
+        };
+        let contract_size = if futures {
+            self.swap_market(symbol).await?.contract_size
+        } else {
+            None
+        };
+        Ok(Self::in_base_units(order, contract_size))
+    }
+
+    async fn get_open_positions(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        let mut params = json!({ "instType": "SWAP" });
+        if let Some(symbol) = symbol {
+            params["instId"] = json!(self.market_symbol(symbol, true)?);
+        }
+        let response = self
+            .rest
+            .okx_signed(
+                Method::GET,
+                "/api/v5/account/positions",
+                params,
+                credentials,
+            )
+            .await?;
+        let entries = response["data"].as_array().cloned().unwrap_or_default();
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let contract_sizes: HashMap<String, f64> = self
+            .get_markets(true)
+            .await?
+            .into_iter()
+            .filter_map(|market| Some((market.symbol, market.contract_size?)))
+            .collect();
+        Ok(entries
+            .iter()
+            .filter_map(|entry| {
+                let symbol = Self::symbol_from_inst_id(entry["instId"].as_str()?);
+                Self::parse_position(entry, contract_sizes.get(&symbol).copied())
+            })
+            .collect())
+    }
+
+    async fn get_open_orders(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        let futures = is_futures_market(ExchangeRestClient::market_type(credentials, market_type));
+        let mut params = json!({ "instType": if futures { "SWAP" } else { "SPOT" } });
+        if let Some(symbol) = symbol {
+            params["instId"] = json!(self.market_symbol(symbol, futures)?);
+        }
+        let response = self
+            .rest
+            .okx_signed(
+                Method::GET,
+                "/api/v5/trade/orders-pending",
+                params,
+                credentials,
+            )
+            .await?;
+        response["data"]
+            .as_array()
+            .map(|orders| orders.iter().map(Self::parse_order).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    async fn get_max_leverage(
+        &self,
+        _credentials: &ExchangeCredentials,
+        symbol: &str,
+    ) -> ArbitrageResult<Option<f64>> {
+        let data = self
+            .rest
+            .public_get(
+                "okx",
+                &self.rest.endpoints(false).okx,
+                "/api/v5/public/instruments",
+                &[
+                    ("instType", "SWAP".to_string()),
+                    ("instId", self.market_symbol(symbol, true)?),
+                ],
+            )
+            .await?;
+        ExchangeRestClient::check_coded_response("okx", &data, "0")?;
+        Ok(Self::parse_markets(&data, true)
+            .first()
+            .and_then(|market| market.limits.leverage.as_ref())
+            .and_then(|leverage| leverage.max))
+    }
+
+    async fn set_leverage(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
+    ) -> ArbitrageResult<()> {
+        let max_leverage = self.get_max_leverage(credentials, symbol).await?;
+        validate_leverage(self.exchange_id(), symbol, leverage, max_leverage)?;
+
+        // OKX keeps a separate leverage per margin mode; set both so it applies whichever
+        // mode orders are placed in (net position mode)
+        let inst_id = self.market_symbol(symbol, true)?;
+        for mode in [MarginMode::Cross, MarginMode::Isolated] {
+            self.rest
+                .okx_signed(
+                    Method::POST,
+                    "/api/v5/account/set-leverage",
+                    json!({
+                        "instId": inst_id,
+                        "lever": leverage.to_string(),
+                        "mgnMode": mode.as_str(),
+                    }),
+                    credentials,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn set_margin_mode(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        // OKX picks the margin mode per order (`tdMode`), so there is no symbol setting to
+        // switch. An open position in the other mode would keep trading in that mode.
+        let positions = self
+            .rest
+            .okx_signed(
+                Method::GET,
+                "/api/v5/account/positions",
+                json!({ "instType": "SWAP", "instId": self.market_symbol(symbol, true)? }),
+                credentials,
+            )
+            .await?;
+        let conflicting = positions["data"].as_array().is_some_and(|entries| {
+            entries.iter().any(|entry| {
+                json_f64(entry, "pos").is_some_and(|pos| pos != 0.0)
+                    && entry["mgnMode"].as_str() != Some(mode.as_str())
+            })
+        });
+        if conflicting {
+            return Err(ArbitrageError::exchange_error(
+                "okx",
+                format!(
+                    "Close the open {} position before switching it to {} margin",
+                    symbol,
+                    mode.as_str()
+                ),
+            )
+            .with_status(409));
+        }
+        Ok(())
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        self.rest
+            .okx_signed(
+                Method::POST,
+                "/api/v5/account/position/margin-balance",
+                json!({
+                    "instId": self.market_symbol(symbol, true)?,
+                    "posSide": "net",
+                    "type": if amount >= 0.0 { "add" } else { "reduce" },
+                    "amt": amount.abs().to_string(),
+                }),
+                credentials,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_trading_fees(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        futures: bool,
+    ) -> ArbitrageResult<TradingFeeRates> {
+        // Swap fee tiers are looked up by instrument family ("BTC-USDT"), spot by instrument
+        let inst_id = self.market_symbol(symbol, false)?;
+        let params = if futures {
+            json!({ "instType": "SWAP", "instFamily": inst_id })
+        } else {
+            json!({ "instType": "SPOT", "instId": inst_id })
+        };
+        let response = self
+            .rest
+            .okx_signed(
+                Method::GET,
+                "/api/v5/account/trade-fee",
+                params,
+                credentials,
+            )
+            .await?;
+        let entry = &response["data"][0];
+        // OKX reports charges as negative numbers (rebates are positive); USDT-margined
+        // swaps have their own `makerU`/`takerU` schedule
+        let rate = |field: &str| {
+            let usdt_field = format!("{}U", field);
+            futures
+                .then(|| json_f64(entry, &usdt_field))
+                .flatten()
+                .or_else(|| json_f64(entry, field))
+                .map(|rate| -rate)
+        };
+        account_fee_rates(self.exchange_id(), symbol, rate("maker"), rate("taker"))
+    }
+
+    async fn get_funding_payments(
+        &self,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        let mut params = json!({
+            "instType": "SWAP",
+            "instId": self.market_symbol(symbol, true)?,
+            "type": "8",
+        });
+        if let Some(since) = since {
+            params["begin"] = json!(since.to_string());
+        }
+        let response = self
+            .rest
+            .okx_signed(Method::GET, "/api/v5/account/bills", params, credentials)
+            .await?;
+        Ok(Self::parse_funding_payments(symbol, &response["data"]))
+    }
+
+    async fn probe_api_key(
+        &self,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        let response = self
+            .rest
+            .okx_signed(
+                Method::GET,
+                "/api/v5/account/config",
+                json!({}),
+                credentials,
+            )
+            .await?;
+        Ok(Self::parse_account_config(&response["data"][0]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::adapters::test_support::{
+        assert_best_first, credentials, fixture, rest_for,
+    };
+    use crate::services::core::trading::exchange_rest::DEFAULT_ORDERBOOK_LIMIT;
+    use crate::services::core::trading::signing::hmac_sha256_base64;
+    use crate::test_utils::mock_http_server::{MockHttpServer, MockRoute};
+    use crate::utils::error::ErrorKind;
+    use serde_json::json;
+
+    const OKX_BOOKS: &str = include_str!("../../../../test_utils/fixtures/okx/books.json");
+    const OKX_UNKNOWN_INSTRUMENT: &str =
+        include_str!("../../../../test_utils/fixtures/okx/error_instrument.json");
+    const OKX_INSTRUMENTS_SWAP: &str =
+        include_str!("../../../../test_utils/fixtures/okx/instruments_swap.json");
+    const OKX_TICKER: &str = include_str!("../../../../test_utils/fixtures/okx/ticker.json");
+    const OKX_FUNDING_RATE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/funding_rate.json");
+    const OKX_ACCOUNT_BALANCE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/account_balance.json");
+    const OKX_POSITIONS: &str = include_str!("../../../../test_utils/fixtures/okx/positions.json");
+    const OKX_ORDERS_PENDING: &str =
+        include_str!("../../../../test_utils/fixtures/okx/orders_pending.json");
+    const OKX_SET_LEVERAGE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/set_leverage.json");
+    const OKX_ACCOUNT_CONFIG: &str =
+        include_str!("../../../../test_utils/fixtures/okx/account_config.json");
+    const OKX_BILLS_FUNDING_FEE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/bills_funding_fee.json");
+    const OKX_TRADE_FEE: &str = include_str!("../../../../test_utils/fixtures/okx/trade_fee.json");
+    const OKX_ORDER_CREATE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/order_create.json");
+    const OKX_ORDER_FILLED: &str =
+        include_str!("../../../../test_utils/fixtures/okx/order_filled.json");
+    const OKX_ORDER_ALGO_CREATE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/order_algo_create.json");
+    const OKX_ORDER_ALGO_LIVE: &str =
+        include_str!("../../../../test_utils/fixtures/okx/order_algo_live.json");
+    const OKX_CANCEL_ORDER_NOT_FOUND: &str =
+        include_str!("../../../../test_utils/fixtures/okx/cancel_order_not_found.json");
+    const OKX_CANCEL_ALGOS: &str =
+        include_str!("../../../../test_utils/fixtures/okx/cancel_algos.json");
+
+    #[tokio::test]
+    async fn test_okx_orderbook_maps_instrument_id() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/market/books",
+            200,
+            OKX_BOOKS,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+
+        let book = adapter
+            .get_orderbook("BTCUSDT", DEFAULT_ORDERBOOK_LIMIT, true)
+            .await
+            .unwrap();
+
+        // OKX returns asks worst-first in this fixture; normalization reorders them
+        assert_eq!(book.asks[0], [41006.3, 0.30178218]);
+        assert_eq!(book.timestamp, 1629966436396);
+        assert_best_first(&book);
+
+        let query = &server.requests()[0].query;
+        assert!(query.contains("instId=BTC-USDT-SWAP"));
+        assert!(query.contains(&format!("sz={}", DEFAULT_ORDERBOOK_LIMIT)));
+    }
+
+    #[tokio::test]
+    async fn test_okx_error_code_maps_to_exchange_error() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/market/books",
+            200,
+            OKX_UNKNOWN_INSTRUMENT,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+
+        let error = adapter
+            .get_orderbook("FOO/USDT", DEFAULT_ORDERBOOK_LIMIT, false)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::ExchangeError);
+        assert_eq!(error.details.unwrap()["exchange_code"], json!("51001"));
+    }
+
+    #[test]
+    fn test_okx_swap_markets_use_contract_value() {
+        let markets = OkxAdapter::parse_markets(&fixture(OKX_INSTRUMENTS_SWAP), true);
+
+        // Inverse contracts are not supported
+        assert_eq!(markets.len(), 1);
+        let btc = &markets[0];
+        assert_eq!(btc.symbol, "BTCUSDT");
+        assert_eq!(btc.contract_size, Some(0.01));
+        assert_eq!(btc.lot_size(), Some(0.01));
+        assert_eq!(btc.limits.amount.as_ref().unwrap().min, Some(0.01));
+    }
+
+    #[test]
+    fn test_okx_instrument_ids() {
+        let adapter = OkxAdapter::new(ExchangeRestClient::new());
+
+        assert_eq!(
+            adapter.market_symbol("BTC/USDT", false).unwrap(),
+            "BTC-USDT"
+        );
+        assert_eq!(
+            adapter.market_symbol("ETHUSDT", true).unwrap(),
+            "ETH-USDT-SWAP"
+        );
+        assert!(adapter.market_symbol("USDT", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_okx_spot_ticker() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/market/ticker",
+            200,
+            OKX_TICKER,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+
+        let ticker = adapter.get_ticker("BTC/USDT", false).await.unwrap();
+
+        assert_eq!(ticker.last, Some(9999.99));
+        assert_eq!(ticker.bid, Some(8888.88));
+        assert_eq!(ticker.timestamp, 1597026383085);
+        assert!((ticker.percentage.unwrap() - 11.111).abs() < 1e-3);
+        assert!(server.requests()[0].query.contains("instId=BTC-USDT"));
+    }
+
+    #[tokio::test]
+    async fn test_okx_funding_rate_derives_interval() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/public/funding-rate",
+            200,
+            OKX_FUNDING_RATE,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+
+        let info = adapter.get_funding_rate("BTCUSDT").await.unwrap();
+
+        assert_eq!(info.exchange, ExchangeIdEnum::OKX);
+        assert!((info.funding_rate - 0.0000792386885340).abs() < 1e-15);
+        assert_eq!(info.next_funding_time, Some(1703088000000));
+        assert_eq!(info.funding_interval_hours, 8);
+        assert!(server.requests()[0].query.contains("instId=BTC-USDT-SWAP"));
+    }
+
+    #[tokio::test]
+    async fn test_okx_balance_is_signed_with_passphrase() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/account/balance",
+            200,
+            OKX_ACCOUNT_BALANCE,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::OKX, "spot");
+        creds.passphrase = Some("okx-passphrase".to_string());
+
+        let balance = adapter.get_balance(&creds).await.unwrap();
+        assert_eq!(balance["data"][0]["totalEq"], "1500.1");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("ok-access-key"), Some("test-api-key"));
+        assert_eq!(
+            request.header("ok-access-passphrase"),
+            Some("okx-passphrase")
+        );
+        let timestamp = request.header("ok-access-timestamp").unwrap();
+        let expected = hmac_sha256_base64(
+            "test-secret",
+            &format!("{}GET/api/v5/account/balance", timestamp),
+        )
+        .unwrap();
+        assert_eq!(request.header("ok-access-sign"), Some(expected.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_okx_positions_convert_contracts_to_base() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new("GET", "/api/v5/account/positions", 200, OKX_POSITIONS),
+            MockRoute::new(
+                "GET",
+                "/api/v5/public/instruments",
+                200,
+                OKX_INSTRUMENTS_SWAP,
+            ),
+        ]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::OKX, "futures");
+        creds.passphrase = Some("okx-passphrase".to_string());
+
+        let positions = adapter.get_open_positions(&creds, None).await.unwrap();
+
+        assert_eq!(positions.len(), 1);
+        let btc = &positions[0];
+        assert_eq!(btc.symbol, "BTCUSDT");
+        assert_eq!(btc.side, "long");
+        assert_eq!(btc.contracts, Some(15.0));
+        // 15 contracts x 0.01 BTC
+        assert!((btc.amount - 0.15).abs() < 1e-12);
+        assert_eq!(btc.id.as_deref(), Some("1234567890"));
+        assert!(server.requests()[0].query.contains("instType=SWAP"));
+    }
+
+    #[test]
+    fn test_okx_pending_order_mapping() {
+        let response = fixture(OKX_ORDERS_PENDING);
+        let order = OkxAdapter::parse_order(&response["data"][0]).unwrap();
+
+        assert_eq!(order.id, "680800019749904384");
+        assert_eq!(order.symbol, "BTCUSDT");
+        assert_eq!(order.status, "open");
+        assert_eq!(order.time_in_force.as_deref(), Some("PO"));
+        assert_eq!(
+            (order.amount, order.filled, order.remaining),
+            (5.0, 2.0, 3.0)
+        );
+        assert_eq!(order.client_order_id, None);
+        assert!((order.fee.unwrap().cost - 0.0065).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_okx_set_leverage_covers_both_margin_modes() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/api/v5/public/instruments",
+                200,
+                OKX_INSTRUMENTS_SWAP,
+            ),
+            MockRoute::new(
+                "POST",
+                "/api/v5/account/set-leverage",
+                200,
+                OKX_SET_LEVERAGE,
+            ),
+            MockRoute::new(
+                "POST",
+                "/api/v5/account/set-leverage",
+                200,
+                OKX_SET_LEVERAGE,
+            ),
+        ]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::OKX, "futures");
+        creds.passphrase = Some("okx-passphrase".to_string());
+
+        adapter.set_leverage(&creds, "BTC/USDT", 20).await.unwrap();
+
+        let modes: Vec<String> = server
+            .requests()
+            .iter()
+            .filter(|r| r.method == "POST")
+            .map(|r| {
+                let body: Value = serde_json::from_str(&r.body).unwrap();
+                assert_eq!(body["instId"], "BTC-USDT-SWAP");
+                assert_eq!(body["lever"], "20");
+                body["mgnMode"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(modes, vec!["cross", "isolated"]);
+    }
+
+    #[tokio::test]
+    async fn test_okx_margin_mode_conflicts_with_open_position() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/account/positions",
+            200,
+            OKX_POSITIONS,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::OKX, "futures");
+        creds.passphrase = Some("okx-passphrase".to_string());
+
+        // The fixture position is cross margined
+        adapter
+            .set_margin_mode(&creds, "BTCUSDT", MarginMode::Cross)
+            .await
+            .unwrap();
+        let error = adapter
+            .set_margin_mode(&creds, "BTCUSDT", MarginMode::Isolated)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ExchangeError);
+        assert_eq!(error.status, Some(409));
+    }
+
+    #[tokio::test]
+    async fn test_okx_swap_fees_use_usdt_schedule() {
+        let server = MockHttpServer::start(vec![MockRoute::new(
+            "GET",
+            "/api/v5/account/trade-fee",
+            200,
+            OKX_TRADE_FEE,
+        )]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let mut creds = credentials(ExchangeIdEnum::OKX, "futures");
+        creds.passphrase = Some("okx-passphrase".to_string());
+
+        let fees = adapter
+            .get_trading_fees(&creds, "BTCUSDT", true)
+            .await
+            .unwrap();
+
+        // Negative OKX rates are charges
+        assert_eq!((fees.maker, fees.taker), (0.00014, 0.0004));
+        let query = &server.requests()[0].query;
+        assert!(query.contains("instType=SWAP"));
+        assert!(query.contains("instFamily=BTC-USDT"));
+    }
+
+    #[test]
+    fn test_okx_account_config_permissions() {
+        let permissions = OkxAdapter::parse_account_config(&fixture(OKX_ACCOUNT_CONFIG)["data"][0]);
+
+        assert!(permissions.can_read);
+        assert!(permissions.can_trade_spot && permissions.can_trade_futures);
+        assert!(!permissions.can_withdraw);
+        assert_eq!(permissions.ip_restricted, Some(true));
+    }
+
+    #[test]
+    fn test_parse_funding_payments_from_bills() {
+        let data = fixture(OKX_BILLS_FUNDING_FEE);
+        let payments = OkxAdapter::parse_funding_payments("BTC/USDT", &data["data"]);
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].amount, -0.1);
+        assert_eq!(payments[1].amount, 0.25);
+        assert_eq!(payments[1].payment_id, "623950854533513219");
+        assert_eq!(payments[1].asset, "USDT");
+    }
+
+    fn okx_futures_credentials() -> ExchangeCredentials {
+        let mut creds = credentials(ExchangeIdEnum::OKX, "futures");
+        creds.passphrase = Some("okx-passphrase".to_string());
+        creds
+    }
+
+    #[tokio::test]
+    async fn test_okx_swap_market_order_is_sized_in_contracts() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/api/v5/public/instruments",
+                200,
+                OKX_INSTRUMENTS_SWAP,
+            ),
+            MockRoute::new("POST", "/api/v5/trade/order", 200, OKX_ORDER_CREATE),
+            MockRoute::new("GET", "/api/v5/trade/order", 200, OKX_ORDER_FILLED),
+        ]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let request = OrderRequest {
+            client_order_id: Some("arbL1".to_string()),
+            ..OrderRequest::market("BTCUSDT", "buy", 0.15)
+        };
+
+        let order = adapter
+            .place_order(&okx_futures_credentials(), &request)
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body["instId"], "BTC-USDT-SWAP");
+        assert_eq!(body["tdMode"], "cross");
+        assert_eq!(body["ordType"], "market");
+        assert_eq!(body["clOrdId"], "arbL1");
+        // 0.15 BTC at 0.01 BTC per contract
+        assert_eq!(body["sz"], "15");
+        assert_eq!(order.id, "680800019749904500");
+        assert_eq!(order.status, "closed");
+        assert!((order.filled - 0.15).abs() < 1e-12);
+        assert_eq!(order.average, Some(65010.5));
+        assert!((order.fee.unwrap().cost - 0.48757875).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_okx_stop_loss_is_a_reduce_only_algo_order() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "GET",
+                "/api/v5/public/instruments",
+                200,
+                OKX_INSTRUMENTS_SWAP,
+            ),
+            MockRoute::new(
+                "POST",
+                "/api/v5/trade/order-algo",
+                200,
+                OKX_ORDER_ALGO_CREATE,
+            ),
+            MockRoute::new("GET", "/api/v5/trade/order-algo", 200, OKX_ORDER_ALGO_LIVE),
+        ]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+        let request = OrderRequest {
+            order_type: OrderType::StopLoss,
+            stop_price: Some(62_000.0),
+            reduce_only: true,
+            ..OrderRequest::market("BTCUSDT", "sell", 0.15)
+        };
+
+        let order = adapter
+            .place_order(&okx_futures_credentials(), &request)
+            .await
+            .unwrap();
+
+        let body: Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body["ordType"], "conditional");
+        assert_eq!(body["slTriggerPx"], "62000");
+        assert_eq!(body["slOrdPx"], "-1");
+        assert_eq!(body["reduceOnly"], true);
+        assert_eq!(order.id, "681096944655273984");
+        assert_eq!(order.type_, "stoploss");
+        assert_eq!(order.status, "open");
+        assert!((order.amount - 0.15).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_okx_trailing_stop_uses_callback_ratio() {
+        let request = OrderRequest {
+            order_type: OrderType::TrailingStop,
+            trailing_percent: Some(1.5),
+            reduce_only: true,
+            client_order_id: Some("trail1".to_string()),
+            ..OrderRequest::market("BTCUSDT", "sell", 0.1)
+        };
+
+        let body = OkxAdapter::order_body(&request, "BTC-USDT-SWAP", true, 10.0);
+
+        assert_eq!(body["ordType"], "move_order_stop");
+        assert_eq!(body["callbackRatio"], "0.015");
+        assert_eq!(body["algoClOrdId"], "trail1");
+        assert!(body.get("clOrdId").is_none());
+    }
+
+    #[test]
+    fn test_okx_contracts_round_down_to_lot_size() {
+        let markets = OkxAdapter::parse_markets(&fixture(OKX_INSTRUMENTS_SWAP), true);
+
+        assert_eq!(
+            OkxAdapter::contracts_for(0.123456, &markets[0]).unwrap(),
+            12.34
+        );
+        assert!(OkxAdapter::contracts_for(0.00001, &markets[0]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_okx_cancel_falls_back_to_algo_orders() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new(
+                "POST",
+                "/api/v5/trade/cancel-order",
+                200,
+                OKX_CANCEL_ORDER_NOT_FOUND,
+            ),
+            MockRoute::new("POST", "/api/v5/trade/cancel-algos", 200, OKX_CANCEL_ALGOS),
+            MockRoute::new(
+                "GET",
+                "/api/v5/trade/order",
+                200,
+                r#"{"code":"51603","msg":"Order does not exist","data":[]}"#,
+            ),
+            MockRoute::new(
+                "GET",
+                "/api/v5/trade/order-algo",
+                200,
+                &OKX_ORDER_ALGO_LIVE.replace(r#""state": "live""#, r#""state": "canceled""#),
+            ),
+            MockRoute::new(
+                "GET",
+                "/api/v5/public/instruments",
+                200,
+                OKX_INSTRUMENTS_SWAP,
+            ),
+        ]);
+        let adapter = OkxAdapter::new(rest_for(&server));
+
+        let order = adapter
+            .cancel_order(
+                &okx_futures_credentials(),
+                "BTCUSDT",
+                "681096944655273984",
+                None,
+            )
+            .await
+            .unwrap();
+
+        let cancel: Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(cancel[0]["algoId"], "681096944655273984");
+        assert_eq!(cancel[0]["instId"], "BTC-USDT-SWAP");
+        assert_eq!(order.status, "canceled");
+    }
+}
@@ -8,12 +8,14 @@ use crate::{
             infrastructure::{
                 database_repositories::DatabaseManager, /* service_container::ServiceContainer, */
             },
-            /* trading::exchange::ExchangeService, */
+            trading::exchange::ExchangeInterface,
             user::user_profile::UserProfileService,
         },
         /* interfaces::telegram::TelegramService, */
     },
-    types::{ArbitrageOpportunity, /* ExchangeIdEnum, */ GlobalOpportunity, UserProfile},
+    types::{
+        ArbitrageOpportunity, /* ExchangeIdEnum, */ GlobalOpportunity, OrderBook, UserProfile,
+    },
     utils::{ArbitrageError, ArbitrageResult},
 };
 use reqwest::Client;
@@ -70,6 +72,57 @@ pub struct OrderbookDepth {
     pub spread: f64,
 }
 
+impl OrderbookDepth {
+    /// Summarize a live order book: quote notional on each side and the spread in percent of mid
+    pub fn from_orderbook(orderbook: &OrderBook) -> Self {
+        Self {
+            bids_depth: orderbook.bid_notional(),
+            asks_depth: orderbook.ask_notional(),
+            spread: orderbook.spread_percentage().unwrap_or(0.0),
+        }
+    }
+}
+
+impl ExchangeMarketData {
+    /// Fetch live order books for `symbols` on one exchange. Symbols whose book cannot be
+    /// fetched are skipped so one delisted pair does not blank the whole snapshot.
+    pub async fn fetch_orderbook_depth<E: ExchangeInterface>(
+        exchange: &E,
+        exchange_id: &str,
+        symbols: &[String],
+        depth_limit: Option<u32>,
+    ) -> Self {
+        let mut orderbook_depth = HashMap::new();
+        for symbol in symbols {
+            match exchange
+                .get_orderbook(exchange_id, symbol, depth_limit)
+                .await
+            {
+                Ok(orderbook) => {
+                    orderbook_depth
+                        .insert(symbol.clone(), OrderbookDepth::from_orderbook(&orderbook));
+                }
+                Err(e) => {
+                    console_log!(
+                        "⚠️ Skipping {} order book on {}: {}",
+                        symbol,
+                        exchange_id,
+                        e
+                    );
+                }
+            }
+        }
+
+        Self {
+            exchange_id: exchange_id.to_string(),
+            funding_rates: HashMap::new(),
+            orderbook_depth,
+            volume_24h: HashMap::new(),
+            last_updated: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MarketContext {
     pub volatility_index: f64,
@@ -938,6 +991,24 @@ mod tests {
         assert_eq!(depth.spread, 0.01);
     }
 
+    #[test]
+    fn test_orderbook_depth_from_orderbook() {
+        let orderbook = OrderBook {
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![[99.0, 2.0], [98.0, 1.0]],
+            asks: vec![[101.0, 1.0], [102.0, 3.0]],
+            timestamp: 0,
+            datetime: String::new(),
+            nonce: None,
+        };
+
+        let depth = OrderbookDepth::from_orderbook(&orderbook);
+
+        assert_eq!(depth.bids_depth, 296.0);
+        assert_eq!(depth.asks_depth, 407.0);
+        assert!((depth.spread - 2.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_market_context_creation() {
         let context = MarketContext {
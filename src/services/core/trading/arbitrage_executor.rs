@@ -0,0 +1,1184 @@
+// src/services/core/trading/arbitrage_executor.rs
+
+//! Opens both legs of a cross-exchange arbitrage together.
+//!
+//! The long and short legs are sent as market orders at the same time and polled until
+//! they settle; anything still resting after `fill_timeout_ms` is cancelled. When the
+//! fills disagree, the lagging leg is first topped up on its own exchange (hedge). If
+//! that is impossible or fails, the excess on the leading leg is closed with a
+//! reduce-only order (unwind). Each step is appended to the position's `execution_log`,
+//! and the position is saved before the orders go out and again once it has settled.
+
+use std::sync::Arc;
+
+use crate::services::core::trading::adapters::new_position;
+use crate::services::core::trading::exchange::ExchangeInterface;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::paper_exchange::ProductionVenue;
+use crate::services::core::trading::positions::{refresh_pnl, PositionsService};
+use crate::services::core::trading::risk_gate::{positions_for_risk, ProposedTrade, RiskGate};
+use crate::services::core::trading::{KvOperationError, KvOperations};
+use crate::services::core::user::user_trading_preferences::{
+    AutomationLevel, FeatureAccess, UserTradingPreferences,
+};
+use crate::types::{
+    ArbitrageOpportunity, ArbitragePosition, ExchangeCredentials, ExchangeIdEnum, ExecutionEvent,
+    ExecutionStep, OrderRequest, PositionSide, PositionStatus,
+};
+use crate::utils::retry::sleep_ms;
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+#[derive(Debug, Clone)]
+pub struct ExecutionConfig {
+    /// Notional cap per leg, in quote currency
+    pub max_position_usd: f64,
+    /// How long a leg may rest before its remainder is cancelled
+    pub fill_timeout_ms: u64,
+    pub poll_interval_ms: u64,
+    /// Relative difference between leg fills still treated as balanced
+    pub balance_tolerance: f64,
+    /// Market both legs trade on
+    pub market_type: String,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            max_position_usd: 10_000.0,
+            fill_timeout_ms: 10_000,
+            poll_interval_ms: 500,
+            balance_tolerance: 0.001,
+            market_type: "futures".to_string(),
+        }
+    }
+}
+
+/// What a user's automation settings allow for a detected opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionDecision {
+    Execute,
+    /// Semi-automatic users confirm each trade before it is sent
+    AwaitApproval,
+    Skip(String),
+}
+
+pub fn execution_decision(preferences: &UserTradingPreferences) -> ExecutionDecision {
+    if !FeatureAccess::from_preferences(preferences).arbitrage_automation {
+        return ExecutionDecision::Skip("Arbitrage automation is disabled".to_string());
+    }
+    match preferences.automation_level {
+        AutomationLevel::FullAuto => ExecutionDecision::Execute,
+        AutomationLevel::SemiAuto => ExecutionDecision::AwaitApproval,
+        AutomationLevel::Manual => ExecutionDecision::Skip("Manual trading only".to_string()),
+    }
+}
+
+/// How long a semi-automatic trade waits for the user to confirm it
+pub const APPROVAL_TTL_MS: u64 = 5 * 60 * 1000;
+
+/// A semi-automatic trade waiting for the user's confirmation
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PendingExecution {
+    pub id: String,
+    pub user_id: String,
+    pub opportunity: ArbitrageOpportunity,
+    pub size_usd: f64,
+    pub expires_at: u64,
+}
+
+/// What happened to an opportunity handed to an automated user
+#[derive(Debug, Clone)]
+pub enum ExecutionOutcome {
+    Executed(Box<ArbitragePosition>),
+    AwaitingApproval(Box<PendingExecution>),
+    Skipped(String),
+}
+
+/// Pending semi-automatic trades, stored under `pending_execution:{user_id}:{id}`
+pub struct ExecutionApprovals<K: KvOperations + Send + Sync + 'static> {
+    kv_store: Arc<K>,
+}
+
+impl<K: KvOperations + Send + Sync + 'static> Clone for ExecutionApprovals<K> {
+    fn clone(&self) -> Self {
+        Self {
+            kv_store: self.kv_store.clone(),
+        }
+    }
+}
+
+impl<K: KvOperations + Send + Sync + 'static> ExecutionApprovals<K> {
+    pub fn new(kv_store: Arc<K>) -> Self {
+        Self { kv_store }
+    }
+
+    fn key(user_id: &str, id: &str) -> String {
+        format!("pending_execution:{}:{}", user_id, id)
+    }
+
+    pub async fn request(
+        &self,
+        user_id: &str,
+        opportunity: &ArbitrageOpportunity,
+        size_usd: f64,
+        now: u64,
+    ) -> ArbitrageResult<PendingExecution> {
+        let pending = PendingExecution {
+            id: uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+            user_id: user_id.to_string(),
+            opportunity: opportunity.clone(),
+            size_usd,
+            expires_at: now + APPROVAL_TTL_MS,
+        };
+        self.kv_store
+            .put(&Self::key(user_id, &pending.id), &pending)
+            .await?;
+        Ok(pending)
+    }
+
+    /// Remove the pending trade so it is acted on at most once. Expired trades come back as `None`.
+    pub async fn take(
+        &self,
+        user_id: &str,
+        id: &str,
+        now: u64,
+    ) -> ArbitrageResult<Option<PendingExecution>> {
+        let key = Self::key(user_id, id);
+        let pending = match self.kv_store.get::<PendingExecution>(&key).await {
+            Ok(pending) => pending,
+            Err(KvOperationError::NotFound(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let Some(pending) = pending else {
+            return Ok(None);
+        };
+        self.kv_store.delete(&key).await?;
+        Ok((pending.expires_at > now).then_some(pending))
+    }
+}
+
+/// The user's credentials for the long and short exchanges of `opportunity`
+fn credentials_for_legs<'a>(
+    opportunity: &ArbitrageOpportunity,
+    credentials: &'a [ExchangeCredentials],
+) -> ArbitrageResult<(&'a ExchangeCredentials, &'a ExchangeCredentials)> {
+    let find = |exchange: ExchangeIdEnum| {
+        credentials
+            .iter()
+            .find(|creds| creds.exchange == exchange)
+            .ok_or_else(|| {
+                ArbitrageError::validation_error(format!("No API key for {}", exchange.as_str()))
+            })
+    };
+    Ok((
+        find(opportunity.long_exchange)?,
+        find(opportunity.short_exchange)?,
+    ))
+}
+
+/// How one leg's order (or orders, after a hedge or unwind) ended up
+#[derive(Debug, Clone)]
+struct LegFill {
+    exchange: ExchangeIdEnum,
+    side: String,
+    order_id: Option<String>,
+    filled: f64,
+    average_price: Option<f64>,
+    fees: f64,
+    error: Option<String>,
+    cancelled: bool,
+}
+
+impl LegFill {
+    fn new(exchange: ExchangeIdEnum, side: &str) -> Self {
+        Self {
+            exchange,
+            side: side.to_string(),
+            order_id: None,
+            filled: 0.0,
+            average_price: None,
+            fees: 0.0,
+            error: None,
+            cancelled: false,
+        }
+    }
+
+    /// Fold in a further fill on the same side, keeping a volume-weighted entry price
+    fn add(&mut self, other: &LegFill) {
+        let total = self.filled + other.filled;
+        if total > 0.0 {
+            let cost = self.filled * self.average_price.unwrap_or(0.0)
+                + other.filled * other.average_price.unwrap_or(0.0);
+            self.average_price = Some(cost / total);
+        }
+        self.filled = total;
+        self.fees += other.fees;
+    }
+
+    /// Fold in a fill on the opposite side; the entry price of what is left is unchanged
+    fn reduce(&mut self, other: &LegFill) {
+        self.filled = (self.filled - other.filled).max(0.0);
+        self.fees += other.fees;
+    }
+
+    fn event(&self, step: ExecutionStep, message: impl Into<String>) -> ExecutionEvent {
+        ExecutionEvent {
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            step,
+            exchange: Some(self.exchange),
+            order_id: self.order_id.clone(),
+            side: Some(self.side.clone()),
+            amount: Some(self.filled),
+            price: self.average_price,
+            message: message.into(),
+        }
+    }
+
+    /// `LegFilled`, `LegCancelled` or `LegFailed` depending on how the order ended
+    fn outcome_event(&self, label: &str) -> ExecutionEvent {
+        match (&self.error, self.cancelled) {
+            (Some(error), _) if self.filled <= 0.0 => self.event(
+                ExecutionStep::LegFailed,
+                format!("{} failed: {}", label, error),
+            ),
+            (_, true) => self.event(
+                ExecutionStep::LegCancelled,
+                format!(
+                    "{} filled {} before the rest was cancelled",
+                    label, self.filled
+                ),
+            ),
+            _ => self.event(
+                ExecutionStep::LegFilled,
+                format!("{} filled {}", label, self.filled),
+            ),
+        }
+    }
+}
+
+fn note(step: ExecutionStep, message: impl Into<String>) -> ExecutionEvent {
+    ExecutionEvent {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        step,
+        exchange: None,
+        order_id: None,
+        side: None,
+        amount: None,
+        price: None,
+        message: message.into(),
+    }
+}
+
+fn opposite_side(side: &str) -> &'static str {
+    if side == "buy" {
+        "sell"
+    } else {
+        "buy"
+    }
+}
+
+/// Orders in these states will not fill any further
+pub(crate) fn is_settled(status: &str) -> bool {
+    matches!(status, "closed" | "canceled" | "expired" | "rejected")
+}
+
+/// Executor over the live exchange service and the worker KV store
+#[cfg(target_arch = "wasm32")]
+pub type ProductionArbitrageExecutor = ArbitrageExecutor<
+    crate::services::core::trading::exchange::ExchangeService,
+    worker::kv::KvStore,
+>;
+
+#[cfg(target_arch = "wasm32")]
+impl ProductionArbitrageExecutor {
+    /// Trade on the venue `preferences` select: the user's exchanges, or their paper
+    /// accounts while paper trading is on
+    pub fn for_preferences(
+        &self,
+        preferences: &UserTradingPreferences,
+    ) -> ArbitrageExecutor<ProductionVenue, worker::kv::KvStore> {
+        self.on_exchange(Arc::new(ProductionVenue::for_user(
+            preferences,
+            &self.exchange,
+            self.positions.kv_store(),
+        )))
+    }
+}
+
+pub struct ArbitrageExecutor<E: ExchangeInterface, K: KvOperations + Send + Sync + 'static> {
+    exchange: Arc<E>,
+    positions: Arc<PositionsService<K>>,
+    config: ExecutionConfig,
+    risk_gate: Option<RiskGate>,
+    approvals: Option<ExecutionApprovals<K>>,
+}
+
+impl<E: ExchangeInterface, K: KvOperations + Send + Sync + 'static> ArbitrageExecutor<E, K> {
+    pub fn new(exchange: Arc<E>, positions: Arc<PositionsService<K>>) -> Self {
+        Self {
+            exchange,
+            positions,
+            config: ExecutionConfig::default(),
+            risk_gate: None,
+            approvals: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: ExecutionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Check both legs together against the user's risk limits before either is sent; each
+    /// order is still checked on its own by the exchange it is placed on
+    pub fn with_risk_gate(mut self, risk_gate: RiskGate) -> Self {
+        self.risk_gate = Some(risk_gate);
+        self
+    }
+
+    /// Hold semi-automatic trades here until the user confirms them
+    pub fn with_approvals(mut self, approvals: ExecutionApprovals<K>) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
+    /// The same executor placing its orders on `exchange` instead
+    pub fn on_exchange<V: ExchangeInterface>(&self, exchange: Arc<V>) -> ArbitrageExecutor<V, K> {
+        ArbitrageExecutor {
+            exchange,
+            positions: self.positions.clone(),
+            config: self.config.clone(),
+            risk_gate: self.risk_gate.clone(),
+            approvals: self.approvals.clone(),
+        }
+    }
+
+    fn approvals(&self) -> ArbitrageResult<&ExecutionApprovals<K>> {
+        self.approvals
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::service_unavailable("Trade approvals not configured"))
+    }
+
+    /// Act on `opportunity` for a user according to their automation level: full-auto users
+    /// trade straight away, semi-auto users get a pending trade to confirm with `approve`.
+    pub async fn handle_opportunity(
+        &self,
+        preferences: &UserTradingPreferences,
+        opportunity: &ArbitrageOpportunity,
+        credentials: &[ExchangeCredentials],
+        size_usd: f64,
+    ) -> ArbitrageResult<ExecutionOutcome> {
+        match execution_decision(preferences) {
+            ExecutionDecision::Skip(reason) => Ok(ExecutionOutcome::Skipped(reason)),
+            ExecutionDecision::AwaitApproval => {
+                // Only ask for trades that could actually be sent
+                credentials_for_legs(opportunity, credentials)?;
+                self.approvals()?
+                    .request(
+                        &preferences.user_id,
+                        opportunity,
+                        size_usd,
+                        chrono::Utc::now().timestamp_millis() as u64,
+                    )
+                    .await
+                    .map(|pending| ExecutionOutcome::AwaitingApproval(Box::new(pending)))
+            }
+            ExecutionDecision::Execute => {
+                let (long, short) = credentials_for_legs(opportunity, credentials)?;
+                self.execute(&preferences.user_id, opportunity, long, short, size_usd)
+                    .await
+                    .map(|position| ExecutionOutcome::Executed(Box::new(position)))
+            }
+        }
+    }
+
+    /// Execute a pending trade the user confirmed. `None` when it expired, was already
+    /// answered or automation has been switched off since.
+    pub async fn approve(
+        &self,
+        preferences: &UserTradingPreferences,
+        pending_id: &str,
+        credentials: &[ExchangeCredentials],
+    ) -> ArbitrageResult<Option<ArbitragePosition>> {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let Some(pending) = self
+            .approvals()?
+            .take(&preferences.user_id, pending_id, now)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let (long, short) = credentials_for_legs(&pending.opportunity, credentials)?;
+        self.execute_for_preferences(
+            preferences,
+            true,
+            &pending.opportunity,
+            long,
+            short,
+            pending.size_usd,
+        )
+        .await
+    }
+
+    /// Drop a pending trade; `false` when there was nothing left to reject
+    pub async fn reject(&self, user_id: &str, pending_id: &str) -> ArbitrageResult<bool> {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        Ok(self
+            .approvals()?
+            .take(user_id, pending_id, now)
+            .await?
+            .is_some())
+    }
+
+    /// Execute on behalf of an automated user. Full-auto users trade straight away and
+    /// semi-auto users only once `approved`; `None` means nothing was sent.
+    pub async fn execute_for_preferences(
+        &self,
+        preferences: &UserTradingPreferences,
+        approved: bool,
+        opportunity: &ArbitrageOpportunity,
+        long_credentials: &ExchangeCredentials,
+        short_credentials: &ExchangeCredentials,
+        size_usd: f64,
+    ) -> ArbitrageResult<Option<ArbitragePosition>> {
+        match execution_decision(preferences) {
+            ExecutionDecision::Execute => {}
+            ExecutionDecision::AwaitApproval if approved => {}
+            ExecutionDecision::AwaitApproval | ExecutionDecision::Skip(_) => return Ok(None),
+        }
+        self.execute(
+            &preferences.user_id,
+            opportunity,
+            long_credentials,
+            short_credentials,
+            size_usd,
+        )
+        .await
+        .map(Some)
+    }
+
+    /// Open both legs of `opportunity` for up to `size_usd` each. The returned position is
+    /// `Open` when the legs match, `Failed` when everything was rolled back and
+    /// `PartiallyFilled` when an imbalance could neither be hedged nor unwound. A trade the
+    /// risk gate rejects fails before anything is saved or sent.
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        opportunity: &ArbitrageOpportunity,
+        long_credentials: &ExchangeCredentials,
+        short_credentials: &ExchangeCredentials,
+        size_usd: f64,
+    ) -> ArbitrageResult<ArbitragePosition> {
+        if opportunity.long_exchange == opportunity.short_exchange {
+            return Err(ArbitrageError::validation_error(
+                "Arbitrage legs must be on different exchanges",
+            ));
+        }
+        if size_usd <= 0.0 || !size_usd.is_finite() {
+            return Err(ArbitrageError::validation_error(format!(
+                "Position size must be positive, got {}",
+                size_usd
+            )));
+        }
+
+        let symbol = if opportunity.pair.is_empty() {
+            opportunity.trading_pair.clone()
+        } else {
+            opportunity.pair.clone()
+        };
+        let price = self.reference_price(opportunity, &symbol).await?;
+        let notional = size_usd.min(self.config.max_position_usd);
+        let amount = notional / price;
+
+        if let Some(risk_gate) = &self.risk_gate {
+            let trade = ProposedTrade {
+                user_id: user_id.to_string(),
+                pair: symbol.clone(),
+                legs: vec![
+                    (opportunity.long_exchange, notional),
+                    (opportunity.short_exchange, notional),
+                ],
+                liquidity_usd: (opportunity.volume > 0.0).then_some(opportunity.volume * price),
+            };
+            let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+            let positions = positions_for_risk(&self.positions, user_id, now_ms).await?;
+            risk_gate.enforce(&trade, &positions).await?;
+        }
+
+        let long_credentials = self.leg_credentials(user_id, long_credentials);
+        let short_credentials = self.leg_credentials(user_id, short_credentials);
+        let mut position = self.new_record(user_id, opportunity, &symbol, amount, notional);
+        position.execution_log.push(note(
+            ExecutionStep::Sized,
+            format!(
+                "{} {} per leg (~{:.2} notional at {})",
+                amount, symbol, notional, price
+            ),
+        ));
+        self.positions.save_position(&position).await?;
+
+        let tag = position.id.replace('-', "");
+        let long_request = self.leg_request(&symbol, "buy", amount, false, &tag[..12], "L");
+        let short_request = self.leg_request(&symbol, "sell", amount, false, &tag[..12], "S");
+        for (exchange, request) in [
+            (opportunity.long_exchange, &long_request),
+            (opportunity.short_exchange, &short_request),
+        ] {
+            position.execution_log.push(ExecutionEvent {
+                exchange: Some(exchange),
+                side: Some(request.side.clone()),
+                amount: Some(request.amount),
+                order_id: request.client_order_id.clone(),
+                ..note(ExecutionStep::LegSubmitted, "Market order sent")
+            });
+        }
+
+        let (mut long_leg, mut short_leg) = futures::join!(
+            self.fill_order(opportunity.long_exchange, &long_credentials, &long_request),
+            self.fill_order(
+                opportunity.short_exchange,
+                &short_credentials,
+                &short_request
+            ),
+        );
+        position
+            .execution_log
+            .push(long_leg.outcome_event("Long leg"));
+        position
+            .execution_log
+            .push(short_leg.outcome_event("Short leg"));
+
+        self.rebalance(
+            &mut position,
+            &symbol,
+            &tag[..12],
+            (&mut long_leg, &long_credentials),
+            (&mut short_leg, &short_credentials),
+        )
+        .await;
+        self.settle(&mut position, &long_leg, &short_leg);
+        self.positions.save_position(&position).await?;
+        if position.status == PositionStatus::Open {
+            // Rest whatever protective orders the position calls for on the exchanges
+            let credentials = [long_credentials, short_credentials];
+            if let Some(protected) = self
+                .positions
+                .sync_protective_orders(self.exchange.as_ref(), &credentials, &position.id)
+                .await?
+            {
+                position = protected;
+            }
+        }
+        Ok(position)
+    }
+
+    async fn reference_price(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        symbol: &str,
+    ) -> ArbitrageResult<f64> {
+        if opportunity.buy_price > 0.0 {
+            return Ok(opportunity.buy_price);
+        }
+        let ticker = self
+            .exchange
+            .get_ticker(opportunity.long_exchange.as_str(), symbol)
+            .await?;
+        ticker
+            .last
+            .or(ticker.close)
+            .filter(|price| *price > 0.0)
+            .ok_or_else(|| {
+                ArbitrageError::validation_error(format!(
+                    "No price available to size {} on {}",
+                    symbol,
+                    opportunity.long_exchange.as_str()
+                ))
+            })
+    }
+
+    fn leg_credentials(
+        &self,
+        user_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ExchangeCredentials {
+        let mut credentials = credentials.clone().with_user(user_id);
+        credentials.exchange_type = self.config.market_type.clone();
+        credentials
+    }
+
+    fn leg_request(
+        &self,
+        symbol: &str,
+        side: &str,
+        amount: f64,
+        reduce_only: bool,
+        tag: &str,
+        suffix: &str,
+    ) -> OrderRequest {
+        OrderRequest {
+            reduce_only,
+            client_order_id: Some(format!("arb{}{}", tag, suffix)),
+            market_type: Some(self.config.market_type.clone()),
+            ..OrderRequest::market(symbol, side, amount)
+        }
+    }
+
+    /// Place `request`, poll until it settles and cancel whatever is left at the deadline
+    async fn fill_order(
+        &self,
+        exchange: ExchangeIdEnum,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> LegFill {
+        let mut leg = LegFill::new(exchange, &request.side);
+        let exchange_id = exchange.as_str();
+        let mut order = match self
+            .exchange
+            .place_order(exchange_id, credentials, request)
+            .await
+        {
+            Ok(order) => order,
+            Err(e) => {
+                leg.error = Some(e.to_string());
+                return leg;
+            }
+        };
+
+        let deadline = chrono::Utc::now().timestamp_millis() as u64 + self.config.fill_timeout_ms;
+        while !is_settled(&order.status)
+            && (chrono::Utc::now().timestamp_millis() as u64) < deadline
+        {
+            sleep_ms(self.config.poll_interval_ms).await;
+            if let Ok(latest) = self
+                .exchange
+                .get_order(
+                    exchange_id,
+                    credentials,
+                    &order.id,
+                    &request.symbol,
+                    request.market_type.as_deref(),
+                )
+                .await
+            {
+                order = latest;
+            }
+        }
+        if !is_settled(&order.status) {
+            match self
+                .exchange
+                .cancel_order(exchange_id, credentials, &order.id, &request.symbol)
+                .await
+            {
+                Ok(cancelled) => order = cancelled,
+                Err(e) => leg.error = Some(format!("remainder could not be cancelled: {}", e)),
+            }
+            leg.cancelled = true;
+        }
+
+        leg.order_id = Some(order.id.clone());
+        leg.filled = order.filled;
+        leg.average_price = order.average.or(order.price);
+        leg.fees = order.fee.as_ref().map(|fee| fee.cost).unwrap_or(0.0);
+        leg
+    }
+
+    fn is_balanced(&self, long: f64, short: f64) -> bool {
+        (long - short).abs() <= self.config.balance_tolerance * long.max(short)
+    }
+
+    /// Hedge the lagging leg, then unwind the leading leg's excess if it is still uneven
+    async fn rebalance(
+        &self,
+        position: &mut ArbitragePosition,
+        symbol: &str,
+        tag: &str,
+        long: (&mut LegFill, &ExchangeCredentials),
+        short: (&mut LegFill, &ExchangeCredentials),
+    ) {
+        if self.is_balanced(long.0.filled, short.0.filled) {
+            return;
+        }
+        let ((lagging, lagging_credentials), (leading, leading_credentials)) =
+            if long.0.filled > short.0.filled {
+                (short, long)
+            } else {
+                (long, short)
+            };
+
+        // A leg whose exchange rejected the order is not retried; only partial fills are topped up
+        if lagging.error.is_none() {
+            let missing = leading.filled - lagging.filled;
+            let request = self.leg_request(symbol, &lagging.side, missing, false, tag, "H");
+            let hedge = self
+                .fill_order(lagging.exchange, lagging_credentials, &request)
+                .await;
+            position.execution_log.push(match &hedge.error {
+                Some(error) if hedge.filled <= 0.0 => hedge.event(
+                    ExecutionStep::LegFailed,
+                    format!("Hedge of {} failed: {}", missing, error),
+                ),
+                _ => hedge.event(
+                    ExecutionStep::Hedged,
+                    format!("Topped up lagging leg by {} of {}", hedge.filled, missing),
+                ),
+            });
+            lagging.add(&hedge);
+            if self.is_balanced(leading.filled, lagging.filled) {
+                return;
+            }
+        }
+
+        let excess = leading.filled - lagging.filled;
+        let request =
+            self.leg_request(symbol, opposite_side(&leading.side), excess, true, tag, "U");
+        let unwind = self
+            .fill_order(leading.exchange, leading_credentials, &request)
+            .await;
+        position.execution_log.push(match &unwind.error {
+            Some(error) if unwind.filled <= 0.0 => unwind.event(
+                ExecutionStep::LegFailed,
+                format!("Unwind of {} failed: {}", excess, error),
+            ),
+            _ => unwind.event(
+                ExecutionStep::Unwound,
+                format!(
+                    "Closed {} of the leading leg's {} excess",
+                    unwind.filled, excess
+                ),
+            ),
+        });
+        leading.reduce(&unwind);
+    }
+
+    fn settle(&self, position: &mut ArbitragePosition, long: &LegFill, short: &LegFill) {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        for (leg, record) in [
+            (long, &mut position.long_position),
+            (short, &mut position.short_position),
+        ] {
+            record.id = leg.order_id.clone();
+            record.amount = leg.filled;
+            record.entry_price = leg.average_price;
+            record.notional = leg.average_price.map(|price| price * leg.filled);
+        }
+        position.entry_price_long = long.average_price.unwrap_or(0.0);
+        position.entry_price_short = short.average_price.unwrap_or(0.0);
+        position.size = Some(long.filled.min(short.filled));
+        position.calculated_size_usd = long.average_price.map(|price| price * long.filled);
+        position.long_fees = long.fees;
+        position.short_fees = short.fees;
+        refresh_pnl(position);
+        position.updated_at = now;
+
+        let (status, state, event) = if long.filled <= 0.0 && short.filled <= 0.0 {
+            (
+                PositionStatus::Failed,
+                "rolled_back",
+                note(
+                    ExecutionStep::RolledBack,
+                    "No exposure left on either exchange",
+                ),
+            )
+        } else if self.is_balanced(long.filled, short.filled) {
+            (
+                PositionStatus::Open,
+                "open",
+                note(
+                    ExecutionStep::Opened,
+                    format!("Both legs open at {}", long.filled.min(short.filled)),
+                ),
+            )
+        } else {
+            (
+                PositionStatus::PartiallyFilled,
+                "unhedged",
+                note(
+                    ExecutionStep::Unhedged,
+                    format!(
+                        "Legs still differ (long {}, short {}); manual action required",
+                        long.filled, short.filled
+                    ),
+                ),
+            )
+        };
+        position.status = status;
+        position.current_state = Some(state.to_string());
+        if position.status == PositionStatus::Failed {
+            position.exit_time = Some(now);
+            position.closed_at = Some(now);
+        }
+        position.execution_log.push(event);
+    }
+
+    fn new_record(
+        &self,
+        user_id: &str,
+        opportunity: &ArbitrageOpportunity,
+        symbol: &str,
+        amount: f64,
+        notional: f64,
+    ) -> ArbitragePosition {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let info = serde_json::json!({});
+        ArbitragePosition {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            opportunity_id: opportunity.id.clone(),
+            long_position: new_position(symbol, "long", 0.0, Some(now), &info),
+            short_position: new_position(symbol, "short", 0.0, Some(now), &info),
+            status: PositionStatus::PartiallyFilled,
+            entry_time: now,
+            exit_time: None,
+            realized_pnl: 0.0,
+            unrealized_pnl: 0.0,
+            total_fees: 0.0,
+            risk_score: 0.5,
+            margin_used: 0.0,
+            symbol: symbol.to_string(),
+            side: PositionSide::Both,
+            entry_price_long: 0.0,
+            entry_price_short: 0.0,
+            take_profit_price: None,
+            volatility_score: None,
+            calculated_size_usd: Some(notional),
+            long_exchange: opportunity.long_exchange,
+            short_exchange: opportunity.short_exchange,
+            size: Some(amount),
+            pnl: Some(0.0),
+            unrealized_pnl_percentage: Some(0.0),
+            max_drawdown: None,
+            created_at: now,
+            holding_period_hours: None,
+            trailing_stop_distance: None,
+            stop_loss_price: None,
+            current_price: None,
+            current_price_long: None,
+            current_price_short: None,
+            max_loss_usd: None,
+            exchange: opportunity.long_exchange,
+            pair: symbol.to_string(),
+            related_positions: Vec::new(),
+            closed_at: None,
+            updated_at: now,
+            risk_reward_ratio: None,
+            last_optimization_check: None,
+            hedge_position_id: None,
+            position_group_id: None,
+            current_state: Some("executing".to_string()),
+            recommended_action: None,
+            risk_percentage_applied: None,
+            optimization_score: None,
+            execution_log: Vec::new(),
+            long_fees: 0.0,
+            short_fees: 0.0,
+            funding_pnl: 0.0,
+            predicted_rate_difference: Some(opportunity.rate_difference),
+            exchange_protection: false,
+            protective_orders: Vec::new(),
+            liquidation_alert_threshold: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::user::user_trading_preferences::AutomationScope;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::test_utils::scripted_exchange::{order, ScriptedExchange};
+    use crate::types::RiskManagementConfig;
+
+    fn executor(
+        exchange: ScriptedExchange,
+    ) -> (
+        ArbitrageExecutor<ScriptedExchange, MockKvStore>,
+        Arc<ScriptedExchange>,
+        Arc<PositionsService<MockKvStore>>,
+    ) {
+        let exchange = Arc::new(exchange);
+        let positions = Arc::new(PositionsService::new(Arc::new(MockKvStore::new())));
+        let executor = ArbitrageExecutor::new(exchange.clone(), positions.clone()).with_config(
+            ExecutionConfig {
+                fill_timeout_ms: 5,
+                poll_interval_ms: 1,
+                ..ExecutionConfig::default()
+            },
+        );
+        (executor, exchange, positions)
+    }
+
+    fn opportunity() -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: "opp-1".to_string(),
+            pair: "BTC/USDT".to_string(),
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            buy_price: 65_000.0,
+            ..ArbitrageOpportunity::default()
+        }
+    }
+
+    fn creds(exchange: ExchangeIdEnum) -> ExchangeCredentials {
+        ExchangeCredentials::new(
+            exchange,
+            "key".to_string(),
+            "secret".to_string(),
+            None,
+            false,
+        )
+    }
+
+    fn steps(position: &ArbitragePosition) -> Vec<ExecutionStep> {
+        position.execution_log.iter().map(|e| e.step).collect()
+    }
+
+    async fn run(exchange: ScriptedExchange) -> (ArbitragePosition, Arc<ScriptedExchange>) {
+        let (executor, exchange, positions) = executor(exchange);
+        let position = executor
+            .execute(
+                "user-1",
+                &opportunity(),
+                &creds(ExchangeIdEnum::Binance),
+                &creds(ExchangeIdEnum::Bybit),
+                6_500.0,
+            )
+            .await
+            .unwrap();
+        let stored = positions.get_position(&position.id).await.unwrap().unwrap();
+        assert_eq!(stored.execution_log.len(), position.execution_log.len());
+        (position, exchange)
+    }
+
+    #[tokio::test]
+    async fn test_both_legs_filled_opens_position() {
+        let (position, exchange) = run(ScriptedExchange::default()).await;
+
+        assert_eq!(position.status, PositionStatus::Open);
+        assert!((position.long_position.amount - 0.1).abs() < 1e-9);
+        assert!((position.short_position.amount - 0.1).abs() < 1e-9);
+        assert_eq!(
+            steps(&position),
+            vec![
+                ExecutionStep::Sized,
+                ExecutionStep::LegSubmitted,
+                ExecutionStep::LegSubmitted,
+                ExecutionStep::LegFilled,
+                ExecutionStep::LegFilled,
+                ExecutionStep::Opened,
+            ]
+        );
+        let sent = exchange.sent();
+        assert_eq!(sent.len(), 2);
+        assert!(sent
+            .iter()
+            .all(|(_, request)| request.market_type.as_deref() == Some("futures")));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_leg_unwinds_the_filled_leg() {
+        let (position, exchange) = run(ScriptedExchange::default().script(
+            ExchangeIdEnum::Bybit,
+            Err(ArbitrageError::exchange_error(
+                "bybit",
+                "insufficient margin",
+            )),
+        ))
+        .await;
+
+        assert_eq!(position.status, PositionStatus::Failed);
+        assert_eq!(position.current_state.as_deref(), Some("rolled_back"));
+        let unwind = &exchange.sent()[2];
+        assert_eq!(unwind.0, ExchangeIdEnum::Binance);
+        assert_eq!(unwind.1.side, "sell");
+        assert!(unwind.1.reduce_only);
+        assert!((unwind.1.amount - 0.1).abs() < 1e-9);
+        let steps = steps(&position);
+        assert!(steps.contains(&ExecutionStep::LegFailed));
+        assert!(steps.ends_with(&[ExecutionStep::Unwound, ExecutionStep::RolledBack]));
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_is_hedged_on_the_lagging_exchange() {
+        let (position, exchange) = run(ScriptedExchange::default().script(
+            ExchangeIdEnum::Bybit,
+            Ok(order("partial", "sell", 0.1, 0.06, "open")),
+        ))
+        .await;
+
+        assert_eq!(position.status, PositionStatus::Open);
+        assert!((position.short_position.amount - 0.1).abs() < 1e-9);
+        let hedge = &exchange.sent()[2];
+        assert_eq!(hedge.0, ExchangeIdEnum::Bybit);
+        assert!(!hedge.1.reduce_only);
+        assert!((hedge.1.amount - 0.04).abs() < 1e-9);
+        assert!(steps(&position).ends_with(&[
+            ExecutionStep::LegCancelled,
+            ExecutionStep::Hedged,
+            ExecutionStep::Opened
+        ]));
+    }
+
+    #[test]
+    fn test_execution_decision_follows_automation_level() {
+        let mut preferences = UserTradingPreferences::new_default("user-1".to_string());
+        assert!(matches!(
+            execution_decision(&preferences),
+            ExecutionDecision::Skip(_)
+        ));
+
+        preferences.automation_level = AutomationLevel::SemiAuto;
+        preferences.automation_scope = AutomationScope::ArbitrageOnly;
+        assert_eq!(
+            execution_decision(&preferences),
+            ExecutionDecision::AwaitApproval
+        );
+
+        preferences.automation_level = AutomationLevel::FullAuto;
+        preferences.automation_scope = AutomationScope::TechnicalOnly;
+        assert!(matches!(
+            execution_decision(&preferences),
+            ExecutionDecision::Skip(_)
+        ));
+
+        preferences.automation_scope = AutomationScope::Both;
+        assert_eq!(execution_decision(&preferences), ExecutionDecision::Execute);
+    }
+
+    fn automated(level: AutomationLevel) -> UserTradingPreferences {
+        let mut preferences = UserTradingPreferences::new_default("user-1".to_string());
+        preferences.automation_level = level;
+        preferences.automation_scope = AutomationScope::ArbitrageOnly;
+        preferences
+    }
+
+    #[tokio::test]
+    async fn test_semi_auto_waits_for_approval_before_sending() {
+        let (executor, exchange, _) = executor(ScriptedExchange::default());
+        let executor =
+            executor.with_approvals(ExecutionApprovals::new(Arc::new(MockKvStore::new())));
+        let preferences = automated(AutomationLevel::SemiAuto);
+        let credentials = [creds(ExchangeIdEnum::Binance), creds(ExchangeIdEnum::Bybit)];
+
+        let outcome = executor
+            .handle_opportunity(&preferences, &opportunity(), &credentials, 6_500.0)
+            .await
+            .unwrap();
+        let ExecutionOutcome::AwaitingApproval(pending) = outcome else {
+            panic!("expected a pending trade, got {:?}", outcome);
+        };
+        assert!(exchange.sent().is_empty());
+
+        let position = executor
+            .approve(&preferences, &pending.id, &credentials)
+            .await
+            .unwrap()
+            .expect("approved trade executes");
+        assert_eq!(position.status, PositionStatus::Open);
+        assert_eq!(exchange.sent().len(), 2);
+
+        // An approval is used once
+        assert!(executor
+            .approve(&preferences, &pending.id, &credentials)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_or_expired_approvals_send_nothing() {
+        let kv = Arc::new(MockKvStore::new());
+        let (executor, exchange, _) = executor(ScriptedExchange::default());
+        let executor = executor.with_approvals(ExecutionApprovals::new(kv.clone()));
+        let preferences = automated(AutomationLevel::SemiAuto);
+        let credentials = [creds(ExchangeIdEnum::Binance), creds(ExchangeIdEnum::Bybit)];
+
+        let rejected = ExecutionApprovals::new(kv.clone())
+            .request("user-1", &opportunity(), 6_500.0, 0)
+            .await
+            .unwrap();
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let fresh = ExecutionApprovals::new(kv.clone())
+            .request("user-1", &opportunity(), 6_500.0, now)
+            .await
+            .unwrap();
+
+        assert!(executor.reject("user-1", &fresh.id).await.unwrap());
+        assert!(executor
+            .approve(&preferences, &fresh.id, &credentials)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(executor
+            .approve(&preferences, &rejected.id, &credentials)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(exchange.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_full_auto_executes_and_manual_is_skipped() {
+        let (executor, exchange, _) = executor(ScriptedExchange::default());
+        let credentials = [creds(ExchangeIdEnum::Binance), creds(ExchangeIdEnum::Bybit)];
+
+        let outcome = executor
+            .handle_opportunity(
+                &automated(AutomationLevel::Manual),
+                &opportunity(),
+                &credentials,
+                6_500.0,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ExecutionOutcome::Skipped(_)));
+        assert!(exchange.sent().is_empty());
+
+        let error = executor
+            .handle_opportunity(
+                &automated(AutomationLevel::FullAuto),
+                &opportunity(),
+                &credentials[..1],
+                6_500.0,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("bybit"));
+
+        let outcome = executor
+            .handle_opportunity(
+                &automated(AutomationLevel::FullAuto),
+                &opportunity(),
+                &credentials,
+                6_500.0,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ExecutionOutcome::Executed(_)));
+        assert_eq!(exchange.sent().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_risk_gate_rejection_sends_nothing() {
+        let (executor, exchange, positions) = executor(ScriptedExchange::default());
+        let executor = executor.with_risk_gate(RiskGate::new(RiskManagementConfig {
+            max_position_size_usd: 5_000.0,
+            ..RiskManagementConfig::default()
+        }));
+
+        let error = executor
+            .execute(
+                "user-1",
+                &opportunity(),
+                &creds(ExchangeIdEnum::Binance),
+                &creds(ExchangeIdEnum::Bybit),
+                6_500.0,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.error_code.as_deref(), Some("RISK_LIMIT"));
+        assert!(exchange.sent().is_empty());
+        assert!(positions.get_all_positions().await.unwrap().is_empty());
+    }
+}
@@ -1,18 +1,29 @@
 // src/services/exchange.rs
 
-use reqwest::Client;
-use serde_json::{json, Value};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use worker::Method;
+use std::sync::Arc;
 
-use crate::services::core::user::user_exchange_api::RateLimitInfo;
+use crate::services::core::trading::adapters::{ExchangeAdapter, ExchangeAdapterRegistry};
+use crate::services::core::trading::exchange_rest::{
+    is_futures_market, ExchangeRestClient, DEFAULT_ORDERBOOK_LIMIT,
+};
+use crate::services::core::trading::risk_gate::{order_notional, OrderRiskCheck};
+use crate::services::core::user::user_exchange_api::{ApiKeyPermissions, RateLimitInfo};
 use crate::services::core::user::user_profile::UserProfileService;
 use crate::types::{
-    CommandPermission, ExchangeCredentials, ExchangeIdEnum, Market, Order, OrderBook, Position,
-    Ticker, TradingFeeRates, TradingFees,
+    CommandPermission, ExchangeCredentials, ExchangeIdEnum, FundingPayment, MarginMode, Market,
+    Order, OrderBook, OrderRequest, Position, Ticker, TradingFees,
 };
 use crate::utils::{ArbitrageError, ArbitrageResult};
 
+/// How long exchange market metadata stays cached in KV
+const MARKETS_CACHE_TTL_SECONDS: u64 = 3600;
+
+/// How long an account's fee tier stays cached; tiers move at most daily with 30-day volume
+const TRADING_FEES_CACHE_TTL_SECONDS: u64 = 6 * 3600;
+
 // Exchange authentication helper
 
 pub trait ExchangeInterface {
@@ -29,6 +40,16 @@ pub trait ExchangeInterface {
         limit: Option<u32>,
     ) -> ArbitrageResult<OrderBook>;
 
+    /// Order book for a specific market ("spot" or a futures type such as "linear"/"swap")
+    #[allow(async_fn_in_trait)]
+    async fn get_market_orderbook(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        limit: Option<u32>,
+        market_type: &str,
+    ) -> ArbitrageResult<OrderBook>;
+
     #[allow(async_fn_in_trait)]
     async fn fetch_funding_rates(
         &self,
@@ -54,6 +75,15 @@ pub trait ExchangeInterface {
         price: Option<f64>,
     ) -> ArbitrageResult<Order>;
 
+    /// Place an order with explicit order type, time-in-force and trigger parameters
+    #[allow(async_fn_in_trait)]
+    async fn place_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order>;
+
     #[allow(async_fn_in_trait)]
     async fn cancel_order(
         &self,
@@ -63,6 +93,17 @@ pub trait ExchangeInterface {
         symbol: &str,
     ) -> ArbitrageResult<Order>;
 
+    /// Current state of an order placed with `market_type` (defaults to the credentials')
+    #[allow(async_fn_in_trait)]
+    async fn get_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        order_id: &str,
+        symbol: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order>;
+
     #[allow(async_fn_in_trait)]
     async fn get_open_orders(
         &self,
@@ -79,6 +120,16 @@ pub trait ExchangeInterface {
         symbol: Option<&str>,
     ) -> ArbitrageResult<Vec<Position>>;
 
+    /// Funding settled on the account's `symbol` perpetual since `since` (ms), oldest first
+    #[allow(async_fn_in_trait)]
+    async fn get_funding_payments(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>>;
+
     #[allow(async_fn_in_trait)]
     async fn set_leverage(
         &self,
@@ -88,6 +139,24 @@ pub trait ExchangeInterface {
         leverage: u32,
     ) -> ArbitrageResult<()>;
 
+    #[allow(async_fn_in_trait)]
+    async fn set_margin_mode(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()>;
+
+    #[allow(async_fn_in_trait)]
+    async fn adjust_isolated_margin(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()>;
+
     #[allow(async_fn_in_trait)]
     async fn get_trading_fees(
         &self,
@@ -102,6 +171,7 @@ pub trait ExchangeInterface {
         exchange_id: &str,
         api_key: &str,
         secret: &str,
+        passphrase: Option<&str>,
     ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)>;
 
     #[allow(async_fn_in_trait)]
@@ -110,9 +180,18 @@ pub trait ExchangeInterface {
         exchange_id: &str,
         api_key: &str,
         secret: &str,
+        passphrase: Option<&str>,
         leverage: Option<i32>,
         exchange_type: Option<&str>,
     ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)>;
+
+    /// What the key may do on the exchange. Keys with withdrawal permission are rejected.
+    #[allow(async_fn_in_trait)]
+    async fn probe_api_key(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions>;
 }
 
 // RBAC-protected exchange operations are now handled by UserExchangeApiService
@@ -175,7 +254,13 @@ impl ApiKeySource {
     }
 
     pub fn validate_for_operation(&self, operation: &str) -> ArbitrageResult<()> {
-        let trading_operations = ["create_order", "cancel_order", "set_leverage"];
+        let trading_operations = [
+            "create_order",
+            "place_order",
+            "cancel_order",
+            "set_leverage",
+            "set_margin_mode",
+        ];
 
         if trading_operations.contains(&operation) && !self.can_execute_trades() {
             return Err(ArbitrageError::validation_error(format!(
@@ -191,11 +276,48 @@ impl ApiKeySource {
 #[allow(dead_code)]
 #[derive(Clone)]
 pub struct ExchangeService {
-    client: Client,
+    adapters: ExchangeAdapterRegistry,
     kv: worker::kv::KvStore,
     super_admin_configs: std::collections::HashMap<String, SuperAdminApiConfig>,
     user_profile_service: Option<UserProfileService>, // Optional for initialization, required for RBAC
-                                                      // hybrid_data_access: Option<crate::services::core::infrastructure::HybridDataAccessService>, // Pipeline integration
+    // hybrid_data_access: Option<crate::services::core::infrastructure::HybridDataAccessService>, // Pipeline integration
+    /// Every order is checked here before it is sent; without one no order is placed
+    risk_check: Option<Arc<dyn OrderRiskCheck + Send + Sync>>,
+}
+
+/// Default limits over the positions stored in `kv`
+#[cfg(target_arch = "wasm32")]
+fn default_risk_check(kv: &worker::kv::KvStore) -> Option<Arc<dyn OrderRiskCheck + Send + Sync>> {
+    use crate::services::core::trading::positions::PositionsService;
+    use crate::services::core::trading::risk_gate::{PositionRiskCheck, RiskGate};
+
+    Some(Arc::new(PositionRiskCheck::new(
+        RiskGate::new(crate::types::RiskManagementConfig::default()),
+        Arc::new(PositionsService::new(Arc::new(kv.clone()))),
+    )))
+}
+
+/// Native builds have no KV-backed positions to check against, so orders pass unless a
+/// check is injected with `set_risk_check`
+#[cfg(not(target_arch = "wasm32"))]
+struct UncheckedOrders;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait::async_trait(?Send)]
+impl OrderRiskCheck for UncheckedOrders {
+    async fn check_order(
+        &self,
+        _credentials: &ExchangeCredentials,
+        _request: &OrderRequest,
+        _notional_usd: f64,
+    ) -> ArbitrageResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_risk_check(_kv: &worker::kv::KvStore) -> Option<Arc<dyn OrderRiskCheck + Send + Sync>> {
+    Some(Arc::new(UncheckedOrders))
 }
 
 impl ExchangeService {
@@ -212,10 +334,9 @@ impl ExchangeService {
                 ))
             })?;
 
-        let client = Client::new();
-
         Ok(Self {
-            client,
+            adapters: ExchangeAdapterRegistry::with_defaults(ExchangeRestClient::new()),
+            risk_check: default_risk_check(&kv),
             kv,
             super_admin_configs: std::collections::HashMap::new(),
             user_profile_service: None, // Will be injected via set_user_profile_service
@@ -231,16 +352,64 @@ impl ExchangeService {
                 ArbitrageError::internal_error(format!("Failed to create mock KV: {}", e))
             })?;
 
-        let client = Client::new();
-
         Ok(Self {
-            client,
+            adapters: ExchangeAdapterRegistry::with_defaults(ExchangeRestClient::new()),
             kv: mock_kv,
             super_admin_configs: HashMap::new(),
             user_profile_service: None,
+            risk_check: None,
         })
     }
 
+    /// The pre-trade check live orders go through, to hold paper orders to the same limits
+    pub fn risk_check(&self) -> Option<Arc<dyn OrderRiskCheck + Send + Sync>> {
+        self.risk_check.clone()
+    }
+
+    /// Replace the pre-trade check, e.g. to apply admin limit overrides
+    pub fn set_risk_check(&mut self, risk_check: Arc<dyn OrderRiskCheck + Send + Sync>) {
+        self.risk_check = Some(risk_check);
+    }
+
+    /// Rebuild the built-in adapters on another REST client (e.g. to target testnet or a mock server)
+    pub fn set_rest_client(&mut self, rest_client: ExchangeRestClient) {
+        self.adapters = ExchangeAdapterRegistry::with_defaults(rest_client);
+    }
+
+    /// Add or replace the adapter serving one exchange
+    pub fn register_adapter(&mut self, adapter: Arc<dyn ExchangeAdapter>) {
+        self.adapters.register(adapter);
+    }
+
+    /// Exchanges with a registered adapter
+    pub fn supported_exchanges(&self) -> Vec<ExchangeIdEnum> {
+        self.adapters.supported_exchanges()
+    }
+
+    /// Market metadata for one symbol. `market_type` selects between the spot pair and the
+    /// perpetual contract that share a symbol.
+    pub async fn get_market(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        market_type: &str,
+    ) -> ArbitrageResult<Market> {
+        let compact = symbol.replace(['/', '-', '_'], "").to_uppercase();
+        let futures = is_futures_market(market_type);
+        self.get_markets(exchange_id)
+            .await?
+            .into_iter()
+            .find(|market| market.symbol == compact && market.contract == futures)
+            .ok_or_else(|| {
+                ArbitrageError::not_found(format!(
+                    "No {} market for {} on {}",
+                    if futures { "futures" } else { "spot" },
+                    symbol,
+                    exchange_id
+                ))
+            })
+    }
+
     /// Set the UserProfile service for database-based RBAC
     pub fn set_user_profile_service(&mut self, user_profile_service: UserProfileService) {
         self.user_profile_service = Some(user_profile_service);
@@ -333,222 +502,46 @@ impl ExchangeService {
         api_source.validate_for_operation(operation)
     }
 
-    // Temporarily removed hybrid data access methods to fix compilation
-    // Will be re-implemented after fixing module dependencies
-    /// Get Binance funding rate using real API
-    pub async fn get_binance_funding_rate(
+    /// Current funding rate of the linear perpetual for `symbol`
+    pub async fn get_funding_rate_direct(
         &self,
+        exchange_id: &str,
         symbol: &str,
     ) -> ArbitrageResult<crate::types::FundingRateInfo> {
-        // Binance Funding Rate History API - get the latest funding rate
-        let endpoint = "/fapi/v1/fundingRate";
-        let params = json!({
-            "symbol": symbol,
-            "limit": 1
-        });
-
-        let response = self
-            .binance_futures_request(endpoint, Method::Get, Some(params), None)
-            .await?;
-
-        // Response is an array, get the first (latest) entry
-        if let Some(rate_data) = response.as_array().and_then(|arr| arr.first()) {
-            let funding_rate = rate_data["fundingRate"]
-                .as_str()
-                .and_then(|s| s.parse::<f64>().ok())
-                .unwrap_or(0.0);
-
-            let funding_time = rate_data["fundingTime"]
-                .as_u64()
-                .and_then(|ts| chrono::DateTime::from_timestamp((ts / 1000) as i64, 0));
-
-            // Note: Binance funding rate API doesn't provide markPrice field
-            // Only returns symbol, fundingRate, and fundingTime
-
-            Ok(crate::types::FundingRateInfo {
-                symbol: symbol.to_string(),
-                funding_rate,
-                timestamp: chrono::Utc::now().timestamp_millis() as u64,
-                datetime: chrono::Utc::now().to_rfc3339(),
-                next_funding_time: funding_time.map(|dt| dt.timestamp_millis() as u64),
-                estimated_rate: Some(funding_rate),
-                estimated_settle_price: None,
-                exchange: ExchangeIdEnum::Binance,
-                funding_interval_hours: 8,
-                mark_price: None,
-                index_price: None,
-                funding_countdown: None,
-                info: rate_data.clone(),
-            })
-        } else {
-            Err(ArbitrageError::not_found(format!(
-                "No funding rate data found for Binance:{}",
-                symbol
-            )))
-        }
+        self.adapters
+            .resolve(exchange_id)?
+            .get_funding_rate(symbol)
+            .await
     }
 
-    /// Get Bybit funding rate using real API
-    pub async fn get_bybit_funding_rate(
-        &self,
-        symbol: &str,
-    ) -> ArbitrageResult<crate::types::FundingRateInfo> {
-        // Bybit V5 funding rate history API
-        let endpoint = "/v5/market/funding/history";
-        let params = json!({
-            "category": "linear",
-            "symbol": symbol,
-            "limit": 1
-        });
-
-        let response = self
-            .bybit_request(endpoint, Method::Get, Some(params), None)
-            .await?;
-
-        if let Some(list) = response["result"]["list"].as_array() {
-            if let Some(rate_data) = list.first() {
-                let funding_rate = rate_data["fundingRate"]
-                    .as_str()
-                    .and_then(|s| s.parse::<f64>().ok())
-                    .unwrap_or(0.0);
-
-                let funding_time = rate_data["fundingRateTimestamp"]
-                    .as_str()
-                    .and_then(|s| s.parse::<i64>().ok())
-                    .and_then(|ts| chrono::DateTime::from_timestamp(ts / 1000, 0));
-
-                return Ok(crate::types::FundingRateInfo {
-                    symbol: symbol.to_string(),
-                    funding_rate,
-                    timestamp: chrono::Utc::now().timestamp_millis() as u64,
-                    datetime: chrono::Utc::now().to_rfc3339(),
-                    next_funding_time: funding_time.map(|dt| dt.timestamp_millis() as u64),
-                    estimated_rate: Some(funding_rate),
-                    estimated_settle_price: None,
-                    exchange: ExchangeIdEnum::Bybit,
-                    funding_interval_hours: 8,
-                    mark_price: None,
-                    index_price: None,
-                    funding_countdown: None,
-                    info: rate_data.clone(),
-                });
-            }
-        }
-
-        Err(ArbitrageError::not_found(format!(
-            "No funding rate data found for Bybit:{}",
-            symbol
-        )))
+    /// Stable per-account cache id that keeps the raw API key out of KV key names
+    fn account_cache_id(credentials: &ExchangeCredentials) -> String {
+        let digest = Sha256::digest(credentials.api_key.as_bytes());
+        hex::encode(&digest[..8])
     }
 
-    /// Get funding rate directly from exchange APIs using real implementations
-    pub async fn get_funding_rate_direct(
-        &self,
-        exchange_id: &str,
-        symbol: &str,
-    ) -> ArbitrageResult<crate::types::FundingRateInfo> {
-        match exchange_id {
-            "binance" => self.get_binance_funding_rate(symbol).await,
-            "bybit" => self.get_bybit_funding_rate(symbol).await,
-            _ => Err(ArbitrageError::not_implemented(format!(
-                "Funding rate not implemented for exchange: {}",
-                exchange_id
-            ))),
+    /// Best-effort KV write: a cache failure must not fail the call that produced `value`
+    async fn cache_put<T: serde::Serialize>(&self, key: &str, value: &T, ttl_seconds: u64) {
+        match serde_json::to_string(value) {
+            Ok(serialized) => match self.kv.put(key, serialized) {
+                Ok(put) => {
+                    if let Err(e) = put.expiration_ttl(ttl_seconds).execute().await {
+                        worker::console_log!("⚠️ Failed to cache {}: {}", key, e);
+                    }
+                }
+                Err(e) => worker::console_log!("⚠️ Failed to cache {}: {}", key, e),
+            },
+            Err(e) => worker::console_log!("⚠️ Failed to serialize {}: {}", key, e),
         }
     }
-
-    // Exchange-specific implementations
-    async fn binance_request(
-        &self,
-        endpoint: &str,
-        method: Method,
-        params: Option<Value>,
-        auth: Option<&ExchangeCredentials>,
-    ) -> ArbitrageResult<Value> {
-        Box::pin(self.binance_request_with_retry(endpoint, method, params, auth, 3)).await
-    }
 }
 
 impl ExchangeInterface for ExchangeService {
-    async fn get_ticker(&self, _exchange_id: &str, symbol: &str) -> ArbitrageResult<Ticker> {
-        // Implementation for getting ticker data
-        let endpoint = format!("/api/v3/ticker/24hr?symbol={}", symbol);
-        let response = self
-            .binance_request(&endpoint, Method::Get, None, None)
-            .await?;
-
-        // Parse response into Ticker
-        Ok(Ticker {
-            symbol: symbol.to_string(),
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            datetime: chrono::Utc::now().to_rfc3339(),
-            high: response
-                .get("highPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            low: response
-                .get("lowPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            bid: response
-                .get("bidPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            bid_volume: response
-                .get("bidQty")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            ask: response
-                .get("askPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            ask_volume: response
-                .get("askQty")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            vwap: response
-                .get("weightedAvgPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            open: response
-                .get("openPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            close: response
-                .get("lastPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            last: response
-                .get("lastPrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            previous_close: response
-                .get("prevClosePrice")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            change: response
-                .get("priceChange")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            percentage: response
-                .get("priceChangePercent")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            average: None,
-            base_volume: response
-                .get("volume")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            quote_volume: response
-                .get("quoteVolume")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            volume: response
-                .get("volume")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse().ok()),
-            info: response,
-        })
+    async fn get_ticker(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<Ticker> {
+        self.adapters
+            .resolve(exchange_id)?
+            .get_ticker(symbol, false)
+            .await
     }
 
     async fn fetch_funding_rates(
@@ -556,273 +549,322 @@ impl ExchangeInterface for ExchangeService {
         exchange_id: &str,
         symbol: Option<&str>,
     ) -> ArbitrageResult<Vec<Value>> {
-        match exchange_id {
-            "binance" => {
-                if let Some(symbol) = symbol {
-                    self.get_binance_funding_rate(symbol)
-                        .await
-                        .map(|rate| vec![serde_json::to_value(rate).unwrap_or_default()])
-                } else {
-                    // Return all funding rates
-                    Ok(vec![])
-                }
-            }
-            "bybit" => {
-                if let Some(symbol) = symbol {
-                    self.get_bybit_funding_rate(symbol)
-                        .await
-                        .map(|rate| vec![serde_json::to_value(rate).unwrap_or_default()])
-                } else {
-                    // Return all funding rates
-                    Ok(vec![])
-                }
-            }
-            _ => Err(crate::utils::ArbitrageError::exchange_error(
-                exchange_id,
-                "Unsupported exchange",
-            )),
+        let adapter = self.adapters.resolve(exchange_id)?;
+        match symbol {
+            Some(symbol) => adapter
+                .get_funding_rate(symbol)
+                .await
+                .map(|rate| vec![serde_json::to_value(rate).unwrap_or_default()]),
+            // Return all funding rates
+            None => Ok(vec![]),
         }
     }
 
-    async fn get_markets(&self, _exchange_id: &str) -> ArbitrageResult<Vec<Market>> {
-        // Placeholder implementation
-        Ok(vec![])
+    async fn get_markets(&self, exchange_id: &str) -> ArbitrageResult<Vec<Market>> {
+        let exchange = exchange_id
+            .parse::<ExchangeIdEnum>()
+            .map_err(ArbitrageError::validation_error)?;
+        let cache_key = format!("markets:{}", exchange.as_str());
+
+        if let Ok(Some(markets)) = self.kv.get(&cache_key).json::<Vec<Market>>().await {
+            return Ok(markets);
+        }
+
+        let adapter = self.adapters.get(exchange)?;
+        let mut markets = adapter.get_markets(false).await?;
+        markets.extend(adapter.get_markets(true).await?);
+
+        self.cache_put(&cache_key, &markets, MARKETS_CACHE_TTL_SECONDS)
+            .await;
+        Ok(markets)
     }
 
     async fn get_orderbook(
         &self,
-        _exchange_id: &str,
+        exchange_id: &str,
         symbol: &str,
-        _limit: Option<u32>,
+        limit: Option<u32>,
     ) -> ArbitrageResult<OrderBook> {
-        // Placeholder implementation
-        Ok(OrderBook {
-            symbol: symbol.to_string(),
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            datetime: chrono::Utc::now().to_rfc3339(),
-            nonce: None,
-            bids: vec![],
-            asks: vec![],
-        })
+        self.get_market_orderbook(exchange_id, symbol, limit, "spot")
+            .await
+    }
+
+    async fn get_market_orderbook(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        limit: Option<u32>,
+        market_type: &str,
+    ) -> ArbitrageResult<OrderBook> {
+        if symbol.trim().is_empty() {
+            return Err(ArbitrageError::validation_error("Symbol is required"));
+        }
+        let limit = limit.unwrap_or(DEFAULT_ORDERBOOK_LIMIT).max(1);
+        self.adapters
+            .resolve(exchange_id)?
+            .get_orderbook(symbol, limit, is_futures_market(market_type))
+            .await
     }
 
     async fn get_balance(
         &self,
-        _exchange_id: &str,
+        exchange_id: &str,
         credentials: &ExchangeCredentials,
     ) -> ArbitrageResult<Value> {
-        // Implementation for getting balance
-        let endpoint = "/api/v3/account";
-        let response = self
-            .binance_request(endpoint, Method::Get, None, Some(credentials))
-            .await?;
-        Ok(response)
+        self.adapters
+            .resolve(exchange_id)?
+            .get_balance(credentials)
+            .await
     }
 
     async fn create_order(
         &self,
-        _exchange_id: &str,
-        _credentials: &ExchangeCredentials,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
         symbol: &str,
         side: &str,
         amount: f64,
         price: Option<f64>,
     ) -> ArbitrageResult<Order> {
-        // Placeholder implementation
-        Ok(Order {
-            id: "placeholder".to_string(),
-            client_order_id: None,
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            datetime: chrono::Utc::now().to_rfc3339(),
-            last_trade_timestamp: None,
-            symbol: symbol.to_string(),
-            type_: "limit".to_string(),
-            time_in_force: None,
-            side: side.to_string(),
-            amount,
-            price,
-            average: None,
-            filled: 0.0,
-            remaining: amount,
-            status: "open".to_string(),
-            fee: None,
-            cost: 0.0,
-            trades: vec![],
-            info: serde_json::Value::Object(serde_json::Map::new()),
-        })
+        let request = match price {
+            Some(price) => OrderRequest::limit(symbol, side, amount, price),
+            None => OrderRequest::market(symbol, side, amount),
+        };
+        self.place_order(exchange_id, credentials, &request).await
+    }
+
+    async fn place_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        let adapter = self.adapters.resolve(exchange_id)?;
+        request
+            .validate()
+            .map_err(ArbitrageError::validation_error)?;
+
+        let market_type = request
+            .market_type
+            .as_deref()
+            .unwrap_or(credentials.exchange_type.as_str());
+        let market = self
+            .get_market(exchange_id, &request.symbol, market_type)
+            .await?;
+        let reference_price = match request.price.or(request.stop_price) {
+            Some(_) => None,
+            None => self
+                .get_market_orderbook(exchange_id, &request.symbol, Some(1), market_type)
+                .await
+                .ok()
+                .and_then(|book| book.mid_price()),
+        };
+        let request = request
+            .conform_to_market(&market, reference_price)
+            .map_err(|e| ArbitrageError::exchange_error(exchange_id, e))?;
+
+        let risk_check = self.risk_check.as_ref().ok_or_else(|| {
+            ArbitrageError::configuration_error("No risk check configured for order placement")
+        })?;
+        risk_check
+            .check_order(
+                credentials,
+                &request,
+                order_notional(&request, reference_price)?,
+            )
+            .await?;
+
+        adapter.place_order(credentials, &request).await
     }
 
     async fn cancel_order(
         &self,
-        _exchange_id: &str,
-        _credentials: &ExchangeCredentials,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
         order_id: &str,
         symbol: &str,
     ) -> ArbitrageResult<Order> {
-        // Placeholder implementation
-        Ok(Order {
-            id: order_id.to_string(),
-            client_order_id: None,
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            datetime: chrono::Utc::now().to_rfc3339(),
-            last_trade_timestamp: None,
-            symbol: symbol.to_string(),
-            type_: "limit".to_string(),
-            time_in_force: None,
-            side: "buy".to_string(),
-            amount: 0.0,
-            price: None,
-            average: None,
-            filled: 0.0,
-            remaining: 0.0,
-            status: "cancelled".to_string(),
-            fee: None,
-            cost: 0.0,
-            trades: vec![],
-            info: serde_json::Value::Object(serde_json::Map::new()),
-        })
+        self.adapters
+            .resolve(exchange_id)?
+            .cancel_order(credentials, symbol, order_id, None)
+            .await
+    }
+
+    async fn get_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        order_id: &str,
+        symbol: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        self.adapters
+            .resolve(exchange_id)?
+            .get_order(credentials, symbol, order_id, market_type)
+            .await
     }
 
     async fn get_open_orders(
         &self,
-        _exchange_id: &str,
-        _credentials: &ExchangeCredentials,
-        _symbol: Option<&str>,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
     ) -> ArbitrageResult<Vec<Order>> {
-        // Placeholder implementation
-        Ok(vec![])
+        self.adapters
+            .resolve(exchange_id)?
+            .get_open_orders(credentials, symbol, None)
+            .await
     }
 
     async fn get_open_positions(
         &self,
-        _exchange_id: &str,
-        _credentials: &ExchangeCredentials,
-        _symbol: Option<&str>,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
     ) -> ArbitrageResult<Vec<Position>> {
-        // Placeholder implementation
-        Ok(vec![])
+        self.adapters
+            .resolve(exchange_id)?
+            .get_open_positions(credentials, symbol)
+            .await
+    }
+
+    async fn get_funding_payments(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        self.adapters
+            .resolve(exchange_id)?
+            .get_funding_payments(credentials, symbol, since)
+            .await
     }
 
     async fn set_leverage(
         &self,
-        _exchange_id: &str,
-        _credentials: &ExchangeCredentials,
-        _symbol: &str,
-        _leverage: u32,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
     ) -> ArbitrageResult<()> {
-        // Placeholder implementation
-        Ok(())
+        self.adapters
+            .resolve(exchange_id)?
+            .set_leverage(credentials, symbol, leverage)
+            .await
     }
 
+    async fn set_margin_mode(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        self.adapters
+            .resolve(exchange_id)?
+            .set_margin_mode(credentials, symbol, mode)
+            .await
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        self.adapters
+            .resolve(exchange_id)?
+            .adjust_isolated_margin(credentials, symbol, amount)
+            .await
+    }
+
+    /// Account-specific fees for `symbol` in the credentials' market, cached per account
     async fn get_trading_fees(
         &self,
-        _exchange_id: &str,
-        _credentials: &ExchangeCredentials,
-        _symbol: &str,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
     ) -> ArbitrageResult<TradingFees> {
-        // Placeholder implementation
-        Ok(TradingFees {
-            trading: TradingFeeRates {
-                maker: 0.001,
-                taker: 0.001,
-                percentage: true,
-                tier_based: false,
-            },
-            funding: Some(TradingFeeRates {
-                maker: 0.0,
-                taker: 0.0,
-                percentage: true,
-                tier_based: false,
-            }),
-        })
+        let adapter = self.adapters.resolve(exchange_id)?;
+        let futures = is_futures_market(&credentials.exchange_type);
+        let cache_key = format!(
+            "trading_fees:{}:{}:{}:{}",
+            adapter.exchange_id().as_str(),
+            Self::account_cache_id(credentials),
+            if futures { "futures" } else { "spot" },
+            symbol.replace(['/', '-', '_'], "").to_uppercase()
+        );
+
+        if let Ok(Some(fees)) = self.kv.get(&cache_key).json::<TradingFees>().await {
+            return Ok(fees);
+        }
+
+        let fees = TradingFees {
+            trading: adapter
+                .get_trading_fees(credentials, symbol, futures)
+                .await?,
+            funding: None,
+        };
+        self.cache_put(&cache_key, &fees, TRADING_FEES_CACHE_TTL_SECONDS)
+            .await;
+        Ok(fees)
     }
 
     async fn test_api_connection(
         &self,
-        _exchange_id: &str,
-        _api_key: &str,
-        _secret: &str,
+        exchange_id: &str,
+        api_key: &str,
+        secret: &str,
+        passphrase: Option<&str>,
     ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
-        // Placeholder implementation: (can_read, can_trade, rate_limit_info)
-        Ok((true, true, None))
+        self.test_api_connection_with_options(exchange_id, api_key, secret, passphrase, None, None)
+            .await
     }
 
+    /// (can_read, can_trade, rate_limit_info); `can_trade` covers the `exchange_type` market,
+    /// or either market when none is given. OKX and Bitget keys need their `passphrase`.
     async fn test_api_connection_with_options(
         &self,
-        _exchange_id: &str,
-        _api_key: &str,
-        _secret: &str,
+        exchange_id: &str,
+        api_key: &str,
+        secret: &str,
+        passphrase: Option<&str>,
         _leverage: Option<i32>,
-        _exchange_type: Option<&str>,
+        exchange_type: Option<&str>,
     ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
-        // Placeholder implementation: (can_read, can_trade, rate_limit_info)
-        Ok((true, true, None))
-    }
-}
+        let exchange = self.adapters.resolve(exchange_id)?.exchange_id();
+        let mut credentials = ExchangeCredentials::new(
+            exchange,
+            api_key.to_string(),
+            secret.to_string(),
+            passphrase.map(str::to_string),
+            false,
+        );
+        if let Some(exchange_type) = exchange_type {
+            credentials.exchange_type = exchange_type.to_string();
+        }
 
-impl ExchangeService {
-    /// Binance futures-specific request method
-    async fn binance_futures_request(
-        &self,
-        endpoint: &str,
-        _method: Method,
-        _params: Option<Value>,
-        _auth: Option<&ExchangeCredentials>,
-    ) -> ArbitrageResult<Value> {
-        // Use the same implementation as binance_request but with futures base URL
-        self.binance_request(endpoint, Method::Get, None, None)
-            .await
+        let permissions = self.probe_api_key(exchange_id, &credentials).await?;
+        Ok((
+            permissions.can_read,
+            permissions.can_trade_market(exchange_type),
+            permissions.rate_limit,
+        ))
     }
 
-    /// Bybit-specific request method
-    async fn bybit_request(
+    async fn probe_api_key(
         &self,
-        endpoint: &str,
-        _method: Method,
-        _params: Option<Value>,
-        _auth: Option<&ExchangeCredentials>,
-    ) -> ArbitrageResult<Value> {
-        // Implementation for Bybit API requests
-        let base_url = "https://api.bybit.com";
-        let _url = format!("{}{}", base_url, endpoint);
-        Ok(serde_json::json!({
-            "result": {},
-            "ret_code": 0,
-            "ret_msg": "OK"
-        }))
-    }
-
-    /// Binance request with retry logic
-    async fn binance_request_with_retry(
-        &self,
-        endpoint: &str,
-        method: Method,
-        params: Option<Value>,
-        auth: Option<&ExchangeCredentials>,
-        max_retries: u32,
-    ) -> ArbitrageResult<Value> {
-        let mut last_error = None;
-
-        for attempt in 0..=max_retries {
-            match self
-                .binance_request(endpoint, method.clone(), params.clone(), auth)
-                .await
-            {
-                Ok(response) => return Ok(response),
-                Err(e) => {
-                    last_error = Some(e);
-                    if attempt < max_retries {
-                        // Wait before retry (exponential backoff)
-                        let delay_ms = 1000 * (2_u64.pow(attempt));
-                        worker::console_log!("Retrying request after {}ms delay", delay_ms);
-                        // In a real implementation, you'd use tokio::time::sleep or similar
-                    }
-                }
-            }
-        }
-
-        Err(last_error.unwrap_or_else(|| {
-            crate::utils::ArbitrageError::exchange_error("unknown", "Max retries exceeded")
-        }))
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        let permissions = self
+            .adapters
+            .resolve(exchange_id)?
+            .probe_api_key(credentials)
+            .await?;
+        permissions.ensure_no_withdrawal(exchange_id)?;
+        Ok(permissions)
     }
 }
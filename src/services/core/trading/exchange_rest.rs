@@ -0,0 +1,747 @@
+// src/services/core/trading/exchange_rest.rs
+
+//! REST transport for exchange APIs: base URL resolution, request signing, HTTP
+//! error mapping and the symbol/number helpers shared by the per-exchange
+//! adapters in `adapters`. This layer has no Worker bindings so it can be
+//! exercised natively against a local mock server.
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, RequestBuilder};
+use serde_json::{json, Value};
+
+use crate::services::core::trading::signing::{
+    encode_query, sign_binance, sign_bitget, sign_bybit, sign_okx, SignedRequest, SigningClock,
+    DEFAULT_RECV_WINDOW_MS,
+};
+use crate::services::core::user::user_exchange_api::RateLimitInfo;
+use crate::types::ExchangeCredentials;
+use crate::utils::error::ErrorDetails;
+use crate::utils::retry::{is_idempotent_method, parse_retry_after, with_retry_after, RetryPolicy};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+/// Price levels per side returned when the caller does not ask for a depth
+pub const DEFAULT_ORDERBOOK_LIMIT: u32 = 20;
+
+/// How long Bybit keeps an IP banned after answering HTTP 403
+const BYBIT_IP_BAN_MS: u64 = 10 * 60 * 1000;
+
+/// Bybit retCode for "too many visits" (HTTP 200 with a rate-limit body)
+const BYBIT_TOO_MANY_VISITS: i64 = 10006;
+
+/// Quote assets recognised when splitting a compact symbol such as "BTCUSDT"
+const QUOTE_ASSETS: [&str; 10] = [
+    "USDT", "USDC", "FDUSD", "BUSD", "TUSD", "DAI", "EUR", "BTC", "ETH", "BNB",
+];
+
+/// Base URLs for every exchange API family we talk to
+#[derive(Debug, Clone)]
+pub struct ExchangeEndpoints {
+    pub binance_spot: String,
+    pub binance_futures: String,
+    pub bybit: String,
+    pub okx: String,
+    pub bitget: String,
+}
+
+impl ExchangeEndpoints {
+    pub fn production() -> Self {
+        Self {
+            binance_spot: "https://api.binance.com".to_string(),
+            binance_futures: "https://fapi.binance.com".to_string(),
+            bybit: "https://api.bybit.com".to_string(),
+            okx: "https://www.okx.com".to_string(),
+            bitget: "https://api.bitget.com".to_string(),
+        }
+    }
+
+    pub fn testnet() -> Self {
+        Self {
+            binance_spot: "https://testnet.binance.vision".to_string(),
+            binance_futures: "https://testnet.binancefuture.com".to_string(),
+            bybit: "https://api-testnet.bybit.com".to_string(),
+            // OKX and Bitget use demo-trading headers on the production host
+            okx: "https://www.okx.com".to_string(),
+            bitget: "https://api.bitget.com".to_string(),
+        }
+    }
+
+    /// Point every exchange at the same base URL (used for mock servers and proxies)
+    pub fn uniform(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self {
+            binance_spot: base_url.clone(),
+            binance_futures: base_url.clone(),
+            bybit: base_url.clone(),
+            okx: base_url.clone(),
+            bitget: base_url,
+        }
+    }
+}
+
+impl Default for ExchangeEndpoints {
+    fn default() -> Self {
+        Self::production()
+    }
+}
+
+/// Whether an exchange type / market type string refers to derivatives rather than spot
+pub fn is_futures_market(market_type: &str) -> bool {
+    matches!(
+        market_type.to_ascii_lowercase().as_str(),
+        "futures" | "future" | "perpetual" | "perp" | "swap" | "linear" | "usdm"
+    )
+}
+
+/// Build an exchange error carrying the exchange-native error code and HTTP status
+pub(crate) fn exchange_api_error(
+    exchange: &str,
+    http_status: u16,
+    exchange_code: Option<Value>,
+    message: &str,
+) -> ArbitrageError {
+    let mut error =
+        ArbitrageError::exchange_error(exchange, format!("{} API error: {}", exchange, message));
+    if let Some(details) = error.details.as_mut() {
+        details.insert("http_status".to_string(), json!(http_status));
+        if let Some(code) = exchange_code {
+            details.insert("exchange_code".to_string(), code);
+        }
+    }
+    error
+}
+
+/// Rate-limit usage from the headers Binance (request weight) and Bybit (per-endpoint quota)
+/// attach to every response. Other exchanges do not report usage.
+pub(crate) fn rate_limit_from_headers(
+    exchange: &str,
+    headers: &HeaderMap,
+) -> Option<RateLimitInfo> {
+    let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+    match exchange {
+        "binance" => {
+            // Weight limits per minute: spot API, then SAPI per IP
+            let (used, limit) = [
+                ("x-mbx-used-weight-1m", 6000),
+                ("x-sapi-used-ip-weight-1m", 12000),
+            ]
+            .into_iter()
+            .find_map(|(name, limit)| Some((header(name)?, limit)))?;
+            let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+            Some(RateLimitInfo {
+                requests_per_minute: limit,
+                requests_remaining: limit.saturating_sub(used as u32),
+                reset_time: (now_ms / 60_000 + 1) * 60_000,
+            })
+        }
+        "bybit" => {
+            // Bybit quotes the endpoint's per-second limit and what is left of it
+            let limit = header("x-bapi-limit")? as u32;
+            Some(RateLimitInfo {
+                requests_per_minute: limit * 60,
+                requests_remaining: header("x-bapi-limit-status")? as u32,
+                reset_time: header("x-bapi-limit-reset-timestamp")?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Read a numeric field that exchanges may encode either as a JSON string or a number
+pub(crate) fn json_f64(value: &Value, key: &str) -> Option<f64> {
+    match value.get(key)? {
+        Value::String(s) if !s.is_empty() => s.parse().ok(),
+        Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+/// Read an integer timestamp that may be encoded as a string or a number
+pub(crate) fn json_u64(value: &Value, key: &str) -> Option<u64> {
+    match value.get(key)? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+/// Read a string or numeric identifier as a String
+pub(crate) fn json_string(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Strip separators from unified symbols ("BTC/USDT" -> "BTCUSDT"). Dated futures keep the
+/// exchange's own symbol ("BTCUSDT_250328", "BTC-28MAR25"), where the separator is part of it.
+pub(crate) fn compact_symbol(symbol: &str) -> String {
+    if is_dated_symbol(symbol) {
+        return symbol.to_uppercase();
+    }
+    symbol.replace(['/', '-', '_'], "").to_uppercase()
+}
+
+/// Whether the part after the last separator is a delivery date, "250328" or "28MAR25"
+fn is_dated_symbol(symbol: &str) -> bool {
+    symbol.rsplit_once(['-', '_']).is_some_and(|(pair, date)| {
+        !pair.is_empty()
+            && matches!(date.len(), 6 | 7)
+            && date.starts_with(|c: char| c.is_ascii_digit())
+            && date.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// Split a unified or compact symbol into (base, quote): "BTC/USDT" and "BTCUSDT" -> ("BTC", "USDT")
+pub(crate) fn split_symbol(symbol: &str) -> Option<(String, String)> {
+    let upper = symbol.to_uppercase();
+    if let Some((base, quote)) = upper.split_once(['/', '-', '_']) {
+        // Drop settlement suffixes like "BTC/USDT:USDT" or "BTC-USDT-SWAP"
+        let quote = quote.split([':', '-']).next().unwrap_or(quote);
+        return Some((base.to_string(), quote.to_string()));
+    }
+    QUOTE_ASSETS.iter().find_map(|quote| {
+        upper
+            .strip_suffix(quote)
+            .filter(|base| !base.is_empty())
+            .map(|base| (base.to_string(), quote.to_string()))
+    })
+}
+
+pub(crate) fn format_decimal(value: f64) -> String {
+    // Display for f64 never uses exponent notation, which exchanges reject
+    format!("{}", value)
+}
+
+pub(crate) fn datetime_from_millis(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp as i64)
+        .unwrap_or_else(chrono::Utc::now)
+        .to_rfc3339()
+}
+
+#[derive(Clone)]
+pub struct ExchangeRestClient {
+    client: Client,
+    endpoints: ExchangeEndpoints,
+    testnet_endpoints: ExchangeEndpoints,
+    recv_window_ms: u64,
+    retry: RetryPolicy,
+}
+
+impl Default for ExchangeRestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExchangeRestClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            endpoints: ExchangeEndpoints::production(),
+            testnet_endpoints: ExchangeEndpoints::testnet(),
+            recv_window_ms: DEFAULT_RECV_WINDOW_MS,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Use the same endpoints for production and testnet credentials
+    pub fn with_endpoints(endpoints: ExchangeEndpoints) -> Self {
+        Self {
+            client: Client::new(),
+            testnet_endpoints: endpoints.clone(),
+            endpoints,
+            recv_window_ms: DEFAULT_RECV_WINDOW_MS,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn endpoints(&self, testnet: bool) -> &ExchangeEndpoints {
+        if testnet {
+            &self.testnet_endpoints
+        } else {
+            &self.endpoints
+        }
+    }
+
+    /// Receive window (ms) sent with signed requests
+    pub fn with_recv_window(mut self, recv_window_ms: u64) -> Self {
+        self.recv_window_ms = recv_window_ms;
+        self
+    }
+
+    /// Backoff applied to idempotent requests that fail transiently
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    fn clock(&self) -> SigningClock {
+        SigningClock::now(self.recv_window_ms)
+    }
+
+    pub(crate) fn market_type<'a>(
+        credentials: &'a ExchangeCredentials,
+        override_type: Option<&'a str>,
+    ) -> &'a str {
+        override_type.unwrap_or(credentials.exchange_type.as_str())
+    }
+
+    /// Send a request and decode the JSON body, mapping HTTP failures into `ArbitrageError`
+    async fn execute(&self, exchange: &str, request: RequestBuilder) -> ArbitrageResult<Value> {
+        self.execute_with_headers(exchange, request)
+            .await
+            .map(|(body, _)| body)
+    }
+
+    async fn execute_with_headers(
+        &self,
+        exchange: &str,
+        request: RequestBuilder,
+    ) -> ArbitrageResult<(Value, HeaderMap)> {
+        let response = request.send().await.map_err(|e| {
+            ArbitrageError::network_error(format!("{} request failed: {}", exchange, e))
+        })?;
+
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let text = response.text().await.map_err(|e| {
+            ArbitrageError::network_error(format!("Failed to read {} response: {}", exchange, e))
+        })?;
+
+        if let Some(error) = Self::rate_limit_response(exchange, status, &headers) {
+            return Err(error);
+        }
+
+        let body: Value = serde_json::from_str(&text).map_err(|e| {
+            if (200..300).contains(&status) {
+                ArbitrageError::parse_error(format!("Invalid {} response JSON: {}", exchange, e))
+            } else {
+                exchange_api_error(exchange, status, None, &text)
+            }
+        })?;
+
+        if !(200..300).contains(&status) {
+            let message = body
+                .get("msg")
+                .or_else(|| body.get("retMsg"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("request failed")
+                .to_string();
+            let code = body.get("code").or_else(|| body.get("retCode")).cloned();
+            return Err(exchange_api_error(exchange, status, code, &message));
+        }
+
+        Ok((body, headers))
+    }
+
+    /// 429 (rate limited) and 418 (Binance IP ban) carry `Retry-After` in seconds.
+    /// Bybit answers an IP ban with a bare 403 and lifts it after ten minutes.
+    fn rate_limit_response(
+        exchange: &str,
+        status: u16,
+        headers: &HeaderMap,
+    ) -> Option<ArbitrageError> {
+        let retry_after = headers
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let (message, retry_after) = match (exchange, status) {
+            (_, 429) => ("rate limit exceeded", retry_after),
+            (_, 418) => ("IP banned for exceeding rate limits", retry_after),
+            ("bybit", 403) => (
+                "IP banned for exceeding rate limits",
+                retry_after.or(Some(BYBIT_IP_BAN_MS)),
+            ),
+            _ => return None,
+        };
+        let mut details = ErrorDetails::new();
+        details.insert("exchange".to_string(), json!(exchange));
+        details.insert("http_status".to_string(), json!(status));
+        let error =
+            ArbitrageError::rate_limit_error(format!("{} {} (HTTP {})", exchange, message, status))
+                .with_details(details);
+        Some(match retry_after {
+            Some(wait) => with_retry_after(error, wait),
+            None => error,
+        })
+    }
+
+    /// Unauthenticated GET returning the decoded JSON body
+    pub async fn public_get(
+        &self,
+        exchange: &str,
+        base_url: &str,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> ArbitrageResult<Value> {
+        let url = format!("{}{}", base_url, path);
+        self.retry
+            .run(true, || {
+                self.execute(exchange, self.client.get(&url).query(query))
+            })
+            .await
+    }
+
+    // ============= SIGNED TRANSPORT =============
+
+    /// Send `method url?query` with the signer's headers and an optional JSON body
+    async fn send_signed(
+        &self,
+        exchange: &str,
+        method: Method,
+        base_url: &str,
+        path: &str,
+        signed: SignedRequest,
+        body: Option<String>,
+    ) -> ArbitrageResult<(Value, HeaderMap)> {
+        let url = if signed.query.is_empty() {
+            format!("{}{}", base_url, path)
+        } else {
+            format!("{}{}?{}", base_url, path, signed.query)
+        };
+        let mut request = self.client.request(method, &url);
+        for (name, value) in signed.headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = body {
+            request = request
+                .header("Content-Type", "application/json")
+                .body(body);
+        }
+        self.execute_with_headers(exchange, request).await
+    }
+
+    /// Split JSON params into a query string (GET/DELETE) or a JSON body (everything else)
+    fn query_or_body(method: &Method, params: &Value) -> (String, Option<String>) {
+        if *method == Method::GET || *method == Method::DELETE {
+            let pairs: Vec<(String, String)> = params
+                .as_object()
+                .map(|map| {
+                    map.iter()
+                        .map(|(key, value)| {
+                            let value = value
+                                .as_str()
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| value.to_string());
+                            (key.clone(), value)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            (encode_query(&pairs), None)
+        } else {
+            (String::new(), Some(params.to_string()))
+        }
+    }
+
+    // ============= BINANCE =============
+
+    /// Signed Binance request: parameters go in the query string followed by the HMAC signature
+    pub async fn binance_signed(
+        &self,
+        method: Method,
+        base_url: &str,
+        path: &str,
+        params: Vec<(String, String)>,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        self.binance_signed_with_limits(method, base_url, path, params, credentials)
+            .await
+            .map(|(body, _)| body)
+    }
+
+    /// `binance_signed` that also reports the request-weight usage headers
+    pub async fn binance_signed_with_limits(
+        &self,
+        method: Method,
+        base_url: &str,
+        path: &str,
+        params: Vec<(String, String)>,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<(Value, Option<RateLimitInfo>)> {
+        let (body, headers) = self
+            .retry
+            .run(is_idempotent_method(method.as_str()), || async {
+                // Re-sign every attempt so the timestamp stays inside the receive window
+                let signed = sign_binance(credentials, &params, self.clock())?;
+                self.send_signed("binance", method.clone(), base_url, path, signed, None)
+                    .await
+            })
+            .await?;
+        Ok((body, rate_limit_from_headers("binance", &headers)))
+    }
+
+    // ============= BYBIT =============
+
+    /// Signed Bybit v5 request. GET parameters are signed as a query string, POST as a JSON body.
+    pub async fn bybit_signed(
+        &self,
+        method: Method,
+        base_url: &str,
+        path: &str,
+        params: Value,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        self.bybit_signed_with_limits(method, base_url, path, params, credentials)
+            .await
+            .map(|(body, _)| body)
+    }
+
+    /// `bybit_signed` that also reports the endpoint quota headers
+    pub async fn bybit_signed_with_limits(
+        &self,
+        method: Method,
+        base_url: &str,
+        path: &str,
+        params: Value,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<(Value, Option<RateLimitInfo>)> {
+        let (query, body) = Self::query_or_body(&method, &params);
+        let (response, headers) = self
+            .retry
+            .run(is_idempotent_method(method.as_str()), || async {
+                let signed = sign_bybit(
+                    credentials,
+                    &query,
+                    body.as_deref().unwrap_or_default(),
+                    self.clock(),
+                )?;
+                let (response, headers) = self
+                    .send_signed(
+                        "bybit",
+                        method.clone(),
+                        base_url,
+                        path,
+                        signed,
+                        body.clone(),
+                    )
+                    .await?;
+                Self::check_bybit_rate_limit(&response, &headers)?;
+                Ok((response, headers))
+            })
+            .await?;
+        Ok((
+            Self::check_bybit_response(response)?,
+            rate_limit_from_headers("bybit", &headers),
+        ))
+    }
+
+    /// "Too many visits" arrives as HTTP 200; the quota reset header says when to come back
+    fn check_bybit_rate_limit(response: &Value, headers: &HeaderMap) -> ArbitrageResult<()> {
+        if response.get("retCode").and_then(|c| c.as_i64()) != Some(BYBIT_TOO_MANY_VISITS) {
+            return Ok(());
+        }
+        let error = ArbitrageError::rate_limit_error(format!(
+            "bybit rate limit exceeded: {}",
+            response["retMsg"].as_str().unwrap_or("too many visits")
+        ));
+        let reset = headers
+            .get("x-bapi-limit-reset-timestamp")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        Err(match reset {
+            Some(reset) => {
+                let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+                with_retry_after(error, reset.saturating_sub(now_ms))
+            }
+            None => error,
+        })
+    }
+
+    /// Bybit reports business errors with HTTP 200 and a non-zero retCode
+    pub fn check_bybit_response(response: Value) -> ArbitrageResult<Value> {
+        let ret_code = response
+            .get("retCode")
+            .and_then(|c| c.as_i64())
+            .unwrap_or(0);
+        if ret_code != 0 {
+            let message = response["retMsg"].as_str().unwrap_or("request failed");
+            return Err(exchange_api_error(
+                "bybit",
+                200,
+                Some(json!(ret_code)),
+                message,
+            ));
+        }
+        Ok(response)
+    }
+
+    // ============= OKX / BITGET =============
+
+    /// Signed OKX v5 request; business errors (non-"0" code) are mapped to `ArbitrageError`
+    pub async fn okx_signed(
+        &self,
+        method: Method,
+        path: &str,
+        params: Value,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        let (query, body) = Self::query_or_body(&method, &params);
+        let base_url = self.endpoints(credentials.is_testnet).okx.clone();
+        let (response, _) = self
+            .retry
+            .run(is_idempotent_method(method.as_str()), || async {
+                let signed = sign_okx(
+                    credentials,
+                    method.as_str(),
+                    path,
+                    &query,
+                    body.as_deref().unwrap_or_default(),
+                    self.clock(),
+                )?;
+                self.send_signed("okx", method.clone(), &base_url, path, signed, body.clone())
+                    .await
+            })
+            .await?;
+        Self::check_coded_response("okx", &response, "0")?;
+        Ok(response)
+    }
+
+    /// Signed Bitget v2 request; business errors (non-"00000" code) are mapped to `ArbitrageError`
+    pub async fn bitget_signed(
+        &self,
+        method: Method,
+        path: &str,
+        params: Value,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        let (query, body) = Self::query_or_body(&method, &params);
+        let base_url = self.endpoints(credentials.is_testnet).bitget.clone();
+        let (response, _) = self
+            .retry
+            .run(is_idempotent_method(method.as_str()), || async {
+                let signed = sign_bitget(
+                    credentials,
+                    method.as_str(),
+                    path,
+                    &query,
+                    body.as_deref().unwrap_or_default(),
+                    self.clock(),
+                )?;
+                self.send_signed(
+                    "bitget",
+                    method.clone(),
+                    &base_url,
+                    path,
+                    signed,
+                    body.clone(),
+                )
+                .await
+            })
+            .await?;
+        Self::check_coded_response("bitget", &response, "00000")?;
+        Ok(response)
+    }
+
+    /// OKX and Bitget wrap every response in `{code, msg, data}` with a string success code
+    pub fn check_coded_response(
+        exchange: &str,
+        response: &Value,
+        success: &str,
+    ) -> ArbitrageResult<()> {
+        match response.get("code").and_then(|c| c.as_str()) {
+            Some(code) if code != success => {
+                let message = response["msg"].as_str().unwrap_or("request failed");
+                Err(exchange_api_error(
+                    exchange,
+                    200,
+                    Some(json!(code)),
+                    message,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_http_server::{MockHttpServer, MockRoute};
+
+    #[test]
+    fn test_split_symbol() {
+        assert_eq!(
+            split_symbol("BTCUSDT"),
+            Some(("BTC".to_string(), "USDT".to_string()))
+        );
+        assert_eq!(
+            split_symbol("eth/btc"),
+            Some(("ETH".to_string(), "BTC".to_string()))
+        );
+        assert_eq!(
+            split_symbol("BTC-USDT-SWAP"),
+            Some(("BTC".to_string(), "USDT".to_string()))
+        );
+        assert_eq!(split_symbol("USDT"), None);
+    }
+
+    #[test]
+    fn test_compact_symbol_keeps_delivery_dates() {
+        assert_eq!(compact_symbol("btc/usdt"), "BTCUSDT");
+        assert_eq!(compact_symbol("BTC-USDT-SWAP"), "BTCUSDTSWAP");
+        assert_eq!(compact_symbol("BTCUSDT_250328"), "BTCUSDT_250328");
+        assert_eq!(compact_symbol("btc-28mar25"), "BTC-28MAR25");
+    }
+
+    fn retrying_client(server: &MockHttpServer) -> ExchangeRestClient {
+        ExchangeRestClient::with_endpoints(ExchangeEndpoints::uniform(server.url()))
+            .with_retry_policy(RetryPolicy::default().with_delays(1, 50).with_jitter(0.0))
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_requests_retry_transient_failures() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new("GET", "/api/v3/time", 503, r#"{"msg":"busy"}"#),
+            MockRoute::new("GET", "/api/v3/time", 429, "{}").with_header("Retry-After", "0"),
+            MockRoute::new("GET", "/api/v3/time", 200, r#"{"serverTime":1}"#),
+        ]);
+        let rest = retrying_client(&server);
+
+        let body = rest
+            .public_get("binance", server.url(), "/api/v3/time", &[])
+            .await
+            .unwrap();
+
+        assert_eq!(body["serverTime"], 1);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_orders_and_bans_are_not_retried() {
+        let server = MockHttpServer::start(vec![
+            MockRoute::new("POST", "/fapi/v1/order", 503, r#"{"msg":"busy"}"#),
+            MockRoute::new("GET", "/api/v3/depth", 418, "{}").with_header("Retry-After", "120"),
+        ]);
+        let rest = retrying_client(&server);
+        let mut creds = ExchangeCredentials::new(
+            crate::types::ExchangeIdEnum::Binance,
+            "key".to_string(),
+            "secret".to_string(),
+            None,
+            false,
+        );
+        creds.exchange_type = "futures".to_string();
+
+        let order = rest
+            .binance_signed(
+                Method::POST,
+                server.url(),
+                "/fapi/v1/order",
+                Vec::new(),
+                &creds,
+            )
+            .await;
+        assert!(order.is_err());
+
+        let banned = rest
+            .public_get("binance", server.url(), "/api/v3/depth", &[])
+            .await
+            .unwrap_err();
+        assert_eq!(banned.kind, crate::utils::error::ErrorKind::RateLimit);
+        assert_eq!(crate::utils::retry::retry_after_ms(&banned), Some(120_000));
+
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -0,0 +1,577 @@
+// src/services/core/trading/funding_ledger.rs
+
+//! Funding payment ledger for funding-rate arbitrage positions.
+//!
+//! Each sync pulls the account's funding history for the symbol on both legs since the
+//! last payment already stored (or the position's entry time), writes every new payment
+//! to the `funding_payments` D1 table and adds it to the position's `funding_pnl`. Carry
+//! summaries compare what was actually settled with what the entry rate difference
+//! predicted for the same holding period.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use worker::wasm_bindgen::JsValue;
+
+use crate::services::core::infrastructure::database_repositories::DatabaseManager;
+use crate::services::core::trading::exchange::ExchangeInterface;
+use crate::services::core::trading::positions::PositionsService;
+use crate::services::core::trading::KvOperations;
+use crate::types::{
+    ArbitragePosition, ExchangeCredentials, ExchangeIdEnum, FundingPayment, PositionStatus,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+/// Settlement interval assumed when turning a per-interval rate into expected carry
+pub const DEFAULT_FUNDING_INTERVAL_HOURS: f64 = 8.0;
+
+const HOUR_MS: f64 = 3_600_000.0;
+
+/// Which side of the arbitrage a payment settled on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FundingLeg {
+    Long,
+    Short,
+}
+
+impl FundingLeg {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FundingLeg::Long => "long",
+            FundingLeg::Short => "short",
+        }
+    }
+}
+
+/// A funding payment attributed to one leg of a stored position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingLedgerEntry {
+    pub position_id: String,
+    pub user_id: String,
+    pub leg: FundingLeg,
+    pub payment: FundingPayment,
+}
+
+/// Realized funding on a position against the carry predicted at entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionCarry {
+    pub position_id: String,
+    pub symbol: String,
+    pub long_funding: f64,
+    pub short_funding: f64,
+    pub realized_funding: f64,
+    /// `None` when the position was not opened from a funding-rate opportunity
+    pub predicted_funding: Option<f64>,
+    pub payments: usize,
+    pub last_payment_at: Option<u64>,
+}
+
+impl PositionCarry {
+    /// Realized minus predicted carry
+    pub fn shortfall(&self) -> Option<f64> {
+        self.predicted_funding
+            .map(|predicted| self.realized_funding - predicted)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserCarry {
+    pub user_id: String,
+    pub realized_funding: f64,
+    pub predicted_funding: f64,
+    pub positions: Vec<PositionCarry>,
+}
+
+/// Persistence for ledger entries
+pub trait FundingLedgerStore {
+    /// Store `entry`; returns false when the exchange payment was already recorded
+    #[allow(async_fn_in_trait)]
+    async fn insert_payment(&self, entry: &FundingLedgerEntry) -> ArbitrageResult<bool>;
+
+    /// Payments on a position, oldest first
+    #[allow(async_fn_in_trait)]
+    async fn payments_for_position(
+        &self,
+        position_id: &str,
+    ) -> ArbitrageResult<Vec<FundingLedgerEntry>>;
+
+    /// Payments on all of a user's positions, oldest first
+    #[allow(async_fn_in_trait)]
+    async fn payments_for_user(&self, user_id: &str) -> ArbitrageResult<Vec<FundingLedgerEntry>>;
+}
+
+/// Notional of the position at entry, in quote currency
+fn entry_notional(position: &ArbitragePosition) -> f64 {
+    position.calculated_size_usd.unwrap_or_else(|| {
+        position.size.unwrap_or(position.long_position.amount) * position.entry_price_long
+    })
+}
+
+/// Carry the entry rate difference predicts for holding `position` until `now` (ms)
+pub fn predicted_funding(position: &ArbitragePosition, now: u64) -> Option<f64> {
+    let rate_difference = position.predicted_rate_difference?;
+    let held_until = position.closed_at.unwrap_or(now);
+    let intervals = held_until.saturating_sub(position.entry_time) as f64
+        / (DEFAULT_FUNDING_INTERVAL_HOURS * HOUR_MS);
+    Some(rate_difference * entry_notional(position) * intervals.floor())
+}
+
+/// Summarise `entries` (all belonging to `position`) as of `now`
+pub fn position_carry(
+    position: &ArbitragePosition,
+    entries: &[FundingLedgerEntry],
+    now: u64,
+) -> PositionCarry {
+    let leg_total = |leg: FundingLeg| -> f64 {
+        entries
+            .iter()
+            .filter(|entry| entry.leg == leg)
+            .map(|entry| entry.payment.amount)
+            .sum()
+    };
+    let long_funding = leg_total(FundingLeg::Long);
+    let short_funding = leg_total(FundingLeg::Short);
+    PositionCarry {
+        position_id: position.id.clone(),
+        symbol: position.symbol.clone(),
+        long_funding,
+        short_funding,
+        realized_funding: long_funding + short_funding,
+        predicted_funding: predicted_funding(position, now),
+        payments: entries.len(),
+        last_payment_at: entries.iter().map(|entry| entry.payment.timestamp).max(),
+    }
+}
+
+/// Summarise a user's ledger across `positions`; positions without payments are included
+pub fn user_carry(
+    user_id: &str,
+    positions: &[ArbitragePosition],
+    entries: &[FundingLedgerEntry],
+    now: u64,
+) -> UserCarry {
+    let mut by_position: HashMap<&str, Vec<FundingLedgerEntry>> = HashMap::new();
+    for entry in entries {
+        by_position
+            .entry(entry.position_id.as_str())
+            .or_default()
+            .push(entry.clone());
+    }
+    let positions: Vec<PositionCarry> = positions
+        .iter()
+        .filter(|position| position.user_id == user_id)
+        .map(|position| {
+            let entries = by_position
+                .get(position.id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            position_carry(position, entries, now)
+        })
+        .collect();
+    UserCarry {
+        user_id: user_id.to_string(),
+        realized_funding: positions.iter().map(|carry| carry.realized_funding).sum(),
+        predicted_funding: positions
+            .iter()
+            .filter_map(|carry| carry.predicted_funding)
+            .sum(),
+        positions,
+    }
+}
+
+/// The user's tracked positions plus the closed ones their ledger still references. Closed
+/// positions leave the open-position index but keep their stored record, so their carry is
+/// looked up by the position ids on the ledger entries.
+pub async fn carry_positions<K>(
+    positions: &PositionsService<K>,
+    user_id: &str,
+    entries: &[FundingLedgerEntry],
+) -> ArbitrageResult<Vec<ArbitragePosition>>
+where
+    K: KvOperations + Send + Sync + 'static,
+{
+    let mut found: Vec<ArbitragePosition> = positions
+        .get_all_positions()
+        .await?
+        .into_iter()
+        .filter(|position| position.user_id == user_id)
+        .collect();
+    for entry in entries {
+        if found
+            .iter()
+            .any(|position| position.id == entry.position_id)
+        {
+            continue;
+        }
+        if let Some(position) = positions.get_position(&entry.position_id).await? {
+            found.push(position);
+        }
+    }
+    Ok(found)
+}
+
+pub struct FundingLedger<E, K, S>
+where
+    E: ExchangeInterface,
+    K: KvOperations + Send + Sync + 'static,
+    S: FundingLedgerStore,
+{
+    exchange: Arc<E>,
+    positions: Arc<PositionsService<K>>,
+    store: Arc<S>,
+}
+
+impl<E, K, S> FundingLedger<E, K, S>
+where
+    E: ExchangeInterface,
+    K: KvOperations + Send + Sync + 'static,
+    S: FundingLedgerStore,
+{
+    pub fn new(exchange: Arc<E>, positions: Arc<PositionsService<K>>, store: Arc<S>) -> Self {
+        Self {
+            exchange,
+            positions,
+            store,
+        }
+    }
+
+    /// Pull and store new funding payments on both legs of `position`. Returns the entries
+    /// that were not in the ledger yet; each is also added to the position's `funding_pnl`.
+    pub async fn sync_position(
+        &self,
+        position: &ArbitragePosition,
+        long_credentials: &ExchangeCredentials,
+        short_credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Vec<FundingLedgerEntry>> {
+        let stored = self.store.payments_for_position(&position.id).await?;
+        let mut recorded = Vec::new();
+        for (leg, exchange, credentials) in [
+            (FundingLeg::Long, position.long_exchange, long_credentials),
+            (
+                FundingLeg::Short,
+                position.short_exchange,
+                short_credentials,
+            ),
+        ] {
+            let since = stored
+                .iter()
+                .filter(|entry| entry.leg == leg)
+                .map(|entry| entry.payment.timestamp + 1)
+                .max()
+                .unwrap_or(position.entry_time);
+            let payments = self
+                .exchange
+                .get_funding_payments(
+                    exchange.as_str(),
+                    credentials,
+                    &position.symbol,
+                    Some(since),
+                )
+                .await?;
+            for payment in payments {
+                if payment.timestamp < position.entry_time
+                    || position
+                        .closed_at
+                        .is_some_and(|closed| payment.timestamp > closed)
+                {
+                    continue;
+                }
+                let entry = FundingLedgerEntry {
+                    position_id: position.id.clone(),
+                    user_id: position.user_id.clone(),
+                    leg,
+                    payment,
+                };
+                if self.store.insert_payment(&entry).await? {
+                    self.positions
+                        .record_funding_payment(&position.id, entry.payment.amount)
+                        .await?;
+                    recorded.push(entry);
+                }
+            }
+        }
+        Ok(recorded)
+    }
+
+    /// Sync every open position of `user_id` whose legs both have credentials. A failing
+    /// position is skipped so one unreachable exchange does not block the rest.
+    pub async fn sync_user_positions(
+        &self,
+        user_id: &str,
+        credentials: &HashMap<ExchangeIdEnum, ExchangeCredentials>,
+    ) -> ArbitrageResult<Vec<FundingLedgerEntry>> {
+        let mut recorded = Vec::new();
+        for position in self.positions.get_all_positions().await? {
+            if position.user_id != user_id
+                || !matches!(
+                    position.status,
+                    PositionStatus::Open | PositionStatus::PartiallyFilled
+                )
+            {
+                continue;
+            }
+            let (Some(long), Some(short)) = (
+                credentials.get(&position.long_exchange),
+                credentials.get(&position.short_exchange),
+            ) else {
+                continue;
+            };
+            match self.sync_position(&position, long, short).await {
+                Ok(entries) => recorded.extend(entries),
+                Err(e) => worker::console_log!(
+                    "⚠️ Funding sync failed for position {}: {}",
+                    position.id,
+                    e
+                ),
+            }
+        }
+        Ok(recorded)
+    }
+
+    pub async fn position_carry(&self, position_id: &str) -> ArbitrageResult<PositionCarry> {
+        let position = self
+            .positions
+            .get_position(position_id)
+            .await?
+            .ok_or_else(|| {
+                ArbitrageError::not_found(format!("Position {} not found", position_id))
+            })?;
+        let entries = self.store.payments_for_position(position_id).await?;
+        Ok(position_carry(
+            &position,
+            &entries,
+            chrono::Utc::now().timestamp_millis() as u64,
+        ))
+    }
+
+    pub async fn user_carry(&self, user_id: &str) -> ArbitrageResult<UserCarry> {
+        let entries = self.store.payments_for_user(user_id).await?;
+        let positions = carry_positions(&self.positions, user_id, &entries).await?;
+        Ok(user_carry(
+            user_id,
+            &positions,
+            &entries,
+            chrono::Utc::now().timestamp_millis() as u64,
+        ))
+    }
+}
+
+fn entry_from_row(row: &serde_json::Value) -> Option<FundingLedgerEntry> {
+    let text = |key: &str| row.get(key)?.as_str().map(str::to_string);
+    let number = |key: &str| row.get(key)?.as_f64();
+    Some(FundingLedgerEntry {
+        position_id: text("position_id")?,
+        user_id: text("user_id")?,
+        leg: if text("leg")? == "short" {
+            FundingLeg::Short
+        } else {
+            FundingLeg::Long
+        },
+        payment: FundingPayment {
+            exchange: ExchangeIdEnum::from_string(&text("exchange")?).ok()?,
+            symbol: text("symbol")?,
+            amount: number("amount")?,
+            asset: text("asset")?,
+            timestamp: number("paid_at")? as u64,
+            payment_id: text("payment_id")?,
+        },
+    })
+}
+
+impl FundingLedgerStore for DatabaseManager {
+    async fn insert_payment(&self, entry: &FundingLedgerEntry) -> ArbitrageResult<bool> {
+        let result = self
+            .execute(
+                "INSERT OR IGNORE INTO funding_payments (
+                    position_id, user_id, exchange, symbol, leg, amount, asset, payment_id,
+                    paid_at, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                &[
+                    entry.position_id.as_str().into(),
+                    entry.user_id.as_str().into(),
+                    entry.payment.exchange.as_str().into(),
+                    entry.payment.symbol.as_str().into(),
+                    entry.leg.as_str().into(),
+                    entry.payment.amount.into(),
+                    entry.payment.asset.as_str().into(),
+                    entry.payment.payment_id.as_str().into(),
+                    JsValue::from(entry.payment.timestamp as f64),
+                    JsValue::from(chrono::Utc::now().timestamp_millis() as f64),
+                ],
+            )
+            .await?;
+        let changes = result
+            .meta()
+            .ok()
+            .flatten()
+            .and_then(|meta| meta.changes)
+            .unwrap_or(0);
+        Ok(changes > 0)
+    }
+
+    async fn payments_for_position(
+        &self,
+        position_id: &str,
+    ) -> ArbitrageResult<Vec<FundingLedgerEntry>> {
+        let result = self
+            .query(
+                "SELECT * FROM funding_payments WHERE position_id = ? ORDER BY paid_at ASC",
+                &[position_id.into()],
+            )
+            .await?;
+        let rows = result.results::<serde_json::Value>().map_err(|e| {
+            ArbitrageError::database_error(format!("Failed to parse funding payments: {}", e))
+        })?;
+        Ok(rows.iter().filter_map(entry_from_row).collect())
+    }
+
+    async fn payments_for_user(&self, user_id: &str) -> ArbitrageResult<Vec<FundingLedgerEntry>> {
+        let result = self
+            .query(
+                "SELECT * FROM funding_payments WHERE user_id = ? ORDER BY paid_at ASC",
+                &[user_id.into()],
+            )
+            .await?;
+        let rows = result.results::<serde_json::Value>().map_err(|e| {
+            ArbitrageError::database_error(format!("Failed to parse funding payments: {}", e))
+        })?;
+        Ok(rows.iter().filter_map(entry_from_row).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::positions::CreatePositionData;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::types::{AccountInfo, PositionSide};
+
+    const NOW: u64 = 1_727_800_000_000;
+
+    async fn funding_position(user_id: &str) -> ArbitragePosition {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        stored_funding_position(&service, user_id).await
+    }
+
+    async fn stored_funding_position(
+        service: &PositionsService<MockKvStore>,
+        user_id: &str,
+    ) -> ArbitragePosition {
+        let account = AccountInfo {
+            account_id: "acc".to_string(),
+            exchange: ExchangeIdEnum::Binance,
+            balances: Vec::new(),
+            total_balance_usd: 10_000.0,
+            available_balance_usd: 10_000.0,
+            used_balance_usd: 0.0,
+            last_updated: 0,
+        };
+        let data = CreatePositionData {
+            pair: "BTC/USDT".to_string(),
+            side: PositionSide::Both,
+            size: None,
+            size_usd: Some(6_500.0),
+            entry_price_long: 65_000.0,
+            entry_price_short: 65_100.0,
+            risk_percentage: None,
+            max_size_usd: None,
+            take_profit_price: None,
+            stop_loss_price: None,
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            exchange: ExchangeIdEnum::Binance,
+        };
+        let mut position = service.create_position(data, &account).await.unwrap();
+        position.user_id = user_id.to_string();
+        position.entry_time = NOW - 25 * HOUR_MS as u64;
+        position.predicted_rate_difference = Some(0.0005);
+        service.save_position(&position).await.unwrap();
+        position
+    }
+
+    fn entry(
+        position: &ArbitragePosition,
+        leg: FundingLeg,
+        amount: f64,
+        id: &str,
+    ) -> FundingLedgerEntry {
+        FundingLedgerEntry {
+            position_id: position.id.clone(),
+            user_id: position.user_id.clone(),
+            leg,
+            payment: FundingPayment {
+                exchange: match leg {
+                    FundingLeg::Long => position.long_exchange,
+                    FundingLeg::Short => position.short_exchange,
+                },
+                symbol: position.symbol.clone(),
+                amount,
+                asset: "USDT".to_string(),
+                timestamp: position.entry_time + 8 * HOUR_MS as u64,
+                payment_id: id.to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_position_carry_compares_realized_with_predicted_intervals() {
+        let position = funding_position("user-1").await;
+        let entries = vec![
+            entry(&position, FundingLeg::Long, -1.0, "1"),
+            entry(&position, FundingLeg::Short, 4.0, "2"),
+        ];
+
+        let carry = position_carry(&position, &entries, NOW);
+
+        assert_eq!(carry.long_funding, -1.0);
+        assert_eq!(carry.short_funding, 4.0);
+        assert_eq!(carry.realized_funding, 3.0);
+        // 25h held is three complete 8h settlements on 6,500 notional
+        assert!((carry.predicted_funding.unwrap() - 9.75).abs() < 1e-9);
+        assert!((carry.shortfall().unwrap() + 6.75).abs() < 1e-9);
+        assert_eq!(carry.payments, 2);
+    }
+
+    #[tokio::test]
+    async fn test_user_carry_only_sums_the_users_positions() {
+        let mine = funding_position("user-1").await;
+        let mut untracked = funding_position("user-1").await;
+        untracked.predicted_rate_difference = None;
+        let theirs = funding_position("user-2").await;
+        let entries = vec![
+            entry(&mine, FundingLeg::Short, 2.5, "1"),
+            entry(&untracked, FundingLeg::Long, 0.5, "2"),
+        ];
+
+        let carry = user_carry("user-1", &[mine, untracked, theirs], &entries, NOW);
+
+        assert_eq!(carry.positions.len(), 2);
+        assert_eq!(carry.realized_funding, 3.0);
+        assert!((carry.predicted_funding - 9.75).abs() < 1e-9);
+        assert_eq!(carry.positions[1].predicted_funding, None);
+    }
+
+    #[tokio::test]
+    async fn test_carry_positions_keep_closed_positions_with_payments() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let open = stored_funding_position(&service, "user-1").await;
+        let closed = stored_funding_position(&service, "user-1").await;
+        let unfunded = stored_funding_position(&service, "user-1").await;
+        stored_funding_position(&service, "user-2").await;
+        service.close_position(&closed.id).await.unwrap();
+        service.close_position(&unfunded.id).await.unwrap();
+        let entries = vec![entry(&closed, FundingLeg::Short, 2.0, "1")];
+
+        let positions = carry_positions(&service, "user-1", &entries).await.unwrap();
+        let carry = user_carry("user-1", &positions, &entries, NOW);
+
+        let mut ids: Vec<&str> = positions.iter().map(|p| p.id.as_str()).collect();
+        ids.sort_unstable();
+        let mut expected = vec![open.id.as_str(), closed.id.as_str()];
+        expected.sort_unstable();
+        assert_eq!(ids, expected);
+        assert_eq!(carry.realized_funding, 2.0);
+    }
+}
@@ -23,6 +23,7 @@ pub trait KvOperations {
     async fn put<T: Serialize + Send + ?Sized>(&self, key: &str, value: &T) -> KvResult<()>;
     async fn get<T: DeserializeOwned + Send>(&self, key: &str) -> KvResult<Option<T>>;
     async fn delete(&self, key: &str) -> KvResult<()>;
+    async fn list_keys(&self, prefix: &str) -> KvResult<Vec<String>>;
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -31,6 +32,7 @@ pub trait KvOperations: Send + Sync {
     async fn put<T: Serialize + Send + Sync + ?Sized>(&self, key: &str, value: &T) -> KvResult<()>;
     async fn get<T: DeserializeOwned + Send>(&self, key: &str) -> KvResult<Option<T>>;
     async fn delete(&self, key: &str) -> KvResult<()>;
+    async fn list_keys(&self, prefix: &str) -> KvResult<Vec<String>>;
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -65,6 +67,26 @@ impl KvOperations for worker::kv::KvStore {
             .map_err(|e| KvOperationError::Storage(e.to_string()))?;
         Ok(())
     }
+
+    async fn list_keys(&self, prefix: &str) -> KvResult<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut list = self.list().prefix(prefix.to_string());
+            if let Some(cursor) = cursor.take() {
+                list = list.cursor(cursor);
+            }
+            let page = list
+                .execute()
+                .await
+                .map_err(|e| KvOperationError::Storage(e.to_string()))?;
+            keys.extend(page.keys.into_iter().map(|key| key.name));
+            match page.cursor.filter(|_| !page.list_complete) {
+                Some(next) => cursor = Some(next),
+                None => return Ok(keys),
+            }
+        }
+    }
 }
 
 // Note: For non-WASM targets, we would need a different KV implementation
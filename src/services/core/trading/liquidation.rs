@@ -0,0 +1,740 @@
+// src/services/core/trading/liquidation.rs
+
+//! Liquidation-distance monitoring for the legs of leveraged arbitrage positions.
+//!
+//! Every leg is a linear (USDT-margined) perpetual. Its liquidation price is where the
+//! margin backing it, less the unrealized loss, falls to the maintenance margin:
+//! - Binance, OKX and Bitget charge maintenance on the notional at the liquidation price.
+//! - Bybit charges it on the entry notional.
+//!
+//! Isolated legs are backed by their own margin. Cross legs are also backed by the free
+//! collateral of the account, so other positions on that account are not taken into account.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::core::infrastructure::notification_module::{
+    NotificationModule, NotificationPriority, NotificationType,
+};
+use crate::services::core::trading::exchange::ExchangeInterface;
+use crate::services::core::trading::kv_operations::KvOperations;
+use crate::services::core::trading::positions::PositionsService;
+use crate::types::{
+    ArbitragePosition, ExchangeCredentials, ExchangeIdEnum, ExecutionEvent, ExecutionStep,
+    MarginMode, Position, PositionSide, PositionStatus,
+};
+use crate::utils::ArbitrageResult;
+
+/// Maintenance margin rate assumed when the exchange did not report one
+pub const DEFAULT_MAINTENANCE_MARGIN_RATE: f64 = 0.005;
+
+/// What the margin of one leg looks like to the exchange's risk engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegMargin {
+    pub exchange: ExchangeIdEnum,
+    pub symbol: String,
+    pub long: bool,
+    pub amount: f64,
+    pub entry_price: f64,
+    pub mark_price: f64,
+    pub leverage: f64,
+    pub maintenance_margin_rate: f64,
+    pub margin_mode: MarginMode,
+    /// Margin assigned to an isolated position; derived from leverage when unknown
+    pub isolated_margin: Option<f64>,
+    /// Free account collateral that also backs a cross-margin position
+    pub cross_collateral: f64,
+}
+
+impl LegMargin {
+    fn side_sign(&self) -> f64 {
+        if self.long {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    /// Margin the position itself holds
+    pub fn position_margin(&self) -> f64 {
+        self.isolated_margin
+            .unwrap_or(self.amount * self.entry_price / self.leverage)
+    }
+
+    /// Margin that absorbs losses before liquidation
+    pub fn backing_margin(&self) -> f64 {
+        match self.margin_mode {
+            MarginMode::Isolated => self.position_margin(),
+            MarginMode::Cross => self.position_margin() + self.cross_collateral,
+        }
+    }
+
+    /// Whether the exchange charges maintenance on the entry notional rather than the
+    /// notional at the liquidation price
+    fn maintenance_on_entry(&self) -> bool {
+        self.exchange == ExchangeIdEnum::Bybit
+    }
+
+    /// Estimated liquidation price, or `None` when the margin covers any price move
+    pub fn liquidation_price(&self) -> Option<f64> {
+        if self.amount <= 0.0 || self.entry_price <= 0.0 || self.leverage <= 0.0 {
+            return None;
+        }
+        let s = self.side_sign();
+        let q = self.amount;
+        let margin = self.backing_margin();
+        let price = if self.maintenance_on_entry() {
+            let maintenance = q * self.entry_price * self.maintenance_margin_rate;
+            self.entry_price - s * (margin - maintenance) / q
+        } else {
+            (s * q * self.entry_price - margin) / (q * (s - self.maintenance_margin_rate))
+        };
+        (price > 0.0 && price.is_finite()).then_some(price)
+    }
+
+    /// Backing margin that would put the liquidation price at `price`
+    pub fn margin_for_liquidation_price(&self, price: f64) -> f64 {
+        let s = self.side_sign();
+        let q = self.amount;
+        if self.maintenance_on_entry() {
+            q * self.entry_price * self.maintenance_margin_rate + s * (self.entry_price - price) * q
+        } else {
+            s * q * self.entry_price - price * q * (s - self.maintenance_margin_rate)
+        }
+    }
+
+    /// Adverse move from the mark price to liquidation, as a fraction of the mark price.
+    /// Zero once the mark is past the liquidation price.
+    pub fn liquidation_distance(&self) -> Option<f64> {
+        let price = self.liquidation_price()?;
+        if self.mark_price <= 0.0 {
+            return None;
+        }
+        let distance = self.side_sign() * (self.mark_price - price) / self.mark_price;
+        Some(distance.max(0.0))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationMonitorConfig {
+    /// Distances, as fractions of the mark price, that each raise an alert once crossed
+    pub alert_thresholds: Vec<f64>,
+    /// Top up isolated margin when a leg gets within `deleverage_threshold`
+    pub auto_deleverage: bool,
+    pub deleverage_threshold: f64,
+    /// Distance the top-up brings a leg back to
+    pub target_distance: f64,
+}
+
+impl Default for LiquidationMonitorConfig {
+    fn default() -> Self {
+        Self {
+            alert_thresholds: vec![0.30, 0.15, 0.05],
+            auto_deleverage: false,
+            deleverage_threshold: 0.05,
+            target_distance: 0.15,
+        }
+    }
+}
+
+/// Liquidation estimate for one leg
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegLiquidationRisk {
+    pub exchange: ExchangeIdEnum,
+    pub long: bool,
+    pub mark_price: f64,
+    pub liquidation_price: f64,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationAlert {
+    pub position_id: String,
+    pub user_id: String,
+    pub symbol: String,
+    /// The leg closest to liquidation
+    pub leg: LegLiquidationRisk,
+    /// Threshold that was crossed
+    pub threshold: f64,
+}
+
+impl LiquidationAlert {
+    pub fn priority(&self) -> NotificationPriority {
+        if self.threshold <= 0.05 {
+            NotificationPriority::Critical
+        } else if self.threshold <= 0.15 {
+            NotificationPriority::High
+        } else {
+            NotificationPriority::Medium
+        }
+    }
+}
+
+/// Isolated margin to move into (positive) or out of (negative) one leg
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginAdjustment {
+    pub exchange: ExchangeIdEnum,
+    pub symbol: String,
+    pub long: bool,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LiquidationReport {
+    pub legs: Vec<LegLiquidationRisk>,
+    pub alert: Option<LiquidationAlert>,
+    /// Adjustments the exchanges accepted
+    pub adjustments: Vec<MarginAdjustment>,
+}
+
+/// Where liquidation alerts are delivered
+pub trait LiquidationAlertSink {
+    #[allow(async_fn_in_trait)]
+    async fn send_liquidation_alert(&self, alert: &LiquidationAlert) -> ArbitrageResult<()>;
+}
+
+impl LiquidationAlertSink for NotificationModule {
+    async fn send_liquidation_alert(&self, alert: &LiquidationAlert) -> ArbitrageResult<()> {
+        let variables = HashMap::from([
+            ("position_id".to_string(), alert.position_id.clone()),
+            ("symbol".to_string(), alert.symbol.clone()),
+            ("exchange".to_string(), alert.leg.exchange.to_string()),
+            (
+                "side".to_string(),
+                if alert.leg.long { "long" } else { "short" }.to_string(),
+            ),
+            (
+                "mark_price".to_string(),
+                format!("{:.4}", alert.leg.mark_price),
+            ),
+            (
+                "liquidation_price".to_string(),
+                format!("{:.4}", alert.leg.liquidation_price),
+            ),
+            (
+                "distance_percent".to_string(),
+                format!("{:.2}", alert.leg.distance * 100.0),
+            ),
+        ]);
+        self.send_notification(
+            alert.user_id.clone(),
+            NotificationType::RiskWarning,
+            variables,
+            HashMap::new(),
+            Some(alert.priority()),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+fn leg_margin(
+    position: &ArbitragePosition,
+    leg: &Position,
+    exchange: ExchangeIdEnum,
+    long: bool,
+    free_collateral: &HashMap<ExchangeIdEnum, f64>,
+) -> Option<LegMargin> {
+    let entry_price = leg.entry_price.unwrap_or(if long {
+        position.entry_price_long
+    } else {
+        position.entry_price_short
+    });
+    let mark_price = if long {
+        position.current_price_long
+    } else {
+        position.current_price_short
+    }
+    .or(leg.mark_price)
+    .unwrap_or(entry_price);
+
+    Some(LegMargin {
+        exchange,
+        symbol: position.symbol.clone(),
+        long,
+        amount: leg.amount.abs(),
+        entry_price,
+        mark_price,
+        // Without leverage there is nothing to estimate from
+        leverage: leg.leverage.filter(|leverage| *leverage > 0.0)?,
+        maintenance_margin_rate: leg
+            .maintenance_margin_percentage
+            .unwrap_or(DEFAULT_MAINTENANCE_MARGIN_RATE),
+        margin_mode: if leg.isolated == Some(false) {
+            MarginMode::Cross
+        } else {
+            MarginMode::Isolated
+        },
+        isolated_margin: leg.collateral.or(leg.initial_margin),
+        cross_collateral: free_collateral.get(&exchange).copied().unwrap_or(0.0),
+    })
+}
+
+/// Leveraged legs of `position`; a single-sided position only has the one leg
+pub fn position_legs(
+    position: &ArbitragePosition,
+    free_collateral: &HashMap<ExchangeIdEnum, f64>,
+) -> Vec<LegMargin> {
+    let mut legs = Vec::new();
+    if position.side != PositionSide::Short {
+        legs.extend(leg_margin(
+            position,
+            &position.long_position,
+            position.long_exchange,
+            true,
+            free_collateral,
+        ));
+    }
+    if position.side != PositionSide::Long {
+        legs.extend(leg_margin(
+            position,
+            &position.short_position,
+            position.short_exchange,
+            false,
+            free_collateral,
+        ));
+    }
+    legs.retain(|leg| leg.amount > 0.0);
+    legs
+}
+
+/// Threshold to alert on at `distance`, if it is tighter than the one already alerted
+pub fn crossed_threshold(thresholds: &[f64], distance: f64, alerted: Option<f64>) -> Option<f64> {
+    let tightest = thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| distance <= *threshold)
+        .min_by(f64::total_cmp)?;
+    match alerted {
+        Some(alerted) if alerted <= tightest => None,
+        _ => Some(tightest),
+    }
+}
+
+/// Isolated margin moves that bring every leg within `deleverage_threshold` back to
+/// `target_distance`. Margin comes first from the excess of another isolated leg on the
+/// same exchange, then from the account's free collateral; cross legs already share it.
+pub fn rebalance_plan(
+    legs: &[LegMargin],
+    free_collateral: &HashMap<ExchangeIdEnum, f64>,
+    config: &LiquidationMonitorConfig,
+) -> Vec<MarginAdjustment> {
+    let target_price =
+        |leg: &LegMargin| leg.mark_price * (1.0 - leg.side_sign() * config.target_distance);
+    let mut free = free_collateral.clone();
+    let mut plan = Vec::new();
+
+    for (index, leg) in legs.iter().enumerate() {
+        let at_risk = leg
+            .liquidation_distance()
+            .is_some_and(|distance| distance <= config.deleverage_threshold);
+        if leg.margin_mode != MarginMode::Isolated || !at_risk {
+            continue;
+        }
+        let shortfall = leg.margin_for_liquidation_price(target_price(leg)) - leg.backing_margin();
+        let mut needed = shortfall;
+
+        for (other_index, other) in legs.iter().enumerate() {
+            if other_index == index
+                || other.exchange != leg.exchange
+                || other.margin_mode != MarginMode::Isolated
+                || needed <= 0.0
+            {
+                continue;
+            }
+            let excess =
+                other.backing_margin() - other.margin_for_liquidation_price(target_price(other));
+            let moved = excess.min(needed);
+            if moved > 0.0 {
+                plan.push(MarginAdjustment {
+                    exchange: other.exchange,
+                    symbol: other.symbol.clone(),
+                    long: other.long,
+                    amount: -moved,
+                });
+                needed -= moved;
+            }
+        }
+
+        let available = free.entry(leg.exchange).or_insert(0.0);
+        let from_free = needed.min(*available).max(0.0);
+        *available -= from_free;
+        // Margin released from the other legs passes through the free balance
+        let total = shortfall - (needed - from_free);
+        if total > 0.0 {
+            plan.push(MarginAdjustment {
+                exchange: leg.exchange,
+                symbol: leg.symbol.clone(),
+                long: leg.long,
+                amount: total,
+            });
+        }
+    }
+    plan
+}
+
+fn margin_event(
+    adjustment: &MarginAdjustment,
+    step: ExecutionStep,
+    message: String,
+) -> ExecutionEvent {
+    ExecutionEvent {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        step,
+        exchange: Some(adjustment.exchange),
+        order_id: None,
+        side: Some(if adjustment.long { "long" } else { "short" }.to_string()),
+        amount: Some(adjustment.amount),
+        price: None,
+        message,
+    }
+}
+
+/// Watches open positions for legs drifting towards liquidation
+pub struct LiquidationMonitor<E, K, N>
+where
+    E: ExchangeInterface,
+    K: KvOperations + Send + Sync + 'static,
+    N: LiquidationAlertSink,
+{
+    exchange: Arc<E>,
+    positions: Arc<PositionsService<K>>,
+    notifier: Arc<N>,
+    config: LiquidationMonitorConfig,
+}
+
+impl<E, K, N> LiquidationMonitor<E, K, N>
+where
+    E: ExchangeInterface,
+    K: KvOperations + Send + Sync + 'static,
+    N: LiquidationAlertSink,
+{
+    pub fn new(
+        exchange: Arc<E>,
+        positions: Arc<PositionsService<K>>,
+        notifier: Arc<N>,
+        config: LiquidationMonitorConfig,
+    ) -> Self {
+        Self {
+            exchange,
+            positions,
+            notifier,
+            config,
+        }
+    }
+
+    /// Estimate liquidation on each leg of the position, alert when its nearest leg crosses
+    /// a new threshold and, with `auto_deleverage`, move isolated margin to the legs at risk.
+    /// `free_collateral` is the available balance per exchange account.
+    pub async fn check_position(
+        &self,
+        position_id: &str,
+        credentials: &[ExchangeCredentials],
+        free_collateral: &HashMap<ExchangeIdEnum, f64>,
+    ) -> ArbitrageResult<LiquidationReport> {
+        let Some(mut position) = self.positions.get_position(position_id).await? else {
+            return Ok(LiquidationReport::default());
+        };
+        if position.status != PositionStatus::Open {
+            return Ok(LiquidationReport::default());
+        }
+
+        let legs = position_legs(&position, free_collateral);
+        let mut report = LiquidationReport {
+            legs: legs
+                .iter()
+                .filter_map(|leg| {
+                    Some(LegLiquidationRisk {
+                        exchange: leg.exchange,
+                        long: leg.long,
+                        mark_price: leg.mark_price,
+                        liquidation_price: leg.liquidation_price()?,
+                        distance: leg.liquidation_distance()?,
+                    })
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let nearest = report
+            .legs
+            .iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+            .cloned();
+
+        let widest = self
+            .config
+            .alert_thresholds
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        match &nearest {
+            Some(leg) => {
+                if let Some(threshold) = crossed_threshold(
+                    &self.config.alert_thresholds,
+                    leg.distance,
+                    position.liquidation_alert_threshold,
+                ) {
+                    let alert = LiquidationAlert {
+                        position_id: position.id.clone(),
+                        user_id: position.user_id.clone(),
+                        symbol: position.symbol.clone(),
+                        leg: leg.clone(),
+                        threshold,
+                    };
+                    // A failed delivery is retried on the next check
+                    if self.notifier.send_liquidation_alert(&alert).await.is_ok() {
+                        position.liquidation_alert_threshold = Some(threshold);
+                    }
+                    report.alert = Some(alert);
+                } else if leg.distance > widest {
+                    position.liquidation_alert_threshold = None;
+                }
+            }
+            None => position.liquidation_alert_threshold = None,
+        }
+
+        if self.config.auto_deleverage {
+            for adjustment in rebalance_plan(&legs, free_collateral, &self.config) {
+                let Some(creds) = credentials
+                    .iter()
+                    .find(|creds| creds.exchange == adjustment.exchange)
+                else {
+                    position.execution_log.push(margin_event(
+                        &adjustment,
+                        ExecutionStep::MarginAdjustmentFailed,
+                        "No credentials to adjust margin".to_string(),
+                    ));
+                    continue;
+                };
+                let result = self
+                    .exchange
+                    .adjust_isolated_margin(
+                        adjustment.exchange.as_str(),
+                        creds,
+                        &adjustment.symbol,
+                        adjustment.amount,
+                    )
+                    .await;
+                match result {
+                    Ok(()) => {
+                        let leg = if adjustment.long {
+                            &mut position.long_position
+                        } else {
+                            &mut position.short_position
+                        };
+                        let current = legs
+                            .iter()
+                            .find(|margin| margin.long == adjustment.long)
+                            .map(LegMargin::position_margin)
+                            .unwrap_or(0.0);
+                        leg.collateral = Some(current + adjustment.amount);
+                        position.execution_log.push(margin_event(
+                            &adjustment,
+                            ExecutionStep::MarginAdjusted,
+                            format!("Isolated margin adjusted by {:.4}", adjustment.amount),
+                        ));
+                        report.adjustments.push(adjustment);
+                    }
+                    Err(e) => position.execution_log.push(margin_event(
+                        &adjustment,
+                        ExecutionStep::MarginAdjustmentFailed,
+                        format!("Margin adjustment rejected: {}", e),
+                    )),
+                }
+            }
+        }
+
+        position.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        self.positions.save_position(&position).await?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::positions::CreatePositionData;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::test_utils::scripted_exchange::ScriptedExchange;
+    use crate::types::AccountInfo;
+    use std::sync::Mutex;
+
+    fn leg(exchange: ExchangeIdEnum, long: bool, margin_mode: MarginMode) -> LegMargin {
+        LegMargin {
+            exchange,
+            symbol: "BTCUSDT".to_string(),
+            long,
+            amount: 0.1,
+            entry_price: 65_000.0,
+            mark_price: 65_000.0,
+            leverage: 10.0,
+            maintenance_margin_rate: 0.005,
+            margin_mode,
+            isolated_margin: None,
+            cross_collateral: 1_000.0,
+        }
+    }
+
+    #[test]
+    fn test_liquidation_price_follows_exchange_formula() {
+        let binance_long = leg(ExchangeIdEnum::Binance, true, MarginMode::Isolated);
+        let bybit_long = leg(ExchangeIdEnum::Bybit, true, MarginMode::Isolated);
+        let okx_short = leg(ExchangeIdEnum::OKX, false, MarginMode::Isolated);
+        let bybit_short = leg(ExchangeIdEnum::Bybit, false, MarginMode::Isolated);
+
+        // 65,000 x 0.9 / 0.995 and 65,000 x (1 - 0.1 + 0.005)
+        assert!((binance_long.liquidation_price().unwrap() - 58_793.969_8).abs() < 1e-3);
+        assert!((bybit_long.liquidation_price().unwrap() - 58_825.0).abs() < 1e-6);
+        // 65,000 x 1.1 / 1.005 and 65,000 x (1 + 0.1 - 0.005)
+        assert!((okx_short.liquidation_price().unwrap() - 71_144.278_6).abs() < 1e-3);
+        assert!((bybit_short.liquidation_price().unwrap() - 71_175.0).abs() < 1e-6);
+
+        // Cross margin adds the free collateral: (6,500 - 1,650) / (0.1 x 0.995)
+        let cross = leg(ExchangeIdEnum::Binance, true, MarginMode::Cross);
+        assert!((cross.liquidation_price().unwrap() - 48_743.718_6).abs() < 1e-3);
+        let mut overfunded = cross.clone();
+        overfunded.cross_collateral = 10_000.0;
+        assert_eq!(overfunded.liquidation_price(), None);
+
+        let mut moved = binance_long.clone();
+        moved.mark_price = 61_000.0;
+        let distance = moved.liquidation_distance().unwrap();
+        assert!((distance - (61_000.0 - 58_793.969_8) / 61_000.0).abs() < 1e-6);
+        let margin = moved.margin_for_liquidation_price(moved.liquidation_price().unwrap());
+        assert!((margin - moved.backing_margin()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_thresholds_alert_once_each_as_distance_shrinks() {
+        let thresholds = [0.30, 0.15, 0.05];
+
+        assert_eq!(crossed_threshold(&thresholds, 0.40, None), None);
+        assert_eq!(crossed_threshold(&thresholds, 0.25, None), Some(0.30));
+        assert_eq!(crossed_threshold(&thresholds, 0.20, Some(0.30)), None);
+        assert_eq!(crossed_threshold(&thresholds, 0.04, Some(0.30)), Some(0.05));
+        assert_eq!(crossed_threshold(&thresholds, 0.10, Some(0.05)), None);
+    }
+
+    #[test]
+    fn test_rebalance_moves_excess_margin_from_the_other_leg_first() {
+        // Long close to liquidation, short far from it, both isolated on Binance
+        let mut long = leg(ExchangeIdEnum::Binance, true, MarginMode::Isolated);
+        long.mark_price = 61_000.0;
+        let mut short = leg(ExchangeIdEnum::Binance, false, MarginMode::Isolated);
+        short.mark_price = 61_000.0;
+        short.isolated_margin = Some(2_000.0);
+        let config = LiquidationMonitorConfig::default();
+        let free = HashMap::from([(ExchangeIdEnum::Binance, 50.0)]);
+
+        let plan = rebalance_plan(&[long.clone(), short], &free, &config);
+
+        assert_eq!(plan.len(), 2);
+        assert!(!plan[0].long && plan[0].amount < 0.0);
+        assert!(plan[1].long);
+        assert!((plan[1].amount + plan[0].amount).abs() < 1e-9);
+        let mut topped_up = long;
+        topped_up.isolated_margin = Some(topped_up.position_margin() + plan[1].amount);
+        assert!((topped_up.liquidation_distance().unwrap() - config.target_distance).abs() < 1e-9);
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        alerts: Mutex<Vec<LiquidationAlert>>,
+    }
+
+    impl LiquidationAlertSink for RecordingSink {
+        async fn send_liquidation_alert(&self, alert: &LiquidationAlert) -> ArbitrageResult<()> {
+            self.alerts.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_monitor_alerts_and_tops_up_the_leg_at_risk() {
+        let positions = Arc::new(PositionsService::new(Arc::new(MockKvStore::new())));
+        let account = AccountInfo {
+            account_id: "acc".to_string(),
+            exchange: ExchangeIdEnum::Binance,
+            balances: Vec::new(),
+            total_balance_usd: 10_000.0,
+            available_balance_usd: 10_000.0,
+            used_balance_usd: 0.0,
+            last_updated: 0,
+        };
+        let data = CreatePositionData {
+            pair: "BTC/USDT".to_string(),
+            side: PositionSide::Both,
+            size: None,
+            size_usd: Some(6_500.0),
+            entry_price_long: 65_000.0,
+            entry_price_short: 65_000.0,
+            risk_percentage: None,
+            max_size_usd: None,
+            take_profit_price: None,
+            stop_loss_price: None,
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            exchange: ExchangeIdEnum::Binance,
+        };
+        let mut position = positions.create_position(data, &account).await.unwrap();
+        for leg in [&mut position.long_position, &mut position.short_position] {
+            leg.amount = 0.1;
+            leg.leverage = Some(10.0);
+            leg.collateral = Some(650.0);
+            leg.entry_price = Some(65_000.0);
+            leg.isolated = Some(true);
+        }
+        position.current_price_long = Some(61_000.0);
+        position.current_price_short = Some(61_000.0);
+        positions.save_position(&position).await.unwrap();
+
+        let exchange = Arc::new(ScriptedExchange::default());
+        let sink = Arc::new(RecordingSink::default());
+        let config = LiquidationMonitorConfig {
+            auto_deleverage: true,
+            ..Default::default()
+        };
+        let monitor =
+            LiquidationMonitor::new(exchange.clone(), positions.clone(), sink.clone(), config);
+        let credentials = [ExchangeCredentials::new(
+            ExchangeIdEnum::Binance,
+            "key".to_string(),
+            "secret".to_string(),
+            None,
+            false,
+        )];
+        let free = HashMap::from([(ExchangeIdEnum::Binance, 5_000.0)]);
+
+        let report = monitor
+            .check_position(&position.id, &credentials, &free)
+            .await
+            .unwrap();
+
+        assert_eq!(report.legs.len(), 2);
+        let alert = report.alert.unwrap();
+        assert_eq!(alert.threshold, 0.05);
+        assert!(alert.leg.long);
+        assert_eq!(alert.priority(), NotificationPriority::Critical);
+        assert_eq!(sink.alerts.lock().unwrap().len(), 1);
+        let adjustments = exchange.margin_adjustments();
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(adjustments[0].0, ExchangeIdEnum::Binance);
+        assert!(adjustments[0].2 > 0.0);
+
+        // Margin now covers the long leg; the alert is not repeated
+        let report = monitor
+            .check_position(&position.id, &credentials, &free)
+            .await
+            .unwrap();
+        assert!(report.alert.is_none());
+        assert_eq!(sink.alerts.lock().unwrap().len(), 1);
+        let stored = positions.get_position(&position.id).await.unwrap().unwrap();
+        assert_eq!(stored.liquidation_alert_threshold, Some(0.05));
+        assert!(stored
+            .execution_log
+            .iter()
+            .any(|event| event.step == ExecutionStep::MarginAdjusted));
+    }
+}
@@ -1,13 +1,31 @@
 // src/services/core/trading/mod.rs
 
+pub mod adapters;
 pub mod ai_exchange_router;
+pub mod arbitrage_executor;
 pub mod exchange;
+pub mod exchange_rest;
+pub mod funding_ledger;
 pub mod kv_operations;
+pub mod liquidation;
+pub mod paper_exchange;
 pub mod positions;
+pub mod risk_gate;
+pub mod signing;
 
+pub use adapters::{ExchangeAdapter, ExchangeAdapterRegistry};
 pub use ai_exchange_router::AiExchangeRouterService;
+pub use arbitrage_executor::{ArbitrageExecutor, ExecutionConfig, ExecutionDecision};
 pub use exchange::ExchangeService;
+pub use exchange_rest::{ExchangeEndpoints, ExchangeRestClient};
+pub use funding_ledger::{FundingLedger, FundingLedgerStore};
+pub use liquidation::{LiquidationAlertSink, LiquidationMonitor, LiquidationMonitorConfig};
+pub use paper_exchange::{
+    LiveMarketData, PaperExchange, PaperMarketData, PaperTradingConfig, RecordedMarketData,
+    TradingVenue,
+};
 pub use positions::PositionsService;
+pub use risk_gate::{ProposedTrade, RiskGate, RiskLimitOverrides, RiskRejection};
 
 // Re-export items from kv_operations to make them directly accessible under the trading module
 pub use kv_operations::{KvOperationError, KvOperations, KvResult};
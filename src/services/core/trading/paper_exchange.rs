@@ -0,0 +1,1856 @@
+// src/services/core/trading/paper_exchange.rs
+
+//! Simulated exchange for trading without real money.
+//!
+//! `PaperExchange` implements `ExchangeInterface`, so the executor, positions, PnL and
+//! notification flows run against it unchanged. Orders fill against live or recorded order
+//! books:
+//! - Market orders walk the opposite side of the book, taking `book_participation` of each
+//!   level, and pay `slippage_bps` on top of the walked price. Whatever the book cannot
+//!   absorb is cancelled.
+//! - Limit orders fill up to their price and rest with the remainder. Conditional orders
+//!   rest until the mid price triggers them. Resting orders are re-checked whenever they
+//!   are polled.
+//! - Futures fills open or reduce a virtual perpetual position against the quote balance.
+//!   Funding is settled at every `funding_interval_ms` boundary the position was open over.
+//!
+//! Each user's virtual account is kept in KV per exchange, keyed by the user id the
+//! credentials carry (see `ExchangeCredentials::paper`). `TradingVenue::for_preferences`
+//! picks between the live exchange and the paper one from the user's paper-trading toggle.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::services::core::trading::exchange::ExchangeInterface;
+use crate::services::core::trading::exchange_rest::{is_futures_market, ExchangeRestClient};
+use crate::services::core::trading::kv_operations::KvOperations;
+use crate::services::core::trading::risk_gate::{base_asset, order_notional, OrderRiskCheck};
+use crate::services::core::user::user_exchange_api::{ApiKeyPermissions, RateLimitInfo};
+use crate::services::core::user::user_trading_preferences::UserTradingPreferences;
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, MarginMode, Market, Order, OrderBook,
+    OrderRequest, OrderType, Position, Ticker, TimeInForce, TradingFee, TradingFeeRates,
+    TradingFees,
+};
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperTradingConfig {
+    /// Quote balance a new account starts with
+    pub starting_balance: f64,
+    pub quote_asset: String,
+    pub maker_fee_rate: f64,
+    pub taker_fee_rate: f64,
+    /// Adverse slippage added to every taker fill, in basis points
+    pub slippage_bps: f64,
+    /// Share of each displayed level an order may take
+    pub book_participation: f64,
+    pub book_depth: u32,
+    pub funding_interval_ms: u64,
+}
+
+impl Default for PaperTradingConfig {
+    fn default() -> Self {
+        Self {
+            starting_balance: 10_000.0,
+            quote_asset: "USDT".to_string(),
+            maker_fee_rate: 0.0002,
+            taker_fee_rate: 0.0005,
+            slippage_bps: 2.0,
+            book_participation: 1.0,
+            book_depth: 50,
+            funding_interval_ms: 8 * 3_600_000,
+        }
+    }
+}
+
+/// Prices paper orders fill against
+pub trait PaperMarketData {
+    #[allow(async_fn_in_trait)]
+    async fn order_book(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        depth: u32,
+    ) -> ArbitrageResult<OrderBook>;
+
+    /// Funding rate per interval for the perpetual; `None` when there is none
+    #[allow(async_fn_in_trait)]
+    async fn funding_rate(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<Option<f64>>;
+}
+
+/// Market data read from a live exchange
+pub struct LiveMarketData<E: ExchangeInterface>(pub Arc<E>);
+
+impl<E: ExchangeInterface> PaperMarketData for LiveMarketData<E> {
+    async fn order_book(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        depth: u32,
+    ) -> ArbitrageResult<OrderBook> {
+        self.0.get_orderbook(exchange_id, symbol, Some(depth)).await
+    }
+
+    async fn funding_rate(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<Option<f64>> {
+        let rates = self
+            .0
+            .fetch_funding_rates(exchange_id, Some(symbol))
+            .await?;
+        Ok(rates
+            .first()
+            .and_then(|rate| rate.get("funding_rate"))
+            .and_then(Value::as_f64))
+    }
+}
+
+/// Market data set by the caller, e.g. replayed from recorded snapshots
+#[derive(Default)]
+pub struct RecordedMarketData {
+    books: std::sync::Mutex<HashMap<(String, String), OrderBook>>,
+    funding_rates: std::sync::Mutex<HashMap<(String, String), f64>>,
+}
+
+impl RecordedMarketData {
+    pub fn set_order_book(&self, exchange_id: &str, book: OrderBook) {
+        self.books
+            .lock()
+            .unwrap()
+            .insert((exchange_id.to_string(), book.symbol.clone()), book);
+    }
+
+    pub fn set_funding_rate(&self, exchange_id: &str, symbol: &str, rate: f64) {
+        self.funding_rates
+            .lock()
+            .unwrap()
+            .insert((exchange_id.to_string(), symbol.to_string()), rate);
+    }
+}
+
+impl PaperMarketData for RecordedMarketData {
+    async fn order_book(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        _depth: u32,
+    ) -> ArbitrageResult<OrderBook> {
+        self.books
+            .lock()
+            .unwrap()
+            .get(&(exchange_id.to_string(), symbol.to_string()))
+            .cloned()
+            .ok_or_else(|| {
+                ArbitrageError::data_unavailable(format!(
+                    "No recorded order book for {} on {}",
+                    symbol, exchange_id
+                ))
+            })
+    }
+
+    async fn funding_rate(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<Option<f64>> {
+        Ok(self
+            .funding_rates
+            .lock()
+            .unwrap()
+            .get(&(exchange_id.to_string(), symbol.to_string()))
+            .copied())
+    }
+}
+
+/// A virtual perpetual position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperPosition {
+    pub symbol: String,
+    /// Signed size; positive when long
+    pub amount: f64,
+    pub entry_price: f64,
+    /// Margin added or removed by hand on top of the initial margin
+    pub margin_adjustment: f64,
+    pub realized_pnl: f64,
+    pub last_funding_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperOrder {
+    pub order: Order,
+    pub request: OrderRequest,
+    pub futures: bool,
+    /// Most favourable mid price since placement, for trailing stops
+    pub extreme_price: Option<f64>,
+}
+
+/// One user's virtual account on one exchange
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperAccount {
+    /// Asset balances; the quote asset also carries futures margin, PnL and funding
+    pub balances: HashMap<String, f64>,
+    pub positions: HashMap<String, PaperPosition>,
+    pub leverage: HashMap<String, u32>,
+    pub margin_modes: HashMap<String, MarginMode>,
+    pub orders: HashMap<String, PaperOrder>,
+    pub funding_payments: Vec<FundingPayment>,
+    pub next_order_id: u64,
+}
+
+impl PaperAccount {
+    fn leverage_for(&self, symbol: &str) -> f64 {
+        self.leverage.get(symbol).copied().unwrap_or(1).max(1) as f64
+    }
+
+    /// Initial margin held by open positions
+    pub fn used_margin(&self) -> f64 {
+        self.positions
+            .values()
+            .map(|position| {
+                position.amount.abs() * position.entry_price / self.leverage_for(&position.symbol)
+                    + position.margin_adjustment
+            })
+            .sum()
+    }
+}
+
+/// Quantity available from `book` for an order on `buy` side, capped at `limit` when set,
+/// with `participation` of each level. Returns the filled amount and its average price.
+pub fn walk_book(
+    book: &OrderBook,
+    buy: bool,
+    amount: f64,
+    limit: Option<f64>,
+    participation: f64,
+) -> (f64, Option<f64>) {
+    let levels = if buy { &book.asks } else { &book.bids };
+    let mut filled = 0.0;
+    let mut cost = 0.0;
+    for [price, size] in levels.iter().copied() {
+        let within_limit =
+            limit.is_none_or(|limit| if buy { price <= limit } else { price >= limit });
+        if filled >= amount || !within_limit {
+            break;
+        }
+        let take = (size * participation).min(amount - filled);
+        filled += take;
+        cost += take * price;
+    }
+    if filled <= 0.0 {
+        return (0.0, None);
+    }
+    (filled, Some(cost / filled))
+}
+
+fn rejected(message: impl Into<String>) -> ArbitrageError {
+    ArbitrageError::validation_error(message)
+}
+
+fn order_type_name(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "market",
+        OrderType::Limit => "limit",
+        OrderType::StopLoss => "stop_market",
+        OrderType::TakeProfit => "take_profit_market",
+        OrderType::StopLossLimit => "stop",
+        OrderType::TakeProfitLimit => "take_profit",
+        OrderType::TrailingStop => "trailing_stop_market",
+    }
+}
+
+/// Whether a conditional order triggers at `mid`
+fn is_triggered(paper: &PaperOrder, mid: f64) -> bool {
+    let buy = paper.request.is_buy();
+    match paper.request.order_type {
+        OrderType::Market | OrderType::Limit => true,
+        OrderType::StopLoss | OrderType::StopLossLimit => paper
+            .request
+            .stop_price
+            .is_some_and(|stop| if buy { mid >= stop } else { mid <= stop }),
+        OrderType::TakeProfit | OrderType::TakeProfitLimit => paper
+            .request
+            .stop_price
+            .is_some_and(|stop| if buy { mid <= stop } else { mid >= stop }),
+        OrderType::TrailingStop => {
+            let (Some(extreme), Some(percent)) =
+                (paper.extreme_price, paper.request.trailing_percent)
+            else {
+                return false;
+            };
+            if buy {
+                mid >= extreme * (1.0 + percent / 100.0)
+            } else {
+                mid <= extreme * (1.0 - percent / 100.0)
+            }
+        }
+    }
+}
+
+pub struct PaperExchange<M: PaperMarketData, K: KvOperations> {
+    market: Arc<M>,
+    store: Arc<K>,
+    config: PaperTradingConfig,
+    clock: Arc<dyn Fn() -> u64 + Send + Sync>,
+    risk_check: Option<Arc<dyn OrderRiskCheck + Send + Sync>>,
+}
+
+impl<M: PaperMarketData, K: KvOperations> PaperExchange<M, K> {
+    pub fn new(market: Arc<M>, store: Arc<K>) -> Self {
+        Self {
+            market,
+            store,
+            config: PaperTradingConfig::default(),
+            clock: Arc::new(|| chrono::Utc::now().timestamp_millis() as u64),
+            risk_check: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: PaperTradingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Replace the wall clock, e.g. to replay recorded data
+    pub fn with_clock(mut self, clock: Arc<dyn Fn() -> u64 + Send + Sync>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Hold paper orders to the same pre-trade limits as live ones
+    pub fn with_risk_check(mut self, risk_check: Arc<dyn OrderRiskCheck + Send + Sync>) -> Self {
+        self.risk_check = Some(risk_check);
+        self
+    }
+
+    fn now(&self) -> u64 {
+        (self.clock)()
+    }
+
+    fn account_key(
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<String> {
+        let user_id = credentials
+            .user_id
+            .as_deref()
+            .ok_or_else(|| rejected("Paper accounts need the user the credentials belong to"))?;
+        Ok(format!("paper_account:{}:{}", exchange_id, user_id))
+    }
+
+    /// The account with funding settled up to now; a new account gets the starting balance
+    pub async fn load_account(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<PaperAccount> {
+        let key = Self::account_key(exchange_id, credentials)?;
+        let mut account = match self.store.get::<PaperAccount>(&key).await? {
+            Some(account) => account,
+            None => PaperAccount {
+                balances: HashMap::from([(
+                    self.config.quote_asset.clone(),
+                    self.config.starting_balance,
+                )]),
+                ..Default::default()
+            },
+        };
+        self.settle_funding(exchange_id, &mut account).await?;
+        Ok(account)
+    }
+
+    async fn save_account(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        account: &PaperAccount,
+    ) -> ArbitrageResult<()> {
+        self.store
+            .put(&Self::account_key(exchange_id, credentials)?, account)
+            .await?;
+        Ok(())
+    }
+
+    async fn mid_price(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<f64> {
+        self.market
+            .order_book(exchange_id, symbol, self.config.book_depth)
+            .await?
+            .mid_price()
+            .ok_or_else(|| {
+                ArbitrageError::data_unavailable(format!("Empty order book for {}", symbol))
+            })
+    }
+
+    /// Credit or debit funding for every interval boundary each position was open over
+    async fn settle_funding(
+        &self,
+        exchange_id: &str,
+        account: &mut PaperAccount,
+    ) -> ArbitrageResult<()> {
+        let now = self.now();
+        let interval = self.config.funding_interval_ms.max(1);
+        let exchange = ExchangeIdEnum::from_string(exchange_id).map_err(rejected)?;
+        let symbols: Vec<String> = account.positions.keys().cloned().collect();
+
+        for symbol in symbols {
+            let position = &account.positions[&symbol];
+            let first = position.last_funding_at / interval + 1;
+            let last = now / interval;
+            if position.amount == 0.0 || last < first {
+                continue;
+            }
+            let rate = self.market.funding_rate(exchange_id, &symbol).await?;
+            let mark = self.mid_price(exchange_id, &symbol).await?;
+            let position = account.positions.get_mut(&symbol).unwrap();
+            position.last_funding_at = now;
+            let Some(rate) = rate else {
+                continue;
+            };
+            for boundary in first..=last {
+                let timestamp = boundary * interval;
+                // Longs pay a positive rate and receive a negative one
+                let amount = -position.amount * mark * rate;
+                *account
+                    .balances
+                    .entry(self.config.quote_asset.clone())
+                    .or_default() += amount;
+                account.funding_payments.push(FundingPayment {
+                    exchange,
+                    symbol: symbol.clone(),
+                    amount,
+                    asset: self.config.quote_asset.clone(),
+                    timestamp,
+                    payment_id: format!("paper-{}-{}", symbol, timestamp),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Book `amount` at `price` into the account, failing when it cannot be afforded
+    fn apply_fill(
+        &self,
+        account: &mut PaperAccount,
+        paper: &PaperOrder,
+        amount: f64,
+        price: f64,
+        fee: f64,
+    ) -> ArbitrageResult<()> {
+        let quote = self.config.quote_asset.clone();
+        let symbol = paper.request.symbol.clone();
+        let buy = paper.request.is_buy();
+        let balance = account.balances.get(&quote).copied().unwrap_or(0.0);
+
+        if !paper.futures {
+            let base = base_asset(&symbol);
+            let held = account.balances.get(&base).copied().unwrap_or(0.0);
+            if buy && balance < amount * price + fee {
+                return Err(rejected(format!(
+                    "Insufficient paper {} balance: {:.2} available",
+                    quote, balance
+                )));
+            }
+            if !buy && held + 1e-12 < amount {
+                return Err(rejected(format!(
+                    "Insufficient paper {} balance: {} available",
+                    base, held
+                )));
+            }
+            let signed = if buy { amount } else { -amount };
+            *account.balances.entry(base).or_default() += signed;
+            *account.balances.entry(quote).or_default() -= signed * price + fee;
+            return Ok(());
+        }
+
+        let leverage = account.leverage_for(&symbol);
+        let now = self.now();
+        let free = balance - account.used_margin();
+        let position = account
+            .positions
+            .entry(symbol.clone())
+            .or_insert_with(|| PaperPosition {
+                symbol: symbol.clone(),
+                amount: 0.0,
+                entry_price: 0.0,
+                margin_adjustment: 0.0,
+                realized_pnl: 0.0,
+                last_funding_at: now,
+            });
+        let signed = if buy { amount } else { -amount };
+        let closing = if position.amount * signed < 0.0 {
+            amount.min(position.amount.abs())
+        } else {
+            0.0
+        };
+        let opening = amount - closing;
+        if opening > 0.0 && free < opening * price / leverage + fee {
+            return Err(rejected(format!(
+                "Insufficient paper margin: {:.2} {} free",
+                free, quote
+            )));
+        }
+
+        let mut realized = 0.0;
+        if closing > 0.0 {
+            realized = closing * (price - position.entry_price) * position.amount.signum();
+            position.amount += closing * signed.signum();
+            position.realized_pnl += realized;
+        }
+        if opening > 0.0 {
+            let size = position.amount.abs();
+            position.entry_price =
+                (size * position.entry_price + opening * price) / (size + opening);
+            position.amount += opening * signed.signum();
+        }
+        if position.amount.abs() < 1e-12 {
+            account.positions.remove(&symbol);
+        }
+        *account.balances.entry(quote).or_default() += realized - fee;
+        Ok(())
+    }
+
+    /// Fill what the book allows of a triggered order and update its state
+    async fn execute(
+        &self,
+        exchange_id: &str,
+        account: &mut PaperAccount,
+        paper: &mut PaperOrder,
+        taker: bool,
+    ) -> ArbitrageResult<()> {
+        let book = self
+            .market
+            .order_book(exchange_id, &paper.request.symbol, self.config.book_depth)
+            .await?;
+        let buy = paper.request.is_buy();
+        let mut remaining = paper.order.remaining;
+        if paper.request.reduce_only && paper.futures {
+            let held = account
+                .positions
+                .get(&paper.request.symbol)
+                .map(|position| position.amount)
+                .unwrap_or(0.0);
+            let reducible = if (held > 0.0) != buy { held.abs() } else { 0.0 };
+            remaining = remaining.min(reducible);
+        }
+        let limit = match paper.request.order_type {
+            OrderType::Market
+            | OrderType::StopLoss
+            | OrderType::TakeProfit
+            | OrderType::TrailingStop => None,
+            _ => paper.request.price,
+        };
+        let (filled, average) =
+            walk_book(&book, buy, remaining, limit, self.config.book_participation);
+        if let (true, Some(average)) = (filled > 0.0, average) {
+            let slippage = if taker {
+                self.config.slippage_bps / 10_000.0
+            } else {
+                0.0
+            };
+            let price = if buy {
+                average * (1.0 + slippage)
+            } else {
+                average * (1.0 - slippage)
+            };
+            let rate = if taker {
+                self.config.taker_fee_rate
+            } else {
+                self.config.maker_fee_rate
+            };
+            let fee = filled * price * rate;
+            self.apply_fill(account, paper, filled, price, fee)?;
+
+            let order = &mut paper.order;
+            let total = order.filled + filled;
+            order.average =
+                Some((order.filled * order.average.unwrap_or(0.0) + filled * price) / total);
+            order.filled = total;
+            order.remaining = (order.amount - total).max(0.0);
+            order.cost += filled * price;
+            let fee_total = order.fee.as_ref().map(|fee| fee.cost).unwrap_or(0.0) + fee;
+            order.fee = Some(TradingFee {
+                currency: self.config.quote_asset.clone(),
+                cost: fee_total,
+                rate: Some(rate),
+            });
+            order.last_trade_timestamp = Some(self.now());
+        }
+
+        let order = &mut paper.order;
+        order.status = if order.remaining <= 1e-12 {
+            "closed"
+        } else if paper.request.order_type == OrderType::Limit
+            && paper.request.time_in_force.unwrap_or_default() == TimeInForce::GTC
+        {
+            "open"
+        } else {
+            // Market and IOC remainders are cancelled; triggered stops behave as market orders
+            "canceled"
+        }
+        .to_string();
+        Ok(())
+    }
+
+    /// Trigger or fill a resting order against the current book
+    async fn refresh_order(
+        &self,
+        exchange_id: &str,
+        account: &mut PaperAccount,
+        paper: &mut PaperOrder,
+    ) -> ArbitrageResult<()> {
+        if paper.order.status != "open" {
+            return Ok(());
+        }
+        let mid = self.mid_price(exchange_id, &paper.request.symbol).await?;
+        if paper.request.order_type == OrderType::TrailingStop {
+            let extreme = paper.extreme_price.unwrap_or(mid);
+            paper.extreme_price = Some(if paper.request.is_buy() {
+                extreme.min(mid)
+            } else {
+                extreme.max(mid)
+            });
+        }
+        if !is_triggered(paper, mid) {
+            return Ok(());
+        }
+        let taker = paper.request.order_type != OrderType::Limit;
+        self.execute(exchange_id, account, paper, taker).await
+    }
+
+    fn new_order(&self, account: &mut PaperAccount, request: &OrderRequest) -> Order {
+        account.next_order_id += 1;
+        let now = self.now();
+        Order {
+            id: format!("paper-{}", account.next_order_id),
+            client_order_id: request.client_order_id.clone(),
+            datetime: chrono::DateTime::from_timestamp_millis(now as i64)
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default(),
+            timestamp: now,
+            last_trade_timestamp: None,
+            status: "open".to_string(),
+            symbol: request.symbol.clone(),
+            type_: order_type_name(request.order_type).to_string(),
+            time_in_force: request
+                .time_in_force
+                .map(|time_in_force| time_in_force.as_str().to_string()),
+            side: request.side.to_lowercase(),
+            amount: request.amount,
+            price: request.price,
+            average: None,
+            filled: 0.0,
+            remaining: request.amount,
+            cost: 0.0,
+            trades: Vec::new(),
+            fee: None,
+            info: json!({ "paper": true, "stop_price": request.stop_price }),
+        }
+    }
+
+    /// Positions valued at the current mid price
+    fn to_position(
+        position: &PaperPosition,
+        leverage: f64,
+        mode: MarginMode,
+        mark: f64,
+    ) -> Position {
+        let size = position.amount.abs();
+        Position {
+            info: json!({ "paper": true }),
+            id: None,
+            symbol: position.symbol.clone(),
+            timestamp: position.last_funding_at,
+            datetime: String::new(),
+            isolated: Some(mode == MarginMode::Isolated),
+            hedged: Some(false),
+            side: if position.amount > 0.0 {
+                "long"
+            } else {
+                "short"
+            }
+            .to_string(),
+            amount: size,
+            contracts: Some(size),
+            contract_size: Some(1.0),
+            entry_price: Some(position.entry_price),
+            mark_price: Some(mark),
+            notional: Some(size * mark),
+            leverage: Some(leverage),
+            collateral: Some(size * position.entry_price / leverage + position.margin_adjustment),
+            initial_margin: Some(size * position.entry_price / leverage),
+            initial_margin_percentage: Some(1.0 / leverage),
+            maintenance_margin: None,
+            maintenance_margin_percentage: None,
+            unrealized_pnl: Some((mark - position.entry_price) * position.amount),
+            realized_pnl: Some(position.realized_pnl),
+            percentage: None,
+        }
+    }
+}
+
+impl<M: PaperMarketData, K: KvOperations> ExchangeInterface for PaperExchange<M, K> {
+    async fn get_markets(&self, exchange_id: &str) -> ArbitrageResult<Vec<Market>> {
+        Err(ArbitrageError::not_implemented(format!(
+            "Paper trading on {} has no market metadata",
+            exchange_id
+        )))
+    }
+
+    async fn get_ticker(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<Ticker> {
+        let book = self
+            .market
+            .order_book(exchange_id, symbol, self.config.book_depth)
+            .await?;
+        let now = self.now();
+        Ok(Ticker {
+            symbol: symbol.to_string(),
+            timestamp: now,
+            datetime: String::new(),
+            high: None,
+            low: None,
+            bid: book.best_bid(),
+            bid_volume: book.bids.first().map(|level| level[1]),
+            ask: book.best_ask(),
+            ask_volume: book.asks.first().map(|level| level[1]),
+            vwap: None,
+            open: None,
+            close: book.mid_price(),
+            last: book.mid_price(),
+            previous_close: None,
+            change: None,
+            percentage: None,
+            average: None,
+            base_volume: None,
+            quote_volume: None,
+            volume: None,
+            info: json!({ "paper": true }),
+        })
+    }
+
+    async fn get_orderbook(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        limit: Option<u32>,
+    ) -> ArbitrageResult<OrderBook> {
+        self.market
+            .order_book(exchange_id, symbol, limit.unwrap_or(self.config.book_depth))
+            .await
+    }
+
+    /// Paper fills are priced off one book per symbol, whatever the market type
+    async fn get_market_orderbook(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        limit: Option<u32>,
+        _market_type: &str,
+    ) -> ArbitrageResult<OrderBook> {
+        self.get_orderbook(exchange_id, symbol, limit).await
+    }
+
+    async fn fetch_funding_rates(
+        &self,
+        exchange_id: &str,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Value>> {
+        let Some(symbol) = symbol else {
+            return Ok(Vec::new());
+        };
+        Ok(self
+            .market
+            .funding_rate(exchange_id, symbol)
+            .await?
+            .map(|rate| json!({ "symbol": symbol, "funding_rate": rate }))
+            .into_iter()
+            .collect())
+    }
+
+    async fn get_balance(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        let account = self.load_account(exchange_id, credentials).await?;
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        let total = account
+            .balances
+            .get(&self.config.quote_asset)
+            .copied()
+            .unwrap_or(0.0);
+        let used = account.used_margin();
+        Ok(json!({
+            "paper": true,
+            "asset": self.config.quote_asset,
+            "total": total,
+            "used": used,
+            "free": total - used,
+            "balances": account.balances,
+        }))
+    }
+
+    async fn create_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        side: &str,
+        amount: f64,
+        price: Option<f64>,
+    ) -> ArbitrageResult<Order> {
+        let request = match price {
+            Some(price) => OrderRequest::limit(symbol, side, amount, price),
+            None => OrderRequest::market(symbol, side, amount),
+        };
+        self.place_order(exchange_id, credentials, &request).await
+    }
+
+    async fn place_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        request.validate().map_err(rejected)?;
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        let futures = is_futures_market(ExchangeRestClient::market_type(
+            credentials,
+            request.market_type.as_deref(),
+        ));
+        if !futures && request.reduce_only {
+            return Err(rejected("Reduce-only orders need a futures market"));
+        }
+
+        let book = self
+            .market
+            .order_book(exchange_id, &request.symbol, self.config.book_depth)
+            .await?;
+        if let Some(risk_check) = &self.risk_check {
+            risk_check
+                .check_order(
+                    credentials,
+                    request,
+                    order_notional(request, book.mid_price())?,
+                )
+                .await?;
+        }
+
+        let order = self.new_order(&mut account, request);
+        let mut paper = PaperOrder {
+            order,
+            request: request.clone(),
+            futures,
+            extreme_price: None,
+        };
+
+        match request.order_type {
+            OrderType::Market => {
+                self.execute(exchange_id, &mut account, &mut paper, true)
+                    .await?
+            }
+            OrderType::Limit => {
+                let marketable = match (request.is_buy(), book.best_ask(), book.best_bid()) {
+                    (true, Some(ask), _) => request.price.is_some_and(|price| price >= ask),
+                    (false, _, Some(bid)) => request.price.is_some_and(|price| price <= bid),
+                    _ => false,
+                };
+                let time_in_force = request.time_in_force.unwrap_or_default();
+                if marketable && time_in_force == TimeInForce::PostOnly {
+                    paper.order.status = "expired".to_string();
+                } else if time_in_force == TimeInForce::FOK {
+                    let limit = request.price;
+                    let (available, _) = walk_book(
+                        &book,
+                        request.is_buy(),
+                        request.amount,
+                        limit,
+                        self.config.book_participation,
+                    );
+                    if available + 1e-12 < request.amount {
+                        paper.order.status = "expired".to_string();
+                    } else {
+                        self.execute(exchange_id, &mut account, &mut paper, true)
+                            .await?;
+                    }
+                } else if marketable {
+                    self.execute(exchange_id, &mut account, &mut paper, true)
+                        .await?;
+                } else if time_in_force == TimeInForce::IOC {
+                    paper.order.status = "canceled".to_string();
+                }
+            }
+            // Conditional orders rest until the mid price triggers them
+            _ => {
+                paper.extreme_price = book.mid_price();
+            }
+        }
+
+        let order = paper.order.clone();
+        account.orders.insert(order.id.clone(), paper);
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        Ok(order)
+    }
+
+    async fn cancel_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        order_id: &str,
+        _symbol: &str,
+    ) -> ArbitrageResult<Order> {
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        let paper = account
+            .orders
+            .get_mut(order_id)
+            .ok_or_else(|| ArbitrageError::not_found(format!("Order {} not found", order_id)))?;
+        if paper.order.status == "open" {
+            paper.order.status = "canceled".to_string();
+        }
+        let order = paper.order.clone();
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        Ok(order)
+    }
+
+    async fn get_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        order_id: &str,
+        _symbol: &str,
+        _market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        let mut paper = account
+            .orders
+            .remove(order_id)
+            .ok_or_else(|| ArbitrageError::not_found(format!("Order {} not found", order_id)))?;
+        let result = self
+            .refresh_order(exchange_id, &mut account, &mut paper)
+            .await;
+        let order = paper.order.clone();
+        account.orders.insert(order.id.clone(), paper);
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        result.map(|_| order)
+    }
+
+    async fn get_open_orders(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        let mut ids: Vec<String> = account
+            .orders
+            .iter()
+            .filter(|(_, paper)| {
+                paper.order.status == "open"
+                    && symbol.is_none_or(|symbol| paper.request.symbol == symbol)
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+
+        let mut open = Vec::new();
+        for id in ids {
+            let mut paper = account.orders.remove(&id).unwrap();
+            self.refresh_order(exchange_id, &mut account, &mut paper)
+                .await?;
+            if paper.order.status == "open" {
+                open.push(paper.order.clone());
+            }
+            account.orders.insert(id, paper);
+        }
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        Ok(open)
+    }
+
+    async fn get_open_positions(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        let account = self.load_account(exchange_id, credentials).await?;
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        let mut positions = Vec::new();
+        for position in account.positions.values() {
+            if symbol.is_some_and(|symbol| position.symbol != symbol) {
+                continue;
+            }
+            let mark = self.mid_price(exchange_id, &position.symbol).await?;
+            let mode = account
+                .margin_modes
+                .get(&position.symbol)
+                .copied()
+                .unwrap_or(MarginMode::Cross);
+            positions.push(Self::to_position(
+                position,
+                account.leverage_for(&position.symbol),
+                mode,
+                mark,
+            ));
+        }
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(positions)
+    }
+
+    async fn get_funding_payments(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        let account = self.load_account(exchange_id, credentials).await?;
+        self.save_account(exchange_id, credentials, &account)
+            .await?;
+        Ok(account
+            .funding_payments
+            .into_iter()
+            .filter(|payment| {
+                payment.symbol == symbol && since.is_none_or(|since| payment.timestamp >= since)
+            })
+            .collect())
+    }
+
+    async fn set_leverage(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
+    ) -> ArbitrageResult<()> {
+        if leverage == 0 {
+            return Err(rejected("Leverage must be at least 1"));
+        }
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        account.leverage.insert(symbol.to_string(), leverage);
+        self.save_account(exchange_id, credentials, &account).await
+    }
+
+    async fn set_margin_mode(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        account.margin_modes.insert(symbol.to_string(), mode);
+        self.save_account(exchange_id, credentials, &account).await
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        let mut account = self.load_account(exchange_id, credentials).await?;
+        if account.margin_modes.get(symbol) != Some(&MarginMode::Isolated) {
+            return Err(rejected(format!(
+                "{} is not in isolated margin mode",
+                symbol
+            )));
+        }
+        let free = account
+            .balances
+            .get(&self.config.quote_asset)
+            .copied()
+            .unwrap_or(0.0)
+            - account.used_margin();
+        if amount > free {
+            return Err(rejected(format!(
+                "Insufficient paper margin: {:.2} {} free",
+                free, self.config.quote_asset
+            )));
+        }
+        let position = account
+            .positions
+            .get_mut(symbol)
+            .ok_or_else(|| ArbitrageError::not_found(format!("No open position in {}", symbol)))?;
+        if position.margin_adjustment + amount < 0.0 {
+            return Err(rejected("Cannot remove more than the margin added"));
+        }
+        position.margin_adjustment += amount;
+        self.save_account(exchange_id, credentials, &account).await
+    }
+
+    async fn get_trading_fees(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+    ) -> ArbitrageResult<TradingFees> {
+        Ok(TradingFees {
+            trading: TradingFeeRates {
+                maker: self.config.maker_fee_rate,
+                taker: self.config.taker_fee_rate,
+                percentage: true,
+                tier_based: false,
+            },
+            funding: None,
+        })
+    }
+
+    async fn test_api_connection(
+        &self,
+        _exchange_id: &str,
+        _api_key: &str,
+        _secret: &str,
+        _passphrase: Option<&str>,
+    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
+        Ok((true, true, None))
+    }
+
+    async fn test_api_connection_with_options(
+        &self,
+        _exchange_id: &str,
+        _api_key: &str,
+        _secret: &str,
+        _passphrase: Option<&str>,
+        _leverage: Option<i32>,
+        _exchange_type: Option<&str>,
+    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
+        Ok((true, true, None))
+    }
+
+    async fn probe_api_key(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        Ok(ApiKeyPermissions {
+            can_read: true,
+            can_trade_spot: true,
+            can_trade_futures: true,
+            can_withdraw: false,
+            ip_restricted: None,
+            rate_limit: None,
+        })
+    }
+}
+
+/// A user's exchange: live, or their paper account when paper mode is on
+pub enum TradingVenue<L: ExchangeInterface, P: ExchangeInterface> {
+    Live(L),
+    Paper(P),
+}
+
+impl<L: ExchangeInterface, P: ExchangeInterface> TradingVenue<L, P> {
+    pub fn for_preferences(preferences: &UserTradingPreferences, live: L, paper: P) -> Self {
+        if preferences.paper_trading {
+            TradingVenue::Paper(paper)
+        } else {
+            TradingVenue::Live(live)
+        }
+    }
+
+    pub fn is_paper(&self) -> bool {
+        matches!(self, TradingVenue::Paper(_))
+    }
+}
+
+/// The live exchange service, or paper accounts in KV fed by its market data
+#[cfg(target_arch = "wasm32")]
+pub type ProductionVenue = TradingVenue<
+    crate::services::core::trading::exchange::ExchangeService,
+    PaperExchange<
+        LiveMarketData<crate::services::core::trading::exchange::ExchangeService>,
+        worker::kv::KvStore,
+    >,
+>;
+
+#[cfg(target_arch = "wasm32")]
+impl ProductionVenue {
+    /// The venue `preferences` select; paper orders go through the live risk check
+    pub fn for_user(
+        preferences: &UserTradingPreferences,
+        exchange: &Arc<crate::services::core::trading::exchange::ExchangeService>,
+        store: Arc<worker::kv::KvStore>,
+    ) -> Self {
+        let mut paper = PaperExchange::new(Arc::new(LiveMarketData(exchange.clone())), store);
+        if let Some(risk_check) = exchange.risk_check() {
+            paper = paper.with_risk_check(risk_check);
+        }
+        TradingVenue::for_preferences(preferences, (**exchange).clone(), paper)
+    }
+}
+
+/// Paper credentials for `user_id` on each of `exchanges`
+pub fn paper_credentials(user_id: &str, exchanges: &[ExchangeIdEnum]) -> Vec<ExchangeCredentials> {
+    exchanges
+        .iter()
+        .map(|exchange| ExchangeCredentials::paper(*exchange, user_id))
+        .collect()
+}
+
+impl<L: ExchangeInterface, P: ExchangeInterface> ExchangeInterface for TradingVenue<L, P> {
+    async fn get_markets(&self, exchange_id: &str) -> ArbitrageResult<Vec<Market>> {
+        match self {
+            TradingVenue::Live(exchange) => exchange.get_markets(exchange_id).await,
+            TradingVenue::Paper(exchange) => exchange.get_markets(exchange_id).await,
+        }
+    }
+
+    async fn get_ticker(&self, exchange_id: &str, symbol: &str) -> ArbitrageResult<Ticker> {
+        match self {
+            TradingVenue::Live(exchange) => exchange.get_ticker(exchange_id, symbol).await,
+            TradingVenue::Paper(exchange) => exchange.get_ticker(exchange_id, symbol).await,
+        }
+    }
+
+    async fn get_orderbook(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        limit: Option<u32>,
+    ) -> ArbitrageResult<OrderBook> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange.get_orderbook(exchange_id, symbol, limit).await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange.get_orderbook(exchange_id, symbol, limit).await
+            }
+        }
+    }
+
+    async fn get_market_orderbook(
+        &self,
+        exchange_id: &str,
+        symbol: &str,
+        limit: Option<u32>,
+        market_type: &str,
+    ) -> ArbitrageResult<OrderBook> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .get_market_orderbook(exchange_id, symbol, limit, market_type)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .get_market_orderbook(exchange_id, symbol, limit, market_type)
+                    .await
+            }
+        }
+    }
+
+    async fn fetch_funding_rates(
+        &self,
+        exchange_id: &str,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Value>> {
+        match self {
+            TradingVenue::Live(exchange) => exchange.fetch_funding_rates(exchange_id, symbol).await,
+            TradingVenue::Paper(exchange) => {
+                exchange.fetch_funding_rates(exchange_id, symbol).await
+            }
+        }
+    }
+
+    async fn get_balance(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        match self {
+            TradingVenue::Live(exchange) => exchange.get_balance(exchange_id, credentials).await,
+            TradingVenue::Paper(exchange) => exchange.get_balance(exchange_id, credentials).await,
+        }
+    }
+
+    async fn create_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        side: &str,
+        amount: f64,
+        price: Option<f64>,
+    ) -> ArbitrageResult<Order> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .create_order(exchange_id, credentials, symbol, side, amount, price)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .create_order(exchange_id, credentials, symbol, side, amount, price)
+                    .await
+            }
+        }
+    }
+
+    async fn place_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .place_order(exchange_id, credentials, request)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .place_order(exchange_id, credentials, request)
+                    .await
+            }
+        }
+    }
+
+    async fn cancel_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        order_id: &str,
+        symbol: &str,
+    ) -> ArbitrageResult<Order> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .cancel_order(exchange_id, credentials, order_id, symbol)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .cancel_order(exchange_id, credentials, order_id, symbol)
+                    .await
+            }
+        }
+    }
+
+    async fn get_order(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        order_id: &str,
+        symbol: &str,
+        market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .get_order(exchange_id, credentials, order_id, symbol, market_type)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .get_order(exchange_id, credentials, order_id, symbol, market_type)
+                    .await
+            }
+        }
+    }
+
+    async fn get_open_orders(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .get_open_orders(exchange_id, credentials, symbol)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .get_open_orders(exchange_id, credentials, symbol)
+                    .await
+            }
+        }
+    }
+
+    async fn get_open_positions(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .get_open_positions(exchange_id, credentials, symbol)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .get_open_positions(exchange_id, credentials, symbol)
+                    .await
+            }
+        }
+    }
+
+    async fn get_funding_payments(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .get_funding_payments(exchange_id, credentials, symbol, since)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .get_funding_payments(exchange_id, credentials, symbol, since)
+                    .await
+            }
+        }
+    }
+
+    async fn set_leverage(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        leverage: u32,
+    ) -> ArbitrageResult<()> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .set_leverage(exchange_id, credentials, symbol, leverage)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .set_leverage(exchange_id, credentials, symbol, leverage)
+                    .await
+            }
+        }
+    }
+
+    async fn set_margin_mode(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .set_margin_mode(exchange_id, credentials, symbol, mode)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .set_margin_mode(exchange_id, credentials, symbol, mode)
+                    .await
+            }
+        }
+    }
+
+    async fn adjust_isolated_margin(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .adjust_isolated_margin(exchange_id, credentials, symbol, amount)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .adjust_isolated_margin(exchange_id, credentials, symbol, amount)
+                    .await
+            }
+        }
+    }
+
+    async fn get_trading_fees(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+        symbol: &str,
+    ) -> ArbitrageResult<TradingFees> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .get_trading_fees(exchange_id, credentials, symbol)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .get_trading_fees(exchange_id, credentials, symbol)
+                    .await
+            }
+        }
+    }
+
+    async fn test_api_connection(
+        &self,
+        exchange_id: &str,
+        api_key: &str,
+        secret: &str,
+        passphrase: Option<&str>,
+    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .test_api_connection(exchange_id, api_key, secret, passphrase)
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .test_api_connection(exchange_id, api_key, secret, passphrase)
+                    .await
+            }
+        }
+    }
+
+    async fn test_api_connection_with_options(
+        &self,
+        exchange_id: &str,
+        api_key: &str,
+        secret: &str,
+        passphrase: Option<&str>,
+        leverage: Option<i32>,
+        exchange_type: Option<&str>,
+    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
+        match self {
+            TradingVenue::Live(exchange) => {
+                exchange
+                    .test_api_connection_with_options(
+                        exchange_id,
+                        api_key,
+                        secret,
+                        passphrase,
+                        leverage,
+                        exchange_type,
+                    )
+                    .await
+            }
+            TradingVenue::Paper(exchange) => {
+                exchange
+                    .test_api_connection_with_options(
+                        exchange_id,
+                        api_key,
+                        secret,
+                        passphrase,
+                        leverage,
+                        exchange_type,
+                    )
+                    .await
+            }
+        }
+    }
+
+    async fn probe_api_key(
+        &self,
+        exchange_id: &str,
+        credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        match self {
+            TradingVenue::Live(exchange) => exchange.probe_api_key(exchange_id, credentials).await,
+            TradingVenue::Paper(exchange) => exchange.probe_api_key(exchange_id, credentials).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::arbitrage_executor::{ArbitrageExecutor, ExecutionConfig};
+    use crate::services::core::trading::positions::PositionsService;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::types::{ArbitrageOpportunity, PositionStatus};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn book(symbol: &str, bids: &[[f64; 2]], asks: &[[f64; 2]]) -> OrderBook {
+        OrderBook {
+            symbol: symbol.to_string(),
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+            timestamp: 0,
+            datetime: String::new(),
+            nonce: None,
+        }
+    }
+
+    fn paper(
+        market: Arc<RecordedMarketData>,
+        clock: Arc<AtomicU64>,
+    ) -> PaperExchange<RecordedMarketData, MockKvStore> {
+        PaperExchange::new(market, Arc::new(MockKvStore::new()))
+            .with_config(PaperTradingConfig {
+                slippage_bps: 10.0,
+                ..PaperTradingConfig::default()
+            })
+            .with_clock(Arc::new(move || clock.load(Ordering::SeqCst)))
+    }
+
+    fn futures(exchange: ExchangeIdEnum) -> ExchangeCredentials {
+        let mut credentials = ExchangeCredentials::paper(exchange, "alice");
+        credentials.exchange_type = "futures".to_string();
+        credentials
+    }
+
+    #[tokio::test]
+    async fn test_market_order_walks_book_with_slippage_fees_and_partial_fill() {
+        let market = Arc::new(RecordedMarketData::default());
+        market.set_order_book(
+            "binance",
+            book(
+                "BTCUSDT",
+                &[[64_990.0, 1.0]],
+                &[[65_000.0, 0.05], [65_100.0, 0.03]],
+            ),
+        );
+        let clock = Arc::new(AtomicU64::new(HOUR_MS));
+        let exchange = paper(market.clone(), clock);
+        let credentials = futures(ExchangeIdEnum::Binance);
+
+        let order = exchange
+            .place_order(
+                "binance",
+                &credentials,
+                &OrderRequest::market("BTCUSDT", "buy", 0.1),
+            )
+            .await
+            .unwrap();
+
+        // Only 0.08 rests on the asks; the rest is cancelled
+        assert_eq!(order.status, "canceled");
+        assert!((order.filled - 0.08).abs() < 1e-12);
+        let walked = (0.05 * 65_000.0 + 0.03 * 65_100.0) / 0.08;
+        let price = walked * 1.001;
+        assert!((order.average.unwrap() - price).abs() < 1e-6);
+        let fee = order.fee.as_ref().unwrap().cost;
+        assert!((fee - 0.08 * price * 0.0005).abs() < 1e-9);
+
+        let positions = exchange
+            .get_open_positions("binance", &credentials, None)
+            .await
+            .unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].side, "long");
+        assert!((positions[0].entry_price.unwrap() - price).abs() < 1e-6);
+
+        // Closing at the bid realises the loss against the quote balance
+        let close = OrderRequest {
+            reduce_only: true,
+            ..OrderRequest::market("BTCUSDT", "sell", 1.0)
+        };
+        let closed = exchange
+            .place_order("binance", &credentials, &close)
+            .await
+            .unwrap();
+        assert!((closed.filled - 0.08).abs() < 1e-12);
+        let exit = 64_990.0 * 0.999;
+        let balance = exchange.get_balance("binance", &credentials).await.unwrap();
+        let expected = 10_000.0 + 0.08 * (exit - price) - fee - 0.08 * exit * 0.0005;
+        assert!((balance["total"].as_f64().unwrap() - expected).abs() < 1e-6);
+        assert!(exchange
+            .get_open_positions("binance", &credentials, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resting_orders_fill_when_polled_and_funding_settles_each_interval() {
+        let market = Arc::new(RecordedMarketData::default());
+        market.set_order_book(
+            "bybit",
+            book("ETHUSDT", &[[3_000.0, 10.0]], &[[3_001.0, 10.0]]),
+        );
+        market.set_funding_rate("bybit", "ETHUSDT", 0.0001);
+        let clock = Arc::new(AtomicU64::new(HOUR_MS));
+        let exchange = paper(market.clone(), clock.clone());
+        let credentials = futures(ExchangeIdEnum::Bybit);
+
+        let sell = exchange
+            .place_order(
+                "bybit",
+                &credentials,
+                &OrderRequest::limit("ETHUSDT", "sell", 1.0, 3_010.0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(sell.status, "open");
+
+        market.set_order_book(
+            "bybit",
+            book("ETHUSDT", &[[3_012.0, 0.4]], &[[3_013.0, 10.0]]),
+        );
+        let partial = exchange
+            .get_order("bybit", &credentials, &sell.id, "ETHUSDT", None)
+            .await
+            .unwrap();
+        assert_eq!(partial.status, "open");
+        assert!((partial.filled - 0.4).abs() < 1e-12);
+        // Resting fills pay the maker fee without slippage
+        assert!((partial.average.unwrap() - 3_012.0).abs() < 1e-9);
+        assert_eq!(partial.fee.as_ref().unwrap().rate, Some(0.0002));
+
+        // Two funding boundaries pass while short 0.4; shorts receive a positive rate
+        clock.store(16 * HOUR_MS + 1, Ordering::SeqCst);
+        let payments = exchange
+            .get_funding_payments("bybit", &credentials, "ETHUSDT", None)
+            .await
+            .unwrap();
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].timestamp, 8 * HOUR_MS);
+        assert!((payments[0].amount - 0.4 * 3_012.5 * 0.0001).abs() < 1e-9);
+
+        let open = exchange
+            .get_open_orders("bybit", &credentials, Some("ETHUSDT"))
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        let cancelled = exchange
+            .cancel_order("bybit", &credentials, &sell.id, "ETHUSDT")
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, "canceled");
+    }
+
+    #[tokio::test]
+    async fn test_executor_opens_arbitrage_on_paper_accounts() {
+        let market = Arc::new(RecordedMarketData::default());
+        for exchange in ["binance", "bybit"] {
+            market.set_order_book(
+                exchange,
+                book("BTC/USDT", &[[64_990.0, 5.0]], &[[65_000.0, 5.0]]),
+            );
+        }
+        let clock = Arc::new(AtomicU64::new(HOUR_MS));
+        let exchange = Arc::new(paper(market, clock));
+        let positions = Arc::new(PositionsService::new(Arc::new(MockKvStore::new())));
+        let executor =
+            ArbitrageExecutor::new(exchange.clone(), positions).with_config(ExecutionConfig {
+                fill_timeout_ms: 5,
+                poll_interval_ms: 1,
+                ..ExecutionConfig::default()
+            });
+        let opportunity = ArbitrageOpportunity {
+            pair: "BTC/USDT".to_string(),
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            buy_price: 65_000.0,
+            ..ArbitrageOpportunity::default()
+        };
+
+        let position = executor
+            .execute(
+                "alice",
+                &opportunity,
+                &ExchangeCredentials::paper(ExchangeIdEnum::Binance, "alice"),
+                &ExchangeCredentials::paper(ExchangeIdEnum::Bybit, "alice"),
+                6_500.0,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(position.status, PositionStatus::Open);
+        assert!((position.long_position.amount - 0.1).abs() < 1e-9);
+        assert!(position.long_fees > 0.0 && position.short_fees > 0.0);
+        let short = exchange
+            .get_open_positions("bybit", &futures(ExchangeIdEnum::Bybit), Some("BTC/USDT"))
+            .await
+            .unwrap();
+        assert_eq!(short[0].side, "short");
+    }
+
+    #[tokio::test]
+    async fn test_risk_check_rejects_paper_orders_over_the_limits() {
+        use crate::services::core::trading::risk_gate::{PositionRiskCheck, RiskGate};
+        use crate::types::RiskManagementConfig;
+
+        let market = Arc::new(RecordedMarketData::default());
+        market.set_order_book(
+            "binance",
+            book("BTCUSDT", &[[64_990.0, 5.0]], &[[65_000.0, 5.0]]),
+        );
+        let clock = Arc::new(AtomicU64::new(HOUR_MS));
+        let positions = Arc::new(PositionsService::new(Arc::new(MockKvStore::new())));
+        let exchange = paper(market, clock).with_risk_check(Arc::new(PositionRiskCheck::new(
+            RiskGate::new(RiskManagementConfig {
+                max_position_size_usd: 5_000.0,
+                ..RiskManagementConfig::default()
+            }),
+            positions,
+        )));
+        let credentials = futures(ExchangeIdEnum::Binance);
+
+        let error = exchange
+            .place_order(
+                "binance",
+                &credentials,
+                &OrderRequest::market("BTCUSDT", "buy", 0.1),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_code.as_deref(), Some("RISK_LIMIT"));
+        assert!(exchange
+            .get_open_orders("binance", &credentials, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let small = exchange
+            .place_order(
+                "binance",
+                &credentials,
+                &OrderRequest::market("BTCUSDT", "buy", 0.05),
+            )
+            .await
+            .unwrap();
+        assert!((small.filled - 0.05).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_accounts_belong_to_the_user_not_the_api_key() {
+        let market = Arc::new(RecordedMarketData::default());
+        market.set_order_book(
+            "binance",
+            book("BTCUSDT", &[[64_990.0, 5.0]], &[[65_000.0, 5.0]]),
+        );
+        let exchange = paper(market, Arc::new(AtomicU64::new(HOUR_MS)));
+        let credentials = futures(ExchangeIdEnum::Binance);
+        exchange
+            .place_order(
+                "binance",
+                &credentials,
+                &OrderRequest::market("BTCUSDT", "buy", 0.01),
+            )
+            .await
+            .unwrap();
+
+        let mut rotated = credentials.clone();
+        rotated.api_key = "another-key".to_string();
+        let positions = exchange
+            .get_open_positions("binance", &rotated, None)
+            .await
+            .unwrap();
+        assert_eq!(positions.len(), 1);
+
+        let mut anonymous = credentials.clone();
+        anonymous.user_id = None;
+        assert!(exchange
+            .get_open_positions("binance", &anonymous, None)
+            .await
+            .is_err());
+    }
+}
@@ -1,10 +1,16 @@
 // src/services/positions.rs
+use std::collections::HashMap;
 use std::sync::Arc;
 
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::infrastructure::database_repositories::PositionRepository;
+use crate::services::core::trading::arbitrage_executor::is_settled;
+use crate::services::core::trading::exchange::ExchangeInterface;
 use crate::services::core::user::user_profile::UserProfileService;
 use crate::types::{
-    AccountInfo, ArbitragePosition, CommandPermission, ExchangeIdEnum, Position, PositionAction,
-    PositionSide, PositionStatus,
+    AccountInfo, ArbitragePosition, CommandPermission, ExchangeCredentials, ExchangeIdEnum,
+    ExecutionEvent, ExecutionStep, OrderRequest, Position, PositionAction, PositionSide,
+    PositionStatus, ProtectionKind, ProtectiveOrder,
 };
 use crate::utils::{ArbitrageError, ArbitrageResult};
 // use std::collections::HashMap; // Removed unused import
@@ -40,15 +46,350 @@ pub struct UpdatePositionData {
     pub pnl: Option<f64>,           // Added field
 }
 
+/// Relative size difference tolerated between a stored leg and the live exchange position
+pub const DEFAULT_SIZE_DRIFT_TOLERANCE: f64 = 0.01;
+
+/// `current_state` given to positions whose legs disagree with the exchange
+pub const OUT_OF_SYNC_STATE: &str = "out_of_sync";
+
+const DAY_MS: u64 = 86_400_000;
+
+/// A stored leg that does not match the exchange
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LegDiscrepancy {
+    /// No open position on the exchange for this leg
+    Missing {
+        exchange: ExchangeIdEnum,
+        side: PositionSide,
+        expected_size: f64,
+    },
+    /// The exchange position size differs by more than the tolerance
+    SizeDrift {
+        exchange: ExchangeIdEnum,
+        side: PositionSide,
+        expected_size: f64,
+        actual_size: f64,
+    },
+}
+
+/// Discrepancies found for one stored arbitrage position
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PositionReconciliation {
+    pub position_id: String,
+    pub discrepancies: Vec<LegDiscrepancy>,
+}
+
+fn compact_symbol(symbol: &str) -> String {
+    symbol.replace(['/', '-', '_'], "").to_uppercase()
+}
+
+/// Compare stored open positions with live exchange positions (sizes in base units).
+/// Exchanges missing from `live` could not be queried and are not checked.
+pub fn reconcile_positions(
+    stored: &[ArbitragePosition],
+    live: &HashMap<ExchangeIdEnum, Vec<Position>>,
+    tolerance: f64,
+) -> Vec<PositionReconciliation> {
+    stored
+        .iter()
+        .filter(|position| position.status == PositionStatus::Open)
+        .filter_map(|position| {
+            let legs = [
+                (
+                    position.long_exchange,
+                    PositionSide::Long,
+                    &position.long_position,
+                ),
+                (
+                    position.short_exchange,
+                    PositionSide::Short,
+                    &position.short_position,
+                ),
+            ];
+            let discrepancies: Vec<LegDiscrepancy> = legs
+                .into_iter()
+                // Legs with no size were never opened
+                .filter(|(_, _, leg)| leg.amount > 0.0)
+                .filter_map(|(exchange, side, leg)| {
+                    let live_positions = live.get(&exchange)?;
+                    let symbol = compact_symbol(if leg.symbol.is_empty() {
+                        &position.pair
+                    } else {
+                        &leg.symbol
+                    });
+                    let side_str = if side == PositionSide::Long {
+                        "long"
+                    } else {
+                        "short"
+                    };
+                    let matching: Vec<&Position> = live_positions
+                        .iter()
+                        .filter(|p| compact_symbol(&p.symbol) == symbol && p.side == side_str)
+                        .collect();
+                    if matching.is_empty() {
+                        return Some(LegDiscrepancy::Missing {
+                            exchange,
+                            side,
+                            expected_size: leg.amount,
+                        });
+                    }
+                    let actual_size: f64 = matching.iter().map(|p| p.amount.abs()).sum();
+                    ((actual_size - leg.amount).abs() / leg.amount > tolerance).then_some(
+                        LegDiscrepancy::SizeDrift {
+                            exchange,
+                            side,
+                            expected_size: leg.amount,
+                            actual_size,
+                        },
+                    )
+                })
+                .collect();
+            (!discrepancies.is_empty()).then(|| PositionReconciliation {
+                position_id: position.id.clone(),
+                discrepancies,
+            })
+        })
+        .collect()
+}
+
 /// Production positions service type alias
 pub type ProductionPositionsService =
     PositionsService<crate::services::core::infrastructure::kv::KVService>;
 
-// PositionsService now uses a generic KV store
+/// Price PnL of one leg: longs gain when the mark rises, shorts when it falls
+pub fn leg_pnl(entry_price: f64, mark_price: f64, amount: f64, long: bool) -> f64 {
+    let move_per_unit = if long {
+        mark_price - entry_price
+    } else {
+        entry_price - mark_price
+    };
+    move_per_unit * amount
+}
+
+/// Entry price and size of a leg that carries exposure. Single-sided positions keep their
+/// size in `size`; two-leg positions size each leg separately.
+fn leg_exposure(position: &ArbitragePosition, long: bool) -> Option<(f64, f64)> {
+    let exposure = match (&position.side, long) {
+        (PositionSide::Long, true) => (position.entry_price_long, position.size?),
+        (PositionSide::Short, false) => (position.entry_price_short, position.size?),
+        (PositionSide::Both, true) => (position.entry_price_long, position.long_position.amount),
+        (PositionSide::Both, false) => (position.entry_price_short, position.short_position.amount),
+        _ => return None,
+    };
+    Some(exposure).filter(|(entry, amount)| *entry > 0.0 && *amount > 0.0)
+}
+
+/// Whether the position's legs have been closed out, so their price PnL is already realized
+fn is_closed_out(position: &ArbitragePosition) -> bool {
+    matches!(
+        position.status,
+        PositionStatus::Closed | PositionStatus::Liquidated
+    )
+}
+
+/// Whether the position may still carry exposure, and so belongs in the position index
+fn is_live(position: &ArbitragePosition) -> bool {
+    matches!(
+        position.status,
+        PositionStatus::Open | PositionStatus::PartiallyFilled
+    )
+}
+
+/// Recompute PnL from each leg's mark price and the fees paid on each leg. Realized PnL
+/// accumulates: fees are deducted as they are recorded, funding is added by
+/// `record_funding_payment` and price PnL moves over when the position closes. Price moves
+/// on open legs are unrealized.
+pub fn refresh_pnl(position: &mut ArbitragePosition) {
+    let total_fees = position.long_fees + position.short_fees;
+    position.realized_pnl -= total_fees - position.total_fees;
+    position.total_fees = total_fees;
+
+    let settled = is_closed_out(position);
+    let mut unrealized = 0.0;
+    let mut cost_basis = 0.0;
+    for long in [true, false] {
+        let Some((entry, amount)) = leg_exposure(position, long) else {
+            continue;
+        };
+        let (mark, leg) = if long {
+            (position.current_price_long, &mut position.long_position)
+        } else {
+            (position.current_price_short, &mut position.short_position)
+        };
+        cost_basis += entry * amount;
+        if settled {
+            continue;
+        }
+        let pnl = mark.map_or(0.0, |mark| leg_pnl(entry, mark, amount, long));
+        leg.mark_price = mark.or(leg.mark_price);
+        leg.unrealized_pnl = Some(pnl);
+        leg.percentage = Some(pnl / (entry * amount) * 100.0);
+        unrealized += pnl;
+    }
+
+    position.unrealized_pnl = unrealized;
+    let net = net_pnl(position);
+    position.pnl = Some(net);
+    position.unrealized_pnl_percentage = (cost_basis > 0.0).then(|| net / cost_basis * 100.0);
+}
+
+/// Close out the legs at their last marks: each leg's price PnL moves from unrealized into
+/// realized on top of the funding and fees already booked
+fn realize_price_pnl(position: &mut ArbitragePosition) {
+    refresh_pnl(position);
+    for leg in [&mut position.long_position, &mut position.short_position] {
+        if let Some(pnl) = leg.unrealized_pnl.take() {
+            leg.realized_pnl = Some(leg.realized_pnl.unwrap_or(0.0) + pnl);
+            leg.unrealized_pnl = Some(0.0);
+        }
+    }
+    position.realized_pnl += position.unrealized_pnl;
+    position.unrealized_pnl = 0.0;
+    position.pnl = Some(net_pnl(position));
+}
+
+/// Settle `position` as closed or liquidated: realize its price PnL and stamp the exit
+fn close_out(position: &mut ArbitragePosition, status: PositionStatus) {
+    realize_price_pnl(position);
+    position.status = status;
+    let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+    position.exit_time = Some(now_ms);
+    position.closed_at = Some(now_ms);
+    position.updated_at = now_ms;
+}
+
+/// Combined PnL across both legs, fees and funding
+pub fn net_pnl(position: &ArbitragePosition) -> f64 {
+    position.realized_pnl + position.unrealized_pnl
+}
+
+/// Reduce-only conditional orders that should rest on the exchange for `position`, with an
+/// empty `order_id`. A trailing distance is converted into a callback rate off entry; two-leg
+/// positions are covered by `hedged_protective_orders`.
+pub fn desired_protective_orders(position: &ArbitragePosition) -> Vec<ProtectiveOrder> {
+    if !position.exchange_protection || position.status != PositionStatus::Open {
+        return Vec::new();
+    }
+    let (exchange, entry_price, close_side) = match position.side {
+        PositionSide::Long => (position.long_exchange, position.entry_price_long, "sell"),
+        PositionSide::Short => (position.short_exchange, position.entry_price_short, "buy"),
+        PositionSide::Both => return hedged_protective_orders(position),
+    };
+    let Some(amount) = position.size.filter(|amount| *amount > 0.0) else {
+        return Vec::new();
+    };
+    let order = |kind, trigger_price, trailing_percent| ProtectiveOrder {
+        kind,
+        exchange,
+        symbol: position.symbol.clone(),
+        side: close_side.to_string(),
+        amount,
+        trigger_price,
+        trailing_percent,
+        order_id: String::new(),
+    };
+
+    let mut orders = Vec::new();
+    if let Some(price) = position.stop_loss_price {
+        orders.push(order(ProtectionKind::StopLoss, Some(price), None));
+    }
+    if let Some(price) = position.take_profit_price {
+        orders.push(order(ProtectionKind::TakeProfit, Some(price), None));
+    }
+    if let Some(distance) = position
+        .trailing_stop_distance
+        .filter(|_| entry_price > 0.0)
+    {
+        orders.push(order(
+            ProtectionKind::TrailingStop,
+            None,
+            Some(distance / entry_price * 100.0),
+        ));
+    }
+    orders
+}
+
+/// Both legs of a hedged position close when price reaches its stop-loss or take-profit
+/// level, so each leg gets a reduce-only order for its own amount at that level. Whether the
+/// order rests as a stop or a take-profit follows from where the level sits against the leg's
+/// entry, so both legs trigger on the same move and the hedge is never left half open.
+fn hedged_protective_orders(position: &ArbitragePosition) -> Vec<ProtectiveOrder> {
+    let levels = [position.stop_loss_price, position.take_profit_price];
+    let mut orders = Vec::new();
+    for long in [true, false] {
+        let Some((entry_price, amount)) = leg_exposure(position, long) else {
+            continue;
+        };
+        let (exchange, close_side) = if long {
+            (position.long_exchange, "sell")
+        } else {
+            (position.short_exchange, "buy")
+        };
+        for level in levels.into_iter().flatten() {
+            let against = if long {
+                level < entry_price
+            } else {
+                level > entry_price
+            };
+            orders.push(ProtectiveOrder {
+                kind: if against {
+                    ProtectionKind::StopLoss
+                } else {
+                    ProtectionKind::TakeProfit
+                },
+                exchange,
+                symbol: position.symbol.clone(),
+                side: close_side.to_string(),
+                amount,
+                trigger_price: Some(level),
+                trailing_percent: None,
+                order_id: String::new(),
+            });
+        }
+    }
+    orders
+}
+
+/// Whether a placed order still enforces `wanted` (ids are ignored)
+fn same_protection(wanted: &ProtectiveOrder, placed: &ProtectiveOrder) -> bool {
+    let close = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => (a - b).abs() <= 1e-9 * a.abs().max(1.0),
+        (None, None) => true,
+        _ => false,
+    };
+    wanted.kind == placed.kind
+        && wanted.exchange == placed.exchange
+        && wanted.side == placed.side
+        && close(Some(wanted.amount), Some(placed.amount))
+        && close(wanted.trigger_price, placed.trigger_price)
+        && close(wanted.trailing_percent, placed.trailing_percent)
+}
+
+fn protection_event(
+    step: ExecutionStep,
+    order: &ProtectiveOrder,
+    message: impl Into<String>,
+) -> ExecutionEvent {
+    ExecutionEvent {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        step,
+        exchange: Some(order.exchange),
+        order_id: (!order.order_id.is_empty()).then(|| order.order_id.clone()),
+        side: Some(order.side.clone()),
+        amount: Some(order.amount),
+        price: order.trigger_price,
+        message: message.into(),
+    }
+}
+
 #[derive(Clone)]
 pub struct PositionsService<T: KvOperations + Send + Sync + 'static> {
     kv_store: Arc<T>,
     user_profile_service: Option<UserProfileService>,
+    /// D1 copy of every write, which also records status transitions in `position_history`
+    #[cfg(target_arch = "wasm32")]
+    position_repository: Option<Arc<PositionRepository>>,
 }
 
 impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
@@ -56,20 +397,60 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         Self {
             kv_store,
             user_profile_service: None,
+            #[cfg(target_arch = "wasm32")]
+            position_repository: None,
         }
     }
 
+    /// Mirror every position write into the positions table and its history
+    #[cfg(target_arch = "wasm32")]
+    pub fn with_position_repository(
+        mut self,
+        position_repository: Arc<PositionRepository>,
+    ) -> Self {
+        self.position_repository = Some(position_repository);
+        self
+    }
+
+    /// The store positions are kept in
+    pub fn kv_store(&self) -> Arc<T> {
+        self.kv_store.clone()
+    }
+
     // Helper to create a KvStore key for a position
     fn position_key(id: &str) -> String {
         format!("position:{}", id)
     }
 
+    /// Write `position` to KV and, when configured, to the positions table
+    async fn put_position(&self, position: &ArbitragePosition) -> ArbitrageResult<()> {
+        self.kv_store
+            .put(&Self::position_key(&position.id), position)
+            .await
+            .map_err(|e| {
+                ArbitrageError::storage_error(format!(
+                    "Failed to save position {}: {}",
+                    position.id, e
+                ))
+            })?;
+        #[cfg(target_arch = "wasm32")]
+        if let Some(repository) = &self.position_repository {
+            repository.save_position(position).await?;
+        }
+        Ok(())
+    }
+
     // Helper to create a KvStore key for user positions
     #[allow(dead_code)] // Will be used for position management
     fn user_positions_key(user_id: &str) -> String {
         format!("user_positions:{}", user_id)
     }
 
+    // Helper to create a KvStore key for the positions a user closed on one UTC day
+    fn closed_positions_key(user_id: &str, day_start_ms: u64) -> String {
+        format!("positions:closed:{}:{}", user_id, day_start_ms)
+    }
+
     /// Set the UserProfile service for database-based RBAC
     pub fn set_user_profile_service(&mut self, user_profile_service: UserProfileService) {
         self.user_profile_service = Some(user_profile_service);
@@ -249,20 +630,18 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
             recommended_action: None,
             risk_percentage_applied: _risk_percentage_applied_for_audit,
             optimization_score: None,
+            execution_log: Vec::new(),
+            long_fees: 0.0,
+            short_fees: 0.0,
+            funding_pnl: 0.0,
+            predicted_rate_difference: None,
+            exchange_protection: false,
+            protective_orders: Vec::new(),
+            liquidation_alert_threshold: None,
         };
 
         // Store position
-        let key = Self::position_key(&id);
-        // let value = serde_json::to_string(&position).map_err(|e| { // Confirmed: This line remains commented out as 'value' is not used with the direct put below.
-        //     ArbitrageError::parse_error(format!("Failed to serialize position: {}", e))
-        // })?;
-
-        self.kv_store.put(&key, &position).await.map_err(|e| {
-            ArbitrageError::storage_error(format!(
-                "KV store put operation failed for position {}: {:?}",
-                position.id, e
-            ))
-        })?;
+        self.put_position(&position).await?;
 
         // Update position index
         self.add_to_position_index(&id).await?;
@@ -284,15 +663,35 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         }
     }
 
+    /// Store `position` as is, keeping it in the index only while it is open
+    pub async fn save_position(&self, position: &ArbitragePosition) -> ArbitrageResult<()> {
+        self.put_position(position).await?;
+        if is_closed_out(position) {
+            self.record_closed_position(position).await?;
+        }
+        if is_live(position) {
+            self.add_to_position_index(&position.id).await
+        } else {
+            self.remove_from_position_index(&position.id)
+                .await
+                .map_err(|e| {
+                    ArbitrageError::storage_error(format!(
+                        "Failed to update position index for closed position {}: {}",
+                        position.id, e
+                    ))
+                })
+        }
+    }
+
+    /// Apply `update_data` to a stored position. Closing or liquidating through here settles
+    /// the position like `close_position`; a settled position cannot change status again.
     pub async fn update_position(
         &self,
         id: &str,
         update_data: UpdatePositionData,
     ) -> ArbitrageResult<Option<ArbitragePosition>> {
-        let position_key = Self::position_key(id);
-        let mut position: ArbitragePosition = match self.kv_store.get(&position_key).await? {
-            Some(p) => p,
-            None => return Ok(None),
+        let Some(mut position) = self.get_position(id).await? else {
+            return Ok(None);
         };
 
         if let Some(size) = update_data.size {
@@ -308,61 +707,40 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
             position.pnl = Some(pnl);
             // TODO: Differentiate between realized and unrealized PNL updates if necessary
         }
-        if let Some(status) = update_data.status {
-            position.status = status;
-            if position.status == PositionStatus::Closed
-                || position.status == PositionStatus::Liquidated
-            {
-                position.exit_time = Some(chrono::Utc::now().timestamp_millis() as u64);
-                position.closed_at = Some(chrono::Utc::now().timestamp_millis() as u64);
-            }
-        }
 
         // Update timestamp
         position.updated_at = chrono::Utc::now().timestamp_millis() as u64; // Corrected: Direct u64 assignment
 
-        self.kv_store
-            .put(&position_key, &position)
-            .await
-            .map_err(|e| {
-                ArbitrageError::storage_error(format!("Failed to update position {}: {}", id, e))
-            })?;
+        match update_data.status {
+            Some(status) if status == position.status => {}
+            Some(_) if is_closed_out(&position) => {
+                return Err(ArbitrageError::validation_error(format!(
+                    "Position {} is already {:?}",
+                    id, position.status
+                )));
+            }
+            Some(status @ (PositionStatus::Closed | PositionStatus::Liquidated)) => {
+                close_out(&mut position, status);
+            }
+            Some(status) => position.status = status,
+            None => {}
+        }
 
+        self.save_position(&position).await?;
         Ok(Some(position))
     }
 
     pub async fn close_position(&self, id: &str) -> ArbitrageResult<bool> {
-        let position_key = Self::position_key(id);
-        let mut position: ArbitragePosition = match self.kv_store.get(&position_key).await? {
-            Some(p) => p,
-            None => return Ok(false), // Position not found
+        let Some(mut position) = self.get_position(id).await? else {
+            return Ok(false); // Position not found
         };
 
-        if position.status == PositionStatus::Closed
-            || position.status == PositionStatus::Liquidated
-        {
+        if is_closed_out(&position) {
             return Ok(true); // Already closed
         }
 
-        position.status = PositionStatus::Closed;
-        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
-        position.exit_time = Some(now_ms);
-        position.closed_at = Some(now_ms);
-        position.updated_at = now_ms; // Ensure this is a direct u64 assignment
-
-        self.kv_store
-            .put(&position_key, &position)
-            .await
-            .map_err(|e| {
-                ArbitrageError::storage_error(format!("Failed to close position {}: {}", id, e))
-            })?;
-        self.remove_from_position_index(id).await.map_err(|e| {
-            ArbitrageError::storage_error(format!(
-                "Failed to update position index for closed position {}: {}",
-                id, e
-            ))
-        })?;
-
+        close_out(&mut position, PositionStatus::Closed);
+        self.save_position(&position).await?;
         Ok(true)
     }
 
@@ -380,6 +758,23 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         Ok(positions)
     }
 
+    /// Positions `user_id` closed or had liquidated during the UTC day containing `at_ms`.
+    /// Closed positions leave the index, so they are read from the day's closing history.
+    pub async fn get_positions_closed_on(
+        &self,
+        user_id: &str,
+        at_ms: u64,
+    ) -> ArbitrageResult<Vec<ArbitragePosition>> {
+        let key = Self::closed_positions_key(user_id, at_ms - at_ms % DAY_MS);
+        let mut positions = Vec::new();
+        for id in self.get_closed_position_ids(&key).await? {
+            if let Some(position) = self.get_position(&id).await? {
+                positions.push(position);
+            }
+        }
+        Ok(positions)
+    }
+
     pub async fn get_open_positions(&self) -> ArbitrageResult<Vec<ArbitragePosition>> {
         let all_positions = self.get_all_positions().await?;
         Ok(all_positions
@@ -388,9 +783,58 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
             .collect())
     }
 
+    /// Check `user_id`'s open positions against the exchanges their credentials can query,
+    /// flagging positions whose legs are gone or have drifted with `current_state = "out_of_sync"`.
+    /// Exchanges that fail to respond are skipped rather than reported as missing legs.
+    pub async fn reconcile_with_exchanges<E: ExchangeInterface>(
+        &self,
+        user_id: &str,
+        exchange_service: &E,
+        credentials: &[ExchangeCredentials],
+        tolerance: f64,
+    ) -> ArbitrageResult<Vec<PositionReconciliation>> {
+        let open_positions: Vec<ArbitragePosition> = self
+            .get_open_positions()
+            .await?
+            .into_iter()
+            .filter(|p| p.user_id == user_id)
+            .collect();
+        let mut live: HashMap<ExchangeIdEnum, Vec<Position>> = HashMap::new();
+        for creds in credentials {
+            let in_use = open_positions
+                .iter()
+                .any(|p| p.long_exchange == creds.exchange || p.short_exchange == creds.exchange);
+            if !in_use || live.contains_key(&creds.exchange) {
+                continue;
+            }
+            if let Ok(positions) = exchange_service
+                .get_open_positions(creds.exchange.as_str(), creds, None)
+                .await
+            {
+                live.insert(creds.exchange, positions);
+            }
+        }
+
+        let reports = reconcile_positions(&open_positions, &live, tolerance);
+        for report in &reports {
+            if let Some(mut position) = open_positions
+                .iter()
+                .find(|p| p.id == report.position_id)
+                .cloned()
+            {
+                position.current_state = Some(OUT_OF_SYNC_STATE.to_string());
+                position.recommended_action = Some("review_exchange_positions".to_string());
+                position.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+                self.put_position(&position).await?;
+            }
+        }
+
+        Ok(reports)
+    }
+
     pub async fn calculate_total_pnl(&self) -> ArbitrageResult<f64> {
         let positions = self.get_open_positions().await?;
-        let total_pnl = positions.iter().filter_map(|pos| pos.pnl).sum();
+        let total_pnl = positions.iter().map(net_pnl).sum();
         Ok(total_pnl)
     }
 
@@ -416,14 +860,45 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         Ok(())
     }
 
-    #[allow(dead_code)]
     async fn remove_from_position_index(&self, position_id: &str) -> ArbitrageResult<()> {
         let mut index = self.get_position_index().await?;
+        let before = index.len();
         index.retain(|id| id != position_id);
-        self.save_position_index(&index).await?;
+        if index.len() != before {
+            self.save_position_index(&index).await?;
+        }
         Ok(())
     }
 
+    async fn get_closed_position_ids(&self, key: &str) -> ArbitrageResult<Vec<String>> {
+        match self.kv_store.get::<Vec<String>>(key).await {
+            Ok(Some(ids)) => Ok(ids),
+            Ok(None) | Err(KvOperationError::NotFound(_)) => Ok(Vec::new()),
+            Err(e) => Err(ArbitrageError::database_error(format!(
+                "Failed to get closed positions {}: {:?}",
+                key, e
+            ))),
+        }
+    }
+
+    async fn record_closed_position(&self, position: &ArbitragePosition) -> ArbitrageResult<()> {
+        let Some(closed_at) = position.exit_time.or(position.closed_at) else {
+            return Ok(());
+        };
+        let key = Self::closed_positions_key(&position.user_id, closed_at - closed_at % DAY_MS);
+        let mut ids = self.get_closed_position_ids(&key).await?;
+        if ids.contains(&position.id) {
+            return Ok(());
+        }
+        ids.push(position.id.clone());
+        self.kv_store.put(&key, &ids).await.map_err(|e| {
+            ArbitrageError::storage_error(format!(
+                "Failed to record closed position {}: {:?}",
+                position.id, e
+            ))
+        })
+    }
+
     async fn save_position_index(&self, index: &[String]) -> ArbitrageResult<()> {
         self.kv_store
             .put("positions:index", index)
@@ -506,15 +981,7 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         position.stop_loss_price = Some(stop_loss_price);
         position.updated_at = chrono::Utc::now().timestamp_millis() as u64; // Ensure u64 assignment
 
-        self.kv_store
-            .put(&position_key, &position)
-            .await
-            .map_err(|e| {
-                ArbitrageError::storage_error(format!(
-                    "Failed to set stop loss for position {}: {}",
-                    position_id, e
-                ))
-            })?;
+        self.put_position(&position).await?;
 
         Ok(true)
     }
@@ -581,15 +1048,7 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         position.take_profit_price = Some(take_profit_price);
         position.updated_at = chrono::Utc::now().timestamp_millis() as u64; // Ensure u64 assignment
 
-        self.kv_store
-            .put(&position_key, &position)
-            .await
-            .map_err(|e| {
-                ArbitrageError::storage_error(format!(
-                    "Failed to set take profit for position {}: {}",
-                    position_id, e
-                ))
-            })?;
+        self.put_position(&position).await?;
 
         Ok(true)
     }
@@ -618,6 +1077,12 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
             ));
         }
 
+        if position.side == PositionSide::Both {
+            return Err(ArbitrageError::validation_error(
+                "Trailing stops would close one leg of a hedged position at a time; set stop-loss or take-profit levels instead.".to_string(),
+            ));
+        }
+
         // Assuming trailing_distance is a price offset for now
         // Logic for percentage-based would be more complex
         position.trailing_stop_distance = Some(trailing_distance);
@@ -626,119 +1091,263 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         // For simplicity, just enabling the parameter.
         position.updated_at = chrono::Utc::now().timestamp_millis() as u64; // Ensure u64 assignment
 
-        self.kv_store
-            .put(&position_key, &position)
-            .await
-            .map_err(|e| {
-                ArbitrageError::storage_error(format!(
-                    "Failed to enable trailing stop for position {}: {}",
-                    position_id, e
-                ))
-            })?;
+        self.put_position(&position).await?;
 
         Ok(true)
     }
 
-    /// Update position with current market price and calculate metrics
-    pub async fn update_position_price(
+    /// Turn exchange-side protection on or off. Takes effect on the next
+    /// `sync_protective_orders`.
+    pub async fn set_exchange_protection(
         &self,
         position_id: &str,
-        current_price: f64, // This is the new mark price for the main leg (e.g., long)
+        enabled: bool,
     ) -> ArbitrageResult<bool> {
-        let position_key = Self::position_key(position_id);
-        let mut position: ArbitragePosition = match self.kv_store.get(&position_key).await? {
-            Some(p) => p,
-            None => return Ok(false), // Position not found
+        let Some(mut position) = self.get_position(position_id).await? else {
+            return Ok(false);
         };
+        position.exchange_protection = enabled;
+        position.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        self.save_position(&position).await?;
+        Ok(true)
+    }
 
-        if position.status != PositionStatus::Open {
-            return Ok(false); // No updates for closed positions
+    /// Apply any new stop-loss, take-profit or trailing distance and bring the exchange
+    /// orders in line with them
+    pub async fn update_protection<E: ExchangeInterface>(
+        &self,
+        exchange_service: &E,
+        credentials: &[ExchangeCredentials],
+        position_id: &str,
+        stop_loss_price: Option<f64>,
+        take_profit_price: Option<f64>,
+        trailing_distance: Option<f64>,
+    ) -> ArbitrageResult<Option<ArbitragePosition>> {
+        if let Some(price) = stop_loss_price {
+            if !self.set_stop_loss(position_id, price).await? {
+                return Ok(None);
+            }
         }
+        if let Some(price) = take_profit_price {
+            if !self.set_take_profit(position_id, price).await? {
+                return Ok(None);
+            }
+        }
+        if let Some(distance) = trailing_distance {
+            if !self.enable_trailing_stop(position_id, distance).await? {
+                return Ok(None);
+            }
+        }
+        self.sync_protective_orders(exchange_service, credentials, position_id)
+            .await
+    }
 
-        // Update the relevant current price based on position side
-        // This is a simplified model; a real system might get bid/ask for long/short legs separately
-        let entry_price_for_pnl_calc = match position.side {
-            PositionSide::Long => {
-                position.current_price_long = Some(current_price);
-                position.long_position.mark_price = Some(current_price);
-                position.entry_price_long
+    /// Reconcile the conditional orders on the exchange with the position's thresholds:
+    /// orders whose threshold changed or went away are cancelled and missing ones placed.
+    /// Once the position is no longer open (or protection is off) every order is cancelled.
+    /// Failures are logged on the position and retried on the next sync, leaving the
+    /// scheduled `check_risk_triggers` as the fallback.
+    pub async fn sync_protective_orders<E: ExchangeInterface>(
+        &self,
+        exchange_service: &E,
+        credentials: &[ExchangeCredentials],
+        position_id: &str,
+    ) -> ArbitrageResult<Option<ArbitragePosition>> {
+        let Some(mut position) = self.get_position(position_id).await? else {
+            return Ok(None);
+        };
+        let wanted = desired_protective_orders(&position);
+        let credentials_for = |exchange: ExchangeIdEnum| {
+            credentials
+                .iter()
+                .find(|credentials| credentials.exchange == exchange)
+        };
+
+        let mut resting = Vec::new();
+        for placed in std::mem::take(&mut position.protective_orders) {
+            if wanted.iter().any(|order| same_protection(order, &placed)) {
+                resting.push(placed);
+                continue;
             }
-            PositionSide::Short => {
-                position.current_price_short = Some(current_price);
-                position.short_position.mark_price = Some(current_price);
-                // Use entry_price_short directly since it's f64
-                // If PnL calculation needs a specific fallback, that logic should be here.
-                if position.entry_price_short > 0.0 {
-                    position.entry_price_short
-                } else {
-                    current_price // Or handle error / specific logic if entry_price_short is required
-                } // Corrected: Ensure this is the expression returned by the block
+            let Some(creds) = credentials_for(placed.exchange) else {
+                position.execution_log.push(protection_event(
+                    ExecutionStep::ProtectionFailed,
+                    &placed,
+                    "No credentials to cancel the stale order",
+                ));
+                resting.push(placed);
+                continue;
+            };
+            let cancelled = exchange_service
+                .cancel_order(
+                    placed.exchange.as_str(),
+                    creds,
+                    &placed.order_id,
+                    &placed.symbol,
+                )
+                .await;
+            match cancelled {
+                Ok(_) => position.execution_log.push(protection_event(
+                    ExecutionStep::ProtectionCancelled,
+                    &placed,
+                    format!("{:?} cancelled", placed.kind),
+                )),
+                Err(e) => {
+                    // An order that already triggered or expired has nothing left to cancel
+                    let settled = exchange_service
+                        .get_order(
+                            placed.exchange.as_str(),
+                            creds,
+                            &placed.order_id,
+                            &placed.symbol,
+                            None,
+                        )
+                        .await
+                        .is_ok_and(|order| is_settled(&order.status));
+                    if !settled {
+                        position.execution_log.push(protection_event(
+                            ExecutionStep::ProtectionFailed,
+                            &placed,
+                            format!("{:?} could not be cancelled: {}", placed.kind, e),
+                        ));
+                        resting.push(placed);
+                    }
+                }
             }
-            PositionSide::Both => {
-                // For 'Both', we might need to update both long and short current prices
-                // or have a primary leg. Assuming long leg is primary for now.
-                position.current_price_long = Some(current_price);
-                position.long_position.mark_price = Some(current_price);
-                // If there's a distinct short leg price update mechanism, it should be handled.
-                // For PnL calculation, 'Both' might be complex (e.g. net PnL of two legs).
-                // Sticking to long leg for now for pnl_calc.
-                position.entry_price_long
+        }
+
+        for mut order in wanted {
+            if resting.iter().any(|placed| same_protection(&order, placed)) {
+                continue;
             }
-        };
+            let Some(creds) = credentials_for(order.exchange) else {
+                position.execution_log.push(protection_event(
+                    ExecutionStep::ProtectionFailed,
+                    &order,
+                    format!("No credentials to place the {:?}", order.kind),
+                ));
+                continue;
+            };
+            let request = OrderRequest {
+                order_type: order.kind.order_type(),
+                stop_price: order.trigger_price,
+                trailing_percent: order.trailing_percent,
+                reduce_only: true,
+                ..OrderRequest::market(&order.symbol, &order.side, order.amount)
+            };
+            match exchange_service
+                .place_order(order.exchange.as_str(), creds, &request)
+                .await
+            {
+                Ok(placed) => {
+                    order.order_id = placed.id;
+                    position.execution_log.push(protection_event(
+                        ExecutionStep::ProtectionPlaced,
+                        &order,
+                        format!("{:?} placed", order.kind),
+                    ));
+                    resting.push(order);
+                }
+                Err(e) => position.execution_log.push(protection_event(
+                    ExecutionStep::ProtectionFailed,
+                    &order,
+                    format!("{:?} rejected: {}", order.kind, e),
+                )),
+            }
+        }
 
-        let size = position.size.unwrap_or(0.0);
-        let pnl_factor = if position.side == PositionSide::Long {
-            1.0
-        } else {
-            -1.0
-        };
+        position.protective_orders = resting;
+        position.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        self.save_position(&position).await?;
+        Ok(Some(position))
+    }
 
-        if size > 0.0 && entry_price_for_pnl_calc > 0.0 {
-            let pnl = (current_price - entry_price_for_pnl_calc) * size * pnl_factor;
-            position.unrealized_pnl = pnl;
-            position.pnl = Some(pnl); // Assuming pnl field tracks unrealized_pnl for open positions
+    /// Close the position and cancel the protective orders it still has on the exchanges
+    pub async fn close_position_with_protection<E: ExchangeInterface>(
+        &self,
+        exchange_service: &E,
+        credentials: &[ExchangeCredentials],
+        position_id: &str,
+    ) -> ArbitrageResult<Option<ArbitragePosition>> {
+        if !self.close_position(position_id).await? {
+            return Ok(None);
+        }
+        self.sync_protective_orders(exchange_service, credentials, position_id)
+            .await
+    }
 
-            let pnl_percentage = (pnl / (entry_price_for_pnl_calc * size)) * 100.0;
-            position.unrealized_pnl_percentage = Some(pnl_percentage);
+    /// Mark the legs to market and recompute PnL. Two-leg positions take a price per leg;
+    /// a single-sided position only reads the price for its own side.
+    pub async fn update_position_price(
+        &self,
+        position_id: &str,
+        long_price: Option<f64>,
+        short_price: Option<f64>,
+    ) -> ArbitrageResult<bool> {
+        let position_key = Self::position_key(position_id);
+        let mut position: ArbitragePosition = match self.kv_store.get(&position_key).await? {
+            Some(p) => p,
+            None => return Ok(false), // Position not found
+        };
 
-            // Update individual leg PNL if structure supports it
-            if position.side == PositionSide::Long {
-                position.long_position.unrealized_pnl = Some(pnl);
-                position.long_position.percentage = Some(pnl_percentage);
-            } else {
-                position.short_position.unrealized_pnl = Some(pnl);
-                position.short_position.percentage = Some(pnl_percentage);
-            }
+        if position.status != PositionStatus::Open {
+            return Ok(false); // No updates for closed positions
+        }
+
+        if long_price.is_some() {
+            position.current_price_long = long_price;
         }
+        if short_price.is_some() {
+            position.current_price_short = short_price;
+        }
+        position.current_price = match position.side {
+            PositionSide::Short => position.current_price_short,
+            _ => position.current_price_long,
+        };
+        refresh_pnl(&mut position);
 
-        // Trailing stop logic
-        if let Some(trailing_distance) = position.trailing_stop_distance {
-            if position.side == PositionSide::Long {
-                let new_stop_loss = current_price - trailing_distance;
-                if new_stop_loss > position.stop_loss_price.unwrap_or(0.0) {
-                    position.stop_loss_price = Some(new_stop_loss);
+        // Trailing stop logic (a price level only means something for single-sided positions)
+        if let (Some(trailing_distance), Some(current_price)) =
+            (position.trailing_stop_distance, position.current_price)
+        {
+            match position.side {
+                PositionSide::Long => {
+                    let new_stop_loss = current_price - trailing_distance;
+                    if new_stop_loss > position.stop_loss_price.unwrap_or(0.0) {
+                        position.stop_loss_price = Some(new_stop_loss);
+                    }
                 }
-            } else {
-                // PositionSide::Short
-                let new_stop_loss = current_price + trailing_distance;
-                if new_stop_loss < position.stop_loss_price.unwrap_or(f64::MAX) {
-                    position.stop_loss_price = Some(new_stop_loss);
+                PositionSide::Short => {
+                    let new_stop_loss = current_price + trailing_distance;
+                    if new_stop_loss < position.stop_loss_price.unwrap_or(f64::MAX) {
+                        position.stop_loss_price = Some(new_stop_loss);
+                    }
                 }
+                PositionSide::Both => {}
             }
         }
 
         position.updated_at = chrono::Utc::now().timestamp_millis() as u64; // Ensure u64 assignment
 
-        self.kv_store
-            .put(&position_key, &position)
-            .await
-            .map_err(|e| {
-                ArbitrageError::storage_error(format!(
-                    "Failed to update price for position {}: {}",
-                    position_id, e
-                ))
-            })?;
+        self.put_position(&position).await?;
+
+        Ok(true)
+    }
 
+    /// Add a settled funding payment (positive when received) to the position's realized PnL
+    pub async fn record_funding_payment(
+        &self,
+        position_id: &str,
+        amount: f64,
+    ) -> ArbitrageResult<bool> {
+        let Some(mut position) = self.get_position(position_id).await? else {
+            return Ok(false);
+        };
+        position.funding_pnl += amount;
+        position.realized_pnl += amount;
+        refresh_pnl(&mut position);
+        position.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        self.save_position(&position).await?;
         Ok(true)
     }
 
@@ -752,45 +1361,59 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
             None => return Ok(None),
         };
 
-        if let Some(current_price) = position.current_price {
-            // Check stop-loss trigger
-            if let Some(stop_loss) = position.stop_loss_price {
-                let should_close = match position.side {
-                    PositionSide::Long => current_price <= stop_loss,
-                    PositionSide::Short => current_price >= stop_loss,
-                    PositionSide::Both => {
-                        // For hedge positions, check both sides
-                        // This is a simplified implementation
-                        current_price <= stop_loss || current_price >= stop_loss
-                    }
-                };
-                if should_close {
+        // Max loss applies to the combined net PnL of both legs, fees and funding
+        if let Some(max_loss) = position.max_loss_usd {
+            if net_pnl(&position) <= -max_loss {
+                return Ok(Some(PositionAction::Close));
+            }
+        }
+
+        // Both legs of a hedged position close once the long leg's mark reaches either level
+        if position.side == PositionSide::Both {
+            if let Some(mark) = position.current_price_long {
+                let entry = position.entry_price_long;
+                let reached = [position.stop_loss_price, position.take_profit_price]
+                    .into_iter()
+                    .flatten()
+                    .any(|level| {
+                        if level < entry {
+                            mark <= level
+                        } else {
+                            mark >= level
+                        }
+                    });
+                if reached {
                     return Ok(Some(PositionAction::Close));
                 }
             }
+            return Ok(Some(PositionAction::Hold));
+        }
 
-            // Check take-profit trigger
-            if let Some(take_profit) = position.take_profit_price {
-                let should_close = match position.side {
-                    PositionSide::Long => current_price >= take_profit,
-                    PositionSide::Short => current_price <= take_profit,
-                    PositionSide::Both => {
-                        // For hedge positions, check both sides
-                        // This is a simplified implementation
-                        current_price >= take_profit || current_price <= take_profit
-                    }
+        let current_price = match position.side {
+            PositionSide::Long => position.current_price_long.or(position.current_price),
+            PositionSide::Short => position.current_price_short.or(position.current_price),
+            PositionSide::Both => None,
+        };
+        if let Some(current_price) = current_price {
+            let long = position.side == PositionSide::Long;
+            if let Some(stop_loss) = position.stop_loss_price {
+                let hit = if long {
+                    current_price <= stop_loss
+                } else {
+                    current_price >= stop_loss
                 };
-                if should_close {
+                if hit {
                     return Ok(Some(PositionAction::Close));
                 }
             }
-
-            // Check max loss trigger
-            if let Some(max_loss) = position.max_loss_usd {
-                if let Some(pnl) = position.pnl {
-                    if pnl <= -max_loss {
-                        return Ok(Some(PositionAction::Close));
-                    }
+            if let Some(take_profit) = position.take_profit_price {
+                let hit = if long {
+                    current_price >= take_profit
+                } else {
+                    current_price <= take_profit
+                };
+                if hit {
+                    return Ok(Some(PositionAction::Close));
                 }
             }
         }
@@ -824,3 +1447,440 @@ impl<T: KvOperations + Send + Sync + 'static> PositionsService<T> {
         Ok(filtered_positions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::adapters::new_position;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::test_utils::scripted_exchange::ScriptedExchange;
+    use crate::types::OrderType;
+
+    async fn stored_position(long_size: f64, short_size: f64) -> ArbitragePosition {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let account = AccountInfo {
+            account_id: "acc".to_string(),
+            exchange: ExchangeIdEnum::Binance,
+            balances: Vec::new(),
+            total_balance_usd: 10_000.0,
+            available_balance_usd: 10_000.0,
+            used_balance_usd: 0.0,
+            last_updated: 0,
+        };
+        let data = CreatePositionData {
+            pair: "BTC/USDT".to_string(),
+            side: PositionSide::Long,
+            size: None,
+            size_usd: Some(6_500.0),
+            entry_price_long: 65_000.0,
+            entry_price_short: 65_100.0,
+            risk_percentage: None,
+            max_size_usd: None,
+            take_profit_price: None,
+            stop_loss_price: None,
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            exchange: ExchangeIdEnum::Binance,
+        };
+        let mut position = service.create_position(data, &account).await.unwrap();
+        position.long_position.amount = long_size;
+        position.short_position.amount = short_size;
+        position
+    }
+
+    fn live(symbol: &str, side: &str, amount: f64) -> Position {
+        new_position(symbol, side, amount, None, &serde_json::json!({}))
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_flags_missing_leg_and_size_drift() {
+        let position = stored_position(0.1, 0.1).await;
+        let live_positions = HashMap::from([
+            (
+                ExchangeIdEnum::Binance,
+                vec![live("BTCUSDT", "long", 0.105)],
+            ),
+            (ExchangeIdEnum::Bybit, vec![live("BTCUSDT", "long", 0.1)]),
+        ]);
+
+        let report = reconcile_positions(
+            std::slice::from_ref(&position),
+            &live_positions,
+            DEFAULT_SIZE_DRIFT_TOLERANCE,
+        );
+
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].position_id, position.id);
+        assert_eq!(
+            report[0].discrepancies,
+            vec![
+                LegDiscrepancy::SizeDrift {
+                    exchange: ExchangeIdEnum::Binance,
+                    side: PositionSide::Long,
+                    expected_size: 0.1,
+                    actual_size: 0.105,
+                },
+                LegDiscrepancy::Missing {
+                    exchange: ExchangeIdEnum::Bybit,
+                    side: PositionSide::Short,
+                    expected_size: 0.1,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_accepts_positions_within_tolerance() {
+        let position = stored_position(0.1, 0.1).await;
+        let live_positions = HashMap::from([
+            (
+                ExchangeIdEnum::Binance,
+                vec![live("BTCUSDT", "long", 0.1005)],
+            ),
+            (ExchangeIdEnum::Bybit, vec![live("BTC-USDT", "short", 0.1)]),
+        ]);
+
+        let report =
+            reconcile_positions(&[position], &live_positions, DEFAULT_SIZE_DRIFT_TOLERANCE);
+
+        assert!(report.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_skips_unqueried_exchanges_and_closed_positions() {
+        let open = stored_position(0.1, 0.1).await;
+        let mut closed = stored_position(0.1, 0.1).await;
+        closed.status = PositionStatus::Closed;
+        // Bybit could not be queried, Binance reports nothing
+        let live_positions = HashMap::from([(ExchangeIdEnum::Binance, Vec::new())]);
+
+        let report = reconcile_positions(
+            &[open.clone(), closed],
+            &live_positions,
+            DEFAULT_SIZE_DRIFT_TOLERANCE,
+        );
+
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].position_id, open.id);
+        assert_eq!(report[0].discrepancies.len(), 1);
+    }
+
+    /// Two-leg position: long 0.1 @ 65,000 on Binance, short 0.1 @ 65,100 on Bybit,
+    /// with 3.0 paid in fees on each leg
+    async fn hedged_position(service: &PositionsService<MockKvStore>) -> ArbitragePosition {
+        let mut position = stored_position(0.1, 0.1).await;
+        position.side = PositionSide::Both;
+        position.entry_price_short = 65_100.0;
+        position.long_fees = 3.0;
+        position.short_fees = 3.0;
+        service.save_position(&position).await.unwrap();
+        position
+    }
+
+    #[tokio::test]
+    async fn test_two_leg_pnl_splits_realized_and_unrealized() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let position = hedged_position(&service).await;
+
+        service
+            .update_position_price(&position.id, Some(66_000.0), Some(66_050.0))
+            .await
+            .unwrap();
+        service
+            .record_funding_payment(&position.id, 12.0)
+            .await
+            .unwrap();
+
+        let updated = service.get_position(&position.id).await.unwrap().unwrap();
+        // Long +100, short -95
+        assert!((updated.unrealized_pnl - 5.0).abs() < 1e-6);
+        assert!((updated.long_position.unrealized_pnl.unwrap() - 100.0).abs() < 1e-6);
+        assert!((updated.short_position.unrealized_pnl.unwrap() + 95.0).abs() < 1e-6);
+        // Funding 12 less fees 6
+        assert!((updated.realized_pnl - 6.0).abs() < 1e-6);
+        assert!((updated.pnl.unwrap() - 11.0).abs() < 1e-6);
+        assert!((service.calculate_total_pnl().await.unwrap() - 11.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_close_realizes_price_pnl_on_top_of_funding_and_fees() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let position = hedged_position(&service).await;
+
+        service
+            .record_funding_payment(&position.id, 12.0)
+            .await
+            .unwrap();
+        service
+            .update_position_price(&position.id, Some(66_000.0), Some(66_050.0))
+            .await
+            .unwrap();
+        assert!(service.close_position(&position.id).await.unwrap());
+
+        let closed = service.get_position(&position.id).await.unwrap().unwrap();
+        // Price +5, funding +12, fees -6
+        assert!((closed.realized_pnl - 11.0).abs() < 1e-6);
+        assert_eq!(closed.unrealized_pnl, 0.0);
+        assert!((closed.long_position.realized_pnl.unwrap() - 100.0).abs() < 1e-6);
+        assert!((closed.short_position.realized_pnl.unwrap() + 95.0).abs() < 1e-6);
+
+        // Funding that settles after the close is added, not recomputed over the price PnL
+        service
+            .record_funding_payment(&position.id, -1.0)
+            .await
+            .unwrap();
+        let closed = service.get_position(&position.id).await.unwrap().unwrap();
+        assert!((closed.realized_pnl - 10.0).abs() < 1e-6);
+        assert!((closed.funding_pnl - 11.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_closing_through_update_settles_and_leaves_the_index() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let position = hedged_position(&service).await;
+        service
+            .update_position_price(&position.id, Some(66_000.0), Some(66_050.0))
+            .await
+            .unwrap();
+        let update = |status| UpdatePositionData {
+            take_profit_price: None,
+            stop_loss_price: None,
+            status: Some(status),
+            size: None,
+            current_price: None,
+            pnl: None,
+        };
+
+        let closed = service
+            .update_position(&position.id, update(PositionStatus::Closed))
+            .await
+            .unwrap()
+            .unwrap();
+        // Price +5 less fees 6, realized as on `close_position`
+        assert!((closed.realized_pnl + 1.0).abs() < 1e-6);
+        assert_eq!(closed.unrealized_pnl, 0.0);
+        assert!(closed.closed_at.is_some());
+        assert!(service.get_all_positions().await.unwrap().is_empty());
+        let today = service
+            .get_positions_closed_on(&closed.user_id, closed.closed_at.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(today.len(), 1);
+
+        // Saving the closed record again does not put it back in the index
+        service.save_position(&closed).await.unwrap();
+        assert!(service.get_all_positions().await.unwrap().is_empty());
+        assert!(service
+            .update_position(&position.id, update(PositionStatus::Open))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_risk_triggers_use_combined_net_pnl() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let mut position = hedged_position(&service).await;
+        position.max_loss_usd = Some(50.0);
+        // The long leg never reaches this level
+        position.stop_loss_price = Some(70_000.0);
+        service.save_position(&position).await.unwrap();
+
+        // Long +100, short -200, fees -6: net -106
+        service
+            .update_position_price(&position.id, Some(66_000.0), Some(67_100.0))
+            .await
+            .unwrap();
+        assert!(matches!(
+            service.check_risk_triggers(&position.id).await.unwrap(),
+            Some(PositionAction::Close)
+        ));
+
+        // Long +100, short -60, fees -6: net +34
+        service
+            .update_position_price(&position.id, None, Some(65_700.0))
+            .await
+            .unwrap();
+        assert!(matches!(
+            service.check_risk_triggers(&position.id).await.unwrap(),
+            Some(PositionAction::Hold)
+        ));
+    }
+
+    fn creds(exchange: ExchangeIdEnum) -> ExchangeCredentials {
+        ExchangeCredentials::new(
+            exchange,
+            "key".to_string(),
+            "secret".to_string(),
+            None,
+            false,
+        )
+    }
+
+    async fn protected_position(service: &PositionsService<MockKvStore>) -> ArbitragePosition {
+        let mut position = stored_position(0.1, 0.0).await;
+        position.exchange_protection = true;
+        service.save_position(&position).await.unwrap();
+        position
+    }
+
+    #[tokio::test]
+    async fn test_protection_places_reduce_only_orders_on_position_exchange() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let exchange = ScriptedExchange::default();
+        let position = protected_position(&service).await;
+
+        let synced = service
+            .update_protection(
+                &exchange,
+                &[creds(ExchangeIdEnum::Binance)],
+                &position.id,
+                Some(63_000.0),
+                Some(68_000.0),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let sent = exchange.sent();
+        assert_eq!(sent.len(), 2);
+        for (exchange_id, request) in &sent {
+            assert_eq!(*exchange_id, ExchangeIdEnum::Binance);
+            assert_eq!(request.side, "sell");
+            assert!(request.reduce_only);
+            assert_eq!(request.amount, position.size.unwrap());
+        }
+        assert_eq!(sent[0].1.order_type, OrderType::StopLoss);
+        assert_eq!(sent[0].1.stop_price, Some(63_000.0));
+        assert_eq!(sent[1].1.order_type, OrderType::TakeProfit);
+        assert_eq!(sent[1].1.stop_price, Some(68_000.0));
+        assert_eq!(synced.protective_orders.len(), 2);
+        assert!(synced
+            .protective_orders
+            .iter()
+            .all(|order| !order.order_id.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_hedged_position_protects_each_leg_at_the_same_level() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let exchange = ScriptedExchange::default();
+        let mut position = hedged_position(&service).await;
+        position.exchange_protection = true;
+        service.save_position(&position).await.unwrap();
+
+        let synced = service
+            .update_protection(
+                &exchange,
+                &[creds(ExchangeIdEnum::Binance), creds(ExchangeIdEnum::Bybit)],
+                &position.id,
+                Some(63_000.0),
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let sent = exchange.sent();
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|(_, request)| request.reduce_only
+            && request.amount == 0.1
+            && request.stop_price == Some(63_000.0)));
+        // A fall to the level stops out the long leg and takes the short leg's profit
+        assert_eq!(sent[0].0, ExchangeIdEnum::Binance);
+        assert_eq!(sent[0].1.side, "sell");
+        assert_eq!(sent[0].1.order_type, OrderType::StopLoss);
+        assert_eq!(sent[1].0, ExchangeIdEnum::Bybit);
+        assert_eq!(sent[1].1.side, "buy");
+        assert_eq!(sent[1].1.order_type, OrderType::TakeProfit);
+        assert_eq!(synced.protective_orders.len(), 2);
+
+        assert!(service
+            .enable_trailing_stop(&position.id, 500.0)
+            .await
+            .is_err());
+        service
+            .update_position_price(&position.id, Some(62_900.0), Some(62_950.0))
+            .await
+            .unwrap();
+        assert!(matches!(
+            service.check_risk_triggers(&position.id).await.unwrap(),
+            Some(PositionAction::Close)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_editing_stop_loss_replaces_only_that_order() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let exchange = ScriptedExchange::default();
+        let credentials = [creds(ExchangeIdEnum::Binance)];
+        let position = protected_position(&service).await;
+        let first = service
+            .update_protection(
+                &exchange,
+                &credentials,
+                &position.id,
+                Some(63_000.0),
+                Some(68_000.0),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let old_stop = first.protective_orders[0].order_id.clone();
+
+        let edited = service
+            .update_protection(
+                &exchange,
+                &credentials,
+                &position.id,
+                Some(64_000.0),
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(exchange.cancelled(), vec![old_stop]);
+        assert_eq!(exchange.sent().len(), 3);
+        assert_eq!(exchange.sent()[2].1.stop_price, Some(64_000.0));
+        assert_eq!(edited.protective_orders.len(), 2);
+        assert!(edited
+            .execution_log
+            .iter()
+            .any(|event| event.step == ExecutionStep::ProtectionCancelled));
+    }
+
+    #[tokio::test]
+    async fn test_closing_position_cancels_protective_orders() {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let exchange = ScriptedExchange::default();
+        let credentials = [creds(ExchangeIdEnum::Binance)];
+        let position = protected_position(&service).await;
+        service
+            .update_protection(
+                &exchange,
+                &credentials,
+                &position.id,
+                Some(63_000.0),
+                None,
+                Some(1_300.0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(exchange.sent()[1].1.order_type, OrderType::TrailingStop);
+        assert!((exchange.sent()[1].1.trailing_percent.unwrap() - 2.0).abs() < 1e-9);
+
+        let closed = service
+            .close_position_with_protection(&exchange, &credentials, &position.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(exchange.cancelled().len(), 2);
+        assert!(closed.protective_orders.is_empty());
+        assert_eq!(closed.status, PositionStatus::Closed);
+    }
+}
@@ -0,0 +1,869 @@
+// src/services/core/trading/risk_gate.rs
+
+//! Pre-trade checks against `RiskManagementConfig`.
+//!
+//! A proposed trade is compared with the user's active positions before any order is
+//! sent. Every limit it would break is reported as a `RiskRejection`, so callers can show
+//! all of them at once. Admins can override individual limits per user; the overrides are
+//! stored as a `DynamicConfigService` configuration (see `RISK_LIMIT_OVERRIDES_TEMPLATE_ID`).
+//!
+//! `ExchangeService` and `PaperExchange` run every order through an `OrderRiskCheck`, so no
+//! placement path can skip the limits.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::services::core::trading::exchange_rest::compact_symbol;
+use crate::services::core::trading::kv_operations::KvOperations;
+use crate::services::core::trading::positions::PositionsService;
+use crate::types::{
+    ArbitragePosition, ExchangeCredentials, ExchangeIdEnum, OrderRequest, PositionSide,
+    PositionStatus, RiskManagementConfig,
+};
+use crate::utils::error::ErrorDetails;
+use crate::utils::{ArbitrageError, ArbitrageResult};
+
+/// `DynamicConfigService` template holding per-user limit overrides
+pub const RISK_LIMIT_OVERRIDES_TEMPLATE_ID: &str = "risk_limit_overrides_v1";
+
+const DAY_MS: u64 = 86_400_000;
+
+/// An order about to be sent, in quote currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedTrade {
+    pub user_id: String,
+    pub pair: String,
+    /// Notional of each leg
+    pub legs: Vec<(ExchangeIdEnum, f64)>,
+    /// Market liquidity backing the trade; `None` skips the liquidity check
+    pub liquidity_usd: Option<f64>,
+}
+
+/// A limit the proposed trade would break
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum RiskRejection {
+    PositionSize {
+        notional_usd: f64,
+        limit_usd: f64,
+    },
+    TotalExposure {
+        exposure_usd: f64,
+        limit_usd: f64,
+    },
+    ExchangeExposure {
+        exchange: ExchangeIdEnum,
+        exposure_usd: f64,
+        limit_usd: f64,
+    },
+    DailyLoss {
+        loss_usd: f64,
+        limit_usd: f64,
+    },
+    OpenPositions {
+        open: u32,
+        limit: u32,
+    },
+    PositionsPerExchange {
+        exchange: ExchangeIdEnum,
+        open: u32,
+        limit: u32,
+    },
+    PositionsPerPair {
+        pair: String,
+        open: u32,
+        limit: u32,
+    },
+    Liquidity {
+        liquidity_usd: f64,
+        minimum_usd: f64,
+    },
+    CorrelatedExposure {
+        asset: String,
+        exposure_usd: f64,
+        limit_usd: f64,
+    },
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRejection::PositionSize {
+                notional_usd,
+                limit_usd,
+            } => write!(
+                f,
+                "position size {:.2} exceeds {:.2}",
+                notional_usd, limit_usd
+            ),
+            RiskRejection::TotalExposure {
+                exposure_usd,
+                limit_usd,
+            } => write!(
+                f,
+                "total exposure {:.2} would exceed {:.2}",
+                exposure_usd, limit_usd
+            ),
+            RiskRejection::ExchangeExposure {
+                exchange,
+                exposure_usd,
+                limit_usd,
+            } => write!(
+                f,
+                "exposure on {} {:.2} would exceed {:.2}",
+                exchange, exposure_usd, limit_usd
+            ),
+            RiskRejection::DailyLoss {
+                loss_usd,
+                limit_usd,
+            } => write!(
+                f,
+                "daily loss {:.2} has reached the {:.2} limit",
+                loss_usd, limit_usd
+            ),
+            RiskRejection::OpenPositions { open, limit } => {
+                write!(f, "{} open positions, limit is {}", open, limit)
+            }
+            RiskRejection::PositionsPerExchange {
+                exchange,
+                open,
+                limit,
+            } => write!(
+                f,
+                "{} open positions on {}, limit is {}",
+                open, exchange, limit
+            ),
+            RiskRejection::PositionsPerPair { pair, open, limit } => {
+                write!(f, "{} open positions in {}, limit is {}", open, pair, limit)
+            }
+            RiskRejection::Liquidity {
+                liquidity_usd,
+                minimum_usd,
+            } => write!(
+                f,
+                "liquidity {:.2} is below {:.2}",
+                liquidity_usd, minimum_usd
+            ),
+            RiskRejection::CorrelatedExposure {
+                asset,
+                exposure_usd,
+                limit_usd,
+            } => write!(
+                f,
+                "exposure correlated with {} {:.2} would exceed {:.2}",
+                asset, exposure_usd, limit_usd
+            ),
+        }
+    }
+}
+
+/// Source of per-user limit overrides, keyed by `RiskManagementConfig` field name
+#[async_trait::async_trait(?Send)]
+pub trait RiskLimitOverrides {
+    async fn risk_limit_overrides(&self, user_id: &str) -> ArbitrageResult<HashMap<String, Value>>;
+}
+
+/// `config` with any recognised override applied; unknown keys are ignored
+pub fn apply_overrides(
+    config: &RiskManagementConfig,
+    overrides: &HashMap<String, Value>,
+) -> RiskManagementConfig {
+    let mut config = config.clone();
+    for (key, value) in overrides {
+        let Some(number) = value.as_f64().filter(|number| *number >= 0.0) else {
+            continue;
+        };
+        match key.as_str() {
+            "max_position_size_usd" => config.max_position_size_usd = number,
+            "max_total_exposure_usd" => config.max_total_exposure_usd = number,
+            "max_exchange_exposure_usd" => config.max_exchange_exposure_usd = number,
+            "daily_loss_limit_usd" => config.daily_loss_limit_usd = number,
+            "max_open_positions" => config.max_open_positions = number as u32,
+            "max_positions_per_exchange" => config.max_positions_per_exchange = number as u32,
+            "max_positions_per_pair" => config.max_positions_per_pair = number as u32,
+            "min_liquidity_usd" => config.min_liquidity_usd = number,
+            "max_correlated_exposure_usd" => config.max_correlated_exposure_usd = number,
+            "max_correlation_threshold" => config.max_correlation_threshold = number,
+            _ => {}
+        }
+    }
+    config
+}
+
+/// Base asset of a pair such as `BTC/USDT`, `ETH-USDT-SWAP` or `SOLUSDT`
+pub fn base_asset(pair: &str) -> String {
+    let pair = pair.to_uppercase();
+    if let Some(base) = pair.split(['/', '-', ':', '_']).next() {
+        if base.len() < pair.len() {
+            return base.to_string();
+        }
+    }
+    ["USDT", "USDC", "BUSD", "USD"]
+        .iter()
+        .find_map(|quote| pair.strip_suffix(quote).filter(|base| !base.is_empty()))
+        .unwrap_or(&pair)
+        .to_string()
+}
+
+fn is_active(position: &ArbitragePosition) -> bool {
+    matches!(
+        position.status,
+        PositionStatus::Open | PositionStatus::PartiallyFilled
+    )
+}
+
+/// Notional of each open leg of `position`, at the latest known price
+fn leg_exposures(position: &ArbitragePosition) -> Vec<(ExchangeIdEnum, f64)> {
+    let mut legs = Vec::new();
+    if position.side != PositionSide::Short {
+        let price = position
+            .current_price_long
+            .unwrap_or(position.entry_price_long);
+        legs.push((
+            position.long_exchange,
+            position.long_position.amount.abs() * price,
+        ));
+    }
+    if position.side != PositionSide::Long {
+        let price = position
+            .current_price_short
+            .unwrap_or(position.entry_price_short);
+        legs.push((
+            position.short_exchange,
+            position.short_position.amount.abs() * price,
+        ));
+    }
+    legs.retain(|(_, notional)| *notional > 0.0);
+    legs
+}
+
+#[derive(Clone)]
+pub struct RiskGate {
+    config: RiskManagementConfig,
+    /// Correlation between base assets, keyed with the assets in sorted order
+    correlations: HashMap<(String, String), f64>,
+    overrides: Option<Arc<dyn RiskLimitOverrides + Send + Sync>>,
+}
+
+impl RiskGate {
+    pub fn new(config: RiskManagementConfig) -> Self {
+        Self {
+            config,
+            correlations: HashMap::new(),
+            overrides: None,
+        }
+    }
+
+    pub fn with_correlation(mut self, a: &str, b: &str, correlation: f64) -> Self {
+        let (a, b) = (base_asset(a), base_asset(b));
+        let key = if a <= b { (a, b) } else { (b, a) };
+        self.correlations.insert(key, correlation);
+        self
+    }
+
+    pub fn with_overrides(mut self, overrides: Arc<dyn RiskLimitOverrides + Send + Sync>) -> Self {
+        self.overrides = Some(overrides);
+        self
+    }
+
+    fn correlation(&self, a: &str, b: &str) -> f64 {
+        if a == b {
+            return 1.0;
+        }
+        let key = if a <= b {
+            (a.to_string(), b.to_string())
+        } else {
+            (b.to_string(), a.to_string())
+        };
+        self.correlations.get(&key).copied().unwrap_or(0.0)
+    }
+
+    /// Limits that apply to `user_id` once admin overrides are taken into account
+    pub async fn limits_for(&self, user_id: &str) -> ArbitrageResult<RiskManagementConfig> {
+        match &self.overrides {
+            Some(source) => Ok(apply_overrides(
+                &self.config,
+                &source.risk_limit_overrides(user_id).await?,
+            )),
+            None => Ok(self.config.clone()),
+        }
+    }
+
+    /// Every limit `trade` would break given `positions`, which may include other users'
+    /// positions. Daily loss counts realised PnL closed since UTC midnight plus the
+    /// unrealised PnL of positions opened since then; older open positions carry PnL from
+    /// earlier days.
+    pub fn evaluate(
+        &self,
+        limits: &RiskManagementConfig,
+        trade: &ProposedTrade,
+        positions: &[ArbitragePosition],
+        now_ms: u64,
+    ) -> Vec<RiskRejection> {
+        let owned: Vec<&ArbitragePosition> = positions
+            .iter()
+            .filter(|position| position.user_id == trade.user_id)
+            .collect();
+        let active: Vec<&ArbitragePosition> = owned
+            .iter()
+            .copied()
+            .filter(|position| is_active(position))
+            .collect();
+        let mut rejections = Vec::new();
+
+        let largest_leg = trade
+            .legs
+            .iter()
+            .map(|(_, notional)| *notional)
+            .fold(0.0, f64::max);
+        if limits.max_position_size_usd > 0.0 && largest_leg > limits.max_position_size_usd {
+            rejections.push(RiskRejection::PositionSize {
+                notional_usd: largest_leg,
+                limit_usd: limits.max_position_size_usd,
+            });
+        }
+
+        let mut exposure_by_exchange: HashMap<ExchangeIdEnum, f64> = HashMap::new();
+        for (exchange, notional) in active.iter().flat_map(|position| leg_exposures(position)) {
+            *exposure_by_exchange.entry(exchange).or_default() += notional;
+        }
+        let trade_notional: f64 = trade.legs.iter().map(|(_, notional)| notional).sum();
+        let total_exposure = exposure_by_exchange.values().sum::<f64>() + trade_notional;
+        if limits.max_total_exposure_usd > 0.0 && total_exposure > limits.max_total_exposure_usd {
+            rejections.push(RiskRejection::TotalExposure {
+                exposure_usd: total_exposure,
+                limit_usd: limits.max_total_exposure_usd,
+            });
+        }
+
+        let mut trade_by_exchange: HashMap<ExchangeIdEnum, f64> = HashMap::new();
+        for (exchange, notional) in &trade.legs {
+            *trade_by_exchange.entry(*exchange).or_default() += notional;
+        }
+        let mut trade_exchanges: Vec<ExchangeIdEnum> = trade_by_exchange.keys().copied().collect();
+        trade_exchanges.sort();
+        for exchange in &trade_exchanges {
+            let exposure = exposure_by_exchange.get(exchange).copied().unwrap_or(0.0)
+                + trade_by_exchange[exchange];
+            if limits.max_exchange_exposure_usd > 0.0 && exposure > limits.max_exchange_exposure_usd
+            {
+                rejections.push(RiskRejection::ExchangeExposure {
+                    exchange: *exchange,
+                    exposure_usd: exposure,
+                    limit_usd: limits.max_exchange_exposure_usd,
+                });
+            }
+        }
+
+        let day_start = now_ms - now_ms % DAY_MS;
+        let realized_today: f64 = owned
+            .iter()
+            .filter(|position| position.exit_time.is_some_and(|exit| exit >= day_start))
+            .map(|position| position.realized_pnl)
+            .sum();
+        let unrealized: f64 = active
+            .iter()
+            .filter(|position| position.created_at >= day_start)
+            .map(|position| position.unrealized_pnl)
+            .sum();
+        let loss = -(realized_today + unrealized);
+        if limits.daily_loss_limit_usd > 0.0 && loss >= limits.daily_loss_limit_usd {
+            rejections.push(RiskRejection::DailyLoss {
+                loss_usd: loss,
+                limit_usd: limits.daily_loss_limit_usd,
+            });
+        }
+
+        let open = active.len() as u32;
+        if limits.max_open_positions > 0 && open >= limits.max_open_positions {
+            rejections.push(RiskRejection::OpenPositions {
+                open,
+                limit: limits.max_open_positions,
+            });
+        }
+        for exchange in &trade_exchanges {
+            let open = active
+                .iter()
+                .filter(|position| {
+                    leg_exposures(position)
+                        .iter()
+                        .any(|(leg_exchange, _)| leg_exchange == exchange)
+                })
+                .count() as u32;
+            if limits.max_positions_per_exchange > 0 && open >= limits.max_positions_per_exchange {
+                rejections.push(RiskRejection::PositionsPerExchange {
+                    exchange: *exchange,
+                    open,
+                    limit: limits.max_positions_per_exchange,
+                });
+            }
+        }
+        let asset = base_asset(&trade.pair);
+        let open = active
+            .iter()
+            .filter(|position| base_asset(&position.symbol) == asset)
+            .count() as u32;
+        if limits.max_positions_per_pair > 0 && open >= limits.max_positions_per_pair {
+            rejections.push(RiskRejection::PositionsPerPair {
+                pair: trade.pair.clone(),
+                open,
+                limit: limits.max_positions_per_pair,
+            });
+        }
+
+        if let Some(liquidity) = trade.liquidity_usd {
+            if limits.min_liquidity_usd > 0.0 && liquidity < limits.min_liquidity_usd {
+                rejections.push(RiskRejection::Liquidity {
+                    liquidity_usd: liquidity,
+                    minimum_usd: limits.min_liquidity_usd,
+                });
+            }
+        }
+
+        let correlated = active
+            .iter()
+            .filter(|position| {
+                self.correlation(&asset, &base_asset(&position.symbol))
+                    .abs()
+                    >= limits.max_correlation_threshold
+            })
+            .flat_map(|position| leg_exposures(position))
+            .map(|(_, notional)| notional)
+            .sum::<f64>()
+            + trade_notional;
+        if limits.max_correlated_exposure_usd > 0.0
+            && correlated > limits.max_correlated_exposure_usd
+        {
+            rejections.push(RiskRejection::CorrelatedExposure {
+                asset,
+                exposure_usd: correlated,
+                limit_usd: limits.max_correlated_exposure_usd,
+            });
+        }
+
+        rejections
+    }
+
+    /// Limits `trade` would break for its user, with overrides applied
+    pub async fn check(
+        &self,
+        trade: &ProposedTrade,
+        positions: &[ArbitragePosition],
+    ) -> ArbitrageResult<Vec<RiskRejection>> {
+        let limits = self.limits_for(&trade.user_id).await?;
+        Ok(self.evaluate(
+            &limits,
+            trade,
+            positions,
+            chrono::Utc::now().timestamp_millis() as u64,
+        ))
+    }
+
+    /// `Ok` when `trade` passes, otherwise a validation error carrying the rejections
+    /// under the `rejections` detail
+    pub async fn enforce(
+        &self,
+        trade: &ProposedTrade,
+        positions: &[ArbitragePosition],
+    ) -> ArbitrageResult<()> {
+        let rejections = self.check(trade, positions).await?;
+        if rejections.is_empty() {
+            return Ok(());
+        }
+        let reasons: Vec<String> = rejections.iter().map(ToString::to_string).collect();
+        let mut details = ErrorDetails::new();
+        details.insert("rejections".to_string(), serde_json::to_value(&rejections)?);
+        Err(ArbitrageError::validation_error(format!(
+            "Trade rejected by risk limits: {}",
+            reasons.join("; ")
+        ))
+        .with_code("RISK_LIMIT")
+        .with_details(details))
+    }
+}
+
+/// `user_id`'s open positions plus those closed today, the positions `RiskGate` needs
+pub async fn positions_for_risk<K: KvOperations + Send + Sync + 'static>(
+    positions: &PositionsService<K>,
+    user_id: &str,
+    now_ms: u64,
+) -> ArbitrageResult<Vec<ArbitragePosition>> {
+    let mut owned: Vec<ArbitragePosition> = positions
+        .get_all_positions()
+        .await?
+        .into_iter()
+        .filter(|position| position.user_id == user_id)
+        .collect();
+    for position in positions.get_positions_closed_on(user_id, now_ms).await? {
+        if !owned.iter().any(|known| known.id == position.id) {
+            owned.push(position);
+        }
+    }
+    Ok(owned)
+}
+
+/// Whether `request` only takes back exposure held in one of `positions` on `exchange`: it
+/// trades against an active leg on the same market for no more than that leg's size.
+/// Spot sells have no reduce-only flag, so this is how they unwind a position.
+fn unwinds_position(
+    request: &OrderRequest,
+    exchange: ExchangeIdEnum,
+    positions: &[ArbitragePosition],
+) -> bool {
+    let symbol = compact_symbol(&request.symbol);
+    positions
+        .iter()
+        .filter(|position| is_active(position) && compact_symbol(&position.pair) == symbol)
+        .any(|position| {
+            let long_size = position.size.unwrap_or(position.long_position.amount);
+            let short_size = position.size.unwrap_or(position.short_position.amount);
+            let legs = match position.side {
+                PositionSide::Long => vec![(position.long_exchange, "sell", long_size)],
+                PositionSide::Short => vec![(position.short_exchange, "buy", short_size)],
+                PositionSide::Both => vec![
+                    (
+                        position.long_exchange,
+                        "sell",
+                        position.long_position.amount,
+                    ),
+                    (
+                        position.short_exchange,
+                        "buy",
+                        position.short_position.amount,
+                    ),
+                ],
+            };
+            legs.into_iter().any(|(leg_exchange, side, size)| {
+                leg_exchange == exchange
+                    && request.side.eq_ignore_ascii_case(side)
+                    && request.amount > 0.0
+                    && request.amount <= size.abs()
+            })
+        })
+}
+
+/// Notional an order is checked at. Orders that add exposure need a limit, trigger or
+/// reference price to be sized.
+pub fn order_notional(
+    request: &OrderRequest,
+    reference_price: Option<f64>,
+) -> ArbitrageResult<f64> {
+    match request.price.or(request.stop_price).or(reference_price) {
+        Some(price) => Ok(request.amount * price),
+        None if request.reduce_only => Ok(0.0),
+        None => Err(ArbitrageError::validation_error(format!(
+            "No price to size the {} order against risk limits",
+            request.symbol
+        ))),
+    }
+}
+
+/// Check run on every order before it reaches an exchange, live or paper
+#[async_trait::async_trait(?Send)]
+pub trait OrderRiskCheck {
+    /// `notional_usd` is the order's size at its limit, trigger or reference price
+    async fn check_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+        notional_usd: f64,
+    ) -> ArbitrageResult<()>;
+}
+
+/// `RiskGate` applied to single orders against the owner's stored positions
+pub struct PositionRiskCheck<K: KvOperations + Send + Sync + 'static> {
+    gate: RiskGate,
+    positions: Arc<PositionsService<K>>,
+}
+
+impl<K: KvOperations + Send + Sync + 'static> PositionRiskCheck<K> {
+    pub fn new(gate: RiskGate, positions: Arc<PositionsService<K>>) -> Self {
+        Self { gate, positions }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<K: KvOperations + Send + Sync + 'static> OrderRiskCheck for PositionRiskCheck<K> {
+    async fn check_order(
+        &self,
+        credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+        notional_usd: f64,
+    ) -> ArbitrageResult<()> {
+        // Reduce-only orders, protective stops among them, can only shrink exposure
+        if request.reduce_only {
+            return Ok(());
+        }
+        let user_id = credentials.user_id.as_deref().ok_or_else(|| {
+            ArbitrageError::validation_error("Order credentials do not name the account owner")
+        })?;
+        let trade = ProposedTrade {
+            user_id: user_id.to_string(),
+            pair: request.symbol.clone(),
+            legs: vec![(credentials.exchange, notional_usd)],
+            liquidity_usd: None,
+        };
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let positions = positions_for_risk(&self.positions, user_id, now_ms).await?;
+        // So do orders that close part or all of a stored position
+        if unwinds_position(request, credentials.exchange, &positions) {
+            return Ok(());
+        }
+        self.gate.enforce(&trade, &positions).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::core::trading::positions::CreatePositionData;
+    use crate::test_utils::mock_kv_store::MockKvStore;
+    use crate::types::AccountInfo;
+
+    const NOW: u64 = 1_760_000_000_000;
+
+    async fn open_position(user_id: &str, pair: &str, notional: f64) -> ArbitragePosition {
+        let service = PositionsService::new(Arc::new(MockKvStore::new()));
+        let account = AccountInfo {
+            account_id: "acc".to_string(),
+            exchange: ExchangeIdEnum::Binance,
+            balances: Vec::new(),
+            total_balance_usd: 100_000.0,
+            available_balance_usd: 100_000.0,
+            used_balance_usd: 0.0,
+            last_updated: 0,
+        };
+        let data = CreatePositionData {
+            pair: pair.to_string(),
+            side: PositionSide::Long,
+            size: None,
+            size_usd: Some(notional),
+            entry_price_long: 100.0,
+            entry_price_short: 100.0,
+            risk_percentage: None,
+            max_size_usd: None,
+            take_profit_price: None,
+            stop_loss_price: None,
+            long_exchange: ExchangeIdEnum::Binance,
+            short_exchange: ExchangeIdEnum::Bybit,
+            exchange: ExchangeIdEnum::Binance,
+        };
+        let mut position = service.create_position(data, &account).await.unwrap();
+        position.user_id = user_id.to_string();
+        position
+    }
+
+    fn trade(pair: &str, notional: f64) -> ProposedTrade {
+        ProposedTrade {
+            user_id: "alice".to_string(),
+            pair: pair.to_string(),
+            legs: vec![
+                (ExchangeIdEnum::Binance, notional),
+                (ExchangeIdEnum::Bybit, notional),
+            ],
+            liquidity_usd: Some(1_000_000.0),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejections_list_every_limit_broken() {
+        let gate = RiskGate::new(RiskManagementConfig::default())
+            .with_correlation("BTC/USDT", "ETH/USDT", 0.9);
+        let mut losing = open_position("alice", "ETH/USDT", 20_000.0).await;
+        losing.created_at = NOW - 1_000;
+        losing.unrealized_pnl = -600.0;
+        let mut closed = open_position("alice", "SOL/USDT", 1_000.0).await;
+        closed.status = PositionStatus::Closed;
+        closed.exit_time = Some(NOW - 1_000);
+        closed.realized_pnl = -500.0;
+        let someone_else = open_position("bob", "BTC/USDT", 40_000.0).await;
+        let positions = vec![losing, closed, someone_else];
+        let limits = gate.limits_for("alice").await.unwrap();
+
+        assert!(gate
+            .evaluate(&limits, &trade("BTC/USDT", 1_000.0), &positions, NOW)
+            .iter()
+            .all(|rejection| matches!(rejection, RiskRejection::DailyLoss { .. })));
+
+        // PnL a position built up before today is not today's loss
+        let tomorrow = NOW + DAY_MS;
+        assert!(gate
+            .evaluate(&limits, &trade("BTC/USDT", 1_000.0), &positions, tomorrow)
+            .is_empty());
+
+        let rejections = gate.evaluate(&limits, &trade("BTCUSDT", 12_000.0), &positions, NOW);
+        assert_eq!(
+            rejections,
+            vec![
+                RiskRejection::PositionSize {
+                    notional_usd: 12_000.0,
+                    limit_usd: 10_000.0,
+                },
+                RiskRejection::ExchangeExposure {
+                    exchange: ExchangeIdEnum::Binance,
+                    exposure_usd: 32_000.0,
+                    limit_usd: 25_000.0,
+                },
+                RiskRejection::DailyLoss {
+                    loss_usd: 1_100.0,
+                    limit_usd: 1_000.0,
+                },
+                RiskRejection::CorrelatedExposure {
+                    asset: "BTC".to_string(),
+                    exposure_usd: 44_000.0,
+                    limit_usd: 30_000.0,
+                },
+            ]
+        );
+
+        let mut thin = trade("SOL/USDT", 1_000.0);
+        thin.liquidity_usd = Some(10_000.0);
+        let limits = RiskManagementConfig {
+            daily_loss_limit_usd: 0.0,
+            max_open_positions: 1,
+            ..limits
+        };
+        assert_eq!(
+            gate.evaluate(&limits, &thin, &positions, NOW),
+            vec![
+                RiskRejection::OpenPositions { open: 1, limit: 1 },
+                RiskRejection::Liquidity {
+                    liquidity_usd: 10_000.0,
+                    minimum_usd: 50_000.0,
+                },
+            ]
+        );
+    }
+
+    struct FixedOverrides(HashMap<String, Value>);
+
+    #[async_trait::async_trait(?Send)]
+    impl RiskLimitOverrides for FixedOverrides {
+        async fn risk_limit_overrides(
+            &self,
+            _user_id: &str,
+        ) -> ArbitrageResult<HashMap<String, Value>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_admin_overrides_raise_limits_and_rejection_carries_reasons() {
+        let positions = vec![open_position("alice", "ETH/USDT", 20_000.0).await];
+        let strict = RiskGate::new(RiskManagementConfig::default());
+
+        let error = strict
+            .enforce(&trade("ETH/USDT", 15_000.0), &positions)
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_code.as_deref(), Some("RISK_LIMIT"));
+        let details = error.details.unwrap();
+        let rejections: Vec<RiskRejection> =
+            serde_json::from_value(details["rejections"].clone()).unwrap();
+        assert!(rejections.contains(&RiskRejection::PositionSize {
+            notional_usd: 15_000.0,
+            limit_usd: 10_000.0,
+        }));
+        assert_eq!(details["rejections"][0]["reason"], "position_size");
+
+        let overrides = HashMap::from([
+            ("max_position_size_usd".to_string(), Value::from(20_000)),
+            ("max_total_exposure_usd".to_string(), Value::from(100_000)),
+            ("max_exchange_exposure_usd".to_string(), Value::from(50_000)),
+            ("max_correlated_exposure_usd".to_string(), Value::from(0)),
+            ("unknown_limit".to_string(), Value::from(1)),
+        ]);
+        let relaxed = RiskGate::new(RiskManagementConfig::default())
+            .with_overrides(Arc::new(FixedOverrides(overrides)));
+        relaxed
+            .enforce(&trade("ETH/USDT", 15_000.0), &positions)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_order_check_counts_positions_closed_today() {
+        let service = Arc::new(PositionsService::new(Arc::new(MockKvStore::new())));
+        let mut closed = open_position("alice", "SOL/USDT", 1_000.0).await;
+        closed.status = PositionStatus::Closed;
+        closed.exit_time = Some(chrono::Utc::now().timestamp_millis() as u64);
+        closed.realized_pnl = -1_200.0;
+        service.save_position(&closed).await.unwrap();
+        let check = PositionRiskCheck::new(
+            RiskGate::new(RiskManagementConfig::default()),
+            service.clone(),
+        );
+        let credentials = ExchangeCredentials::paper(ExchangeIdEnum::Binance, "alice");
+
+        let error = check
+            .check_order(
+                &credentials,
+                &OrderRequest::market("BTCUSDT", "buy", 0.01),
+                650.0,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("daily loss 1200.00"));
+
+        // Reducing an existing position is always allowed
+        let close = OrderRequest {
+            reduce_only: true,
+            ..OrderRequest::market("BTCUSDT", "sell", 0.01)
+        };
+        check
+            .check_order(&credentials, &close, 650.0)
+            .await
+            .unwrap();
+
+        // Spot sells carry no reduce-only flag: selling what a stored position holds is an
+        // unwind, selling more or on another exchange adds exposure
+        let mut spot = open_position("alice", "BTC/USDT", 6_500.0).await;
+        spot.size = Some(0.1);
+        spot.long_position.amount = 0.1;
+        service.save_position(&spot).await.unwrap();
+        let spot_sell = OrderRequest {
+            market_type: Some("spot".to_string()),
+            ..OrderRequest::market("BTCUSDT", "sell", 0.1)
+        };
+        check
+            .check_order(&credentials, &spot_sell, 6_500.0)
+            .await
+            .unwrap();
+        let oversized = OrderRequest {
+            amount: 0.2,
+            ..spot_sell.clone()
+        };
+        assert!(check
+            .check_order(&credentials, &oversized, 13_000.0)
+            .await
+            .is_err());
+        let elsewhere = ExchangeCredentials::paper(ExchangeIdEnum::OKX, "alice");
+        assert!(check
+            .check_order(&elsewhere, &spot_sell, 6_500.0)
+            .await
+            .is_err());
+        assert!(check
+            .check_order(
+                &credentials,
+                &OrderRequest::market("BTCUSDT", "buy", 0.1),
+                6_500.0,
+            )
+            .await
+            .is_err());
+
+        let mut anonymous = credentials.clone();
+        anonymous.user_id = None;
+        assert!(check
+            .check_order(
+                &anonymous,
+                &OrderRequest::market("BTCUSDT", "buy", 0.01),
+                650.0,
+            )
+            .await
+            .is_err());
+    }
+}
@@ -1,6 +1,9 @@
 // src/services/dynamic_config.rs
 
 use crate::services::core::infrastructure::DatabaseManager;
+use crate::services::core::trading::risk_gate::{
+    RiskLimitOverrides, RISK_LIMIT_OVERRIDES_TEMPLATE_ID,
+};
 use crate::types::SubscriptionTier;
 use crate::utils::{ArbitrageError, ArbitrageResult};
 use chrono::Utc;
@@ -557,6 +560,10 @@ impl DynamicConfigService {
         let strategy_template = self.create_trading_strategy_template();
         self.create_template(&strategy_template).await?;
 
+        // Create admin risk limit override template
+        let overrides_template = self.create_risk_limit_overrides_template();
+        self.create_template(&overrides_template).await?;
+
         // Create system presets
         self.create_system_presets().await?;
 
@@ -646,6 +653,118 @@ impl DynamicConfigService {
         }
     }
 
+    /// Limits admins can override per user; each parameter replaces the matching
+    /// `RiskManagementConfig` field in the pre-trade risk gate
+    fn create_risk_limit_overrides_template(&self) -> DynamicConfigTemplate {
+        let limit = |key: &str, name: &str, description: &str, parameter_type: ParameterType| {
+            ConfigParameter {
+                key: key.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                parameter_type,
+                default_value: serde_json::Value::Null,
+                validation_rules: ValidationRules {
+                    required: false,
+                    custom_validation: None,
+                    depends_on: None,
+                    min_subscription_tier: None,
+                },
+                is_required: false,
+                visible: false,
+                group: "Risk Limits".to_string(),
+            }
+        };
+        let count = ParameterType::Integer {
+            min: Some(0),
+            max: None,
+        };
+
+        DynamicConfigTemplate {
+            template_id: RISK_LIMIT_OVERRIDES_TEMPLATE_ID.to_string(),
+            name: "Risk Limit Overrides".to_string(),
+            description: "Per-user overrides of the pre-trade risk limits, set by admins"
+                .to_string(),
+            version: "1.0".to_string(),
+            category: ConfigCategory::RiskManagement,
+            parameters: vec![
+                limit(
+                    "max_position_size_usd",
+                    "Maximum Position Size (USD)",
+                    "Largest notional of a single leg",
+                    ParameterType::Currency,
+                ),
+                limit(
+                    "max_total_exposure_usd",
+                    "Maximum Total Exposure (USD)",
+                    "Combined notional of all open legs",
+                    ParameterType::Currency,
+                ),
+                limit(
+                    "max_exchange_exposure_usd",
+                    "Maximum Exposure per Exchange (USD)",
+                    "Combined notional of open legs on one exchange",
+                    ParameterType::Currency,
+                ),
+                limit(
+                    "daily_loss_limit_usd",
+                    "Daily Loss Limit (USD)",
+                    "Loss since UTC midnight after which new trades are refused",
+                    ParameterType::Currency,
+                ),
+                limit(
+                    "max_open_positions",
+                    "Maximum Open Positions",
+                    "Open positions allowed at once",
+                    count.clone(),
+                ),
+                limit(
+                    "max_positions_per_exchange",
+                    "Maximum Positions per Exchange",
+                    "Open positions allowed on one exchange",
+                    count.clone(),
+                ),
+                limit(
+                    "max_positions_per_pair",
+                    "Maximum Positions per Pair",
+                    "Open positions allowed in one base asset",
+                    count,
+                ),
+                limit(
+                    "min_liquidity_usd",
+                    "Minimum Liquidity (USD)",
+                    "Market liquidity a trade needs",
+                    ParameterType::Currency,
+                ),
+                limit(
+                    "max_correlated_exposure_usd",
+                    "Maximum Correlated Exposure (USD)",
+                    "Combined notional across correlated assets",
+                    ParameterType::Currency,
+                ),
+                limit(
+                    "max_correlation_threshold",
+                    "Correlation Threshold",
+                    "Correlation at which assets count as correlated exposure",
+                    ParameterType::Percentage,
+                ),
+            ],
+            created_at: Utc::now().timestamp_millis() as u64,
+            created_by: "system".to_string(),
+            is_system_template: true,
+            subscription_tier_required: SubscriptionTier::Free,
+        }
+    }
+
+    /// Replace the risk limits overridden for `user_id`; an empty map restores the defaults
+    pub async fn set_risk_limit_overrides(
+        &self,
+        user_id: &str,
+        overrides: HashMap<String, serde_json::Value>,
+    ) -> ArbitrageResult<UserConfigInstance> {
+        self.apply_user_config(user_id, RISK_LIMIT_OVERRIDES_TEMPLATE_ID, overrides, None)
+            .await
+    }
+
     fn create_trading_strategy_template(&self) -> DynamicConfigTemplate {
         DynamicConfigTemplate {
             template_id: "trading_strategy_v1".to_string(),
@@ -818,6 +937,21 @@ impl DynamicConfigService {
     }
 }
 
+#[async_trait::async_trait(?Send)]
+impl RiskLimitOverrides for DynamicConfigService {
+    async fn risk_limit_overrides(
+        &self,
+        user_id: &str,
+    ) -> ArbitrageResult<HashMap<String, serde_json::Value>> {
+        Ok(self
+            .get_user_config(user_id, RISK_LIMIT_OVERRIDES_TEMPLATE_ID)
+            .await?
+            .filter(|config| config.is_active)
+            .map(|config| config.parameter_values)
+            .unwrap_or_default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
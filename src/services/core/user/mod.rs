@@ -17,8 +17,8 @@ pub use session_management::SessionManagementService;
 pub use user_access::UserAccessService;
 pub use user_activity::UserActivityService;
 pub use user_exchange_api::{
-    AddApiKeyRequest, ApiKeyValidationResult, ExchangeCompatibilityResult, RateLimitInfo,
-    UpdateApiKeyRequest, UserExchangeApiService,
+    AddApiKeyRequest, ApiKeyPermissions, ApiKeyValidationResult, ExchangeCompatibilityResult,
+    RateLimitInfo, UpdateApiKeyRequest, UserExchangeApiService,
 };
 pub use user_profile::UserProfileService;
 pub use user_trading_preferences::UserTradingPreferencesService;
@@ -1,6 +1,7 @@
 use crate::log_info;
 use crate::services::core::infrastructure::D1Service;
 use crate::services::core::trading::exchange::{ExchangeInterface, ExchangeService};
+use crate::services::core::trading::exchange_rest::is_futures_market;
 use crate::services::core::user::UserProfileService;
 use crate::types::{ApiKeyProvider, ExchangeCredentials, ExchangeIdEnum, UserApiKey};
 use crate::utils::{ArbitrageError, ArbitrageResult};
@@ -34,16 +35,57 @@ pub struct ApiKeyValidationResult {
     pub rate_limit_info: Option<RateLimitInfo>,
     pub error_message: Option<String>,
     pub last_validated: u64,
+    #[serde(default)]
+    pub permissions: Option<ApiKeyPermissions>,
 }
 
 /// Rate Limit Information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RateLimitInfo {
     pub requests_per_minute: u32,
     pub requests_remaining: u32,
     pub reset_time: u64,
 }
 
+/// What an exchange reports an API key is allowed to do
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyPermissions {
+    pub can_read: bool,
+    pub can_trade_spot: bool,
+    pub can_trade_futures: bool,
+    pub can_withdraw: bool,
+    /// Whether the key only works from whitelisted IPs; `None` when the exchange does not say
+    pub ip_restricted: Option<bool>,
+    pub rate_limit: Option<RateLimitInfo>,
+}
+
+impl ApiKeyPermissions {
+    pub fn can_trade(&self) -> bool {
+        self.can_trade_spot || self.can_trade_futures
+    }
+
+    /// Whether the key may trade the `market_type` market, or either market when none is given
+    pub fn can_trade_market(&self, market_type: Option<&str>) -> bool {
+        match market_type {
+            Some(market) if is_futures_market(market) => self.can_trade_futures,
+            Some(_) => self.can_trade_spot,
+            None => self.can_trade(),
+        }
+    }
+
+    /// Keys that can withdraw are never stored: a leaked key must not be able to move funds out
+    pub fn ensure_no_withdrawal(&self, exchange_id: &str) -> ArbitrageResult<()> {
+        if self.can_withdraw {
+            return Err(ArbitrageError::validation_error(format!(
+                "{} API key has withdrawal permission enabled. Create a key without withdrawals and try again.",
+                exchange_id
+            ))
+            .with_code("WITHDRAWAL_PERMISSION"));
+        }
+        Ok(())
+    }
+}
+
 /// Exchange Compatibility Result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeCompatibilityResult {
@@ -105,9 +147,7 @@ impl UserExchangeApiService {
         request: AddApiKeyRequest,
     ) -> ArbitrageResult<UserApiKey> {
         // Validate the API key first
-        let validation_result = self
-            .validate_api_key_connectivity(&request.exchange_id, &request.api_key, &request.secret)
-            .await?;
+        let validation_result = self.validate_api_key_connectivity(&request).await?;
 
         if !validation_result.is_valid {
             return Err(ArbitrageError::validation_error(format!(
@@ -378,11 +418,17 @@ impl UserExchangeApiService {
                         api_key: self.decrypt_string(&api_key.encrypted_key)?,
                         api_secret: decrypted_secret.clone(),
                         secret: decrypted_secret,
-                        passphrase: None, // TODO: Add passphrase support to UserApiKey if needed
+                        passphrase: api_key
+                            .metadata
+                            .get("encrypted_passphrase")
+                            .and_then(|v| v.as_str())
+                            .map(|encrypted| self.decrypt_string(encrypted))
+                            .transpose()?,
                         sandbox: false,
                         is_testnet: api_key.is_testnet,
                         default_leverage: 1, // Default leverage
                         exchange_type: format!("{:?}", exchange_id), // Convert enum to string
+                        user_id: Some(user_id.to_string()),
                     };
 
                     exchange_credentials.push((*exchange_id, credentials));
@@ -393,26 +439,30 @@ impl UserExchangeApiService {
         Ok(exchange_credentials)
     }
 
-    /// Validate API key connectivity and permissions
+    /// Validate API key connectivity and permissions by probing the exchange
+    /// with the full credentials from the request. Keys that cannot read the
+    /// account, or that carry withdrawal permission, are reported as invalid.
     pub async fn validate_api_key_connectivity(
         &self,
-        exchange_id: &str,
-        api_key: &str,
-        secret: &str,
+        request: &AddApiKeyRequest,
     ) -> ArbitrageResult<ApiKeyValidationResult> {
-        // Try to make a simple API call to test connectivity
-        match self
-            .test_api_connectivity(exchange_id, api_key, secret)
-            .await
-        {
-            Ok((can_read, can_trade, rate_limit)) => Ok(ApiKeyValidationResult {
+        let probe = match self.test_api_connectivity(request).await {
+            Ok(permissions) if !permissions.can_read => Err(ArbitrageError::validation_error(
+                "API key does not have read permission".to_string(),
+            )),
+            other => other,
+        };
+
+        match probe {
+            Ok(permissions) => Ok(ApiKeyValidationResult {
                 is_valid: true,
-                can_read_market_data: can_read,
-                can_trade,
+                can_read_market_data: permissions.can_read,
+                can_trade: permissions.can_trade_market(request.exchange_type.as_deref()),
                 exchange_status: "connected".to_string(),
-                rate_limit_info: rate_limit,
+                rate_limit_info: permissions.rate_limit.clone(),
                 error_message: None,
                 last_validated: Utc::now().timestamp() as u64,
+                permissions: Some(permissions),
             }),
             Err(e) => Ok(ApiKeyValidationResult {
                 is_valid: false,
@@ -422,6 +472,7 @@ impl UserExchangeApiService {
                 rate_limit_info: None,
                 error_message: Some(e.to_string()),
                 last_validated: Utc::now().timestamp() as u64,
+                permissions: None,
             }),
         }
     }
@@ -484,29 +535,27 @@ impl UserExchangeApiService {
         Ok(result)
     }
 
-    /// Test API connectivity with actual exchange call
+    /// Probe the exchange with the request's credentials
     async fn test_api_connectivity(
         &self,
-        exchange_id: &str,
-        api_key: &str,
-        secret: &str,
-    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
-        // Try to get account info or balance to test API
-        match self
-            .exchange_service
-            .test_api_connection(exchange_id, api_key, secret)
-            .await
-        {
-            Ok((can_read_result, can_trade_result, rate_limit_info_result)) => {
-                // Directly use the destructured tuple values
-                let can_read = can_read_result;
-                let can_trade = can_trade_result;
-                let rate_limit = rate_limit_info_result;
-
-                Ok((can_read, can_trade, rate_limit))
-            }
-            Err(e) => Err(e),
+        request: &AddApiKeyRequest,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        let exchange = ExchangeIdEnum::from_string(&request.exchange_id)
+            .map_err(|e| ArbitrageError::validation_error(format!("Invalid exchange ID: {}", e)))?;
+        let mut credentials = ExchangeCredentials::new(
+            exchange,
+            request.api_key.clone(),
+            request.secret.clone(),
+            request.passphrase.clone(),
+            request.is_testnet.unwrap_or(false),
+        );
+        if let Some(exchange_type) = &request.exchange_type {
+            credentials.exchange_type = exchange_type.clone();
         }
+
+        self.exchange_service
+            .probe_api_key(&request.exchange_id, &credentials)
+            .await
     }
 
     /// AES-GCM encryption for API keys with secure key derivation
@@ -735,4 +784,20 @@ mod tests {
         assert!(result.arbitrage_compatible);
         assert!(result.technical_compatible);
     }
+
+    #[test]
+    fn test_can_trade_market_follows_futures_aliases() {
+        let futures_only = ApiKeyPermissions {
+            can_read: true,
+            can_trade_futures: true,
+            ..Default::default()
+        };
+
+        for market in ["futures", "swap", "perp", "linear", "USDM"] {
+            assert!(futures_only.can_trade_market(Some(market)), "{}", market);
+        }
+        assert!(!futures_only.can_trade_market(Some("spot")));
+        assert!(!futures_only.can_trade_market(Some("margin")));
+        assert!(futures_only.can_trade_market(None));
+    }
 }
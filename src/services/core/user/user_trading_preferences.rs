@@ -93,6 +93,9 @@ pub struct UserTradingPreferences {
     pub arbitrage_enabled: bool,
     pub technical_enabled: bool,
     pub advanced_analytics_enabled: bool,
+    /// Route orders to the simulated paper exchange instead of the user's real accounts
+    #[serde(default)]
+    pub paper_trading: bool,
 
     // User Preferences
     pub preferred_notification_channels: Vec<String>, // ["telegram", "email", "push"]
@@ -133,6 +136,7 @@ impl UserTradingPreferences {
             arbitrage_enabled: true,           // Default access to arbitrage
             technical_enabled: false,          // Opt-in to technical trading
             advanced_analytics_enabled: false, // Opt-in to advanced features
+            paper_trading: false,
             preferred_notification_channels: vec!["telegram".to_string()],
             trading_hours_timezone: "UTC".to_string(),
             trading_hours_start: "00:00".to_string(),
@@ -531,6 +535,32 @@ impl UserTradingPreferencesService {
         Ok(preferences)
     }
 
+    /// Switch a user between paper and live trading
+    pub async fn set_paper_trading(
+        &self,
+        user_id: &str,
+        enabled: bool,
+    ) -> ArbitrageResult<UserTradingPreferences> {
+        let mut preferences = self.get_or_create_preferences(user_id).await?;
+        preferences.paper_trading = enabled;
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            preferences.updated_at = js_sys::Date::now() as u64;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            preferences.updated_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+        }
+
+        self.update_preferences(&preferences).await?;
+
+        Ok(preferences)
+    }
+
     /// Get feature access for user
     pub async fn get_feature_access(&self, user_id: &str) -> ArbitrageResult<FeatureAccess> {
         let preferences = self.get_or_create_preferences(user_id).await?;
@@ -8,20 +8,37 @@ use crate::services::core::analysis::market_analysis::MarketAnalysisService;
 use crate::services::core::analysis::technical_analysis::TechnicalAnalysisService;
 use crate::services::core::infrastructure::DatabaseManager;
 // use crate::services::core::opportunities::opportunity_categorization::CategorizedOpportunity;
+use crate::services::core::opportunities::opportunity_categorization::UserOpportunityPreferences;
 use crate::services::core::opportunities::opportunity_distribution::NotificationSender;
 use crate::services::core::opportunities::opportunity_distribution::OpportunityDistributionService;
 use crate::services::core::opportunities::opportunity_engine::OpportunityEngine;
+use crate::services::core::opportunities::opportunity_filter::field_help;
+use crate::services::core::trading::arbitrage_executor::PendingExecution;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::arbitrage_executor::ProductionArbitrageExecutor;
 use crate::services::core::trading::exchange::ExchangeService;
+use crate::services::core::trading::funding_ledger::UserCarry;
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::funding_ledger::{
+    carry_positions, user_carry, FundingLedgerStore,
+};
+use crate::services::core::trading::liquidation::{LiquidationAlert, LiquidationAlertSink};
+#[cfg(target_arch = "wasm32")]
+use crate::services::core::trading::paper_exchange::paper_credentials;
 use crate::services::core::user::session_management::SessionManagementService;
 
 #[cfg(target_arch = "wasm32")]
 use crate::services::core::trading::positions::PositionsService;
+use crate::services::core::user::user_exchange_api::UserExchangeApiService;
 use crate::services::core::user::user_profile::UserProfileService;
 use crate::services::core::user::user_trading_preferences::UserTradingPreferencesService;
 use crate::services::interfaces::telegram::core::bot_client::TelegramConfig;
 use crate::services::interfaces::telegram::telegram_keyboard::InlineKeyboard;
-use crate::types::OpportunityData;
+#[cfg(target_arch = "wasm32")]
+use crate::types::ExchangeIdEnum;
+use crate::types::{ArbitragePosition, OpportunityData, PositionStatus};
 use crate::types::{GroupRateLimitConfig, GroupRegistration, GroupSettings, MessageAnalytics};
+use crate::utils::formatter::escape_markdown_v2;
 use crate::utils::{ArbitrageError, ArbitrageResult};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -409,9 +426,11 @@ pub struct TelegramService {
     ai_integration_service: Option<AiIntelligenceService>,
     // Trading services
     exchange_service: Option<ExchangeService>,
-    #[allow(dead_code)]
     #[cfg(target_arch = "wasm32")]
     positions_service: Option<PositionsService<worker::kv::KvStore>>,
+    #[cfg(target_arch = "wasm32")]
+    arbitrage_executor: Option<Arc<ProductionArbitrageExecutor>>,
+    user_exchange_api_service: Option<Arc<UserExchangeApiService>>,
 }
 
 #[allow(dead_code)]
@@ -440,6 +459,9 @@ impl TelegramService {
             exchange_service: None,
             #[cfg(target_arch = "wasm32")]
             positions_service: None as Option<PositionsService<worker::kv::KvStore>>,
+            #[cfg(target_arch = "wasm32")]
+            arbitrage_executor: None,
+            user_exchange_api_service: None,
         }
     }
 
@@ -508,6 +530,29 @@ impl TelegramService {
         self.ai_integration_service = Some(ai_integration_service);
     }
 
+    /// Set the Positions service used for position and funding views
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_positions_service(
+        &mut self,
+        positions_service: PositionsService<worker::kv::KvStore>,
+    ) {
+        self.positions_service = Some(positions_service);
+    }
+
+    /// Set the executor used to carry out trades users approve
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_arbitrage_executor(&mut self, arbitrage_executor: Arc<ProductionArbitrageExecutor>) {
+        self.arbitrage_executor = Some(arbitrage_executor);
+    }
+
+    /// Set the service holding users' exchange API keys
+    pub fn set_user_exchange_api_service(
+        &mut self,
+        user_exchange_api_service: Arc<UserExchangeApiService>,
+    ) {
+        self.user_exchange_api_service = Some(user_exchange_api_service);
+    }
+
     /// Set the Exchange service for trading operations
     pub fn set_exchange_service(&mut self, exchange_service: ExchangeService) {
         self.exchange_service = Some(exchange_service);
@@ -1276,6 +1321,56 @@ impl TelegramService {
                     return Ok("🔒 Security Notice: This bot is designed for private chat interactions. Please message me directly for full functionality and enhanced privacy.".to_string());
                 }
 
+                if let Some(args) = command_args(text, "/filter") {
+                    let user_id = message
+                        .get("from")
+                        .and_then(|from| from.get("id"))
+                        .and_then(|id| id.as_i64())
+                        .ok_or_else(|| {
+                            ArbitrageError::validation_error("Message has no sender id")
+                        })?;
+                    return self.handle_filter_command(&user_id.to_string(), args).await;
+                }
+
+                if let Some(args) = command_args(text, "/paper") {
+                    let telegram_id = message
+                        .get("from")
+                        .and_then(|from| from.get("id"))
+                        .and_then(|id| id.as_i64())
+                        .ok_or_else(|| {
+                            ArbitrageError::validation_error("Message has no sender id")
+                        })?;
+                    return self.handle_paper_command(telegram_id, args).await;
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                if command_args(text, "/funding").is_some() {
+                    let telegram_id = message
+                        .get("from")
+                        .and_then(|from| from.get("id"))
+                        .and_then(|id| id.as_i64())
+                        .ok_or_else(|| {
+                            ArbitrageError::validation_error("Message has no sender id")
+                        })?;
+                    return self.handle_funding_command(telegram_id).await;
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                for (command, approve) in [("/approve", true), ("/reject", false)] {
+                    if let Some(args) = command_args(text, command) {
+                        let telegram_id = message
+                            .get("from")
+                            .and_then(|from| from.get("id"))
+                            .and_then(|id| id.as_i64())
+                            .ok_or_else(|| {
+                                ArbitrageError::validation_error("Message has no sender id")
+                            })?;
+                        return self
+                            .handle_approval_command(telegram_id, args, approve)
+                            .await;
+                    }
+                }
+
                 // Default response for other messages
                 return Ok(format!("Received: {}", text));
             }
@@ -1439,6 +1534,329 @@ impl TelegramService {
 
         message
     }
+
+    /// Format realized funding against the carry predicted when each position was opened
+    pub fn format_funding_carry(&self, carry: &UserCarry) -> String {
+        let mut message = String::from("💸 *Funding Carry*\n\n");
+        if carry.positions.is_empty() {
+            message.push_str("No funding-rate positions yet.\n");
+            return message;
+        }
+
+        for position in &carry.positions {
+            message.push_str(&format!(
+                "📌 *{}* `{}`\n",
+                position.symbol, position.position_id
+            ));
+            message.push_str(&format!(
+                "• Realized: {:+.4} (long {:+.4} / short {:+.4})\n",
+                position.realized_funding, position.long_funding, position.short_funding
+            ));
+            match (position.predicted_funding, position.shortfall()) {
+                (Some(predicted), Some(shortfall)) => message.push_str(&format!(
+                    "• Predicted: {:+.4} ({:+.4} vs prediction)\n",
+                    predicted, shortfall
+                )),
+                _ => message.push_str("• Predicted: n/a\n"),
+            }
+            message.push_str(&format!("• Payments: {}\n\n", position.payments));
+        }
+
+        message.push_str(&format!(
+            "*Total realized:* {:+.4}\n*Total predicted:* {:+.4}\n",
+            carry.realized_funding, carry.predicted_funding
+        ));
+        message
+    }
+
+    /// Build the funding carry view for `user_id` from the D1 ledger and stored positions
+    #[cfg(target_arch = "wasm32")]
+    pub async fn get_funding_carry_message(&self, user_id: &str) -> ArbitrageResult<String> {
+        let positions_service = self.positions_service.as_ref().ok_or_else(|| {
+            ArbitrageError::service_unavailable("Positions service not available")
+        })?;
+        let d1_service = self
+            .d1_service
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::service_unavailable("D1 service not available"))?;
+        let entries = d1_service.payments_for_user(user_id).await?;
+        let positions = carry_positions(positions_service, user_id, &entries).await?;
+        let carry = user_carry(
+            user_id,
+            &positions,
+            &entries,
+            chrono::Utc::now().timestamp_millis() as u64,
+        );
+        Ok(self.format_funding_carry(&carry))
+    }
+
+    /// `/funding`: realized against predicted funding carry on the sender's positions
+    #[cfg(target_arch = "wasm32")]
+    pub async fn handle_funding_command(&self, telegram_id: i64) -> ArbitrageResult<String> {
+        let profile = self
+            .user_profile_service
+            .as_ref()
+            .ok_or_else(|| {
+                ArbitrageError::service_unavailable("User profile service not available")
+            })?
+            .get_user_by_telegram_id(telegram_id)
+            .await?
+            .ok_or_else(|| ArbitrageError::not_found("User profile not found"))?;
+        self.get_funding_carry_message(&profile.user_id).await
+    }
+
+    /// `/filter [show]`, `/filter set <expression>` and `/filter clear` for the user's custom
+    /// opportunity filter; invalid expressions are answered with the parse error and field list
+    pub async fn handle_filter_command(
+        &self,
+        user_id: &str,
+        args: &str,
+    ) -> ArbitrageResult<String> {
+        let (action, expression) = args
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((args.trim(), ""));
+        let source = match action.to_lowercase().as_str() {
+            "set" if !expression.trim().is_empty() => Some(expression),
+            "clear" | "off" => None,
+            "" | "show" => {
+                let preferences = self.load_opportunity_preferences(user_id).await?;
+                return Ok(format_custom_filter(preferences.custom_filter.as_deref()));
+            }
+            _ => return Ok(filter_usage()),
+        };
+
+        let mut preferences = self.load_opportunity_preferences(user_id).await?;
+        if let Err(e) = preferences.set_custom_filter(source) {
+            return Ok(format!("❌ {}\n\n{}", e.message, filter_usage()));
+        }
+        preferences.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+        let value = serde_json::to_value(&preferences).map_err(|e| {
+            ArbitrageError::parse_error(format!("Failed to serialize preferences: {}", e))
+        })?;
+        self.d1_service
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::service_unavailable("D1 service not available"))?
+            .store_user_opportunity_preferences(user_id, &value)
+            .await?;
+        Ok(format_custom_filter(preferences.custom_filter.as_deref()))
+    }
+
+    /// `/approve <id>` runs a semi-automatic trade waiting on the user, `/reject <id>` drops it
+    #[cfg(target_arch = "wasm32")]
+    pub async fn handle_approval_command(
+        &self,
+        telegram_id: i64,
+        args: &str,
+        approve: bool,
+    ) -> ArbitrageResult<String> {
+        let Some(pending_id) = args.split_whitespace().next() else {
+            return Ok("Usage: /approve <id> or /reject <id>".to_string());
+        };
+        let executor = self
+            .arbitrage_executor
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::service_unavailable("Trade execution not available"))?;
+        let profile = self
+            .user_profile_service
+            .as_ref()
+            .ok_or_else(|| {
+                ArbitrageError::service_unavailable("User profile service not available")
+            })?
+            .get_user_by_telegram_id(telegram_id)
+            .await?
+            .ok_or_else(|| ArbitrageError::not_found("User profile not found"))?;
+
+        if !approve {
+            return Ok(if executor.reject(&profile.user_id, pending_id).await? {
+                format!("🚫 Trade {} rejected; nothing was sent.", pending_id)
+            } else {
+                format!("⌛ Trade {} is no longer pending.", pending_id)
+            });
+        }
+
+        let preferences = self
+            .user_trading_preferences_service
+            .as_ref()
+            .ok_or_else(|| {
+                ArbitrageError::service_unavailable("Trading preferences service not available")
+            })?
+            .get_or_create_preferences(&profile.user_id)
+            .await?;
+        let credentials: Vec<_> = if preferences.paper_trading {
+            paper_credentials(&profile.user_id, &ExchangeIdEnum::all_supported())
+        } else {
+            self.user_exchange_api_service
+                .as_ref()
+                .ok_or_else(|| {
+                    ArbitrageError::service_unavailable("API key service not available")
+                })?
+                .get_user_api_keys(&profile.user_id)
+                .await?
+                .into_iter()
+                .map(|(_, credentials)| credentials)
+                .collect()
+        };
+        Ok(
+            match executor
+                .for_preferences(&preferences)
+                .approve(&preferences, pending_id, &credentials)
+                .await?
+            {
+                Some(position) => format_execution_result(&position),
+                None => format!("⌛ Trade {} is no longer pending.", pending_id),
+            },
+        )
+    }
+
+    /// `/paper on|off` switches the user between paper and live trading; `/paper` shows the mode
+    pub async fn handle_paper_command(
+        &self,
+        telegram_id: i64,
+        args: &str,
+    ) -> ArbitrageResult<String> {
+        let enabled = match args.trim().to_lowercase().as_str() {
+            "" | "status" => None,
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => return Ok(paper_usage().to_string()),
+        };
+        let profile = self
+            .user_profile_service
+            .as_ref()
+            .ok_or_else(|| {
+                ArbitrageError::service_unavailable("User profile service not available")
+            })?
+            .get_user_by_telegram_id(telegram_id)
+            .await?
+            .ok_or_else(|| ArbitrageError::not_found("User profile not found"))?;
+        let preferences_service =
+            self.user_trading_preferences_service
+                .as_ref()
+                .ok_or_else(|| {
+                    ArbitrageError::service_unavailable("Trading preferences service not available")
+                })?;
+        let preferences = match enabled {
+            Some(enabled) => {
+                preferences_service
+                    .set_paper_trading(&profile.user_id, enabled)
+                    .await?
+            }
+            None => {
+                preferences_service
+                    .get_or_create_preferences(&profile.user_id)
+                    .await?
+            }
+        };
+        Ok(format_paper_trading(preferences.paper_trading))
+    }
+
+    async fn load_opportunity_preferences(
+        &self,
+        user_id: &str,
+    ) -> ArbitrageResult<UserOpportunityPreferences> {
+        let d1_service = self
+            .d1_service
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::service_unavailable("D1 service not available"))?;
+        match d1_service.get_user_opportunity_preferences(user_id).await? {
+            Some(stored) => UserOpportunityPreferences::from_stored(stored),
+            None => Ok(UserOpportunityPreferences::default_for_user(
+                user_id.to_string(),
+            )),
+        }
+    }
+}
+
+/// Arguments after `command` (or `command@BotName`), or `None` for other messages
+fn command_args<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(command)?;
+    match rest.chars().next() {
+        None => Some(""),
+        Some(c) if c.is_whitespace() => Some(rest.trim()),
+        Some('@') => Some(
+            rest.split_once(char::is_whitespace)
+                .map(|(_, args)| args.trim())
+                .unwrap_or(""),
+        ),
+        Some(_) => None,
+    }
+}
+
+/// Ask a semi-automatic user to confirm a trade before anything is sent
+pub fn format_pending_execution(pending: &PendingExecution) -> String {
+    let opportunity = &pending.opportunity;
+    format!(
+        "🤖 Trade waiting for your approval\n{}: long {} / short {}\nUp to {:.2} USD per leg\n\nReply /approve {} to execute or /reject {} to skip. It expires in {} minutes.",
+        opportunity.pair,
+        opportunity.long_exchange.as_str(),
+        opportunity.short_exchange.as_str(),
+        pending.size_usd,
+        pending.id,
+        pending.id,
+        crate::services::core::trading::arbitrage_executor::APPROVAL_TTL_MS / 60_000
+    )
+}
+
+/// How an automated or approved trade ended up
+pub fn format_execution_result(position: &ArbitragePosition) -> String {
+    let headline = match position.status {
+        PositionStatus::Open => "✅ Trade opened",
+        PositionStatus::Failed => "❌ Trade failed and was rolled back",
+        _ => "⚠️ Trade only partly filled; check your exchange positions",
+    };
+    format!(
+        "{}\n{}: long {} on {} / short {} on {}",
+        headline,
+        position.symbol,
+        position.long_position.amount,
+        position.long_exchange.as_str(),
+        position.short_position.amount,
+        position.short_exchange.as_str()
+    )
+}
+
+/// Warn the user that a leg of their position is drifting towards liquidation
+pub fn format_liquidation_alert(alert: &LiquidationAlert) -> String {
+    format!(
+        "🚨 Liquidation risk on {}\n{} {} leg is {:.2}% from liquidation\nMark {:.4} / liquidation {:.4}\nPosition {}",
+        alert.symbol,
+        alert.leg.exchange.as_str(),
+        if alert.leg.long { "long" } else { "short" },
+        alert.leg.distance * 100.0,
+        alert.leg.mark_price,
+        alert.leg.liquidation_price,
+        alert.position_id
+    )
+}
+
+fn format_paper_trading(enabled: bool) -> String {
+    if enabled {
+        "📝 Paper trading is on: trades fill against live order books on virtual accounts and no real orders are sent.\n\nUse /paper off to trade with your exchange accounts again.".to_string()
+    } else {
+        "💼 Paper trading is off: trades are sent to your exchange accounts.\n\nUse /paper on to practise on virtual accounts.".to_string()
+    }
+}
+
+fn paper_usage() -> &'static str {
+    "Usage:\n/paper - show whether paper trading is on\n/paper on\n/paper off"
+}
+
+fn format_custom_filter(filter: Option<&str>) -> String {
+    match filter {
+        Some(filter) => format!(
+            "🔎 Your opportunity filter:\n{}\n\nOnly opportunities matching it are sent to you. Use /filter clear to remove it.",
+            filter
+        ),
+        None => "🔎 No opportunity filter set; you receive every opportunity in your enabled categories.\n\nUse /filter set <expression> to add one.".to_string(),
+    }
+}
+
+fn filter_usage() -> String {
+    format!(
+        "Usage:\n/filter - show your filter\n/filter set <expression>\n/filter clear\n\nExample:\n/filter set pair in [BTC, ETH] and net_edge > 0.05% and exchange != mexc and volume_24h > 10M\n\nFields:\n{}",
+        field_help()
+    )
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -1539,9 +1957,32 @@ impl NotificationSender for TelegramService {
     }
 }
 
+impl LiquidationAlertSink for TelegramService {
+    async fn send_liquidation_alert(&self, alert: &LiquidationAlert) -> ArbitrageResult<()> {
+        let chat_id = self
+            .user_profile_service
+            .as_ref()
+            .ok_or_else(|| {
+                ArbitrageError::service_unavailable("User profile service not available")
+            })?
+            .get_user_profile(&alert.user_id)
+            .await?
+            .and_then(|profile| profile.telegram_user_id)
+            .ok_or_else(|| {
+                ArbitrageError::not_found(format!("No Telegram chat for user {}", alert.user_id))
+            })?;
+        self.send_message_to_chat(
+            &chat_id.to_string(),
+            &escape_markdown_v2(&format_liquidation_alert(alert)),
+        )
+        .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::core::opportunities::opportunity_filter::FilterExpr;
     // Removed unused imports: CommandPermission, SubscriptionTier, UserAccessLevel, UserProfile
 
     // Mock UserProfileManagement for testing
@@ -1574,4 +2015,123 @@ mod tests {
         assert!(message.contains("/add_alias"));
         assert!(message.contains("/reset_preferences"));
     }
+
+    #[test]
+    fn test_format_funding_carry() {
+        use crate::services::core::trading::funding_ledger::PositionCarry;
+
+        let service = TelegramService::new(TelegramConfig::default());
+        let carry = UserCarry {
+            user_id: "user-1".to_string(),
+            realized_funding: 3.0,
+            predicted_funding: 9.75,
+            positions: vec![PositionCarry {
+                position_id: "pos-1".to_string(),
+                symbol: "BTC/USDT".to_string(),
+                long_funding: -1.0,
+                short_funding: 4.0,
+                realized_funding: 3.0,
+                predicted_funding: Some(9.75),
+                payments: 2,
+                last_payment_at: None,
+            }],
+        };
+
+        let message = service.format_funding_carry(&carry);
+
+        assert!(message.contains("💸 *Funding Carry*"));
+        assert!(message.contains("Realized: +3.0000 (long -1.0000 / short +4.0000)"));
+        assert!(message.contains("Predicted: +9.7500 (-6.7500 vs prediction)"));
+        assert!(message.contains("*Total predicted:* +9.7500"));
+    }
+
+    #[test]
+    fn test_filter_command_args() {
+        assert_eq!(command_args("/filter", "/filter"), Some(""));
+        assert_eq!(
+            command_args("/filter set pair == BTC", "/filter"),
+            Some("set pair == BTC")
+        );
+        assert_eq!(
+            command_args("/filter@ArbEdgeBot clear", "/filter"),
+            Some("clear")
+        );
+        assert_eq!(command_args("/filters", "/filter"), None);
+        let usage = filter_usage();
+        assert!(usage.contains("volume_24h - "));
+        let example = usage
+            .lines()
+            .filter_map(|line| line.strip_prefix("/filter set "))
+            .find(|example| *example != "<expression>")
+            .unwrap();
+        assert!(FilterExpr::parse(example).is_ok());
+    }
+
+    #[test]
+    fn test_format_pending_execution_names_the_approval_commands() {
+        use crate::types::{ArbitrageOpportunity, ExchangeIdEnum};
+
+        let pending = PendingExecution {
+            id: "a1b2c3d4".to_string(),
+            user_id: "user-1".to_string(),
+            opportunity: ArbitrageOpportunity {
+                pair: "BTC/USDT".to_string(),
+                long_exchange: ExchangeIdEnum::Binance,
+                short_exchange: ExchangeIdEnum::Bybit,
+                ..ArbitrageOpportunity::default()
+            },
+            size_usd: 250.0,
+            expires_at: 0,
+        };
+
+        let message = format_pending_execution(&pending);
+        assert!(message.contains("BTC/USDT: long binance / short bybit"));
+        assert!(message.contains("250.00 USD"));
+        assert!(message.contains("/approve a1b2c3d4"));
+        assert!(message.contains("/reject a1b2c3d4"));
+    }
+
+    #[test]
+    fn test_format_liquidation_alert_names_the_leg_at_risk() {
+        use crate::services::core::trading::liquidation::LegLiquidationRisk;
+        use crate::types::ExchangeIdEnum;
+
+        let alert = LiquidationAlert {
+            position_id: "pos-1".to_string(),
+            user_id: "user-1".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            leg: LegLiquidationRisk {
+                exchange: ExchangeIdEnum::Bybit,
+                long: false,
+                mark_price: 100.0,
+                liquidation_price: 104.0,
+                distance: 0.04,
+            },
+            threshold: 0.05,
+        };
+
+        let message = format_liquidation_alert(&alert);
+        assert!(message.contains("Liquidation risk on BTC/USDT"));
+        assert!(message.contains("bybit short leg is 4.00% from liquidation"));
+        assert!(message.contains("Position pos-1"));
+    }
+
+    #[tokio::test]
+    async fn test_paper_command_explains_its_arguments() {
+        let service = TelegramService::new(TelegramConfig::default());
+
+        let reply = service
+            .handle_webhook(json!({
+                "message": {
+                    "text": "/paper maybe",
+                    "chat": { "type": "private" },
+                    "from": { "id": 42 }
+                }
+            }))
+            .await
+            .unwrap();
+        assert_eq!(reply, paper_usage());
+        assert!(format_paper_trading(true).contains("/paper off"));
+        assert!(format_paper_trading(false).contains("/paper on"));
+    }
 }
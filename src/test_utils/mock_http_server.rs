@@ -0,0 +1,165 @@
+// Minimal HTTP/1.1 mock server that replays recorded exchange responses.
+// Runs on a background thread bound to 127.0.0.1 and records every request it receives.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+
+/// A canned response served for `METHOD path` (query string ignored when matching)
+#[derive(Debug, Clone)]
+pub struct MockRoute {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl MockRoute {
+    pub fn new(method: &str, path: &str, status: u16, body: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            body: body.to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A request captured by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Header lookup by lower-case name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(|s| s.as_str())
+    }
+}
+
+pub struct MockHttpServer {
+    url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockHttpServer {
+    /// Start serving `routes`. Routes sharing a method and path are replayed in order,
+    /// with the last one repeating once the sequence is exhausted.
+    pub fn start(routes: Vec<MockRoute>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("mock server addr")
+        );
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let routes = Arc::new(Mutex::new(routes));
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                Self::handle(stream, &routes, &recorded);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().clone()
+    }
+
+    fn handle(
+        mut stream: TcpStream,
+        routes: &Mutex<Vec<MockRoute>>,
+        recorded: &Mutex<Vec<RecordedRequest>>,
+    ) -> Option<()> {
+        let mut reader = BufReader::new(stream.try_clone().ok()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).ok()?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?.to_string();
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path.to_string(), query.to_string()),
+            None => (target, String::new()),
+        };
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).ok()?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let content_length = headers
+            .get("content-length")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body).ok()?;
+
+        let route = {
+            let mut routes = routes.lock();
+            let matching: Vec<usize> = routes
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.method == method && r.path == path)
+                .map(|(i, _)| i)
+                .collect();
+            match matching.len() {
+                0 => None,
+                1 => Some(routes[matching[0]].clone()),
+                _ => Some(routes.remove(matching[0])),
+            }
+        };
+        let route = route.unwrap_or_else(|| {
+            MockRoute::new(&method, &path, 404, r#"{"code":-1,"msg":"no mock route"}"#)
+        });
+
+        // Record before responding so callers observe the request once they have a response
+        recorded.lock().push(RecordedRequest {
+            method: method.clone(),
+            path: path.clone(),
+            query,
+            headers,
+            body: String::from_utf8_lossy(&body).to_string(),
+        });
+
+        let mut response = format!(
+            "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            route.status,
+            route.body.len()
+        );
+        for (name, value) in &route.headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        response.push_str(&route.body);
+        stream.write_all(response.as_bytes()).ok()?;
+        stream.flush().ok()
+    }
+}
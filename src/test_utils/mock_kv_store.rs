@@ -116,6 +116,21 @@ impl KvOperations for MockKvStore {
         *self.operation_count.lock() += 1;
         Ok(())
     }
+
+    async fn list_keys(&self, prefix: &str) -> KvResult<Vec<String>> {
+        if self.error_simulation.is_some() {
+            return Err(KvOperationError::Storage("Unknown KV error".to_string()));
+        }
+        let data_guard = self.data.lock();
+        *self.operation_count.lock() += 1;
+        let mut keys: Vec<String> = data_guard
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
 }
 
 impl MockKvStore {
@@ -1,7 +1,11 @@
 // src/test_utils/mod.rs
 
+#[cfg(test)]
+pub mod mock_http_server;
 #[cfg(test)]
 pub mod mock_kv_store;
+#[cfg(test)]
+pub mod scripted_exchange;
 
 // Add test utility functions
 #[cfg(test)]
@@ -105,6 +109,7 @@ pub fn create_mock_arbitrage_opportunity() -> crate::types::ArbitrageOpportunity
         r#type: ArbitrageType::CrossExchange,
         details: Some("Cross-exchange arbitrage between Binance and Bybit".to_string()),
         min_exchanges_required: 2,
+        volume_24h: None,
     }
 }
 
@@ -0,0 +1,271 @@
+// src/test_utils/scripted_exchange.rs
+
+//! In-memory `ExchangeInterface` that records the orders it is sent.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::services::core::trading::exchange::ExchangeInterface;
+use crate::services::core::user::user_exchange_api::{ApiKeyPermissions, RateLimitInfo};
+use crate::types::{
+    ExchangeCredentials, ExchangeIdEnum, FundingPayment, MarginMode, Market, Order, OrderBook,
+    OrderRequest, Position, Ticker, TradingFees,
+};
+use crate::utils::ArbitrageResult;
+
+/// Fills every order in full unless a scripted response is queued for the exchange.
+/// Orders stay as they were placed when polled, so an open order times out.
+#[derive(Default)]
+pub struct ScriptedExchange {
+    scripted: Mutex<HashMap<ExchangeIdEnum, VecDeque<ArbitrageResult<Order>>>>,
+    orders: Mutex<HashMap<String, Order>>,
+    sent: Mutex<Vec<(ExchangeIdEnum, OrderRequest)>>,
+    cancelled: Mutex<Vec<String>>,
+    margin_adjustments: Mutex<Vec<(ExchangeIdEnum, String, f64)>>,
+}
+
+impl ScriptedExchange {
+    pub fn script(self, exchange: ExchangeIdEnum, response: ArbitrageResult<Order>) -> Self {
+        self.scripted
+            .lock()
+            .unwrap()
+            .entry(exchange)
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    pub fn sent(&self) -> Vec<(ExchangeIdEnum, OrderRequest)> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// `(exchange, symbol, amount)` passed to `adjust_isolated_margin`, in call order
+    pub fn margin_adjustments(&self) -> Vec<(ExchangeIdEnum, String, f64)> {
+        self.margin_adjustments.lock().unwrap().clone()
+    }
+
+    /// Ids passed to `cancel_order`, in call order
+    pub fn cancelled(&self) -> Vec<String> {
+        self.cancelled.lock().unwrap().clone()
+    }
+}
+
+pub fn order(id: &str, side: &str, amount: f64, filled: f64, status: &str) -> Order {
+    Order {
+        id: id.to_string(),
+        client_order_id: None,
+        datetime: String::new(),
+        timestamp: 0,
+        last_trade_timestamp: None,
+        status: status.to_string(),
+        symbol: "BTCUSDT".to_string(),
+        type_: "market".to_string(),
+        time_in_force: None,
+        side: side.to_string(),
+        amount,
+        price: None,
+        average: Some(65_000.0),
+        filled,
+        remaining: amount - filled,
+        cost: filled * 65_000.0,
+        trades: Vec::new(),
+        fee: None,
+        info: Value::Null,
+    }
+}
+
+impl ExchangeInterface for ScriptedExchange {
+    async fn get_markets(&self, _exchange_id: &str) -> ArbitrageResult<Vec<Market>> {
+        unimplemented!()
+    }
+    async fn get_ticker(&self, _exchange_id: &str, _symbol: &str) -> ArbitrageResult<Ticker> {
+        unimplemented!()
+    }
+    async fn get_orderbook(
+        &self,
+        _exchange_id: &str,
+        _symbol: &str,
+        _limit: Option<u32>,
+    ) -> ArbitrageResult<OrderBook> {
+        unimplemented!()
+    }
+    async fn get_market_orderbook(
+        &self,
+        _exchange_id: &str,
+        _symbol: &str,
+        _limit: Option<u32>,
+        _market_type: &str,
+    ) -> ArbitrageResult<OrderBook> {
+        unimplemented!()
+    }
+    async fn fetch_funding_rates(
+        &self,
+        _exchange_id: &str,
+        _symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Value>> {
+        unimplemented!()
+    }
+    async fn get_balance(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<Value> {
+        unimplemented!()
+    }
+    async fn create_order(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _side: &str,
+        _amount: f64,
+        _price: Option<f64>,
+    ) -> ArbitrageResult<Order> {
+        unimplemented!()
+    }
+    async fn place_order(
+        &self,
+        exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        request: &OrderRequest,
+    ) -> ArbitrageResult<Order> {
+        let exchange = ExchangeIdEnum::from_string(exchange_id).unwrap();
+        self.sent.lock().unwrap().push((exchange, request.clone()));
+        let scripted = self
+            .scripted
+            .lock()
+            .unwrap()
+            .get_mut(&exchange)
+            .and_then(|queue| queue.pop_front());
+        let id = format!("{}-{}", exchange_id, self.sent.lock().unwrap().len());
+        let placed = match scripted {
+            Some(response) => response?,
+            None => order(&id, &request.side, request.amount, request.amount, "closed"),
+        };
+        self.orders
+            .lock()
+            .unwrap()
+            .insert(placed.id.clone(), placed.clone());
+        Ok(placed)
+    }
+    async fn cancel_order(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        order_id: &str,
+        _symbol: &str,
+    ) -> ArbitrageResult<Order> {
+        self.cancelled.lock().unwrap().push(order_id.to_string());
+        let mut order = self.orders.lock().unwrap()[order_id].clone();
+        order.status = "canceled".to_string();
+        self.orders
+            .lock()
+            .unwrap()
+            .insert(order_id.to_string(), order.clone());
+        Ok(order)
+    }
+    async fn get_order(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        order_id: &str,
+        _symbol: &str,
+        _market_type: Option<&str>,
+    ) -> ArbitrageResult<Order> {
+        Ok(self.orders.lock().unwrap()[order_id].clone())
+    }
+    async fn get_open_orders(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Order>> {
+        unimplemented!()
+    }
+    async fn get_open_positions(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: Option<&str>,
+    ) -> ArbitrageResult<Vec<Position>> {
+        unimplemented!()
+    }
+    async fn get_funding_payments(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _since: Option<u64>,
+    ) -> ArbitrageResult<Vec<FundingPayment>> {
+        unimplemented!()
+    }
+    async fn set_leverage(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _leverage: u32,
+    ) -> ArbitrageResult<()> {
+        unimplemented!()
+    }
+    async fn set_margin_mode(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+        _mode: MarginMode,
+    ) -> ArbitrageResult<()> {
+        unimplemented!()
+    }
+    async fn adjust_isolated_margin(
+        &self,
+        exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        symbol: &str,
+        amount: f64,
+    ) -> ArbitrageResult<()> {
+        let exchange = ExchangeIdEnum::from_string(exchange_id).unwrap();
+        self.margin_adjustments
+            .lock()
+            .unwrap()
+            .push((exchange, symbol.to_string(), amount));
+        Ok(())
+    }
+    async fn get_trading_fees(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+        _symbol: &str,
+    ) -> ArbitrageResult<TradingFees> {
+        unimplemented!()
+    }
+    async fn test_api_connection(
+        &self,
+        _exchange_id: &str,
+        _api_key: &str,
+        _secret: &str,
+        _passphrase: Option<&str>,
+    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
+        unimplemented!()
+    }
+    async fn test_api_connection_with_options(
+        &self,
+        _exchange_id: &str,
+        _api_key: &str,
+        _secret: &str,
+        _passphrase: Option<&str>,
+        _leverage: Option<i32>,
+        _exchange_type: Option<&str>,
+    ) -> ArbitrageResult<(bool, bool, Option<RateLimitInfo>)> {
+        unimplemented!()
+    }
+    async fn probe_api_key(
+        &self,
+        _exchange_id: &str,
+        _credentials: &ExchangeCredentials,
+    ) -> ArbitrageResult<ApiKeyPermissions> {
+        unimplemented!()
+    }
+}
@@ -32,6 +32,179 @@ pub enum OrderStatus {
     PendingCancel, // For orders that have a cancel request but not yet confirmed
 }
 
+impl OrderStatus {
+    /// Normalize an exchange-native order status (Binance, Bybit, OKX, Bitget) into `OrderStatus`.
+    pub fn from_exchange_status(status: &str) -> Self {
+        match status.to_ascii_lowercase().as_str() {
+            "new" | "live" | "untriggered" | "triggered" | "open" | "init" => OrderStatus::Open,
+            "partially_filled" | "partiallyfilled" | "partially-filled" => {
+                OrderStatus::PartiallyFilled
+            }
+            "filled" | "closed" | "full-fill" => OrderStatus::Filled,
+            "canceled" | "cancelled" | "partiallyfilledcanceled" | "deactivated" => {
+                OrderStatus::Canceled
+            }
+            "rejected" => OrderStatus::Rejected,
+            "expired" | "expired_in_match" => OrderStatus::Expired,
+            "pending_cancel" | "pendingcancel" => OrderStatus::PendingCancel,
+            _ => OrderStatus::PendingNew,
+        }
+    }
+
+    /// Stable string used in `Order::status` (open, closed, canceled, expired, rejected).
+    pub fn as_order_status_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Open
+            | OrderStatus::PartiallyFilled
+            | OrderStatus::PendingNew
+            | OrderStatus::PendingCancel => "open",
+            OrderStatus::Closed | OrderStatus::Filled => "closed",
+            OrderStatus::Canceled => "canceled",
+            OrderStatus::Rejected => "rejected",
+            OrderStatus::Expired => "expired",
+        }
+    }
+}
+
+/// Time-in-force policy for limit orders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum TimeInForce {
+    #[default]
+    GTC,
+    IOC,
+    FOK,
+    PostOnly,
+}
+
+impl TimeInForce {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeInForce::GTC => "GTC",
+            TimeInForce::IOC => "IOC",
+            TimeInForce::FOK => "FOK",
+            TimeInForce::PostOnly => "PO",
+        }
+    }
+}
+
+/// Parameters for placing an order on an exchange.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRequest {
+    pub symbol: String,
+    pub side: String, // buy, sell
+    pub order_type: OrderType,
+    pub amount: f64,
+    pub price: Option<f64>,
+    pub stop_price: Option<f64>, // Trigger price for stop / take-profit orders
+    pub trailing_percent: Option<f64>, // Callback rate for trailing stops
+    pub time_in_force: Option<TimeInForce>,
+    pub reduce_only: bool,
+    pub client_order_id: Option<String>,
+    pub market_type: Option<String>, // Overrides ExchangeCredentials::exchange_type (spot, futures)
+}
+
+impl OrderRequest {
+    pub fn market(symbol: &str, side: &str, amount: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            order_type: OrderType::Market,
+            amount,
+            price: None,
+            stop_price: None,
+            trailing_percent: None,
+            time_in_force: None,
+            reduce_only: false,
+            client_order_id: None,
+            market_type: None,
+        }
+    }
+
+    pub fn limit(symbol: &str, side: &str, amount: f64, price: f64) -> Self {
+        Self {
+            order_type: OrderType::Limit,
+            price: Some(price),
+            time_in_force: Some(TimeInForce::GTC),
+            ..Self::market(symbol, side, amount)
+        }
+    }
+
+    pub fn is_buy(&self) -> bool {
+        self.side.eq_ignore_ascii_case("buy")
+    }
+
+    /// Basic sanity checks shared by every exchange before an order is signed and sent.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.symbol.is_empty() {
+            return Err("Order symbol cannot be empty".to_string());
+        }
+        if !self.side.eq_ignore_ascii_case("buy") && !self.side.eq_ignore_ascii_case("sell") {
+            return Err(format!("Invalid order side: {}", self.side));
+        }
+        if !(self.amount.is_finite() && self.amount > 0.0) {
+            return Err(format!(
+                "Order amount must be positive, got {}",
+                self.amount
+            ));
+        }
+        let needs_price = matches!(
+            self.order_type,
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit
+        );
+        if needs_price && !self.price.is_some_and(|p| p > 0.0) {
+            return Err(format!(
+                "{:?} orders require a positive price",
+                self.order_type
+            ));
+        }
+        let needs_trigger = matches!(
+            self.order_type,
+            OrderType::StopLoss
+                | OrderType::TakeProfit
+                | OrderType::StopLossLimit
+                | OrderType::TakeProfitLimit
+        );
+        if needs_trigger && !self.stop_price.is_some_and(|p| p > 0.0) {
+            return Err(format!(
+                "{:?} orders require a positive stop price",
+                self.order_type
+            ));
+        }
+        if self.order_type == OrderType::TrailingStop
+            && !self.trailing_percent.is_some_and(|p| p > 0.0)
+        {
+            return Err("Trailing stop orders require a positive trailing percent".to_string());
+        }
+        Ok(())
+    }
+
+    /// Round amount and prices to the market's lot and tick sizes and check its limits.
+    /// `reference_price` lets market orders be checked against the minimum notional.
+    pub fn conform_to_market(
+        &self,
+        market: &Market,
+        reference_price: Option<f64>,
+    ) -> Result<OrderRequest, String> {
+        if !market.active {
+            return Err(format!("Market {} is not active", market.symbol));
+        }
+
+        let mut request = self.clone();
+        request.amount = market.round_amount(self.amount);
+        request.price = self.price.map(|p| market.round_price(p));
+        request.stop_price = self.stop_price.map(|p| market.round_price(p));
+
+        // Reduce-only orders close existing exposure and are exempt from the minimum notional
+        let notional_price = if request.reduce_only {
+            None
+        } else {
+            request.price.or(request.stop_price).or(reference_price)
+        };
+        market.validate_order(request.amount, notional_price)?;
+        Ok(request)
+    }
+}
+
 /// Account status for user accounts
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -323,6 +496,9 @@ pub struct ExchangeCredentials {
     pub is_testnet: bool,
     pub default_leverage: u32,
     pub exchange_type: String,
+    /// Owner of the account; orders are checked against this user's risk limits
+    #[serde(default)]
+    pub user_id: Option<String>,
 }
 
 impl ExchangeCredentials {
@@ -343,8 +519,26 @@ impl ExchangeCredentials {
             is_testnet,
             default_leverage: 1,
             exchange_type: "spot".to_string(),
+            user_id: None,
         }
     }
+
+    pub fn with_user(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+
+    /// Credentials addressing `user_id`'s paper-trading account on `exchange`
+    pub fn paper(exchange: ExchangeIdEnum, user_id: &str) -> Self {
+        Self::new(
+            exchange,
+            format!("paper:{}", user_id),
+            String::new(),
+            None,
+            false,
+        )
+        .with_user(user_id)
+    }
 }
 
 /// User profile structure
@@ -1052,7 +1246,8 @@ pub enum ArbitrageType {
     FundingRate,
     SpotFutures,
     CrossExchange,
-    Price, // Price arbitrage between exchanges
+    Price,      // Price arbitrage between exchanges
+    Triangular, // Three-leg cycle within a single exchange
 }
 
 /// Trading analytics data structure for tracking user trading performance
@@ -1726,6 +1921,50 @@ pub struct RiskManagementConfig {
     pub enable_take_profit: bool,
     pub enable_trailing_stop: bool,
     pub correlation_limit: f64,
+    // Pre-trade limits; zero disables a check
+    #[serde(default)]
+    pub max_exchange_exposure_usd: f64,
+    #[serde(default)]
+    pub daily_loss_limit_usd: f64,
+    #[serde(default)]
+    pub max_open_positions: u32,
+    #[serde(default)]
+    pub min_liquidity_usd: f64,
+    /// Combined notional across assets correlated at `max_correlation_threshold` or more
+    #[serde(default)]
+    pub max_correlated_exposure_usd: f64,
+}
+
+impl Default for RiskManagementConfig {
+    fn default() -> Self {
+        Self {
+            max_position_size_percent: 0.1,
+            max_correlation_threshold: 0.8,
+            stop_loss_percentage: 0.02,
+            take_profit_percentage: 0.04,
+            max_drawdown_percentage: 0.2,
+            risk_per_trade_percentage: 0.01,
+            min_risk_reward_ratio: 1.5,
+            max_positions_per_exchange: 10,
+            max_positions_per_pair: 3,
+            max_position_size_usd: 10_000.0,
+            max_total_exposure_usd: 50_000.0,
+            volatility_threshold: 0.05,
+            default_stop_loss_percentage: 0.02,
+            default_take_profit_percentage: 0.04,
+            max_portfolio_risk_percentage: 0.1,
+            max_single_position_risk_percentage: 0.02,
+            enable_stop_loss: true,
+            enable_take_profit: true,
+            enable_trailing_stop: false,
+            correlation_limit: 0.7,
+            max_exchange_exposure_usd: 25_000.0,
+            daily_loss_limit_usd: 1_000.0,
+            max_open_positions: 10,
+            min_liquidity_usd: 50_000.0,
+            max_correlated_exposure_usd: 30_000.0,
+        }
+    }
 }
 
 /// Distribution strategy for opportunities
@@ -1855,6 +2094,9 @@ pub struct ArbitrageOpportunity {
     pub r#type: ArbitrageType,
     pub details: Option<String>,
     pub min_exchanges_required: u8, // **ALWAYS 2** for arbitrage
+    /// 24h quote volume in USD of the thinner leg's market, when its tickers were read
+    #[serde(default)]
+    pub volume_24h: Option<f64>,
 }
 
 impl Default for ArbitrageOpportunity {
@@ -1894,6 +2136,7 @@ impl Default for ArbitrageOpportunity {
             r#type: ArbitrageType::CrossExchange,
             details: None,
             min_exchanges_required: 2,
+            volume_24h: None,
         }
     }
 }
@@ -1945,6 +2188,7 @@ impl ArbitrageOpportunity {
             r#type: ArbitrageType::CrossExchange,
             details: None,
             min_exchanges_required: 2,
+            volume_24h: None,
         }
     }
 }
@@ -2916,6 +3160,9 @@ pub struct Market {
     pub settle: Option<String>,
     pub settle_id: Option<String>,
     pub contract_size: Option<f64>,
+    /// Delivery time in milliseconds for dated futures; `None` for spot and perpetuals
+    #[serde(default)]
+    pub expiry: Option<u64>,
     pub linear: Option<bool>,
     pub inverse: Option<bool>,
     pub taker: f64,
@@ -2950,6 +3197,123 @@ pub struct MarketPrecision {
     pub price: Option<i32>,
     pub base: Option<i32>,
     pub quote: Option<i32>,
+    /// Minimum price increment; takes precedence over `price` decimals when set
+    #[serde(default)]
+    pub tick_size: Option<f64>,
+    /// Minimum quantity increment; takes precedence over `amount` decimals when set
+    #[serde(default)]
+    pub lot_size: Option<f64>,
+}
+
+/// Decimal places needed to represent a step such as 0.001 (-> 3)
+fn step_decimals(step: f64) -> i32 {
+    let mut decimals = 0;
+    let mut scaled = step;
+    while decimals < 16 && (scaled - scaled.round()).abs() > 1e-9 * scaled.abs().max(1.0) {
+        scaled *= 10.0;
+        decimals += 1;
+    }
+    decimals
+}
+
+/// Trim floating point noise left by step arithmetic (0.30000000000000004 -> 0.3)
+fn round_to_step_decimals(value: f64, step: f64) -> f64 {
+    let factor = 10f64.powi(step_decimals(step));
+    (value * factor).round() / factor
+}
+
+impl Market {
+    /// Price increment from the explicit tick size, else from price decimals
+    pub fn tick_size(&self) -> Option<f64> {
+        self.precision
+            .tick_size
+            .filter(|tick| *tick > 0.0)
+            .or_else(|| self.precision.price.map(|p| 10f64.powi(-p)))
+    }
+
+    /// Quantity increment from the explicit lot size, else from amount decimals
+    pub fn lot_size(&self) -> Option<f64> {
+        self.precision
+            .lot_size
+            .filter(|lot| *lot > 0.0)
+            .or_else(|| self.precision.amount.map(|p| 10f64.powi(-p)))
+    }
+
+    /// Minimum order value in quote currency
+    pub fn min_notional(&self) -> Option<f64> {
+        self.limits.cost.as_ref().and_then(|cost| cost.min)
+    }
+
+    /// Round a price to the nearest valid tick
+    pub fn round_price(&self, price: f64) -> f64 {
+        match self.tick_size() {
+            Some(tick) => round_to_step_decimals((price / tick).round() * tick, tick),
+            None => price,
+        }
+    }
+
+    /// Round a quantity down to the lot size so we never exceed the intended size
+    pub fn round_amount(&self, amount: f64) -> f64 {
+        match self.lot_size() {
+            // The epsilon keeps exact multiples like 0.3 / 0.1 from flooring to 2
+            Some(lot) => round_to_step_decimals((amount / lot + 1e-9).floor() * lot, lot),
+            None => amount,
+        }
+    }
+
+    /// Check a (rounded) order against amount, price and notional limits.
+    /// Notional includes the contract size for contract markets.
+    pub fn validate_order(&self, amount: f64, price: Option<f64>) -> Result<(), String> {
+        if amount <= 0.0 {
+            return Err(format!(
+                "Amount for {} rounds to zero at lot size {}",
+                self.symbol,
+                self.lot_size().unwrap_or(0.0)
+            ));
+        }
+        if let Some(limits) = &self.limits.amount {
+            if let Some(min) = limits.min.filter(|min| amount < *min) {
+                return Err(format!(
+                    "Amount {} below minimum {} for {}",
+                    amount, min, self.symbol
+                ));
+            }
+            if let Some(max) = limits.max.filter(|max| *max > 0.0 && amount > *max) {
+                return Err(format!(
+                    "Amount {} above maximum {} for {}",
+                    amount, max, self.symbol
+                ));
+            }
+        }
+
+        let Some(price) = price else {
+            return Ok(());
+        };
+        if let Some(limits) = &self.limits.price {
+            if let Some(min) = limits.min.filter(|min| *min > 0.0 && price < *min) {
+                return Err(format!(
+                    "Price {} below minimum {} for {}",
+                    price, min, self.symbol
+                ));
+            }
+            if let Some(max) = limits.max.filter(|max| *max > 0.0 && price > *max) {
+                return Err(format!(
+                    "Price {} above maximum {} for {}",
+                    price, max, self.symbol
+                ));
+            }
+        }
+        if let Some(min_notional) = self.min_notional() {
+            let notional = amount * price * self.contract_size.unwrap_or(1.0);
+            if notional < min_notional {
+                return Err(format!(
+                    "Order value {:.8} below minimum notional {} for {}",
+                    notional, min_notional, self.symbol
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Order structure
@@ -3017,6 +3381,19 @@ pub struct TradingFeeRates {
     pub tier_based: bool,
 }
 
+/// A funding settlement credited to or debited from a perpetual futures account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingPayment {
+    pub exchange: ExchangeIdEnum,
+    pub symbol: String,
+    /// Positive when received, negative when paid
+    pub amount: f64,
+    pub asset: String,
+    pub timestamp: u64,
+    /// Exchange-assigned id of the ledger entry, unique per exchange
+    pub payment_id: String,
+}
+
 /// Represents a single entry (bid or ask) in an order book.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub struct OrderBookEntry {
@@ -3035,6 +3412,43 @@ pub struct OrderBook {
     pub nonce: Option<u64>,
 }
 
+impl OrderBook {
+    /// Highest bid price (bids are sorted best-first)
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.first().map(|level| level[0])
+    }
+
+    /// Lowest ask price (asks are sorted best-first)
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.first().map(|level| level[0])
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Bid/ask spread as a percentage of the mid price
+    pub fn spread_percentage(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask(), self.mid_price()) {
+            (Some(bid), Some(ask), Some(mid)) if mid > 0.0 => Some((ask - bid) / mid * 100.0),
+            _ => None,
+        }
+    }
+
+    /// Total quote value resting on the bid side
+    pub fn bid_notional(&self) -> f64 {
+        self.bids.iter().map(|level| level[0] * level[1]).sum()
+    }
+
+    /// Total quote value resting on the ask side
+    pub fn ask_notional(&self) -> f64 {
+        self.asks.iter().map(|level| level[0] * level[1]).sum()
+    }
+}
+
 /// Ticker structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ticker {
@@ -3141,6 +3555,96 @@ pub struct ArbitragePosition {
     pub recommended_action: Option<String>, // Added for ai_intelligence.rs
     pub risk_percentage_applied: Option<f64>, // Added for ai_intelligence.rs
     pub optimization_score: Option<f64>, // Added for ai_intelligence.rs
+    /// Steps taken while opening or unwinding the legs, oldest first
+    #[serde(default)]
+    pub execution_log: Vec<ExecutionEvent>,
+    /// Trading fees paid on each leg, in quote currency
+    #[serde(default)]
+    pub long_fees: f64,
+    #[serde(default)]
+    pub short_fees: f64,
+    /// Net funding settled on both legs so far; positive when received
+    #[serde(default)]
+    pub funding_pnl: f64,
+    /// Funding rate difference per interval the opportunity predicted at entry
+    #[serde(default)]
+    pub predicted_rate_difference: Option<f64>,
+    /// Mirror stop-loss, take-profit and trailing stop as conditional orders on the exchange
+    #[serde(default)]
+    pub exchange_protection: bool,
+    /// Conditional orders currently resting on the exchange for this position
+    #[serde(default)]
+    pub protective_orders: Vec<ProtectiveOrder>,
+    /// Tightest liquidation-distance threshold already alerted on; cleared once the legs
+    /// move back beyond every threshold
+    #[serde(default)]
+    pub liquidation_alert_threshold: Option<f64>,
+}
+
+/// Which threshold a protective order enforces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionKind {
+    StopLoss,
+    TakeProfit,
+    TrailingStop,
+}
+
+impl ProtectionKind {
+    pub fn order_type(&self) -> OrderType {
+        match self {
+            ProtectionKind::StopLoss => OrderType::StopLoss,
+            ProtectionKind::TakeProfit => OrderType::TakeProfit,
+            ProtectionKind::TrailingStop => OrderType::TrailingStop,
+        }
+    }
+}
+
+/// A reduce-only conditional order placed on the exchange on behalf of a position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectiveOrder {
+    pub kind: ProtectionKind,
+    pub exchange: ExchangeIdEnum,
+    pub symbol: String,
+    pub side: String,
+    pub amount: f64,
+    pub trigger_price: Option<f64>,
+    /// Callback rate in percent, for trailing stops
+    pub trailing_percent: Option<f64>,
+    pub order_id: String,
+}
+
+/// Step of a two-leg execution recorded on `ArbitragePosition::execution_log`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStep {
+    Sized,
+    LegSubmitted,
+    LegFilled,
+    LegFailed,
+    LegCancelled,
+    Hedged,
+    Unwound,
+    Opened,
+    RolledBack,
+    Unhedged,
+    ProtectionPlaced,
+    ProtectionCancelled,
+    ProtectionFailed,
+    MarginAdjusted,
+    MarginAdjustmentFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionEvent {
+    pub timestamp: u64,
+    pub step: ExecutionStep,
+    pub exchange: Option<ExchangeIdEnum>,
+    pub order_id: Option<String>,
+    pub side: Option<String>,
+    pub amount: Option<f64>,
+    pub price: Option<f64>,
+    pub message: String,
 }
 
 /// Position side enumeration
@@ -3152,6 +3656,35 @@ pub enum PositionSide {
     Both, // For hedge mode
 }
 
+/// Futures margin mode for a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginMode {
+    Isolated,
+    Cross,
+}
+
+impl MarginMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarginMode::Isolated => "isolated",
+            MarginMode::Cross => "cross",
+        }
+    }
+}
+
+impl std::str::FromStr for MarginMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "isolated" => Ok(MarginMode::Isolated),
+            "cross" | "crossed" => Ok(MarginMode::Cross),
+            _ => Err(format!("Unknown margin mode: {}", s)),
+        }
+    }
+}
+
 /// Position status enumeration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -26,6 +26,7 @@ pub fn get_category_emoji(category: &OpportunityCategory) -> &'static str {
         OpportunityCategory::AiRecommended => "🤖",
         OpportunityCategory::BeginnerFriendly => "🌱",
         OpportunityCategory::AdvancedStrategies => "🎖️",
+        OpportunityCategory::BasisCarry => "🏦",
     }
 }
 
@@ -493,6 +494,12 @@ pub fn format_opportunity_message(opportunity: &ArbitrageOpportunity) -> String
                 diff_escaped
             ));
         }
+        ArbitrageType::Triangular => {
+            message.push_str(&format!(
+                "\n🔺 *Action:* Cycle on `{}`\n💰 *Gross Edge:* `{}%`",
+                long_exchange_escaped, diff_escaped
+            ));
+        }
     }
 
     // Add net difference if available
@@ -8,6 +8,7 @@ pub mod formatter;
 pub mod helpers;
 pub mod kv_standards;
 pub mod logger;
+pub mod retry;
 pub mod time; // Added time module
 
 // Re-export commonly used items
@@ -0,0 +1,237 @@
+// src/utils/retry.rs
+
+//! Shared retry/backoff policy for outbound exchange calls.
+//!
+//! Only idempotent requests are retried, and only on transient failures: network
+//! errors, HTTP 5xx and rate limits. A `retry_after_ms` detail on the error (set from
+//! `Retry-After` or an exchange's reset header) takes precedence over the computed
+//! backoff. When that wait exceeds `max_delay_ms`, as it does for a Binance 418 IP ban,
+//! the error is returned straight away instead of sleeping through the ban.
+
+use std::future::Future;
+
+use rand::Rng;
+use serde_json::json;
+
+use crate::utils::error::{ArbitrageError, ErrorKind};
+use crate::utils::ArbitrageResult;
+
+/// Error detail key holding how long (ms) the server asked us to wait
+pub const RETRY_AFTER_DETAIL: &str = "retry_after_ms";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Fraction of each delay that is randomised away (0.0 = fixed delays)
+    pub jitter_ratio: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            jitter_ratio: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_delays(mut self, base_delay_ms: u64, max_delay_ms: u64) -> Self {
+        self.base_delay_ms = base_delay_ms;
+        self.max_delay_ms = max_delay_ms;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter_ratio: f64) -> Self {
+        self.jitter_ratio = jitter_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Exponential backoff for the zero-based `attempt`, capped and jittered
+    pub fn backoff_ms(&self, attempt: u32) -> u64 {
+        let delay = self
+            .base_delay_ms
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(self.max_delay_ms);
+        let spread = (delay as f64 * self.jitter_ratio) as u64;
+        if spread == 0 {
+            return delay;
+        }
+        delay - rand::thread_rng().gen_range(0..=spread)
+    }
+
+    /// How long to wait before retrying `error`, or `None` when it should be returned as is
+    pub fn retry_delay_ms(
+        &self,
+        error: &ArbitrageError,
+        attempt: u32,
+        idempotent: bool,
+    ) -> Option<u64> {
+        if !idempotent || attempt >= self.max_retries || !is_transient(error) {
+            return None;
+        }
+        match retry_after_ms(error) {
+            Some(wait) if wait > self.max_delay_ms => None,
+            Some(wait) => Some(wait.max(self.backoff_ms(attempt))),
+            None => Some(self.backoff_ms(attempt)),
+        }
+    }
+
+    /// Run `operation`, retrying transient failures of idempotent requests
+    pub async fn run<T, F, Fut>(&self, idempotent: bool, mut operation: F) -> ArbitrageResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ArbitrageResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(error) => match self.retry_delay_ms(&error, attempt, idempotent) {
+                    Some(delay_ms) => {
+                        sleep_ms(delay_ms).await;
+                        attempt += 1;
+                    }
+                    None => return Err(error),
+                },
+            }
+        }
+    }
+}
+
+/// HTTP methods that can safely be replayed. Order placement (POST) never is.
+pub fn is_idempotent_method(method: &str) -> bool {
+    matches!(
+        method.to_ascii_uppercase().as_str(),
+        "GET" | "HEAD" | "OPTIONS" | "PUT" | "DELETE"
+    )
+}
+
+/// Network failures, rate limits and 5xx responses
+pub fn is_transient(error: &ArbitrageError) -> bool {
+    match error.kind {
+        ErrorKind::NetworkError | ErrorKind::RateLimit => true,
+        _ => error
+            .details
+            .as_ref()
+            .and_then(|details| details.get("http_status"))
+            .and_then(|status| status.as_u64())
+            .is_some_and(|status| (500..600).contains(&status)),
+    }
+}
+
+/// Attach the server-requested wait to an error
+pub fn with_retry_after(mut error: ArbitrageError, retry_after_ms: u64) -> ArbitrageError {
+    error
+        .details
+        .get_or_insert_with(Default::default)
+        .insert(RETRY_AFTER_DETAIL.to_string(), json!(retry_after_ms));
+    error
+}
+
+pub fn retry_after_ms(error: &ArbitrageError) -> Option<u64> {
+    error.details.as_ref()?.get(RETRY_AFTER_DETAIL)?.as_u64()
+}
+
+/// Parse a `Retry-After` header given in seconds into milliseconds
+pub fn parse_retry_after(value: &str) -> Option<u64> {
+    value.trim().parse::<u64>().ok().map(|s| s * 1000)
+}
+
+pub async fn sleep_ms(ms: u64) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        gloo_timers::future::TimeoutFuture::new(ms.min(u32::MAX as u64) as u32).await;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast() -> RetryPolicy {
+        RetryPolicy::default().with_delays(1, 5).with_jitter(0.0)
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped_with_jitter_below_delay() {
+        let policy = RetryPolicy::default()
+            .with_delays(100, 1_000)
+            .with_jitter(0.0);
+        assert_eq!(policy.backoff_ms(0), 100);
+        assert_eq!(policy.backoff_ms(2), 400);
+        assert_eq!(policy.backoff_ms(10), 1_000);
+
+        let jittered = policy.with_jitter(0.5);
+        for _ in 0..50 {
+            let delay = jittered.backoff_ms(1);
+            assert!((100..=200).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_retry_decisions() {
+        let policy = fast();
+        let network = ArbitrageError::network_error("reset");
+        let bad_request = ArbitrageError::validation_error("bad symbol");
+
+        assert_eq!(policy.retry_delay_ms(&network, 0, true), Some(1));
+        assert_eq!(policy.retry_delay_ms(&network, 0, false), None);
+        assert_eq!(policy.retry_delay_ms(&network, 3, true), None);
+        assert_eq!(policy.retry_delay_ms(&bad_request, 0, true), None);
+
+        let short_wait = with_retry_after(ArbitrageError::rate_limit_error("slow down"), 4);
+        assert_eq!(policy.retry_delay_ms(&short_wait, 0, true), Some(4));
+        let ban = with_retry_after(ArbitrageError::rate_limit_error("banned"), 120_000);
+        assert_eq!(policy.retry_delay_ms(&ban, 0, true), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_idempotent_operations_only() {
+        let calls = Cell::new(0);
+        let result = fast()
+            .run(true, || async {
+                calls.set(calls.get() + 1);
+                if calls.get() < 3 {
+                    Err(ArbitrageError::network_error("reset"))
+                } else {
+                    Ok(calls.get())
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        calls.set(0);
+        let result: ArbitrageResult<()> = fast()
+            .run(false, || async {
+                calls.set(calls.get() + 1);
+                Err(ArbitrageError::network_error("reset"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -62,6 +62,7 @@ async fn test_user_registration_flow_integration() {
         arbitrage_enabled: true,
         technical_enabled: false, // Beginner shouldn't have technical enabled
         advanced_analytics_enabled: false, // Beginner shouldn't have advanced features
+        paper_trading: false,
 
         // User Preferences
         preferred_notification_channels: vec!["telegram".to_string()],
@@ -158,6 +159,7 @@ async fn test_user_registration_flow_integration() {
         arbitrage_enabled: true,
         technical_enabled: true,
         advanced_analytics_enabled: true,
+        paper_trading: false,
 
         preferred_notification_channels: vec!["telegram".to_string(), "email".to_string()],
         trading_hours_timezone: "UTC".to_string(),
@@ -262,6 +264,7 @@ async fn test_user_registration_service_interface_validation() {
         arbitrage_enabled: true,
         technical_enabled: true,
         advanced_analytics_enabled: false,
+        paper_trading: false,
 
         preferred_notification_channels: vec!["telegram".to_string()],
         trading_hours_timezone: "UTC".to_string(),
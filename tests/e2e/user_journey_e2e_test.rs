@@ -78,6 +78,7 @@ fn create_test_preferences(
         arbitrage_enabled: true,
         technical_enabled: false,
         advanced_analytics_enabled: false,
+        paper_trading: false,
         preferred_notification_channels: vec!["telegram".to_string()],
         trading_hours_timezone: "UTC".to_string(),
         trading_hours_start: "00:00".to_string(),
@@ -161,6 +161,7 @@ fn create_test_opportunity() -> ArbitrageOpportunity {
         r#type: ArbitrageType::CrossExchange,
         details: Some("Test opportunity details".to_string()),
         min_exchanges_required: 2,
+        volume_24h: None,
     }
 }
 
@@ -606,6 +607,7 @@ mod performance_tests {
                 r#type: ArbitrageType::CrossExchange,
                 details: Some("High load test opportunity".to_string()),
                 min_exchanges_required: 2,
+                volume_24h: None,
             };
 
             // Simulate distribution analytics recording
@@ -880,6 +882,7 @@ mod e2e_workflow_tests {
                 r#type: ArbitrageType::CrossExchange,
                 details: Some("High load behavior test opportunity".to_string()),
                 min_exchanges_required: 2,
+                volume_24h: None,
             };
 
             // Record analytics for each opportunity
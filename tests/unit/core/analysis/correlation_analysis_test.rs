@@ -112,6 +112,7 @@ fn create_test_user_preferences(trading_focus: TradingFocus) -> UserTradingPrefe
         arbitrage_enabled: true,
         technical_enabled: true,
         advanced_analytics_enabled: false,
+        paper_trading: false,
         preferred_notification_channels: vec!["telegram".to_string()],
         trading_hours_timezone: "UTC".to_string(),
         trading_hours_start: "00:00".to_string(),
@@ -529,6 +529,7 @@ impl MockExchangeService {
                     settle: None,
                     settle_id: None,
                     contract_size: None,
+                    expiry: None,
                     linear: None,
                     inverse: None,
                     taker: 0.001,
@@ -540,6 +541,8 @@ impl MockExchangeService {
                         price: Some(2),
                         base: None,
                         quote: None,
+                        tick_size: None,
+                        lot_size: None,
                     },
                     limits: MarketLimits {
                         amount: Some(MinMax {